@@ -4,14 +4,33 @@
 //! workflow engine. It supports executing, validating, listing, and inspecting
 //! workflows with proper error handling and user-friendly output.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use clap::{Parser, Subcommand, ValueEnum};
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_yml::Value;
 use tracing::{debug, error, info, warn};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
-use crate::core::engine::{ExecuteWorkflowOptions, WorkflowExecutor};
+use crate::core::checkpoint::CheckpointStore;
+use crate::core::control::ExecutionControl;
+use crate::core::engine::{ExecuteWorkflowOptions, FileFormat, WorkflowExecutor, WorkflowResult};
 use crate::core::heap::Heap;
+use crate::core::migration::migrate;
+use crate::core::run::{generate_run_id, WorkflowRun};
+use crate::core::schema::workflow_schema;
+use crate::core::source::SourceFile;
+use crate::core::validation::{Diagnostic, WorkflowValidator};
+use crate::infrastructure::logging::{self, RunLogWriter};
+use crate::infrastructure::run_store::{RunRecord, RunStore};
+use crate::infrastructure::{paths, plugins};
+use crate::nodes::registry::NodeRegistry;
+use crate::nodes::wasm::WasmPermissions;
+use crate::shared::types::workflow::status::Status;
 use crate::shared::types::workflow::workflow::Workflow;
+use crate::shared::utils::duration::parse_duration;
 
 /// Main CLI configuration structure
 ///
@@ -87,6 +106,35 @@ pub enum Commands {
         /// Output format for results
         #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
         format: OutputFormat,
+
+        /// Directory where run checkpoints are stored
+        #[arg(long, value_name = "DIR")]
+        state_dir: Option<PathBuf>,
+
+        /// Value for a workflow input, may be repeated
+        #[arg(short, long = "input", value_name = "KEY=VALUE", value_parser = parse_input)]
+        inputs: Vec<(String, Value)>,
+
+        #[command(flatten)]
+        wasm: WasmArgs,
+    },
+
+    /// Resume a failed or interrupted run from its last checkpoint
+    Resume {
+        /// Identifier of the run to resume
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+
+        /// Output format for results
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Directory where run checkpoints are stored
+        #[arg(long, value_name = "DIR")]
+        state_dir: Option<PathBuf>,
+
+        #[command(flatten)]
+        wasm: WasmArgs,
     },
 
     /// List available workflows in a directory
@@ -105,6 +153,10 @@ pub enum Commands {
         /// Path to the workflow file
         #[arg(value_name = "FILE")]
         file: PathBuf,
+
+        /// Report unknown fields as warnings instead of errors
+        #[arg(long)]
+        lenient: bool,
     },
 
     /// Display information about a workflow
@@ -113,6 +165,143 @@ pub enum Commands {
         #[arg(value_name = "FILE")]
         file: PathBuf,
     },
+
+    /// Upgrade a workflow file to the current file format version in place
+    Migrate {
+        /// Path to the workflow file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Print the migrated workflow instead of rewriting the file
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List the plugins available to workflows, describing new or changed ones
+    Plugins {
+        /// Directory of the workflows using the plugins
+        #[arg(value_name = "DIR", default_value = ".")]
+        directory: PathBuf,
+    },
+
+    /// Print the JSON Schema of workflow files, for editor integration
+    Schema {
+        /// Write the schema to a file instead of standard output
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Inspect the history of past runs
+    Runs {
+        /// Run history operation
+        #[command(subcommand)]
+        command: RunsCommand,
+    },
+}
+
+/// Operations on the run history
+#[derive(Subcommand)]
+pub enum RunsCommand {
+    /// List past runs, most recent first
+    List,
+
+    /// Show the details of a run
+    Show {
+        /// Identifier of the run
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+
+        /// Output format for the run details
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Print the log output of a run
+    Logs {
+        /// Identifier of the run
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+    },
+
+    /// Remove runs older than the given age
+    Prune {
+        /// Maximum age of the runs to keep, e.g. `30d` or `12h`
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        older_than: std::time::Duration,
+    },
+}
+
+/// Host resources granted to WebAssembly nodes, besides those granted by
+/// the `options.wasm` section of the workflow
+#[derive(Args, Clone, Debug, Default)]
+pub struct WasmArgs {
+    /// Directory readable by WebAssembly nodes, may be repeated
+    #[arg(long = "wasm-dir", value_name = "HOST:GUEST", value_parser = parse_dir_grant)]
+    dirs: Vec<(PathBuf, String)>,
+
+    /// Directory writable by WebAssembly nodes, may be repeated
+    #[arg(long = "wasm-writable-dir", value_name = "HOST:GUEST", value_parser = parse_dir_grant)]
+    writable_dirs: Vec<(PathBuf, String)>,
+
+    /// Environment variable visible to WebAssembly nodes, may be repeated
+    #[arg(long = "wasm-env", value_name = "NAME")]
+    env: Vec<String>,
+}
+
+impl WasmArgs {
+    /// Returns the permissions granted by the arguments
+    fn permissions(self) -> WasmPermissions {
+        let permissions = self
+            .dirs
+            .into_iter()
+            .fold(WasmPermissions::new(), |permissions, (host, guest)| {
+                permissions.with_read_only_dir(host, guest)
+            });
+        let permissions = self
+            .writable_dirs
+            .into_iter()
+            .fold(permissions, |permissions, (host, guest)| {
+                permissions.with_dir(host, guest)
+            });
+        self.env
+            .into_iter()
+            .fold(permissions, WasmPermissions::with_env)
+    }
+}
+
+/// Parse a `HOST:GUEST` directory grant
+///
+/// The guest path follows the last colon, so that host paths may contain
+/// colons.
+fn parse_dir_grant(raw: &str) -> Result<(PathBuf, String), String> {
+    match raw.rsplit_once(':') {
+        Some((host, guest)) if !host.is_empty() && !guest.is_empty() => {
+            Ok((PathBuf::from(host), guest.to_string()))
+        }
+        _ => Err(format!("invalid directory '{}', expected HOST:GUEST", raw)),
+    }
+}
+
+/// Parse a `KEY=VALUE` workflow input
+///
+/// The value is parsed as a YAML scalar, so `count=3` yields a number and
+/// `enabled=true` a boolean. An empty value yields an empty string.
+fn parse_input(raw: &str) -> Result<(String, Value), String> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("invalid input '{}', expected KEY=VALUE", raw))?;
+
+    if key.trim().is_empty() {
+        return Err(format!("invalid input '{}', missing key", raw));
+    }
+
+    let value = if value.is_empty() {
+        Value::String(String::new())
+    } else {
+        serde_yml::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+    };
+
+    Ok((key.trim().to_string(), value))
 }
 
 /// Supported output formats for workflow information
@@ -168,13 +357,26 @@ impl CliApp {
                 file,
                 validate,
                 format,
-            } => Self::handle_execute(file, validate, format),
+                state_dir,
+                inputs,
+                wasm,
+            } => Self::handle_execute(file, validate, format, state_dir, inputs, wasm),
+            Commands::Resume {
+                run_id,
+                format,
+                state_dir,
+                wasm,
+            } => Self::handle_resume(run_id, format, state_dir, wasm),
             Commands::List {
                 path: directory,
                 detailed,
             } => Self::handle_list(directory, detailed),
-            Commands::Validate { file } => Self::handle_validate(file),
+            Commands::Validate { file, lenient } => Self::handle_validate(file, lenient),
             Commands::Info { file } => Self::handle_info(file),
+            Commands::Migrate { file, dry_run } => Self::handle_migrate(file, dry_run),
+            Commands::Plugins { directory } => Self::handle_plugins(directory),
+            Commands::Schema { output } => Self::handle_schema(output),
+            Commands::Runs { command } => Self::handle_runs(command),
         };
 
         match result {
@@ -206,11 +408,20 @@ impl CliApp {
             level.into()
         };
 
-        tracing_subscriber::fmt()
-            .with_max_level(level)
-            .with_target(false)
-            .with_thread_ids(false)
-            .with_thread_names(false)
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_thread_ids(false)
+                    .with_thread_names(false),
+            )
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_target(false)
+                    .with_writer(RunLogWriter),
+            )
+            .with(LevelFilter::from_level(level))
             .init();
 
         Ok(())
@@ -223,26 +434,183 @@ impl CliApp {
     /// * `file` - Path to the workflow file
     /// * `validate` - Whether to validate before execution
     /// * `format` - Output format for results
+    /// * `state_dir` - Directory for run checkpoints, defaults to the local state directory
+    /// * `inputs` - Values provided for the workflow inputs
+    /// * `wasm` - Host resources granted to WebAssembly nodes
     ///
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an error on failure.
-    fn handle_execute(file: PathBuf, validate: bool, format: OutputFormat) -> anyhow::Result<()> {
+    fn handle_execute(
+        file: PathBuf,
+        validate: bool,
+        format: OutputFormat,
+        state_dir: Option<PathBuf>,
+        inputs: Vec<(String, Value)>,
+        wasm: WasmArgs,
+    ) -> anyhow::Result<()> {
         info!("Executing workflow from file: {:?}", file);
 
         if validate {
-            Self::handle_validate(file.clone())?;
+            Self::handle_validate(file.clone(), false)?;
         }
 
-        let mut heap = Heap::new();
-        let options = ExecuteWorkflowOptions::new(file);
+        let options = inputs.into_iter().fold(
+            ExecuteWorkflowOptions::new(file)
+                .with_run_id(generate_run_id())
+                .with_state_dir(state_dir.unwrap_or_else(paths::checkpoint_dir))
+                .with_wasm_permissions(wasm.permissions()),
+            |options, (name, value)| options.with_input(name, value),
+        );
 
-        match WorkflowExecutor::execute(options, &mut heap) {
-            Ok(workflow) => {
-                info!("Workflow executed successfully");
-                Self::output_workflow(&workflow, format)?;
-                Ok(())
+        Self::run_workflow(options, format)
+    }
+
+    /// Handle the resume command
+    ///
+    /// # Arguments
+    ///
+    /// * `run_id` - Identifier of the run to resume
+    /// * `format` - Output format for results
+    /// * `state_dir` - Directory for run checkpoints, defaults to the local state directory
+    /// * `wasm` - Host resources granted to WebAssembly nodes
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error on failure.
+    fn handle_resume(
+        run_id: String,
+        format: OutputFormat,
+        state_dir: Option<PathBuf>,
+        wasm: WasmArgs,
+    ) -> anyhow::Result<()> {
+        info!("Resuming run: {}", run_id);
+
+        let store = CheckpointStore::new(state_dir.unwrap_or_else(paths::checkpoint_dir));
+        let checkpoint = store.load(&run_id).map_err(|e| {
+            error!("Failed to load checkpoint: {}", e);
+            anyhow::anyhow!("No checkpoint found for run '{}': {}", run_id, e)
+        })?;
+
+        let options = ExecuteWorkflowOptions::from_checkpoint(checkpoint)
+            .with_state_dir(store.dir())
+            .with_wasm_permissions(wasm.permissions());
+
+        Self::run_workflow(options, format)
+    }
+
+    /// Returns the node types available to a workflow file
+    ///
+    /// Besides the built-in node types, these include the plugins found next
+    /// to the workflow file and on `PATH`.
+    fn registry(file: &Path) -> NodeRegistry {
+        plugins::registry(file.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Returns the node types available to a workflow file, without running
+    /// any plugin executable
+    ///
+    /// Plugin executables are only included once described, by a run or by
+    /// the plugins command.
+    fn cached_registry(file: &Path) -> NodeRegistry {
+        plugins::cached_registry(file.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Run a workflow in the background and report its outcome
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Configuration options for workflow execution
+    /// * `format` - Output format for results
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the workflow completed, or an error if it failed or was stopped.
+    fn run_workflow(options: ExecuteWorkflowOptions, format: OutputFormat) -> anyhow::Result<()> {
+        let run_id = options
+            .run_id()
+            .map(str::to_string)
+            .unwrap_or_else(generate_run_id);
+        let registry = Self::registry(options.path());
+        let options = options.with_run_id(run_id.clone()).with_registry(registry);
+        info!("Run id: {}", run_id);
+        let resumable = options.state_dir().is_some();
+
+        let store = RunStore::new(paths::runs_dir());
+        let mut record = store.load(&run_id).unwrap_or_else(|_| {
+            RunRecord::start(&run_id, options.path(), options.inputs().clone())
+        });
+        record.status = Status::Running;
+        Self::save_record(&store, &record);
+        match store.log_path(&run_id) {
+            Ok(path) => {
+                if let Err(e) = logging::start(&path) {
+                    warn!("Failed to capture run logs: {}", e);
+                }
             }
+            Err(e) => warn!("Failed to capture run logs: {}", e),
+        }
+
+        let path = options.path().clone();
+        let handle = WorkflowExecutor::spawn(options, Heap::new());
+        Self::install_interrupt_handler(handle.control().clone())?;
+
+        let result = handle.join();
+        match &result {
+            Ok(run) => record.complete(run),
+            Err(e) => record.fail(e),
+        }
+
+        let outcome = Self::report_run(result, &path, format, resumable);
+        logging::stop();
+        Self::save_record(&store, &record);
+
+        outcome
+    }
+
+    /// Report the outcome of a finished run
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - Result returned by the workflow thread
+    /// * `path` - Path to the workflow file that was executed
+    /// * `format` - Output format for results
+    /// * `resumable` - Whether the run was checkpointed and can be resumed
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the workflow completed, or an error if it failed or was stopped.
+    fn report_run(
+        result: WorkflowResult<WorkflowRun>,
+        path: &Path,
+        format: OutputFormat,
+        resumable: bool,
+    ) -> anyhow::Result<()> {
+        match result {
+            Ok(run) => match (run.status, &run.error) {
+                (Status::Stopped, _) => {
+                    warn!("Workflow stopped before completion");
+                    Self::output_run(&run, format)?;
+                    if resumable {
+                        info!("Resume this run with: colossus resume {}", run.run_id);
+                    }
+                    Err(anyhow::anyhow!("Workflow execution stopped"))
+                }
+                (_, Some(e)) => {
+                    error!("Failed to execute workflow: {}", e);
+                    Self::print_failure(&run, path);
+                    Self::output_run(&run, format)?;
+                    if resumable {
+                        info!("Resume this run with: colossus resume {}", run.run_id);
+                    }
+                    Err(anyhow::anyhow!("Workflow execution failed: {}", e))
+                }
+                _ => {
+                    info!("Workflow executed successfully");
+                    Self::output_workflow(&run.workflow, format)?;
+                    Ok(())
+                }
+            },
             Err(e) => {
                 error!("Failed to execute workflow: {}", e);
                 Err(anyhow::anyhow!("Workflow execution failed: {}", e))
@@ -250,6 +618,105 @@ impl CliApp {
         }
     }
 
+    /// Install a Ctrl-C handler that gracefully stops the running workflow
+    ///
+    /// The first interrupt lets the in-flight node finish before stopping;
+    /// a second interrupt exits immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `control` - Execution control of the running workflow
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if the handler cannot be installed.
+    fn install_interrupt_handler(control: ExecutionControl) -> anyhow::Result<()> {
+        ctrlc::set_handler(move || {
+            if control.is_stopped() {
+                eprintln!("Interrupted again, exiting immediately");
+                std::process::exit(130);
+            }
+
+            warn!(
+                "Interrupt received, stopping after the current node (press Ctrl-C again to exit)"
+            );
+            control.stop();
+        })?;
+
+        Ok(())
+    }
+
+    /// Save a run record, logging instead of failing on errors
+    ///
+    /// Recording history must never prevent a workflow from running.
+    fn save_record(store: &RunStore, record: &RunRecord) {
+        if let Err(e) = store.save(record) {
+            warn!("Failed to record run '{}': {}", record.run_id, e);
+        }
+    }
+
+    /// Handle the runs command
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The run history operation to perform
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error on failure.
+    fn handle_runs(command: RunsCommand) -> anyhow::Result<()> {
+        let store = RunStore::new(paths::runs_dir());
+
+        match command {
+            RunsCommand::List => {
+                let records = store.list()?;
+                if records.is_empty() {
+                    println!("No runs recorded in {:?}", store.dir());
+                    return Ok(());
+                }
+
+                println!("Recorded runs ({}):", records.len());
+                for record in records {
+                    println!(
+                        "  {}  {:<9} {}  {:>8}  {}",
+                        record.run_id,
+                        format!("{:?}", record.status),
+                        record.started_at.format("%Y-%m-%d %H:%M:%S"),
+                        record
+                            .duration_ms
+                            .map(|ms| format!("{}ms", ms))
+                            .unwrap_or_else(|| "-".to_string()),
+                        record
+                            .workflow_name
+                            .unwrap_or_else(|| record.workflow_path.display().to_string()),
+                    );
+                }
+            }
+            RunsCommand::Show { run_id, format } => {
+                let record = store.load(&run_id)?;
+                Self::output_record(&record, format)?;
+            }
+            RunsCommand::Logs { run_id } => {
+                print!("{}", store.read_logs(&run_id)?);
+            }
+            RunsCommand::Prune { older_than } => {
+                let cutoff = Utc::now() - chrono::Duration::from_std(older_than)?;
+                let removed = store.prune(cutoff)?;
+
+                let checkpoints = CheckpointStore::new(paths::checkpoint_dir());
+                for run_id in &removed {
+                    if let Err(e) = checkpoints.remove(run_id) {
+                        warn!("Failed to remove checkpoint of run '{}': {}", run_id, e);
+                    }
+                }
+
+                println!("Removed {} run(s)", removed.len());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Handle the list command
     ///
     /// # Arguments
@@ -299,35 +766,45 @@ impl CliApp {
     /// # Arguments
     ///
     /// * `file` - Path to the workflow file
+    /// * `lenient` - Whether unknown fields are reported as warnings
     ///
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an error on failure.
-    fn handle_validate(file: PathBuf) -> anyhow::Result<()> {
+    fn handle_validate(file: PathBuf, lenient: bool) -> anyhow::Result<()> {
         info!("Validating workflow file: {:?}", file);
 
         if !file.exists() {
             return Err(anyhow::anyhow!("Workflow file does not exist: {:?}", file));
         }
 
-        // Try to parse the workflow to validate it
-        let mut heap = Heap::new();
-        let options = ExecuteWorkflowOptions::new(file);
+        let (source, workflow) = Self::load_source(&file).map_err(|e| {
+            error!("Failed to load workflow: {}", e);
+            anyhow::anyhow!("Workflow validation failed: {}", e)
+        })?;
+        let report = WorkflowValidator::new()
+            .with_registry(Self::cached_registry(&file))
+            .with_lenient(lenient)
+            .validate_source(&source)?;
 
-        match WorkflowExecutor::execute(options, &mut heap) {
-            Ok(workflow) => {
-                info!("Workflow validation successful");
-                println!("✓ Workflow is valid");
-                println!("  Name: {}", workflow.name_or("Unnamed"));
-                println!("  Version: {}", workflow.version_or("Unknown"));
-                println!("  Nodes: {}", workflow.node_count());
-                Ok(())
-            }
-            Err(e) => {
-                error!("Workflow validation failed: {}", e);
-                Err(anyhow::anyhow!("Workflow validation failed: {}", e))
-            }
+        for diagnostic in report.diagnostics() {
+            println!("{}\n", diagnostic.render(&source));
         }
+
+        if !report.is_valid() {
+            error!("Workflow validation failed");
+            return Err(anyhow::anyhow!(
+                "Workflow validation failed with {} error(s)",
+                report.errors().count()
+            ));
+        }
+
+        info!("Workflow validation successful");
+        println!("✓ Workflow is valid");
+        println!("  Name: {}", workflow.name_or("Unnamed"));
+        println!("  Version: {}", workflow.version_or("Unknown"));
+        println!("  Nodes: {}", workflow.node_count());
+        Ok(())
     }
 
     /// Handle the info command
@@ -346,12 +823,19 @@ impl CliApp {
             return Err(anyhow::anyhow!("Workflow file does not exist: {:?}", file));
         }
 
-        let mut heap = Heap::new();
-        let options = ExecuteWorkflowOptions::new(file);
-
-        match WorkflowExecutor::execute(options, &mut heap) {
-            Ok(workflow) => {
+        match Self::load_source(&file) {
+            Ok((source, workflow)) => {
                 Self::output_workflow(&workflow, OutputFormat::Text)?;
+
+                let report = WorkflowValidator::new()
+                    .with_registry(Self::cached_registry(&file))
+                    .validate_source(&source)?;
+                if !report.is_empty() {
+                    println!("Diagnostics:");
+                    for diagnostic in report.diagnostics() {
+                        println!("{}\n", diagnostic.render(&source));
+                    }
+                }
                 Ok(())
             }
             Err(e) => {
@@ -361,6 +845,171 @@ impl CliApp {
         }
     }
 
+    /// Handle the migrate command
+    ///
+    /// The original file is kept next to the migrated one with a `.bak`
+    /// extension appended, since comments and formatting are not preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Path to the workflow file
+    /// * `dry_run` - Whether to print the migrated workflow instead of writing it
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error on failure.
+    fn handle_migrate(file: PathBuf, dry_run: bool) -> anyhow::Result<()> {
+        info!("Migrating workflow file: {:?}", file);
+
+        let source = SourceFile::read(&file)?;
+        let mut document = source.document()?;
+        let report = migrate(&mut document)?;
+
+        if !report.is_migrated() {
+            println!(
+                "✓ {} already uses format version {}",
+                file.display(),
+                report.to
+            );
+            return Ok(());
+        }
+
+        let migrated = match source.format() {
+            FileFormat::Json => serde_json::to_string_pretty(&document)? + "\n",
+            FileFormat::Yaml => serde_yml::to_string(&document)?,
+        };
+        source.format().parse_content(&migrated).map_err(|e| {
+            anyhow::anyhow!("Migrated workflow is invalid, file left unchanged: {}", e)
+        })?;
+
+        if dry_run {
+            print!("{}", migrated);
+            return Ok(());
+        }
+
+        let mut backup = file.clone().into_os_string();
+        backup.push(".bak");
+        std::fs::write(&backup, source.content())?;
+        std::fs::write(&file, migrated)?;
+
+        println!(
+            "✓ Migrated {} from format version {} to {}",
+            file.display(),
+            report.from,
+            report.to
+        );
+        for description in &report.applied {
+            println!("  - {}", description);
+        }
+        println!("  Original saved to {}", PathBuf::from(backup).display());
+        Ok(())
+    }
+
+    /// Handle the plugins command
+    ///
+    /// Unlike the other commands inspecting workflows, this one runs the
+    /// `describe` handshake of the plugins missing from the plugin cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - Directory of the workflows using the plugins
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error on failure.
+    fn handle_plugins(directory: PathBuf) -> anyhow::Result<()> {
+        let discovered = plugins::discover(&directory);
+        if discovered.is_empty() {
+            println!("No plugins found");
+            return Ok(());
+        }
+
+        println!("Plugins:");
+        for plugin in &discovered {
+            let kind = match plugin {
+                plugins::Discovered::Executable(_) => "executable",
+                plugins::Discovered::Wasm(_) => "wasm",
+            };
+            println!(
+                "  {} ({}) - {}",
+                plugin.node_type(),
+                kind,
+                plugin.path().display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Handle the schema command
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - File to write the schema to, or `None` for standard output
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error on failure.
+    fn handle_schema(output: Option<PathBuf>) -> anyhow::Result<()> {
+        let schema = workflow_schema(&plugins::cached_registry(Path::new("")));
+        let json = serde_json::to_string_pretty(&schema)?;
+
+        match output {
+            Some(path) => {
+                std::fs::write(&path, json + "\n")?;
+                info!("Workflow schema written to {:?}", path);
+            }
+            None => println!("{}", json),
+        }
+
+        Ok(())
+    }
+
+    /// Read and parse a workflow file, keeping its source for diagnostics
+    ///
+    /// Parse errors are printed with the offending source snippet before
+    /// being returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Path to the workflow file
+    ///
+    /// # Returns
+    ///
+    /// Returns the source file together with the parsed workflow.
+    fn load_source(file: &Path) -> WorkflowResult<(SourceFile, Workflow)> {
+        let source = SourceFile::read(file)?;
+
+        match source.parse() {
+            Ok(workflow) => Ok((source, workflow)),
+            Err(e) => {
+                let mut diagnostic = Diagnostic::from(&e);
+                diagnostic.locate(&source);
+                eprintln!("{}\n", diagnostic.render(&source));
+                Err(e)
+            }
+        }
+    }
+
+    /// Print the diagnostic of a failed run with the offending source snippet
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The failed run
+    /// * `path` - Path to the workflow file that was executed
+    fn print_failure(run: &WorkflowRun, path: &Path) {
+        let Some(mut diagnostic) = run.diagnostic() else {
+            return;
+        };
+
+        match SourceFile::read(path) {
+            Ok(source) => {
+                diagnostic.locate(&source);
+                eprintln!("{}\n", diagnostic.render(&source));
+            }
+            Err(_) => eprintln!("{}\n", diagnostic),
+        }
+    }
+
     /// Output workflow information in the specified format
     ///
     /// # Arguments
@@ -394,7 +1043,15 @@ impl CliApp {
         println!("Name: {}", workflow.name_or("Unnamed"));
         println!("ID: {}", workflow.id.as_deref().unwrap_or("Not specified"));
         println!("Version: {}", workflow.version_or("Unknown"));
-        println!("Nodes: {}", workflow.node_count());
+        if workflow.expanded_node_count() != workflow.node_count() {
+            println!(
+                "Nodes: {} ({} after matrix expansion)",
+                workflow.node_count(),
+                workflow.expanded_node_count()
+            );
+        } else {
+            println!("Nodes: {}", workflow.node_count());
+        }
 
         if let Some(inputs) = &workflow.inputs {
             println!("Inputs: {}", inputs.len());
@@ -421,6 +1078,21 @@ impl CliApp {
                 if let Some(when) = &node.when {
                     println!("    Condition: {}", when);
                 }
+                if node.matrix.is_some() {
+                    println!("    Matrix: {} combination(s)", node.run_count());
+                }
+            }
+        }
+
+        for (label, handlers) in [
+            ("On Failure", &workflow.on_failure),
+            ("Finally", &workflow.finally),
+        ] {
+            if let Some(handlers) = handlers {
+                println!("{}:", label);
+                for node in handlers {
+                    println!("  - {} (type: {})", node.id, node.node_type);
+                }
             }
         }
 
@@ -457,6 +1129,143 @@ impl CliApp {
         Ok(())
     }
 
+    /// Output the partial results of an interrupted or failed run
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The workflow run to output
+    /// * `format` - The output format
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error on failure.
+    fn output_run(run: &WorkflowRun, format: OutputFormat) -> anyhow::Result<()> {
+        let report = serde_json::json!({
+            "workflow": run.workflow.name_or("Unnamed"),
+            "status": run.status,
+            "nodes": run.nodes,
+            "on_failure": run.on_failure,
+            "finally": run.finally,
+            "outputs": run.outputs,
+            "error": run.error.as_ref().map(|e| e.to_string()),
+        });
+
+        match format {
+            OutputFormat::Text => {
+                println!("Run Summary");
+                println!("===========");
+                println!("Workflow: {}", run.workflow.name_or("Unnamed"));
+                println!("Status: {:?}", run.status);
+                for (label, nodes) in [
+                    ("Nodes", &run.nodes),
+                    ("On Failure", &run.on_failure),
+                    ("Finally", &run.finally),
+                ] {
+                    if label != "Nodes" && nodes.is_empty() {
+                        continue;
+                    }
+                    println!("{}:", label);
+                    for node in nodes {
+                        match &node.error {
+                            Some(error) => {
+                                println!("  - {}: {:?} ({})", node.id, node.status, error)
+                            }
+                            None => println!("  - {}: {:?}", node.id, node.status),
+                        }
+                    }
+                }
+                if !run.outputs.is_empty() {
+                    println!("Outputs:");
+                    let mut outputs: Vec<_> = run.outputs.iter().collect();
+                    outputs.sort_by(|a, b| a.0.cmp(b.0));
+                    for (key, value) in outputs {
+                        println!("  {}: {}", key, serde_json::to_string(value)?);
+                    }
+                }
+            }
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            OutputFormat::Yaml => println!("{}", serde_yml::to_string(&report)?),
+        }
+
+        Ok(())
+    }
+
+    /// Output a recorded run in the specified format
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The run record to output
+    /// * `format` - The output format
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error on failure.
+    fn output_record(record: &RunRecord, format: OutputFormat) -> anyhow::Result<()> {
+        match format {
+            OutputFormat::Text => {
+                println!("Run: {}", record.run_id);
+                println!("Workflow: {}", record.workflow_path.display());
+                if let Some(name) = &record.workflow_name {
+                    println!("Name: {}", name);
+                }
+                println!("Status: {:?}", record.status);
+                println!("Started: {}", record.started_at.to_rfc3339());
+                if let Some(finished_at) = record.finished_at {
+                    println!("Finished: {}", finished_at.to_rfc3339());
+                }
+                if let Some(duration_ms) = record.duration_ms {
+                    println!("Duration: {}ms", duration_ms);
+                }
+                if !record.inputs.is_empty() {
+                    println!("Inputs:");
+                    let mut inputs: Vec<_> = record.inputs.iter().collect();
+                    inputs.sort_by(|a, b| a.0.cmp(b.0));
+                    for (key, value) in inputs {
+                        println!("  {}: {}", key, serde_json::to_string(value)?);
+                    }
+                }
+                for (label, nodes) in [
+                    ("Nodes", &record.nodes),
+                    ("On Failure", &record.on_failure),
+                    ("Finally", &record.finally),
+                ] {
+                    if label != "Nodes" && nodes.is_empty() {
+                        continue;
+                    }
+                    println!("{}:", label);
+                    for node in nodes {
+                        let duration = node
+                            .duration_ms
+                            .map(|ms| format!(" in {}ms", ms))
+                            .unwrap_or_default();
+                        match &node.error {
+                            Some(error) => println!(
+                                "  - {}: {:?}{} ({})",
+                                node.id, node.status, duration, error
+                            ),
+                            None => println!("  - {}: {:?}{}", node.id, node.status, duration),
+                        }
+                    }
+                }
+                if !record.outputs.is_empty() {
+                    println!("Outputs:");
+                    let mut outputs: Vec<_> = record.outputs.iter().collect();
+                    outputs.sort_by(|a, b| a.0.cmp(b.0));
+                    for (key, value) in outputs {
+                        println!("  {}: {}", key, serde_json::to_string(value)?);
+                    }
+                }
+                if let Some(error) = &record.error {
+                    println!("Error: {}", error);
+                }
+            }
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(record)?),
+            OutputFormat::Yaml => println!("{}", serde_yml::to_string(record)?),
+        }
+
+        Ok(())
+    }
+
     /// Discover workflow files in a directory
     ///
     /// # Arguments
@@ -491,7 +1300,7 @@ impl CliApp {
     /// # Returns
     ///
     /// Returns `true` if the file is a workflow file, `false` otherwise.
-    fn is_workflow_file(path: &PathBuf) -> bool {
+    fn is_workflow_file(path: &Path) -> bool {
         path.extension()
             .and_then(|ext| ext.to_str())
             .map(|ext| matches!(ext.to_lowercase().as_str(), "yml" | "yaml" | "json"))
@@ -508,15 +1317,21 @@ impl CliApp {
     ///
     /// Returns workflow information as a string, or an error on failure.
     fn get_workflow_info(path: &PathBuf) -> anyhow::Result<String> {
-        let mut heap = Heap::new();
-        let options = ExecuteWorkflowOptions::new(path);
-
-        let workflow = WorkflowExecutor::execute(options, &mut heap)?;
+        let source = SourceFile::read(path)?;
+        let workflow = source.parse()?;
+        let report = WorkflowValidator::new()
+            .with_registry(Self::cached_registry(path))
+            .validate_source(&source)?;
+        let status = match report.errors().count() {
+            0 => "valid".to_string(),
+            errors => format!("{} error(s)", errors),
+        };
 
         Ok(format!(
-            "  {} ({} nodes) - {}",
+            "  {} ({} nodes, {}) - {}",
             workflow.name_or("Unnamed"),
             workflow.node_count(),
+            status,
             path.file_name().unwrap().to_string_lossy()
         ))
     }
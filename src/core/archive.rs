@@ -0,0 +1,894 @@
+//! Archive formats
+//!
+//! This module reads and writes tar, gzip-compressed tar and zip archives
+//! with the `tar`, `flate2` and `zip` crates. Archives hold files and
+//! directories, with their Unix permissions and modification times; other
+//! entries, such as symbolic links, are skipped when reading.
+//!
+//! Entries are streamed: an [`ArchiveWriter`] copies the content of each
+//! file from a reader, and an [`ArchiveReader`] passes the content of each
+//! entry to a visitor as a reader, so archives are never held in memory.
+//! Reading is bounded by [`SizeLimits`] on the decompressed size of the
+//! archive and of each entry, so that zip and gzip bombs fail early.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::io::{Cursor, Read};
+//!
+//! use colossus::core::archive::{ArchiveFormat, ArchiveReader, ArchiveWriter, Entry};
+//!
+//! let mut writer = ArchiveWriter::new(ArchiveFormat::Zip, Cursor::new(Vec::new()));
+//! writer
+//!     .append(&Entry::file("bin/app", 4).with_mode(0o755), &b"\x7fELF"[..])
+//!     .unwrap();
+//! let bytes = writer.finish().unwrap();
+//!
+//! let mut reader = ArchiveReader::new(ArchiveFormat::Zip, bytes);
+//! reader
+//!     .for_each(|entry, content| {
+//!         assert_eq!(entry.path, "bin/app");
+//!         assert_eq!(entry.mode, 0o755);
+//!         let mut bytes = Vec::new();
+//!         content.read_to_end(&mut bytes)?;
+//!         assert_eq!(bytes, b"\x7fELF");
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Names of the supported archive formats
+pub const ARCHIVE_FORMATS: [&str; 3] = ["tar", "tar.gz", "zip"];
+
+/// An archive format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// Uncompressed tar archive
+    Tar,
+    /// Tar archive compressed with gzip
+    TarGz,
+    /// Zip archive
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Infers the format from the extension of a path
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::archive::ArchiveFormat;
+    ///
+    /// assert_eq!(ArchiveFormat::from_path("dist/app.tar.gz"), Some(ArchiveFormat::TarGz));
+    /// assert_eq!(ArchiveFormat::from_path("app.tgz"), Some(ArchiveFormat::TarGz));
+    /// assert_eq!(ArchiveFormat::from_path("app.gz"), None);
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = ArchiveError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "tar" => Ok(Self::Tar),
+            "tar.gz" | "tgz" => Ok(Self::TarGz),
+            "zip" => Ok(Self::Zip),
+            _ => Err(ArchiveError::Unknown(name.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+            Self::Zip => "zip",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Errors that can occur while reading or writing an archive
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ArchiveError {
+    /// The format name is not supported
+    #[error("Unknown archive format '{0}' (available: tar, tar.gz, zip)")]
+    Unknown(String),
+
+    /// The archive is malformed
+    #[error("Invalid {format} archive: {message}")]
+    Invalid {
+        format: ArchiveFormat,
+        message: String,
+    },
+
+    /// The archive is larger than its limit once decompressed
+    #[error("{format} archive is larger than {limit} bytes once decompressed")]
+    TooLarge { format: ArchiveFormat, limit: u64 },
+
+    /// An entry is larger than its limit once decompressed
+    #[error("{format} archive entry {path} is larger than {limit} bytes once decompressed")]
+    EntryTooLarge {
+        format: ArchiveFormat,
+        path: String,
+        limit: u64,
+    },
+
+    /// Reading or writing the underlying file failed
+    #[error("{0}")]
+    Io(String),
+}
+
+/// Result type for archive operations
+pub type ArchiveResult<T> = Result<T, ArchiveError>;
+
+/// Default limit of the decompressed size of an archive, 4 GiB
+pub const DEFAULT_MAX_SIZE: u64 = 4 << 30;
+
+/// Default limit of the decompressed size of an archive entry, 1 GiB
+pub const DEFAULT_MAX_ENTRY_SIZE: u64 = 1 << 30;
+
+/// Limits on the decompressed size of an archive being read
+///
+/// The total limit counts the content of the entries of zip archives, and
+/// the whole decompressed stream of tar archives, headers included.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::archive::SizeLimits;
+///
+/// let limits = SizeLimits::new().with_total(100 << 20).with_entry(10 << 20);
+/// assert_eq!(limits.total(), 100 << 20);
+/// assert_eq!(limits.entry(), 10 << 20);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    total: u64,
+    entry: u64,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        Self {
+            total: DEFAULT_MAX_SIZE,
+            entry: DEFAULT_MAX_ENTRY_SIZE,
+        }
+    }
+}
+
+impl SizeLimits {
+    /// Creates the default limits, 4 GiB in total and 1 GiB per entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the limit of the decompressed size of the archive, in bytes
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = total;
+        self
+    }
+
+    /// Sets the limit of the decompressed size of each entry, in bytes
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_entry(mut self, entry: u64) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    /// Returns the limit of the decompressed size of the archive
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the limit of the decompressed size of each entry
+    pub fn entry(&self) -> u64 {
+        self.entry
+    }
+
+    /// Checks the declared size of an entry, adding it to the total so far
+    fn check(&self, format: ArchiveFormat, entry: &Entry, total: &mut u64) -> ArchiveResult<()> {
+        if entry.size > self.entry {
+            return Err(ArchiveError::EntryTooLarge {
+                format,
+                path: entry.path.clone(),
+                limit: self.entry,
+            });
+        }
+        *total = total.saturating_add(entry.size);
+        if *total > self.total {
+            return Err(ArchiveError::TooLarge {
+                format,
+                limit: self.total,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Kind of an archive entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A file
+    File,
+    /// A directory
+    Directory,
+}
+
+/// An entry of an archive, without its content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Path of the entry as stored, with `/` separators
+    pub path: String,
+    /// Kind of the entry
+    pub kind: EntryKind,
+    /// Size of the content of the entry, in bytes
+    pub size: u64,
+    /// Unix permissions of the entry
+    pub mode: u32,
+    /// Modification time of the entry, in seconds since the Unix epoch
+    pub modified: i64,
+}
+
+impl Entry {
+    /// Creates a file entry of `size` bytes, readable by everyone
+    pub fn file(path: impl Into<String>, size: u64) -> Self {
+        Self {
+            path: path.into(),
+            kind: EntryKind::File,
+            size,
+            mode: 0o644,
+            modified: 0,
+        }
+    }
+
+    /// Creates a directory entry, readable by everyone
+    pub fn directory(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            kind: EntryKind::Directory,
+            size: 0,
+            mode: 0o755,
+            modified: 0,
+        }
+    }
+
+    /// Sets the Unix permissions of the entry
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = mode & 0o7777;
+        self
+    }
+
+    /// Sets the modification time of the entry, in seconds since the epoch
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_modified(mut self, modified: i64) -> Self {
+        self.modified = modified;
+        self
+    }
+}
+
+/// A writer adding entries to an archive
+///
+/// Tar archives are written with GNU headers, and zip archives with
+/// DEFLATE compression and ZIP64 records for files of 4 GiB or more.
+pub struct ArchiveWriter<W: Write + Seek> {
+    inner: Writer<W>,
+}
+
+enum Writer<W: Write + Seek> {
+    Tar(tar::Builder<W>),
+    TarGz(tar::Builder<GzEncoder<W>>),
+    Zip(Box<ZipWriter<W>>),
+}
+
+impl<W: Write + Seek> ArchiveWriter<W> {
+    /// Creates a writer of an archive in the given format
+    pub fn new(format: ArchiveFormat, writer: W) -> Self {
+        let inner = match format {
+            ArchiveFormat::Tar => Writer::Tar(tar::Builder::new(writer)),
+            ArchiveFormat::TarGz => Writer::TarGz(tar::Builder::new(GzEncoder::new(
+                writer,
+                Compression::default(),
+            ))),
+            ArchiveFormat::Zip => Writer::Zip(Box::new(ZipWriter::new(writer))),
+        };
+        Self { inner }
+    }
+
+    /// Adds an entry, copying the `size` bytes of a file from `content`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArchiveError::Io` if reading the content or writing the
+    /// archive fails.
+    pub fn append(&mut self, entry: &Entry, content: impl Read) -> ArchiveResult<()> {
+        let content = content.take(entry.size);
+        let appended = match &mut self.inner {
+            Writer::Tar(builder) => append_tar(builder, entry, content),
+            Writer::TarGz(builder) => append_tar(builder, entry, content),
+            Writer::Zip(zip) => append_zip(zip, entry, content),
+        };
+        appended.map_err(|e| ArchiveError::Io(format!("{}: {}", entry.path, e)))
+    }
+
+    /// Writes the end of the archive
+    ///
+    /// # Returns
+    ///
+    /// Returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArchiveError::Io` if writing the archive fails.
+    pub fn finish(self) -> ArchiveResult<W> {
+        let finished = match self.inner {
+            Writer::Tar(builder) => builder.into_inner(),
+            Writer::TarGz(builder) => builder.into_inner().and_then(GzEncoder::finish),
+            Writer::Zip(zip) => zip.finish().map_err(io::Error::other),
+        };
+        finished.map_err(|e| ArchiveError::Io(e.to_string()))
+    }
+}
+
+/// Adds an entry to a tar archive
+fn append_tar<W: Write>(
+    builder: &mut tar::Builder<W>,
+    entry: &Entry,
+    content: impl Read,
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_mode(entry.mode);
+    header.set_mtime(entry.modified.max(0) as u64);
+    match entry.kind {
+        EntryKind::File => {
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(entry.size);
+            builder.append_data(&mut header, &entry.path, content)
+        }
+        EntryKind::Directory => {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            let path = format!("{}/", entry.path.trim_end_matches('/'));
+            builder.append_data(&mut header, path, io::empty())
+        }
+    }
+}
+
+/// Adds an entry to a zip archive
+fn append_zip<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    entry: &Entry,
+    mut content: impl Read,
+) -> io::Result<()> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(entry.mode)
+        .last_modified_time(dos_time(entry.modified))
+        .large_file(entry.size >= u32::MAX as u64);
+    match entry.kind {
+        EntryKind::File => {
+            zip.start_file(entry.path.as_str(), options)
+                .map_err(io::Error::other)?;
+            io::copy(&mut content, zip)?;
+        }
+        EntryKind::Directory => zip
+            .add_directory(entry.path.trim_end_matches('/'), options)
+            .map_err(io::Error::other)?,
+    }
+    Ok(())
+}
+
+/// A reader of the entries of an archive, within size limits
+pub struct ArchiveReader<R: Read + Seek> {
+    format: ArchiveFormat,
+    reader: R,
+    limits: SizeLimits,
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Creates a reader of an archive in the given format, with the default
+    /// size limits
+    pub fn new(format: ArchiveFormat, reader: R) -> Self {
+        Self {
+            format,
+            reader,
+            limits: SizeLimits::default(),
+        }
+    }
+
+    /// Sets the limits on the decompressed size of the archive
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_limits(mut self, limits: SizeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Lists the files and directories of the archive
+    ///
+    /// The content of zip entries is not decompressed, while tar archives
+    /// are read to the end.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`for_each`](Self::for_each).
+    pub fn entries(&mut self) -> ArchiveResult<Vec<Entry>> {
+        let mut entries = Vec::new();
+        self.for_each(|entry, _| {
+            entries.push(entry.clone());
+            Ok(())
+        })?;
+        Ok(entries)
+    }
+
+    /// Reads the files and directories of the archive in order, passing
+    /// each with a reader of its content to `visit`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArchiveError::Invalid` if the archive is malformed,
+    /// `ArchiveError::TooLarge` or `ArchiveError::EntryTooLarge` if it
+    /// exceeds the size limits, or `ArchiveError::Io` if `visit` fails.
+    pub fn for_each<F>(&mut self, mut visit: F) -> ArchiveResult<()>
+    where
+        F: FnMut(&Entry, &mut dyn Read) -> io::Result<()>,
+    {
+        let format = self.format;
+        let limits = self.limits;
+        // Errors found while reading content, reported instead of the
+        // errors of the visitor reading it
+        let failure = Cell::new(None);
+
+        self.reader
+            .seek(SeekFrom::Start(0))
+            .map_err(|e| ArchiveError::Io(e.to_string()))?;
+        let read = match format {
+            ArchiveFormat::Tar => {
+                let stream = Counted::new(&mut self.reader, format, limits, &failure);
+                read_tar(stream, format, limits, &failure, &mut visit)
+            }
+            ArchiveFormat::TarGz => {
+                let stream =
+                    Counted::new(GzDecoder::new(&mut self.reader), format, limits, &failure);
+                read_tar(stream, format, limits, &failure, &mut visit)
+            }
+            ArchiveFormat::Zip => read_zip(&mut self.reader, limits, &failure, &mut visit),
+        };
+        read.map_err(|e| failure.take().unwrap_or(e))
+    }
+}
+
+/// Reads the entries of a decompressed tar stream
+fn read_tar(
+    stream: impl Read,
+    format: ArchiveFormat,
+    limits: SizeLimits,
+    failure: &Cell<Option<ArchiveError>>,
+    visit: &mut dyn FnMut(&Entry, &mut dyn Read) -> io::Result<()>,
+) -> ArchiveResult<()> {
+    let invalid = |e: io::Error| invalid(format, e.to_string());
+    let mut archive = tar::Archive::new(stream);
+    let mut total = 0;
+
+    for item in archive.entries().map_err(invalid)? {
+        let mut item = item.map_err(invalid)?;
+        let header = item.header();
+        let (kind, default_mode) = match header.entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous => (EntryKind::File, 0o644),
+            tar::EntryType::Directory => (EntryKind::Directory, 0o755),
+            _ => continue,
+        };
+        let entry = Entry {
+            path: String::from_utf8_lossy(&item.path_bytes())
+                .trim_end_matches('/')
+                .to_string(),
+            kind,
+            size: match kind {
+                EntryKind::File => item.size(),
+                EntryKind::Directory => 0,
+            },
+            mode: header.mode().map_or(default_mode, |mode| mode & 0o7777),
+            modified: header.mtime().unwrap_or(0) as i64,
+        };
+        limits.check(format, &entry, &mut total)?;
+
+        let mut content = Bounded::new(&mut item, format, &entry, failure);
+        visit(&entry, &mut content).map_err(|e| ArchiveError::Io(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Reads the entries of a zip archive
+fn read_zip(
+    reader: impl Read + Seek,
+    limits: SizeLimits,
+    failure: &Cell<Option<ArchiveError>>,
+    visit: &mut dyn FnMut(&Entry, &mut dyn Read) -> io::Result<()>,
+) -> ArchiveResult<()> {
+    let format = ArchiveFormat::Zip;
+    let invalid = |e: zip::result::ZipError| invalid(format, e.to_string());
+    let mut archive = ZipArchive::new(reader).map_err(invalid)?;
+    let mut total = 0;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(invalid)?;
+        if file.is_symlink() {
+            continue;
+        }
+        let (kind, default_mode) = match file.is_dir() {
+            true => (EntryKind::Directory, 0o755),
+            false => (EntryKind::File, 0o644),
+        };
+        let entry = Entry {
+            path: file.name().trim_end_matches('/').to_string(),
+            kind,
+            size: match kind {
+                EntryKind::File => file.size(),
+                EntryKind::Directory => 0,
+            },
+            mode: file
+                .unix_mode()
+                .map(|mode| mode & 0o7777)
+                .filter(|mode| *mode != 0)
+                .unwrap_or(default_mode),
+            modified: file.last_modified().map_or(0, unix_time),
+        };
+        limits.check(format, &entry, &mut total)?;
+
+        let mut content = Bounded::new(&mut file, format, &entry, failure);
+        visit(&entry, &mut content).map_err(|e| ArchiveError::Io(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Creates an error of a malformed archive
+fn invalid(format: ArchiveFormat, message: impl Into<String>) -> ArchiveError {
+    ArchiveError::Invalid {
+        format,
+        message: message.into(),
+    }
+}
+
+/// Records the first error found while reading content
+fn record(failure: &Cell<Option<ArchiveError>>, error: ArchiveError) -> io::Error {
+    let message = error.to_string();
+    let first = failure.take().unwrap_or(error);
+    failure.set(Some(first));
+    io::Error::other(message)
+}
+
+/// A decompressed stream failing once it exceeds the total size limit
+struct Counted<'a, R> {
+    inner: R,
+    remaining: u64,
+    format: ArchiveFormat,
+    limit: u64,
+    failure: &'a Cell<Option<ArchiveError>>,
+}
+
+impl<'a, R> Counted<'a, R> {
+    fn new(
+        inner: R,
+        format: ArchiveFormat,
+        limits: SizeLimits,
+        failure: &'a Cell<Option<ArchiveError>>,
+    ) -> Self {
+        Self {
+            inner,
+            remaining: limits.total,
+            format,
+            limit: limits.total,
+            failure,
+        }
+    }
+}
+
+impl<R: Read> Read for Counted<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read as u64 > self.remaining {
+            let error = ArchiveError::TooLarge {
+                format: self.format,
+                limit: self.limit,
+            };
+            return Err(record(self.failure, error));
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// The content of an entry, failing if it is larger than declared
+///
+/// The declared sizes are checked against the limits before the content is
+/// read, so an entry stays within them even if its header lies.
+struct Bounded<'a, R> {
+    inner: R,
+    remaining: u64,
+    format: ArchiveFormat,
+    path: &'a str,
+    failure: &'a Cell<Option<ArchiveError>>,
+}
+
+impl<'a, R> Bounded<'a, R> {
+    fn new(
+        inner: R,
+        format: ArchiveFormat,
+        entry: &'a Entry,
+        failure: &'a Cell<Option<ArchiveError>>,
+    ) -> Self {
+        Self {
+            inner,
+            remaining: entry.size,
+            format,
+            path: &entry.path,
+            failure,
+        }
+    }
+}
+
+impl<R: Read> Read for Bounded<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = match self.inner.read(buf) {
+            Ok(read) => read,
+            Err(e) => {
+                let message = format!("{}: {}", self.path, e);
+                return Err(record(self.failure, invalid(self.format, message)));
+            }
+        };
+        if read as u64 > self.remaining {
+            let message = format!("{} is larger than its declared size", self.path);
+            return Err(record(self.failure, invalid(self.format, message)));
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Converts a Unix time to the MS-DOS time of zip entries
+///
+/// MS-DOS times start in 1980 and have a two-second resolution.
+fn dos_time(modified: i64) -> zip::DateTime {
+    DateTime::from_timestamp(modified, 0)
+        .map(|time| time.naive_utc())
+        .and_then(|time| {
+            zip::DateTime::from_date_and_time(
+                u16::try_from(time.year()).ok()?,
+                time.month() as u8,
+                time.day() as u8,
+                time.hour() as u8,
+                time.minute() as u8,
+                time.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Converts the MS-DOS time of a zip entry to a Unix time
+fn unix_time(time: zip::DateTime) -> i64 {
+    NaiveDate::from_ymd_opt(time.year() as i32, time.month() as u32, time.day() as u32)
+        .and_then(|date| {
+            date.and_hms_opt(
+                time.hour() as u32,
+                time.minute() as u32,
+                time.second() as u32,
+            )
+        })
+        .map_or(0, |time| time.and_utc().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn entries() -> Vec<(Entry, Vec<u8>)> {
+        vec![
+            (
+                Entry::directory("bin").with_modified(1_700_000_000),
+                Vec::new(),
+            ),
+            (
+                Entry::file("bin/app", 19)
+                    .with_mode(0o755)
+                    .with_modified(1_700_000_000),
+                b"#!/bin/sh\necho app\n".to_vec(),
+            ),
+            (
+                Entry::file("README.md", 600).with_modified(1_700_000_002),
+                b"# App\n".repeat(100),
+            ),
+            (
+                Entry::file(
+                    format!("{}/deep.txt", "nested/".repeat(30).trim_end_matches('/')),
+                    0,
+                )
+                .with_modified(1_700_000_004),
+                Vec::new(),
+            ),
+            // Zip times start in 1980, so every entry has a time
+            (
+                Entry::file(format!("{}.txt", "n".repeat(150)), 4).with_modified(1_700_000_004),
+                b"long".to_vec(),
+            ),
+        ]
+    }
+
+    fn write(format: ArchiveFormat, entries: &[(Entry, Vec<u8>)]) -> Vec<u8> {
+        let mut writer = ArchiveWriter::new(format, Cursor::new(Vec::new()));
+        for (entry, content) in entries {
+            writer.append(entry, content.as_slice()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn read(
+        format: ArchiveFormat,
+        bytes: Vec<u8>,
+        limits: SizeLimits,
+    ) -> ArchiveResult<Vec<(Entry, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        ArchiveReader::new(format, Cursor::new(bytes))
+            .with_limits(limits)
+            .for_each(|entry, content| {
+                let mut bytes = Vec::new();
+                content.read_to_end(&mut bytes)?;
+                entries.push((entry.clone(), bytes));
+                Ok(())
+            })?;
+        Ok(entries)
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        for format in [ArchiveFormat::Tar, ArchiveFormat::TarGz, ArchiveFormat::Zip] {
+            let bytes = write(format, &entries());
+            assert_eq!(
+                read(format, bytes.clone(), SizeLimits::new()).unwrap(),
+                entries(),
+                "{}",
+                format
+            );
+
+            let listed = ArchiveReader::new(format, Cursor::new(bytes))
+                .entries()
+                .unwrap();
+            assert_eq!(listed.len(), 5);
+            assert_eq!(listed[2].size, 600);
+        }
+    }
+
+    #[test]
+    fn test_archive_formats() {
+        assert_eq!("TAR.GZ".parse(), Ok(ArchiveFormat::TarGz));
+        assert_eq!("tgz".parse(), Ok(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::Zip.to_string(), "zip");
+        assert_eq!(
+            ArchiveFormat::from_path("a/b.TAR"),
+            Some(ArchiveFormat::Tar)
+        );
+        assert_eq!(
+            "rar".parse::<ArchiveFormat>().unwrap_err().to_string(),
+            "Unknown archive format 'rar' (available: tar, tar.gz, zip)"
+        );
+    }
+
+    #[test]
+    fn test_archive_compresses() {
+        let entries = vec![(Entry::file("log.txt", 50_000), b"line\n".repeat(10_000))];
+        assert!(write(ArchiveFormat::Zip, &entries).len() < 1_000);
+        assert!(write(ArchiveFormat::TarGz, &entries).len() < 1_000);
+    }
+
+    #[test]
+    fn test_archive_invalid() {
+        let limits = SizeLimits::new();
+        assert!(read(ArchiveFormat::Zip, b"not a zip".to_vec(), limits).is_err());
+        assert!(read(
+            ArchiveFormat::TarGz,
+            b"not gzip data at all".to_vec(),
+            limits
+        )
+        .is_err());
+
+        let mut tar = write(ArchiveFormat::Tar, &entries());
+        tar[0] = b'X';
+        assert!(read(ArchiveFormat::Tar, tar, limits)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid tar archive: "));
+
+        let mut zip = write(ArchiveFormat::Zip, &entries());
+        let content = zip.windows(4).position(|w| w == b"long").unwrap_or(80);
+        zip[content] ^= 0xff;
+        assert!(read(ArchiveFormat::Zip, zip, limits).is_err());
+    }
+
+    #[test]
+    fn test_archive_size_limits() {
+        // A few kilobytes of gzip or zip expanding to 10 MiB
+        let bomb = vec![(Entry::file("zeros", 10 << 20), vec![0; 10 << 20])];
+        for format in [ArchiveFormat::TarGz, ArchiveFormat::Zip] {
+            let bytes = write(format, &bomb);
+            assert!(bytes.len() < 100_000);
+
+            let error = read(format, bytes.clone(), SizeLimits::new().with_entry(1 << 20));
+            assert_eq!(
+                error.unwrap_err().to_string(),
+                format!(
+                    "{} archive entry zeros is larger than 1048576 bytes once decompressed",
+                    format
+                )
+            );
+            let error = read(format, bytes, SizeLimits::new().with_total(1 << 20));
+            assert_eq!(
+                error.unwrap_err(),
+                ArchiveError::TooLarge {
+                    format,
+                    limit: 1 << 20
+                }
+            );
+        }
+
+        // Empty files add up to a large tar stream through their headers
+        let empty: Vec<_> = (0..1_000)
+            .map(|i| (Entry::file(format!("{}", i), 0), Vec::new()))
+            .collect();
+        let bytes = write(ArchiveFormat::TarGz, &empty);
+        let error = read(
+            ArchiveFormat::TarGz,
+            bytes,
+            SizeLimits::new().with_total(100_000),
+        );
+        assert_eq!(
+            error.unwrap_err(),
+            ArchiveError::TooLarge {
+                format: ArchiveFormat::TarGz,
+                limit: 100_000
+            }
+        );
+    }
+}
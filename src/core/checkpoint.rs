@@ -0,0 +1,492 @@
+//! Workflow checkpoints
+//!
+//! This module persists the state of a workflow run after each node so that
+//! a failed or interrupted run can be resumed from the last successful node.
+//! A checkpoint records the heap, the status of every node, and hashes of the
+//! workflow file used to detect incompatible changes before resuming.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::digest::to_hex;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::heap::Heap;
+use crate::core::run::{is_valid_run_id, WorkflowRun};
+use crate::shared::types::workflow::node::WorkflowNode;
+use crate::shared::types::workflow::status::Status;
+use crate::shared::types::workflow::workflow::Workflow;
+
+/// Computes the SHA-256 hash of a workflow file content
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::checkpoint::hash_content;
+///
+/// assert_eq!(hash_content("name: test"), hash_content("name: test"));
+/// assert_ne!(hash_content("name: test"), hash_content("name: other"));
+/// ```
+pub fn hash_content(content: &str) -> String {
+    to_hex(&Sha256::digest(content.as_bytes()))
+}
+
+/// Computes a fingerprint of a node definition
+///
+/// Two nodes have the same fingerprint when their id, type, input, and
+/// condition are identical, regardless of how the file is formatted.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::checkpoint::node_fingerprint;
+/// use colossus::shared::types::workflow::node::WorkflowNode;
+///
+/// let node = WorkflowNode::new("log1", "Log", None);
+/// assert_eq!(node_fingerprint(&node), node_fingerprint(&node.clone()));
+/// ```
+pub fn node_fingerprint(node: &WorkflowNode) -> String {
+    let canonical = serde_json::to_string(node).unwrap_or_default();
+    hash_content(&canonical)
+}
+
+/// Checkpointed state of a single node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeCheckpoint {
+    /// Identifier of the node
+    pub id: String,
+
+    /// Status reached by the node
+    pub status: Status,
+
+    /// Fingerprint of the node definition at the time of the run
+    pub fingerprint: String,
+}
+
+/// Persisted state of a workflow run
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use colossus::core::checkpoint::Checkpoint;
+/// use colossus::core::heap::Heap;
+/// use colossus::core::run::WorkflowRun;
+/// use colossus::shared::types::workflow::workflow::Workflow;
+///
+/// let run = WorkflowRun::new(Workflow::new("Example")).with_run_id("run-1");
+/// let checkpoint = Checkpoint::capture(&run, PathBuf::from("workflow.yml"), "hash", &Heap::new());
+/// assert_eq!(checkpoint.run_id, "run-1");
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Identifier of the checkpointed run
+    pub run_id: String,
+
+    /// Path of the workflow file that was executed
+    pub workflow_path: PathBuf,
+
+    /// Hash of the workflow file content
+    pub workflow_hash: String,
+
+    /// Overall status of the run when the checkpoint was written
+    pub status: Status,
+
+    /// State of every node, in definition order
+    pub nodes: Vec<NodeCheckpoint>,
+
+    /// Heap contents when the checkpoint was written
+    pub heap: Heap,
+}
+
+impl Checkpoint {
+    /// Captures the current state of a run
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The run being checkpointed
+    /// * `workflow_path` - Path of the workflow file
+    /// * `workflow_hash` - Hash of the workflow file content
+    /// * `heap` - The current heap
+    pub fn capture(
+        run: &WorkflowRun,
+        workflow_path: impl Into<PathBuf>,
+        workflow_hash: impl Into<String>,
+        heap: &Heap,
+    ) -> Self {
+        let definitions = run.workflow.nodes().unwrap_or_default();
+        let nodes = run
+            .nodes
+            .iter()
+            .zip(definitions)
+            .map(|(node, definition)| NodeCheckpoint {
+                id: node.id.clone(),
+                status: node.status,
+                fingerprint: node_fingerprint(definition),
+            })
+            .collect();
+
+        Self {
+            run_id: run.run_id.clone(),
+            workflow_path: workflow_path.into(),
+            workflow_hash: workflow_hash.into(),
+            status: run.status,
+            nodes,
+            heap: heap.clone(),
+        }
+    }
+
+    /// Checks if the node with the given id completed successfully
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| node.id == id && node.status == Status::Done)
+    }
+
+    /// Checks that the run can be resumed against the current workflow
+    ///
+    /// A workflow whose file is unchanged is always compatible. Otherwise,
+    /// every node that already completed must still be defined at the same
+    /// position with an identical definition; nodes that have not completed
+    /// yet may be freely changed, added, or removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `workflow` - The workflow as currently defined on disk
+    /// * `workflow_hash` - Hash of the current workflow file content
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::IncompatibleCheckpoint` describing the first
+    /// incompatible change, or if the run already completed.
+    pub fn check_compatible(&self, workflow: &Workflow, workflow_hash: &str) -> WorkflowResult<()> {
+        if self.status == Status::Done {
+            return Err(WorkflowError::IncompatibleCheckpoint(format!(
+                "run '{}' already completed",
+                self.run_id
+            )));
+        }
+
+        if self.workflow_hash == workflow_hash {
+            return Ok(());
+        }
+
+        let definitions = workflow.nodes().unwrap_or_default();
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.status != Status::Done {
+                continue;
+            }
+
+            match definitions.get(index) {
+                Some(definition) if definition.id != node.id => {
+                    return Err(WorkflowError::IncompatibleCheckpoint(format!(
+                        "completed node '{}' was replaced by '{}'",
+                        node.id, definition.id
+                    )));
+                }
+                Some(definition) if node_fingerprint(definition) != node.fingerprint => {
+                    return Err(WorkflowError::IncompatibleCheckpoint(format!(
+                        "completed node '{}' has changed",
+                        node.id
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    return Err(WorkflowError::IncompatibleCheckpoint(format!(
+                        "completed node '{}' no longer exists",
+                        node.id
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Directory-backed store of checkpoints, one JSON file per run
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::checkpoint::CheckpointStore;
+///
+/// let store = CheckpointStore::new("/tmp/colossus/checkpoints");
+/// assert!(store.path("run-1").unwrap().ends_with("run-1.json"));
+/// assert!(store.path("../run-1").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    /// Creates a store rooted at the given directory
+    ///
+    /// The directory is created when the first checkpoint is saved.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory of the store
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path of the checkpoint file for a run
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::InvalidRunId` if the run id could name a file
+    /// outside the store, see [`is_valid_run_id`].
+    pub fn path(&self, run_id: &str) -> WorkflowResult<PathBuf> {
+        if !is_valid_run_id(run_id) {
+            return Err(WorkflowError::InvalidRunId(run_id.to_string()));
+        }
+
+        Ok(self.dir.join(format!("{}.json", run_id)))
+    }
+
+    /// Saves a checkpoint, replacing any previous checkpoint of the same run
+    ///
+    /// The file is written to a temporary location first and then renamed,
+    /// so an interrupted write never leaves a truncated checkpoint behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run id is not valid, or if the directory or
+    /// file cannot be written.
+    pub fn save(&self, checkpoint: &Checkpoint) -> WorkflowResult<()> {
+        let path = self.path(&checkpoint.run_id)?;
+        std::fs::create_dir_all(&self.dir)?;
+
+        let json = serde_json::to_string_pretty(checkpoint)
+            .map_err(|e| WorkflowError::Checkpoint(e.to_string()))?;
+        let temp = path.with_extension("json.tmp");
+
+        std::fs::write(&temp, json)?;
+        std::fs::rename(&temp, &path)?;
+
+        Ok(())
+    }
+
+    /// Loads the checkpoint of a run
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::InvalidRunId` if the run id is not valid,
+    /// `WorkflowError::NotFound` if the run has no checkpoint, or
+    /// `WorkflowError::Checkpoint` if the file cannot be parsed.
+    pub fn load(&self, run_id: &str) -> WorkflowResult<Checkpoint> {
+        let path = self.path(run_id)?;
+
+        if !path.exists() {
+            return Err(WorkflowError::NotFound { path });
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content).map_err(|e| WorkflowError::Checkpoint(e.to_string()))
+    }
+
+    /// Removes the checkpoint of a run, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run id is not valid, or if the file exists
+    /// but cannot be removed.
+    pub fn remove(&self, run_id: &str) -> WorkflowResult<()> {
+        match std::fs::remove_file(self.path(run_id)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_yml::Value;
+
+    fn workflow(inputs: &[(&str, &str)]) -> Workflow {
+        let mut workflow = Workflow::new("Test Workflow");
+        for (id, input) in inputs {
+            workflow.add_node(WorkflowNode::new(
+                *id,
+                "Log",
+                Some(Value::String(input.to_string())),
+            ));
+        }
+        workflow
+    }
+
+    fn checkpoint(workflow: Workflow, statuses: &[Status]) -> Checkpoint {
+        let mut run = WorkflowRun::new(workflow).with_run_id("run-1");
+        run.status = Status::Failed;
+        for (node, status) in run.nodes.iter_mut().zip(statuses) {
+            node.status = *status;
+        }
+
+        let mut heap = Heap::new();
+        heap.insert("a", Some(Value::String("one".to_string())));
+
+        Checkpoint::capture(&run, "workflow.yml", "original", &heap)
+    }
+
+    #[test]
+    fn test_hash_content_is_hex_sha256() {
+        let hash = hash_content("");
+        assert_eq!(
+            hash,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_node_fingerprint_changes_with_input() {
+        let node = WorkflowNode::new("a", "Log", Some(Value::String("one".to_string())));
+        let changed = WorkflowNode::new("a", "Log", Some(Value::String("two".to_string())));
+
+        assert_ne!(node_fingerprint(&node), node_fingerprint(&changed));
+    }
+
+    #[test]
+    fn test_checkpoint_capture() {
+        let checkpoint = checkpoint(
+            workflow(&[("a", "one"), ("b", "two")]),
+            &[Status::Done, Status::Failed],
+        );
+
+        assert_eq!(checkpoint.run_id, "run-1");
+        assert_eq!(checkpoint.status, Status::Failed);
+        assert_eq!(checkpoint.nodes.len(), 2);
+        assert!(checkpoint.is_completed("a"));
+        assert!(!checkpoint.is_completed("b"));
+        assert_eq!(
+            checkpoint.heap.get("a"),
+            Some(&Value::String("one".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_check_compatible_same_hash() {
+        let checkpoint = checkpoint(workflow(&[("a", "one")]), &[Status::Done]);
+        assert!(checkpoint
+            .check_compatible(&Workflow::new("Anything"), "original")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_compatible_pending_nodes_may_change() {
+        let checkpoint = checkpoint(
+            workflow(&[("a", "one"), ("b", "two")]),
+            &[Status::Done, Status::Failed],
+        );
+        let changed = workflow(&[("a", "one"), ("b", "fixed"), ("c", "new")]);
+
+        assert!(checkpoint.check_compatible(&changed, "changed").is_ok());
+    }
+
+    #[test]
+    fn test_check_compatible_completed_node_changed() {
+        let checkpoint = checkpoint(
+            workflow(&[("a", "one"), ("b", "two")]),
+            &[Status::Done, Status::Failed],
+        );
+        let changed = workflow(&[("a", "changed"), ("b", "two")]);
+
+        let result = checkpoint.check_compatible(&changed, "changed");
+        assert!(matches!(
+            result,
+            Err(WorkflowError::IncompatibleCheckpoint(message)) if message.contains("'a' has changed")
+        ));
+    }
+
+    #[test]
+    fn test_check_compatible_completed_node_removed() {
+        let checkpoint = checkpoint(
+            workflow(&[("a", "one"), ("b", "two")]),
+            &[Status::Done, Status::Done],
+        );
+        let changed = workflow(&[("a", "one")]);
+
+        let result = checkpoint.check_compatible(&changed, "changed");
+        assert!(matches!(
+            result,
+            Err(WorkflowError::IncompatibleCheckpoint(message)) if message.contains("no longer exists")
+        ));
+    }
+
+    #[test]
+    fn test_check_compatible_completed_node_replaced() {
+        let checkpoint = checkpoint(workflow(&[("a", "one")]), &[Status::Done]);
+        let changed = workflow(&[("z", "one")]);
+
+        assert!(checkpoint.check_compatible(&changed, "changed").is_err());
+    }
+
+    #[test]
+    fn test_check_compatible_completed_run() {
+        let mut checkpoint = checkpoint(workflow(&[("a", "one")]), &[Status::Done]);
+        checkpoint.status = Status::Done;
+
+        assert!(checkpoint
+            .check_compatible(&workflow(&[("a", "one")]), "original")
+            .is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(dir.path().join("checkpoints"));
+        let checkpoint = checkpoint(workflow(&[("a", "one")]), &[Status::Done]);
+
+        store.save(&checkpoint).unwrap();
+        assert!(store.path("run-1").unwrap().exists());
+
+        let loaded = store.load("run-1").unwrap();
+        assert_eq!(loaded.run_id, checkpoint.run_id);
+        assert_eq!(loaded.nodes, checkpoint.nodes);
+        assert_eq!(loaded.heap.get("a"), checkpoint.heap.get("a"));
+
+        store.remove("run-1").unwrap();
+        assert!(!store.path("run-1").unwrap().exists());
+        assert!(store.remove("run-1").is_ok());
+    }
+
+    #[test]
+    fn test_checkpoint_store_rejects_invalid_run_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(dir.path().join("checkpoints"));
+        let outside = dir.path().join("outside.json");
+        std::fs::write(&outside, "{}").unwrap();
+
+        for run_id in ["../outside", "a/b", "a\\b", "..", ""] {
+            assert!(matches!(
+                store.load(run_id),
+                Err(WorkflowError::InvalidRunId(_))
+            ));
+            assert!(store.remove(run_id).is_err());
+        }
+        assert!(outside.exists());
+
+        let mut checkpoint = checkpoint(workflow(&[("a", "one")]), &[Status::Done]);
+        checkpoint.run_id = "../outside".to_string();
+        assert!(matches!(
+            store.save(&checkpoint),
+            Err(WorkflowError::InvalidRunId(_))
+        ));
+    }
+
+    #[test]
+    fn test_checkpoint_store_load_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(dir.path());
+
+        assert!(matches!(
+            store.load("missing"),
+            Err(WorkflowError::NotFound { .. })
+        ));
+    }
+}
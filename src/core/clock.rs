@@ -0,0 +1,95 @@
+//! Time source of workflow runs
+//!
+//! Nodes that wait or read the current time go through the [`Clock`] of the
+//! run context instead of the system clock, so that tests can substitute a
+//! [`ManualClock`] and run deterministically without sleeping.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time, able to wait
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current time
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Blocks for the given duration
+    fn sleep(&self, duration: Duration);
+}
+
+/// Clock reading the system time and sleeping the current thread
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Clock whose time only moves when told to
+///
+/// Sleeping advances the time by the slept duration and returns
+/// immediately, so code waiting on the clock runs instantly.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use chrono::{TimeZone, Utc};
+/// use colossus::core::clock::{Clock, ManualClock};
+///
+/// let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+/// let clock = ManualClock::new(start);
+///
+/// clock.sleep(Duration::from_secs(90));
+/// assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+/// assert_eq!(clock.slept(), Duration::from_secs(90));
+/// ```
+#[derive(Debug)]
+pub struct ManualClock {
+    start: DateTime<Utc>,
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    /// Creates a clock set to the given time
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            now: Mutex::new(start),
+        }
+    }
+
+    /// Moves the time forward
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX);
+    }
+
+    /// Returns the time elapsed since the clock was created
+    pub fn slept(&self) -> Duration {
+        (self.now() - self.start).to_std().unwrap_or_default()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+/// Returns the clock used when none is injected
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
@@ -0,0 +1,483 @@
+//! Run context shared with executing nodes
+//!
+//! This module contains [`RunContext`], which describes the workflow being
+//! executed to the nodes it runs: the file it was loaded from, the node
+//! types available, the execution control of the run, the clock and
+//! randomness source of the run, and the chain of workflow files that called it. Nodes running other workflows use it to
+//! resolve relative paths and to detect call cycles.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::core::clock::{system_clock, Clock};
+use crate::core::control::ExecutionControl;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::random::{system_random, RandomSource};
+use crate::nodes::registry::NodeRegistry;
+use crate::nodes::wasm::WasmPermissions;
+
+/// Default maximum nesting depth of sub-workflows
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// Context of a workflow run, available to every node it executes
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use colossus::core::context::RunContext;
+///
+/// let context = RunContext::new("pipelines/release.yml");
+/// assert_eq!(context.base_dir(), Path::new("pipelines"));
+/// assert_eq!(context.resolve("build.yml"), Path::new("pipelines/build.yml"));
+/// assert_eq!(context.depth(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunContext {
+    /// Path of the workflow file being executed
+    workflow_path: PathBuf,
+
+    /// Node types available to the workflow
+    registry: NodeRegistry,
+
+    /// Execution control of the outermost run
+    control: ExecutionControl,
+
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
+
+    /// Source of random numbers
+    random: Arc<dyn RandomSource>,
+
+    /// Host directories WebAssembly nodes may access
+    wasm_permissions: WasmPermissions,
+
+    /// Canonical paths of the calling workflows, outermost first
+    call_stack: Vec<PathBuf>,
+
+    /// Maximum nesting depth of sub-workflows
+    max_depth: usize,
+}
+
+impl RunContext {
+    /// Creates the context of a top-level run
+    ///
+    /// # Arguments
+    ///
+    /// * `workflow_path` - Path of the workflow file being executed
+    pub fn new(workflow_path: impl Into<PathBuf>) -> Self {
+        Self {
+            workflow_path: workflow_path.into(),
+            registry: NodeRegistry::new(),
+            control: ExecutionControl::new(),
+            clock: system_clock(),
+            random: system_random(),
+            wasm_permissions: WasmPermissions::new(),
+            call_stack: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Sets the node types available to the workflow
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_registry(mut self, registry: NodeRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Sets the execution control of the run
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_control(mut self, control: ExecutionControl) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Sets the clock of the run, e.g. a [`crate::core::clock::ManualClock`] in tests
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the randomness source of the run, e.g. a
+    /// [`crate::core::random::SeededRandom`] in tests
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_random(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.random = random;
+        self
+    }
+
+    /// Sets the host directories WebAssembly nodes may access
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_wasm_permissions(mut self, permissions: WasmPermissions) -> Self {
+        self.wasm_permissions = permissions;
+        self
+    }
+
+    /// Sets the maximum nesting depth of sub-workflows
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the path of the workflow file being executed
+    pub fn workflow_path(&self) -> &Path {
+        &self.workflow_path
+    }
+
+    /// Returns the directory containing the workflow file
+    pub fn base_dir(&self) -> &Path {
+        self.workflow_path.parent().unwrap_or_else(|| Path::new(""))
+    }
+
+    /// Returns the node types available to the workflow
+    pub fn registry(&self) -> &NodeRegistry {
+        &self.registry
+    }
+
+    /// Returns the execution control of the run
+    pub fn control(&self) -> &ExecutionControl {
+        &self.control
+    }
+
+    /// Returns the clock of the run
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Returns the randomness source of the run
+    pub fn random(&self) -> &dyn RandomSource {
+        self.random.as_ref()
+    }
+
+    /// Returns the host directories WebAssembly nodes may access
+    pub fn wasm_permissions(&self) -> &WasmPermissions {
+        &self.wasm_permissions
+    }
+
+    /// Returns the nesting depth of the workflow, 0 for a top-level run
+    pub fn depth(&self) -> usize {
+        self.call_stack.len()
+    }
+
+    /// Returns the maximum nesting depth of sub-workflows
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Resolves a path relative to the directory of the workflow file
+    ///
+    /// Absolute paths are returned unchanged.
+    pub fn resolve(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.base_dir().join(path)
+    }
+
+    /// Resolves a path that must stay within the directory of the workflow file
+    ///
+    /// Nodes reading or writing files use this to confine them to the
+    /// workflow directory. The path is resolved like [`resolve`](Self::resolve)
+    /// and `..` components are applied, and the result is rejected if it
+    /// leaves the directory, directly or through a symbolic link. The path
+    /// does not need to exist.
+    ///
+    /// # Returns
+    ///
+    /// Returns the absolute path, without `.` or `..` components.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::OutsideWorkflowDir` if the path is outside the
+    /// workflow directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::context::RunContext;
+    /// use colossus::core::engine::WorkflowError;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let context = RunContext::new(dir.path().join("main.yml"));
+    ///
+    /// let path = context.resolve_within("dist/../dist/app.zip").unwrap();
+    /// assert!(path.ends_with("dist/app.zip"));
+    ///
+    /// assert!(matches!(
+    ///     context.resolve_within("../secret.txt"),
+    ///     Err(WorkflowError::OutsideWorkflowDir { .. })
+    /// ));
+    /// ```
+    pub fn resolve_within(&self, path: impl AsRef<Path>) -> WorkflowResult<PathBuf> {
+        let outside = || WorkflowError::OutsideWorkflowDir {
+            path: path.as_ref().to_path_buf(),
+        };
+
+        let base = normalize(&absolute(self.base_dir()));
+        let resolved = normalize(&base.join(path.as_ref()));
+        if !resolved.starts_with(&base) {
+            return Err(outside());
+        }
+
+        // The deepest existing part of the path must not be a link leading out
+        let existing = resolved
+            .ancestors()
+            .find(|ancestor| ancestor.symlink_metadata().is_ok());
+        if let Some(existing) = existing {
+            let target = existing.canonicalize().map_err(|_| outside())?;
+            if !target.starts_with(canonical(&base)) {
+                return Err(outside());
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Creates the context of a sub-workflow called by this workflow
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the called workflow file, relative to this one
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NotFound` if the file does not exist,
+    /// `WorkflowError::WorkflowCycle` if the file is already being executed
+    /// by a caller, or `WorkflowError::MaxDepthExceeded` if calling it would
+    /// nest workflows deeper than allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::context::RunContext;
+    /// use colossus::core::engine::WorkflowError;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// std::fs::write(dir.path().join("main.yml"), "nodes: []").unwrap();
+    /// std::fs::write(dir.path().join("child.yml"), "nodes: []").unwrap();
+    ///
+    /// let context = RunContext::new(dir.path().join("main.yml"));
+    /// let child = context.enter("child.yml").unwrap();
+    /// assert_eq!(child.depth(), 1);
+    ///
+    /// assert!(matches!(child.enter("main.yml"), Err(WorkflowError::WorkflowCycle(_))));
+    /// ```
+    pub fn enter(&self, path: impl AsRef<Path>) -> WorkflowResult<RunContext> {
+        let path = self.resolve(path);
+        let target = path
+            .canonicalize()
+            .map_err(|_| WorkflowError::NotFound { path: path.clone() })?;
+
+        let mut call_stack = self.call_stack.clone();
+        call_stack.push(canonical(&self.workflow_path));
+
+        if let Some(start) = call_stack.iter().position(|caller| caller == &target) {
+            let cycle: Vec<String> = call_stack[start..]
+                .iter()
+                .chain(std::iter::once(&target))
+                .map(|file| file.display().to_string())
+                .collect();
+            return Err(WorkflowError::WorkflowCycle(cycle.join(" -> ")));
+        }
+
+        if call_stack.len() > self.max_depth {
+            return Err(WorkflowError::MaxDepthExceeded(self.max_depth));
+        }
+
+        Ok(RunContext {
+            workflow_path: path,
+            registry: self.registry.clone(),
+            control: self.control.clone(),
+            clock: self.clock.clone(),
+            random: self.random.clone(),
+            wasm_permissions: self.wasm_permissions.clone(),
+            call_stack,
+            max_depth: self.max_depth,
+        })
+    }
+}
+
+impl Default for RunContext {
+    fn default() -> Self {
+        Self::new("workflow.yml")
+    }
+}
+
+/// Returns a path made absolute against the current directory
+fn absolute(path: &Path) -> PathBuf {
+    match path.is_absolute() {
+        true => path.to_path_buf(),
+        false => std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+/// Returns a path without `.` components, and with `..` components applied
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Returns the canonical form of a path, or the path itself if it cannot be resolved
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflows(names: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for name in names {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "nodes: []").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_run_context_new() {
+        let context = RunContext::new("dir/main.yml");
+
+        assert_eq!(context.workflow_path(), Path::new("dir/main.yml"));
+        assert_eq!(context.base_dir(), Path::new("dir"));
+        assert_eq!(context.depth(), 0);
+        assert_eq!(context.max_depth(), DEFAULT_MAX_DEPTH);
+        assert!(context.registry().contains("Log"));
+    }
+
+    #[test]
+    fn test_run_context_resolve() {
+        let context = RunContext::new("dir/main.yml");
+
+        assert_eq!(context.resolve("sub/a.yml"), Path::new("dir/sub/a.yml"));
+        assert_eq!(context.resolve("/abs/a.yml"), Path::new("/abs/a.yml"));
+        assert_eq!(
+            RunContext::new("main.yml").resolve("a.yml"),
+            Path::new("a.yml")
+        );
+    }
+
+    #[test]
+    fn test_run_context_resolve_within() {
+        let dir = workflows(&["main.yml", "data/input.txt"]);
+        let context = RunContext::new(dir.path().join("main.yml"));
+        let base = dir.path().canonicalize().unwrap();
+
+        let inside = context.resolve_within("data/./new/../input.txt").unwrap();
+        assert_eq!(inside.canonicalize().unwrap(), base.join("data/input.txt"));
+        assert!(context.resolve_within("out/missing/file.txt").is_ok());
+        assert!(context.resolve_within(dir.path().join("data")).is_ok());
+
+        for path in ["../main.yml", "data/../../x", "/etc/passwd"] {
+            assert!(
+                matches!(
+                    context.resolve_within(path),
+                    Err(WorkflowError::OutsideWorkflowDir { .. })
+                ),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_context_resolve_within_rejects_links_leading_out() {
+        let dir = workflows(&["main.yml"]);
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+        std::os::unix::fs::symlink("missing", dir.path().join("dangling")).unwrap();
+        let context = RunContext::new(dir.path().join("main.yml"));
+
+        assert!(context.resolve_within("link/file.txt").is_err());
+        assert!(context.resolve_within("dangling").is_err());
+        assert!(context.resolve_within("main.yml").is_ok());
+    }
+
+    #[test]
+    fn test_run_context_enter_resolves_relative_to_caller() {
+        let dir = workflows(&["main.yml", "lib/build.yml", "lib/common.yml"]);
+        let context = RunContext::new(dir.path().join("main.yml"));
+
+        let build = context.enter("lib/build.yml").unwrap();
+        assert_eq!(build.base_dir(), dir.path().join("lib"));
+
+        let common = build.enter("common.yml").unwrap();
+        assert_eq!(common.depth(), 2);
+        assert_eq!(common.workflow_path(), dir.path().join("lib/common.yml"));
+    }
+
+    #[test]
+    fn test_run_context_enter_missing_file() {
+        let dir = workflows(&["main.yml"]);
+        let context = RunContext::new(dir.path().join("main.yml"));
+
+        assert!(matches!(
+            context.enter("missing.yml"),
+            Err(WorkflowError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_run_context_enter_detects_cycles() {
+        let dir = workflows(&["a.yml", "b.yml"]);
+        let a = RunContext::new(dir.path().join("a.yml"));
+
+        assert!(matches!(
+            a.enter("a.yml"),
+            Err(WorkflowError::WorkflowCycle(_))
+        ));
+
+        let b = a.enter("b.yml").unwrap();
+        match b.enter("./a.yml") {
+            Err(WorkflowError::WorkflowCycle(cycle)) => {
+                assert!(cycle.contains("a.yml -> "));
+                assert!(cycle.ends_with("a.yml"));
+                assert_eq!(cycle.matches(" -> ").count(), 2);
+            }
+            other => panic!("expected a cycle, got {:?}", other.map(|c| c.depth())),
+        }
+    }
+
+    #[test]
+    fn test_run_context_enter_limits_depth() {
+        let dir = workflows(&["a.yml", "b.yml", "c.yml"]);
+        let a = RunContext::new(dir.path().join("a.yml")).with_max_depth(1);
+
+        let b = a.enter("b.yml").unwrap();
+        assert!(matches!(
+            b.enter("c.yml"),
+            Err(WorkflowError::MaxDepthExceeded(1))
+        ));
+    }
+}
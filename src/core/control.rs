@@ -0,0 +1,309 @@
+//! Execution control for running workflows
+//!
+//! This module provides the handle used to steer a workflow while it is
+//! running on a background thread: pausing after the current node,
+//! resuming, or stopping gracefully.
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::run::WorkflowRun;
+use crate::shared::types::workflow::status::Status;
+
+/// Signal requested by the controller of a running workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    /// Keep executing nodes
+    Continue,
+    /// Hold before the next node until resumed
+    Pause,
+    /// Stop before the next node
+    Stop,
+}
+
+/// Shared state between the controller and the executing workflow
+#[derive(Debug)]
+struct ControlState {
+    signal: Signal,
+    status: Status,
+}
+
+/// Cancellation token shared between a running workflow and its controller
+///
+/// Cloning an `ExecutionControl` yields another handle to the same state, so
+/// it can be moved into signal handlers or other threads. The engine checks
+/// the token between nodes: a node that is already running always finishes.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::control::ExecutionControl;
+/// use colossus::shared::types::workflow::status::Status;
+///
+/// let control = ExecutionControl::new();
+/// control.pause();
+/// assert!(control.is_paused());
+///
+/// control.resume();
+/// assert!(!control.is_paused());
+///
+/// control.stop();
+/// assert!(control.is_stopped());
+/// assert_eq!(control.status(), Status::Pending);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExecutionControl {
+    inner: Arc<(Mutex<ControlState>, Condvar)>,
+}
+
+impl ExecutionControl {
+    /// Creates a new control token for a pending workflow
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new((
+                Mutex::new(ControlState {
+                    signal: Signal::Continue,
+                    status: Status::Pending,
+                }),
+                Condvar::new(),
+            )),
+        }
+    }
+
+    /// Requests the workflow to pause once the current node has finished
+    ///
+    /// Has no effect if a stop has already been requested.
+    pub fn pause(&self) {
+        let mut state = self.lock();
+        if state.signal == Signal::Continue {
+            state.signal = Signal::Pause;
+        }
+    }
+
+    /// Resumes a paused workflow
+    ///
+    /// Has no effect if a stop has already been requested.
+    pub fn resume(&self) {
+        let mut state = self.lock();
+        if state.signal == Signal::Pause {
+            state.signal = Signal::Continue;
+            self.inner.1.notify_all();
+        }
+    }
+
+    /// Requests the workflow to stop once the current node has finished
+    ///
+    /// A paused workflow is woken up so that it can stop immediately.
+    pub fn stop(&self) {
+        let mut state = self.lock();
+        state.signal = Signal::Stop;
+        self.inner.1.notify_all();
+    }
+
+    /// Checks if a pause has been requested
+    pub fn is_paused(&self) -> bool {
+        self.lock().signal == Signal::Pause
+    }
+
+    /// Checks if a stop has been requested
+    pub fn is_stopped(&self) -> bool {
+        self.lock().signal == Signal::Stop
+    }
+
+    /// Returns the current status of the controlled workflow
+    pub fn status(&self) -> Status {
+        self.lock().status
+    }
+
+    /// Updates the status reported to the controller
+    pub(crate) fn set_status(&self, status: Status) {
+        self.lock().status = status;
+    }
+
+    /// Blocks while the workflow is paused
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the workflow may run its next node, or `false` if a
+    /// stop has been requested.
+    pub(crate) fn wait_for_turn(&self) -> bool {
+        let mut state = self.lock();
+
+        if state.signal == Signal::Pause {
+            let previous = state.status;
+            state.status = Status::Paused;
+
+            while state.signal == Signal::Pause {
+                state = self
+                    .inner
+                    .1
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+
+            state.status = previous;
+        }
+
+        state.signal != Signal::Stop
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ControlState> {
+        self.inner
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for ExecutionControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle to a workflow running on a background thread
+///
+/// Returned by [`WorkflowExecutor::spawn`](crate::core::engine::WorkflowExecutor::spawn).
+/// The handle exposes the same operations as [`ExecutionControl`] and lets the
+/// caller wait for the final [`WorkflowRun`].
+#[derive(Debug)]
+pub struct WorkflowHandle {
+    control: ExecutionControl,
+    thread: JoinHandle<WorkflowResult<WorkflowRun>>,
+}
+
+impl WorkflowHandle {
+    /// Creates a handle from a control token and the executing thread
+    pub(crate) fn new(
+        control: ExecutionControl,
+        thread: JoinHandle<WorkflowResult<WorkflowRun>>,
+    ) -> Self {
+        Self { control, thread }
+    }
+
+    /// Returns the control token, e.g. to move it into a signal handler
+    pub fn control(&self) -> &ExecutionControl {
+        &self.control
+    }
+
+    /// Pauses the workflow after the current node
+    pub fn pause(&self) {
+        self.control.pause();
+    }
+
+    /// Resumes a paused workflow
+    pub fn resume(&self) {
+        self.control.resume();
+    }
+
+    /// Stops the workflow after the current node
+    pub fn stop(&self) {
+        self.control.stop();
+    }
+
+    /// Returns the current status of the workflow
+    pub fn status(&self) -> Status {
+        self.control.status()
+    }
+
+    /// Checks if the workflow thread has finished
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Waits for the workflow to finish and returns its run
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that prevented the workflow from running, or
+    /// `WorkflowError::NodeExecutionFailed` if the workflow thread panicked.
+    pub fn join(self) -> WorkflowResult<WorkflowRun> {
+        self.thread.join().map_err(|_| {
+            WorkflowError::NodeExecutionFailed("Workflow execution thread panicked".to_string())
+        })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_execution_control_new() {
+        let control = ExecutionControl::new();
+        assert!(!control.is_paused());
+        assert!(!control.is_stopped());
+        assert_eq!(control.status(), Status::Pending);
+    }
+
+    #[test]
+    fn test_execution_control_pause_resume() {
+        let control = ExecutionControl::new();
+
+        control.pause();
+        assert!(control.is_paused());
+
+        control.resume();
+        assert!(!control.is_paused());
+        assert!(control.wait_for_turn());
+    }
+
+    #[test]
+    fn test_execution_control_stop_overrides_pause() {
+        let control = ExecutionControl::new();
+
+        control.stop();
+        control.pause();
+        control.resume();
+
+        assert!(control.is_stopped());
+        assert!(!control.wait_for_turn());
+    }
+
+    #[test]
+    fn test_execution_control_clone_shares_state() {
+        let control = ExecutionControl::new();
+        let other = control.clone();
+
+        other.stop();
+        assert!(control.is_stopped());
+    }
+
+    #[test]
+    fn test_execution_control_wait_blocks_while_paused() {
+        let control = ExecutionControl::new();
+        control.set_status(Status::Running);
+        control.pause();
+
+        let waiter = control.clone();
+        let thread = thread::spawn(move || waiter.wait_for_turn());
+
+        while control.status() != Status::Paused {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!thread.is_finished());
+
+        control.resume();
+        assert!(thread.join().unwrap());
+        assert_eq!(control.status(), Status::Running);
+    }
+
+    #[test]
+    fn test_execution_control_stop_wakes_paused_workflow() {
+        let control = ExecutionControl::new();
+        control.pause();
+
+        let waiter = control.clone();
+        let thread = thread::spawn(move || waiter.wait_for_turn());
+
+        while control.status() != Status::Paused {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        control.stop();
+        assert!(!thread.join().unwrap());
+    }
+}
@@ -0,0 +1,149 @@
+//! Message digests
+//!
+//! This module computes the SHA-256, SHA-1 and MD5 digests of data, e.g. to
+//! fingerprint build artifacts or compare them with published checksums.
+//! SHA-1 and MD5 are broken for security purposes and are only provided to
+//! check checksums published with them.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use colossus::core::digest::{digest, to_hex, HashAlgorithm};
+//!
+//! let algorithm: HashAlgorithm = "md5".parse().unwrap();
+//! assert_eq!(to_hex(&digest(algorithm, b"abc")), "900150983cd24fb0d6963f7d28e17f72");
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Names of the supported algorithms
+pub const ALGORITHMS: [&str; 3] = ["sha256", "sha1", "md5"];
+
+/// A digest algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    /// SHA-256, the default
+    #[default]
+    Sha256,
+    /// SHA-1
+    Sha1,
+    /// MD5
+    Md5,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().replace('-', "").as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "sha1" => Ok(Self::Sha1),
+            "md5" => Ok(Self::Md5),
+            _ => Err(format!(
+                "Unknown hash algorithm '{}' (available: {})",
+                name,
+                ALGORITHMS.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Sha256 => "sha256",
+            Self::Sha1 => "sha1",
+            Self::Md5 => "md5",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Computes the digest of data
+pub fn digest(algorithm: HashAlgorithm, data: &[u8]) -> Vec<u8> {
+    match algorithm {
+        HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+        HashAlgorithm::Sha1 => Sha1::digest(data).to_vec(),
+        HashAlgorithm::Md5 => Md5::digest(data).to_vec(),
+    }
+}
+
+/// Formats bytes as lowercase hexadecimal
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(algorithm: HashAlgorithm, data: &[u8]) -> String {
+        to_hex(&digest(algorithm, data))
+    }
+
+    #[test]
+    fn test_digest_sha256() {
+        assert_eq!(
+            hex(HashAlgorithm::Sha256, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_digest_sha1() {
+        assert_eq!(
+            hex(HashAlgorithm::Sha1, b""),
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+        );
+        assert_eq!(
+            hex(HashAlgorithm::Sha1, b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(
+                HashAlgorithm::Sha1,
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(
+            hex(HashAlgorithm::Sha1, &[b'a'; 1_000_000]),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+
+    #[test]
+    fn test_digest_md5() {
+        assert_eq!(
+            hex(HashAlgorithm::Md5, b""),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            hex(HashAlgorithm::Md5, b"message digest"),
+            "f96b697d7cb7938d525a2f31aaf161d0"
+        );
+        assert_eq!(
+            hex(
+                HashAlgorithm::Md5,
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+            ),
+            "57edf4a22be3c955ac49da2e2107b67a"
+        );
+    }
+
+    #[test]
+    fn test_hash_algorithm_names() {
+        assert_eq!("SHA-256".parse(), Ok(HashAlgorithm::Sha256));
+        assert_eq!("sha1".parse(), Ok(HashAlgorithm::Sha1));
+        assert_eq!(HashAlgorithm::Md5.to_string(), "md5");
+        assert_eq!(
+            "crc32".parse::<HashAlgorithm>(),
+            Err("Unknown hash algorithm 'crc32' (available: sha256, sha1, md5)".to_string())
+        );
+    }
+}
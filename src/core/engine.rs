@@ -4,15 +4,43 @@
 //! It handles file parsing, format detection, and workflow execution
 //! with comprehensive error handling.
 
-use std::path::PathBuf;
-
-use tracing::error;
-
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+
+use serde_yml::{Mapping, Value};
+use tracing::{error, info, warn};
+
+use crate::core::checkpoint::{hash_content, Checkpoint, CheckpointStore};
+use crate::core::clock::{system_clock, Clock};
+use crate::core::context::RunContext;
+use crate::core::control::{ExecutionControl, WorkflowHandle};
+use crate::core::expression::Expression;
 use crate::core::heap::Heap;
+use crate::core::migration::{migrate, MigrationError};
+use crate::core::random::{system_random, RandomSource};
+use crate::core::run::{is_valid_run_id, NodeRun, WorkflowRun};
+use crate::core::source::{SourceFile, Span};
+use crate::core::validation::ValidationReport;
+use crate::nodes::assert::AssertionFailure;
 use crate::nodes::base::BaseNodeRunOptions;
+use crate::nodes::registry::NodeRegistry;
+use crate::nodes::wasm::WasmPermissions;
 use crate::nodes::NodeBuilder;
+use crate::shared::types::workflow::matrix::NodeMatrix;
+use crate::shared::types::workflow::node::WorkflowNode;
+use crate::shared::types::workflow::output::WorkflowOutput;
+use crate::shared::types::workflow::status::Status;
 use crate::shared::types::workflow::workflow::Workflow;
 
+/// Name under which `on_failure` and `finally` nodes see the outcome of the run
+pub const RUN_BINDING: &str = "run";
+
 /// Configuration options for workflow execution
 ///
 /// This struct encapsulates all the configuration needed to execute a workflow,
@@ -21,6 +49,30 @@ use crate::shared::types::workflow::workflow::Workflow;
 pub struct ExecuteWorkflowOptions {
     /// Path to the workflow file
     path: PathBuf,
+
+    /// Identifier of the run, generated when not set
+    run_id: Option<String>,
+
+    /// Directory where a checkpoint is written after each node
+    state_dir: Option<PathBuf>,
+
+    /// Checkpoint of a previous run to resume from
+    resume_from: Option<Checkpoint>,
+
+    /// Values provided for the workflow inputs
+    inputs: HashMap<String, Value>,
+
+    /// Node types available to the workflow
+    registry: NodeRegistry,
+
+    /// Source of the current time for the nodes
+    clock: Arc<dyn Clock>,
+
+    /// Source of random numbers for the nodes
+    random: Arc<dyn RandomSource>,
+
+    /// Host directories WebAssembly nodes may access
+    wasm_permissions: WasmPermissions,
 }
 
 impl ExecuteWorkflowOptions {
@@ -40,7 +92,53 @@ impl ExecuteWorkflowOptions {
     /// let options = ExecuteWorkflowOptions::new(PathBuf::from("workflow.yml"));
     /// ```
     pub fn new<P: Into<PathBuf>>(path: P) -> Self {
-        Self { path: path.into() }
+        Self {
+            path: path.into(),
+            run_id: None,
+            state_dir: None,
+            resume_from: None,
+            inputs: HashMap::new(),
+            registry: NodeRegistry::new(),
+            clock: system_clock(),
+            random: system_random(),
+            wasm_permissions: WasmPermissions::new(),
+        }
+    }
+
+    /// Creates options that resume the run recorded in a checkpoint
+    ///
+    /// The workflow path and run identifier are taken from the checkpoint.
+    /// Nodes that completed in the previous attempt are skipped and the heap
+    /// is restored before execution continues.
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoint` - The checkpoint of the run to resume
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::checkpoint::CheckpointStore;
+    /// use colossus::core::engine::ExecuteWorkflowOptions;
+    ///
+    /// let store = CheckpointStore::new("/tmp/colossus/checkpoints");
+    /// if let Ok(checkpoint) = store.load("1760000000000-0a1b00") {
+    ///     let options = ExecuteWorkflowOptions::from_checkpoint(checkpoint)
+    ///         .with_state_dir(store.dir());
+    /// }
+    /// ```
+    pub fn from_checkpoint(checkpoint: Checkpoint) -> Self {
+        Self {
+            path: checkpoint.workflow_path.clone(),
+            run_id: Some(checkpoint.run_id.clone()),
+            state_dir: None,
+            resume_from: Some(checkpoint),
+            inputs: HashMap::new(),
+            registry: NodeRegistry::new(),
+            clock: system_clock(),
+            random: system_random(),
+            wasm_permissions: WasmPermissions::new(),
+        }
     }
 
     /// Returns a reference to the workflow file path
@@ -72,13 +170,190 @@ impl ExecuteWorkflowOptions {
         self.path = path.into();
         self
     }
+
+    /// Returns the run identifier, if one was set
+    pub fn run_id(&self) -> Option<&str> {
+        self.run_id.as_deref()
+    }
+
+    /// Builder method to set the run identifier
+    ///
+    /// The identifier must satisfy [`is_valid_run_id`], otherwise running
+    /// the workflow fails with `WorkflowError::InvalidRunId`.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_id` - Identifier of the run
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    /// Returns the checkpoint directory, if checkpointing is enabled
+    pub fn state_dir(&self) -> Option<&Path> {
+        self.state_dir.as_deref()
+    }
+
+    /// Builder method to enable checkpointing into a state directory
+    ///
+    /// A checkpoint is written after each node, and removed once the run
+    /// completes successfully.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_dir` - Directory where checkpoints are stored
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::ExecuteWorkflowOptions;
+    ///
+    /// let options = ExecuteWorkflowOptions::new("workflow.yml")
+    ///     .with_state_dir("/tmp/colossus/checkpoints");
+    /// assert!(options.state_dir().is_some());
+    /// ```
+    pub fn with_state_dir<P: Into<PathBuf>>(mut self, state_dir: P) -> Self {
+        self.state_dir = Some(state_dir.into());
+        self
+    }
+
+    /// Returns the checkpoint being resumed, if any
+    pub fn resume_from(&self) -> Option<&Checkpoint> {
+        self.resume_from.as_ref()
+    }
+
+    /// Returns the values provided for the workflow inputs
+    pub fn inputs(&self) -> &HashMap<String, Value> {
+        &self.inputs
+    }
+
+    /// Builder method to provide a value for a workflow input
+    ///
+    /// Provided values take precedence over the defaults declared in the
+    /// workflow's `inputs` section.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the input
+    /// * `value` - Value of the input
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::ExecuteWorkflowOptions;
+    /// use serde_yml::Value;
+    ///
+    /// let options = ExecuteWorkflowOptions::new("workflow.yml")
+    ///     .with_input("user_name", Value::String("Ada".to_string()));
+    /// assert_eq!(options.inputs().len(), 1);
+    /// ```
+    pub fn with_input(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.inputs.insert(name.into(), value);
+        self
+    }
+
+    /// Returns the registry of node types available to the workflow
+    pub fn registry(&self) -> &NodeRegistry {
+        &self.registry
+    }
+
+    /// Builder method to set the registry of available node types
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - Registry used to build the workflow nodes
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::ExecuteWorkflowOptions;
+    /// use colossus::nodes::{log::LogNode, registry::NodeRegistry};
+    ///
+    /// let registry = NodeRegistry::new()
+    ///     .with_node("Print", |input| Ok(Box::new(LogNode::new(input))));
+    /// let options = ExecuteWorkflowOptions::new("workflow.yml").with_registry(registry);
+    /// assert!(options.registry().contains("Print"));
+    /// ```
+    pub fn with_registry(mut self, registry: NodeRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Returns the clock of the run
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Builder method to set the clock of the run
+    ///
+    /// Nodes waiting or reading the current time use this clock, so a
+    /// [`ManualClock`](crate::core::clock::ManualClock) makes them
+    /// deterministic.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the randomness source of the run
+    pub fn random(&self) -> &Arc<dyn RandomSource> {
+        &self.random
+    }
+
+    /// Builder method to set the randomness source of the run
+    ///
+    /// Nodes producing random values use this source, so a
+    /// [`SeededRandom`](crate::core::random::SeededRandom) makes them
+    /// deterministic.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_random(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.random = random;
+        self
+    }
+
+    /// Returns the host directories WebAssembly nodes may access
+    pub fn wasm_permissions(&self) -> &WasmPermissions {
+        &self.wasm_permissions
+    }
+
+    /// Builder method to grant WebAssembly nodes access to host directories
+    ///
+    /// WebAssembly nodes cannot access any file unless granted here.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_wasm_permissions(mut self, permissions: WasmPermissions) -> Self {
+        self.wasm_permissions = permissions;
+        self
+    }
 }
 
 impl Default for ExecuteWorkflowOptions {
     fn default() -> Self {
-        Self {
-            path: PathBuf::from("workflow.yml"),
-        }
+        Self::new("workflow.yml")
     }
 }
 
@@ -117,9 +392,76 @@ pub enum WorkflowError {
     #[error("Node execution failed: {0}")]
     NodeExecutionFailed(String),
 
+    /// A node accessed a path outside the directory of the workflow file
+    #[error("Path {path} is outside the workflow directory")]
+    OutsideWorkflowDir { path: PathBuf },
+
     /// Invalid node type
     #[error("Invalid node type: {0}")]
     InvalidNode(String),
+
+    /// Checkpoint could not be written or read
+    #[error("Checkpoint error: {0}")]
+    Checkpoint(String),
+
+    /// A run identifier contains characters that are not allowed
+    #[error("Invalid run id '{0}': only letters, digits, '-' and '_' are allowed")]
+    InvalidRunId(String),
+
+    /// Checkpoint cannot be resumed against the current workflow
+    #[error("Cannot resume run: {0}")]
+    IncompatibleCheckpoint(String),
+
+    /// A workflow calls itself, directly or through other workflows
+    #[error("Workflow call cycle: {0}")]
+    WorkflowCycle(String),
+
+    /// Sub-workflows are nested deeper than allowed
+    #[error("Sub-workflows are nested more than {0} levels deep")]
+    MaxDepthExceeded(usize),
+
+    /// An imported file cannot be read or parsed
+    #[error("Failed to import {path}: {source}")]
+    Import {
+        path: PathBuf,
+        source: Box<WorkflowError>,
+    },
+
+    /// A file imports itself, directly or through other files
+    #[error("Import cycle: {0}")]
+    ImportCycle(String),
+
+    /// A node uses a template that is not defined or imported
+    #[error("Unknown node template: {0}")]
+    UnknownTemplate(String),
+
+    /// An iteration of a `for_each` or `matrix` node failed
+    ///
+    /// The iteration is named by its index, or by its matrix combination.
+    #[error("Iteration {iteration} failed: {source}")]
+    IterationFailed {
+        iteration: String,
+        source: Box<WorkflowError>,
+    },
+
+    /// Checks of an `Assert` node did not hold
+    #[error("Assertion failed in node '{node}': {}", AssertionFailure::join(.failures))]
+    AssertionFailed {
+        node: String,
+        failures: Vec<AssertionFailure>,
+    },
+
+    /// A plugin executable does not follow the external node protocol
+    #[error("Plugin {program}: {message}")]
+    Plugin { program: PathBuf, message: String },
+
+    /// The workflow file format version cannot be migrated
+    #[error("Failed to migrate workflow file: {0}")]
+    Migration(#[from] MigrationError),
+
+    /// Static validation found errors in the workflow
+    #[error("Workflow is invalid: {0}")]
+    Validation(ValidationReport),
 }
 
 /// Result type for workflow operations
@@ -128,6 +470,22 @@ pub enum WorkflowError {
 /// workflow operations with proper error handling.
 pub type WorkflowResult<T> = Result<T, WorkflowError>;
 
+impl WorkflowError {
+    /// Returns the location of the error in the workflow file, if known
+    ///
+    /// Only parse errors carry a location; the span covers a single
+    /// character.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            WorkflowError::JsonParse(e) if e.line() > 0 => Some(Span::new(e.line(), e.column(), 1)),
+            WorkflowError::YamlParse(e) => e
+                .location()
+                .map(|location| Span::new(location.line(), location.column(), 1)),
+            _ => None,
+        }
+    }
+}
+
 /// Supported file formats for workflow files
 ///
 /// This enum represents the different file formats that can be parsed
@@ -166,7 +524,7 @@ impl FileFormat {
     /// let txt_path = PathBuf::from("workflow.txt");
     /// assert_eq!(FileFormat::from_path(&txt_path), None);
     /// ```
-    pub fn from_path(path: &PathBuf) -> Option<Self> {
+    pub fn from_path(path: &Path) -> Option<Self> {
         path.extension()
             .and_then(|ext| ext.to_str())
             .and_then(|ext| match ext.to_lowercase().as_str() {
@@ -178,6 +536,9 @@ impl FileFormat {
 
     /// Parses workflow content based on the format
     ///
+    /// Content written for an older file format version is migrated to the
+    /// current one before being parsed.
+    ///
     /// # Arguments
     ///
     /// * `content` - The file content as a string
@@ -186,125 +547,977 @@ impl FileFormat {
     ///
     /// Returns a `WorkflowResult` containing the parsed workflow or an error
     ///
-    /// # Examples
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::{FileFormat, WorkflowResult};
+    /// use colossus::shared::types::workflow::workflow::Workflow;
+    ///
+    /// let yaml_content = r#"
+    /// name: "Test Workflow"
+    /// version: "1.0.0"
+    /// "#;
+    ///
+    /// match FileFormat::Yaml.parse_content(yaml_content) {
+    ///     Ok(workflow) => println!("Parsed workflow: {:?}", workflow),
+    ///     Err(e) => eprintln!("Parse error: {}", e),
+    /// }
+    /// ```
+    pub fn parse_content(self, content: &str) -> WorkflowResult<Workflow> {
+        let mut document: Value = self.parse_document(content)?;
+
+        if migrate(&mut document)?.is_migrated() {
+            return serde_yml::from_value(document).map_err(|e| {
+                // Errors found in the original text carry a location, so
+                // prefer them when the text is invalid on its own
+                match self.parse_document::<Workflow>(content) {
+                    Err(original) => original,
+                    Ok(_) => WorkflowError::YamlParse(e),
+                }
+            });
+        }
+
+        // Parse the text itself so that errors keep their location
+        self.parse_document(content)
+    }
+
+    /// Parses content in this format into any deserializable type
+    fn parse_document<T: DeserializeOwned>(self, content: &str) -> WorkflowResult<T> {
+        match self {
+            FileFormat::Json => serde_json::from_str(content).map_err(WorkflowError::JsonParse),
+            FileFormat::Yaml => serde_yml::from_str(content).map_err(WorkflowError::YamlParse),
+        }
+    }
+}
+
+/// Workflow executor that handles the execution logic
+///
+/// This struct encapsulates the workflow execution logic, making it
+/// easier to test and maintain.
+#[derive(Debug)]
+pub struct WorkflowExecutor;
+
+impl WorkflowExecutor {
+    /// Executes a workflow from the given options
+    ///
+    /// This function reads and parses a workflow file, supporting both JSON and YAML formats.
+    /// The format is automatically detected based on the file extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Configuration options for workflow execution
+    /// * `heap` - The heap containing shared data for the workflow execution
+    ///
+    /// # Returns
+    ///
+    /// Returns a `WorkflowResult` containing the parsed workflow or an error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::{ExecuteWorkflowOptions, WorkflowExecutor};
+    /// use colossus::core::heap::Heap;
+    ///
+    /// let options = ExecuteWorkflowOptions::new("workflows/simple-log.yml");
+    /// let mut heap = Heap::new();
+    /// match WorkflowExecutor::execute(options, &mut heap) {
+    ///     Ok(workflow) => println!("Workflow loaded: {:?}", workflow),
+    ///     Err(e) => eprintln!("Failed to execute workflow: {}", e),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The file does not exist
+    /// - The file format is not supported
+    /// - The file cannot be read
+    /// - The file content cannot be parsed
+    /// - A node cannot be built or fails to execute
+    ///
+    /// A failing node does not stop the run: the remaining nodes still run,
+    /// and the error of the first failing node is returned once they are
+    /// done. Use [`WorkflowExecutor::run`] to get the status of every node.
+    pub fn execute(options: ExecuteWorkflowOptions, heap: &mut Heap) -> WorkflowResult<Workflow> {
+        let run = Self::run(options, heap, &ExecutionControl::new())?;
+
+        match run.error {
+            Some(error) => Err(error),
+            None => Ok(run.workflow),
+        }
+    }
+
+    /// Executes a workflow on a background thread
+    ///
+    /// The returned handle can pause the workflow after the current node,
+    /// resume it, or stop it gracefully. Joining the handle yields the
+    /// [`WorkflowRun`], including partial results if the run was stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Configuration options for workflow execution
+    /// * `heap` - The heap the workflow starts with
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::{ExecuteWorkflowOptions, WorkflowExecutor};
+    /// use colossus::core::heap::Heap;
+    ///
+    /// let options = ExecuteWorkflowOptions::new("workflows/simple-log.yml");
+    /// let handle = WorkflowExecutor::spawn(options, Heap::new());
+    ///
+    /// match handle.join() {
+    ///     Ok(run) => println!("Workflow finished with status {:?}", run.status),
+    ///     Err(e) => eprintln!("Failed to execute workflow: {}", e),
+    /// }
+    /// ```
+    pub fn spawn(options: ExecuteWorkflowOptions, heap: Heap) -> WorkflowHandle {
+        let control = ExecutionControl::new();
+        let thread_control = control.clone();
+
+        let thread = std::thread::spawn(move || {
+            let mut heap = heap;
+            Self::run(options, &mut heap, &thread_control)
+        });
+
+        WorkflowHandle::new(control, thread)
+    }
+
+    /// Executes a workflow under the given execution control
+    ///
+    /// Unlike [`WorkflowExecutor::execute`], a failed or stopped workflow is
+    /// not reported as an error: the returned [`WorkflowRun`] carries the
+    /// status of every node and the error that stopped the run, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Configuration options for workflow execution
+    /// * `heap` - The heap containing shared data for the workflow execution
+    /// * `control` - Token checked between nodes to pause or stop the run
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the workflow file cannot be
+    /// found, read, or parsed, or if the run identifier is not valid.
+    pub fn run(
+        options: ExecuteWorkflowOptions,
+        heap: &mut Heap,
+        control: &ExecutionControl,
+    ) -> WorkflowResult<WorkflowRun> {
+        if let Some(run_id) = options.run_id().filter(|id| !is_valid_run_id(id)) {
+            return Err(WorkflowError::InvalidRunId(run_id.to_string()));
+        }
+
+        let source = SourceFile::read(options.path())?;
+        let workflow = source.parse()?;
+        let workflow_hash = hash_content(source.content());
+
+        let mut run = WorkflowRun::new(workflow);
+        if let Some(run_id) = options.run_id() {
+            run.run_id = run_id.to_string();
+        }
+        run.inputs = Self::seed_heap(&run.workflow, options.inputs(), heap);
+        run.workflow_hash = Some(workflow_hash.clone());
+
+        if let Some(checkpoint) = options.resume_from() {
+            checkpoint.check_compatible(&run.workflow, &workflow_hash)?;
+            info!("Resuming run '{}'", checkpoint.run_id);
+
+            heap.merge(checkpoint.heap.clone());
+            for node in &mut run.nodes {
+                if checkpoint.is_completed(&node.id) {
+                    node.status = Status::Done;
+                }
+            }
+        }
+
+        let checkpointer = options.state_dir().map(|dir| Checkpointer {
+            store: CheckpointStore::new(dir),
+            workflow_path: options
+                .path()
+                .canonicalize()
+                .unwrap_or_else(|_| options.path().clone()),
+            workflow_hash,
+        });
+
+        let context = RunContext::new(options.path())
+            .with_registry(options.registry().clone())
+            .with_control(control.clone())
+            .with_clock(options.clock().clone())
+            .with_random(options.random().clone())
+            .with_wasm_permissions(options.wasm_permissions().clone());
+
+        Ok(Self::execute_nodes(
+            run,
+            heap,
+            &context,
+            checkpointer.as_ref(),
+        ))
+    }
+
+    /// Executes an already loaded workflow within a run context
+    ///
+    /// This is used to run sub-workflows: the workflow is seeded with the
+    /// given inputs, executed against `heap`, and shares the registry and
+    /// execution control of the context. No checkpoint is written.
+    ///
+    /// # Arguments
+    ///
+    /// * `workflow` - The workflow to execute
+    /// * `inputs` - Values provided for the workflow inputs
+    /// * `heap` - The heap the workflow runs against
+    /// * `context` - Context of the workflow, see [`RunContext::enter`]
+    ///
+    /// # Returns
+    ///
+    /// Returns the `WorkflowRun` describing the outcome of every node
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use colossus::core::context::RunContext;
+    /// use colossus::core::engine::{FileFormat, WorkflowExecutor};
+    /// use colossus::core::heap::Heap;
+    /// use serde_yml::Value;
+    ///
+    /// let workflow = FileFormat::Yaml
+    ///     .parse_content("inputs:\n  - name: who\n    type: string\nnodes: []\noutput:\n  greeting: Hello ${{ who }}\n")
+    ///     .unwrap();
+    /// let inputs = HashMap::from([("who".to_string(), Value::from("World"))]);
+    ///
+    /// let run = WorkflowExecutor::run_in_context(workflow, &inputs, &mut Heap::new(), &RunContext::default());
+    /// assert_eq!(run.outputs.get("greeting"), Some(&Value::from("Hello World")));
+    /// ```
+    pub fn run_in_context(
+        workflow: Workflow,
+        inputs: &HashMap<String, Value>,
+        heap: &mut Heap,
+        context: &RunContext,
+    ) -> WorkflowRun {
+        let mut run = WorkflowRun::new(workflow);
+        run.inputs = Self::seed_heap(&run.workflow, inputs, heap);
+
+        Self::execute_nodes(run, heap, context, None)
+    }
+
+    /// Loads and parses a workflow file without executing it
+    ///
+    /// The format is automatically detected based on the file extension.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The file does not exist
+    /// - The file format is not supported
+    /// - The file cannot be read
+    /// - The file content cannot be parsed
+    pub fn load(options: &ExecuteWorkflowOptions) -> WorkflowResult<Workflow> {
+        SourceFile::read(options.path())?.parse()
+    }
+
+    /// Seeds the heap with the workflow variables and inputs
+    ///
+    /// Variables are inserted first, then input defaults, then the provided
+    /// input values, so that provided values take precedence.
+    ///
+    /// # Arguments
+    ///
+    /// * `workflow` - The workflow being executed
+    /// * `provided` - Values provided for the workflow inputs
+    /// * `heap` - The heap to seed
+    ///
+    /// # Returns
+    ///
+    /// Returns the effective input values
+    fn seed_heap(
+        workflow: &Workflow,
+        provided: &HashMap<String, Value>,
+        heap: &mut Heap,
+    ) -> HashMap<String, Value> {
+        for variable in workflow.variables.iter().flatten() {
+            heap.insert(variable.name.clone(), Some(variable.value.clone()));
+        }
+
+        let mut inputs = HashMap::new();
+
+        for input in workflow.inputs.iter().flatten() {
+            if let Some(default) = &input.default {
+                inputs.insert(input.name.clone(), default.clone());
+            }
+        }
+
+        for (name, value) in provided {
+            if !workflow
+                .inputs
+                .iter()
+                .flatten()
+                .any(|input| &input.name == name)
+            {
+                warn!("Input '{}' is not declared by the workflow", name);
+            }
+            inputs.insert(name.clone(), value.clone());
+        }
+
+        for (name, value) in &inputs {
+            heap.insert(name.clone(), Some(value.clone()));
+        }
+
+        inputs
+    }
+
+    /// Executes all nodes in a workflow
+    ///
+    /// Nodes run in definition order. Before each node the execution control
+    /// is consulted, so a pause or stop takes effect once the in-flight node
+    /// has finished. Nodes on untaken branches or with a false `when`
+    /// condition are skipped, see [`WorkflowExecutor::skip_reason`].
+    ///
+    /// A failing node stores no output and the remaining nodes still run;
+    /// the run then fails with the error of the first failing node.
+    ///
+    /// # Arguments
+    ///
+    /// * `workflow` - The workflow to execute
+    /// * `heap` - The heap containing shared data
+    /// * `context` - Context of the run, providing the registry and control
+    /// * `checkpointer` - Writer of checkpoints after each node, if enabled
+    ///
+    /// # Returns
+    ///
+    /// Returns the `WorkflowRun` describing the outcome of every node
+    fn execute_nodes(
+        mut run: WorkflowRun,
+        heap: &mut Heap,
+        context: &RunContext,
+        checkpointer: Option<&Checkpointer>,
+    ) -> WorkflowRun {
+        let nodes = run.workflow.nodes.clone().unwrap_or_default();
+        let concurrency = run
+            .workflow
+            .options
+            .as_ref()
+            .map_or(1, |options| options.concurrency_or(1).max(1) as usize);
+        let control = context.control();
+        // Only the outermost run reports its status through the control
+        let is_root = context.depth() == 0;
+
+        let context = match Self::grant_wasm_permissions(&run.workflow, context) {
+            Ok(context) => context,
+            Err(e) => {
+                error!("Workflow options are invalid: {}", e);
+                run.status = Status::Failed;
+                run.error = Some(e);
+                run.finished_at = Some(Utc::now());
+                if is_root {
+                    control.set_status(run.status);
+                }
+                return run;
+            }
+        };
+        let context = &context;
+
+        run.status = Status::Running;
+        if is_root {
+            control.set_status(Status::Running);
+        }
+
+        for (index, node) in nodes.iter().enumerate() {
+            if run.nodes[index].status == Status::Done {
+                info!(
+                    "Skipping node '{}' completed in a previous attempt",
+                    node.id
+                );
+                continue;
+            }
+
+            if !control.wait_for_turn() {
+                info!("Workflow stopped before node '{}'", node.id);
+                for pending in &mut run.nodes[index..] {
+                    if pending.status != Status::Done {
+                        pending.status = Status::Stopped;
+                    }
+                }
+                run.status = Status::Stopped;
+                break;
+            }
+
+            let key = node.id.clone();
+            run.nodes[index].status = Status::Running;
+            run.nodes[index].error = None;
+
+            let result = match Self::skip_reason(node, heap) {
+                Ok(Some(reason)) => {
+                    info!("Skipping node '{}': {}", node.id, reason);
+                    heap.insert(key, None);
+                    run.nodes[index].status = Status::Skipped;
+                    if let Some(checkpointer) = checkpointer {
+                        checkpointer.record(&run, heap);
+                    }
+                    continue;
+                }
+                Ok(None) => {
+                    let started = Instant::now();
+                    let result = Self::execute_node(node, heap, context, concurrency);
+                    run.nodes[index].duration_ms = Some(started.elapsed().as_millis() as u64);
+                    result
+                }
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(output) => {
+                    heap.insert(key, Some(output));
+                    run.nodes[index].status = Status::Done;
+                }
+                Err(e) => {
+                    error!("Node '{}' execution failed: {}", key, e);
+                    heap.insert(key, None);
+                    run.nodes[index].status = Status::Failed;
+                    run.nodes[index].error = Some(e.to_string());
+                    // The run reports the first failure
+                    run.error.get_or_insert(e);
+                }
+            }
+
+            if let Some(checkpointer) = checkpointer {
+                checkpointer.record(&run, heap);
+            }
+        }
+
+        if run.status == Status::Running {
+            run.status = match run.error {
+                Some(_) => Status::Failed,
+                None => Status::Done,
+            };
+        }
+
+        Self::execute_handlers(&mut run, heap, context, concurrency);
+
+        run.outputs = Self::evaluate_output(&run.workflow, heap);
+        run.finished_at = Some(Utc::now());
+        if is_root {
+            control.set_status(run.status);
+        }
+
+        if let Some(checkpointer) = checkpointer {
+            checkpointer.finish(&run, heap);
+        }
+
+        run
+    }
+
+    /// Adds the grants of the `options.wasm` section of a workflow to the
+    /// permissions of its context
+    ///
+    /// Granted directories are relative to the workflow file, and must be
+    /// inside its directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::OutsideWorkflowDir` if a granted directory is
+    /// outside the directory of the workflow file.
+    fn grant_wasm_permissions(
+        workflow: &Workflow,
+        context: &RunContext,
+    ) -> WorkflowResult<RunContext> {
+        let Some(wasm) = workflow
+            .options
+            .as_ref()
+            .and_then(|options| options.wasm.as_ref())
+        else {
+            return Ok(context.clone());
+        };
+
+        let mut permissions = context.wasm_permissions().clone();
+        for dir in wasm.dirs.iter().flatten() {
+            let host = context.resolve_within(&dir.host)?;
+            permissions = if dir.writable {
+                permissions.with_dir(host, dir.guest.clone())
+            } else {
+                permissions.with_read_only_dir(host, dir.guest.clone())
+            };
+        }
+        for name in wasm.env.iter().flatten() {
+            permissions = permissions.with_env(name.clone());
+        }
+
+        Ok(context.clone().with_wasm_permissions(permissions))
+    }
+
+    /// Executes the `on_failure` nodes of a failed run, then the `finally` nodes
+    ///
+    /// Handler nodes run even when the run was stopped, without consulting
+    /// the execution control, since they usually release resources. They see
+    /// the outcome of the main nodes as `run.status`, `run.error`, and
+    /// `run.failed_node`. A failing handler node does not prevent the next
+    /// ones from running.
+    ///
+    /// The error of the main nodes is kept: a failing handler only fails the
+    /// run when the main nodes all succeeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The run whose main nodes have been executed
+    /// * `heap` - The heap containing the outputs of the main nodes
+    /// * `context` - Context of the run, providing the registry
+    /// * `concurrency` - Maximum number of iterations executed simultaneously
+    fn execute_handlers(
+        run: &mut WorkflowRun,
+        heap: &mut Heap,
+        context: &RunContext,
+        concurrency: usize,
+    ) {
+        let on_failure = match run.status {
+            Status::Failed => run.workflow.on_failure.clone().unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let finally = run.workflow.finally.clone().unwrap_or_default();
+        if on_failure.is_empty() && finally.is_empty() {
+            return;
+        }
+
+        let failed_node = run
+            .nodes
+            .iter()
+            .find(|node| node.status == Status::Failed)
+            .map(|node| Value::from(node.id.as_str()));
+        let mut details = Mapping::new();
+        details.insert(
+            Value::from("status"),
+            serde_yml::to_value(run.status).unwrap_or(Value::Null),
+        );
+        details.insert(
+            Value::from("error"),
+            run.error
+                .as_ref()
+                .map_or(Value::Null, |e| Value::from(e.to_string())),
+        );
+        details.insert(
+            Value::from("failed_node"),
+            failed_node.unwrap_or(Value::Null),
+        );
+
+        // `run` only exists while the handlers execute
+        let shadowed = heap
+            .contains_key(RUN_BINDING)
+            .then(|| heap.get(RUN_BINDING).cloned());
+        heap.insert(RUN_BINDING, Some(Value::Mapping(details)));
+
+        let mut handler_error = None;
+        for (nodes, runs) in [
+            (&on_failure, &mut run.on_failure),
+            (&finally, &mut run.finally),
+        ] {
+            for (node, node_run) in nodes.iter().zip(runs.iter_mut()) {
+                if let Err(e) = Self::execute_handler(node, node_run, heap, context, concurrency) {
+                    handler_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match shadowed {
+            Some(value) => heap.insert(RUN_BINDING, value),
+            None => heap.remove(RUN_BINDING),
+        };
+
+        if let Some(e) = handler_error {
+            if run.status == Status::Done {
+                run.status = Status::Failed;
+                run.error = Some(e);
+            }
+        }
+    }
+
+    /// Executes a single `on_failure` or `finally` node
+    ///
+    /// # Returns
+    ///
+    /// Returns the error that made the node fail, after recording it in the
+    /// node run.
+    fn execute_handler(
+        node: &WorkflowNode,
+        node_run: &mut NodeRun,
+        heap: &mut Heap,
+        context: &RunContext,
+        concurrency: usize,
+    ) -> WorkflowResult<()> {
+        let key = node.id.clone();
+        node_run.status = Status::Running;
+        node_run.error = None;
+
+        let result = match Self::skip_reason(node, heap) {
+            Ok(Some(reason)) => {
+                info!("Skipping node '{}': {}", node.id, reason);
+                heap.insert(key, None);
+                node_run.status = Status::Skipped;
+                return Ok(());
+            }
+            Ok(None) => {
+                let started = Instant::now();
+                let result = Self::execute_node(node, heap, context, concurrency);
+                node_run.duration_ms = Some(started.elapsed().as_millis() as u64);
+                result
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(output) => {
+                heap.insert(key, Some(output));
+                node_run.status = Status::Done;
+                Ok(())
+            }
+            Err(e) => {
+                error!("Node '{}' execution failed: {}", key, e);
+                heap.insert(key, None);
+                node_run.status = Status::Failed;
+                node_run.error = Some(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// Decides whether a node is skipped instead of executed
+    ///
+    /// A node is skipped when its `on_branch` names a branch its switch did
+    /// not take, including when the switch itself was skipped, or when its
+    /// `when` condition is false. The branch is checked first, so conditions
+    /// of nodes on untaken branches are not evaluated.
+    ///
+    /// # Returns
+    ///
+    /// Returns why the node is skipped, or `None` if it should run.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeExecutionFailed` if `on_branch` is not of
+    /// the form `<switch id>.<branch>`, or if the `when` condition cannot be
+    /// parsed or evaluated.
+    fn skip_reason(node: &WorkflowNode, heap: &Heap) -> WorkflowResult<Option<String>> {
+        if let Some(on_branch) = &node.on_branch {
+            let (switch, branch) = node.branch().ok_or_else(|| {
+                WorkflowError::NodeExecutionFailed(format!(
+                    "{}: on_branch '{}' must be of the form <switch id>.<branch>",
+                    node.id, on_branch
+                ))
+            })?;
+
+            let taken = heap
+                .get(switch)
+                .and_then(|output| output.get("branch"))
+                .and_then(Value::as_str);
+            match taken {
+                Some(taken) if taken == branch => {}
+                Some(taken) => {
+                    return Ok(Some(format!(
+                        "switch '{}' took branch '{}', not '{}'",
+                        switch, taken, branch
+                    )))
+                }
+                None => return Ok(Some(format!("switch '{}' did not run", switch))),
+            }
+        }
+
+        if let Some(when) = &node.when {
+            let condition = Expression::parse(when)
+                .and_then(|expression| expression.evaluate_bool(heap))
+                .map_err(|e| {
+                    WorkflowError::NodeExecutionFailed(format!(
+                        "{}: invalid `when` expression '{}': {}",
+                        node.id, when, e
+                    ))
+                })?;
+            if !condition {
+                return Ok(Some(format!("condition '{}' is false", when)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Builds and executes a single node
+    ///
+    /// Nodes with `for_each` are executed once per item, and nodes with a
+    /// `matrix` once per combination, see
+    /// [`WorkflowExecutor::execute_iterations`].
+    ///
+    /// # Arguments
     ///
-    /// ```rust
-    /// use colossus::core::engine::{FileFormat, WorkflowResult};
-    /// use colossus::shared::types::workflow::workflow::Workflow;
+    /// * `node` - The node definition
+    /// * `heap` - The heap containing shared data
+    /// * `context` - Context of the run, providing the registry
+    /// * `concurrency` - Maximum number of iterations executed simultaneously
     ///
-    /// let yaml_content = r#"
-    /// name: "Test Workflow"
-    /// version: "1.0.0"
-    /// "#;
+    /// # Returns
     ///
-    /// match FileFormat::Yaml.parse_content(yaml_content) {
-    ///     Ok(workflow) => println!("Parsed workflow: {:?}", workflow),
-    ///     Err(e) => eprintln!("Parse error: {}", e),
-    /// }
-    /// ```
-    pub fn parse_content(self, content: &str) -> WorkflowResult<Workflow> {
-        match self {
-            FileFormat::Json => serde_json::from_str(content).map_err(WorkflowError::JsonParse),
-            FileFormat::Yaml => serde_yml::from_str(content).map_err(WorkflowError::YamlParse),
+    /// Returns the node output, or the error that made it fail
+    fn execute_node(
+        node: &WorkflowNode,
+        heap: &mut Heap,
+        context: &RunContext,
+        concurrency: usize,
+    ) -> WorkflowResult<Value> {
+        if let Some(template) = &node.uses {
+            return Err(WorkflowError::UnknownTemplate(template.clone()));
         }
-    }
-}
 
-/// Workflow executor that handles the execution logic
-///
-/// This struct encapsulates the workflow execution logic, making it
-/// easier to test and maintain.
-#[derive(Debug)]
-pub struct WorkflowExecutor;
+        match (&node.for_each, &node.matrix) {
+            (Some(_), Some(_)) => Err(WorkflowError::NodeExecutionFailed(format!(
+                "{}: for_each and matrix cannot be combined",
+                node.id
+            ))),
+            (Some(items), None) => Self::execute_for_each(node, items, heap, context, concurrency),
+            (None, Some(matrix)) => Self::execute_matrix(node, matrix, heap, context, concurrency),
+            (None, None) => Self::execute_once(node, heap, context),
+        }
+    }
 
-impl WorkflowExecutor {
-    /// Executes a workflow from the given options
+    /// Executes a node once per item of its `for_each` sequence
     ///
-    /// This function reads and parses a workflow file, supporting both JSON and YAML formats.
-    /// The format is automatically detected based on the file extension.
+    /// Every iteration exposes the item as `item` and its position as
+    /// `index`.
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `options` - Configuration options for workflow execution
-    /// * `heap` - The heap containing shared data for the workflow execution
+    /// Returns the sequence of the outputs of the iterations, in item order.
+    fn execute_for_each(
+        node: &WorkflowNode,
+        items: &Value,
+        heap: &Heap,
+        context: &RunContext,
+        concurrency: usize,
+    ) -> WorkflowResult<Value> {
+        let items = match heap.parse(Some(items.clone())) {
+            Some(Value::Sequence(items)) => items,
+            other => {
+                return Err(WorkflowError::NodeExecutionFailed(format!(
+                    "{}: for_each must evaluate to a sequence, got {}",
+                    node.id,
+                    other
+                        .as_ref()
+                        .and_then(|value| serde_yml::to_string(value).ok())
+                        .map_or("nothing".to_string(), |text| text.trim_end().to_string())
+                )))
+            }
+        };
+
+        let iterations = items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                (
+                    index.to_string(),
+                    vec![
+                        ("item".to_string(), item),
+                        ("index".to_string(), Value::from(index)),
+                    ],
+                )
+            })
+            .collect();
+
+        let outputs = Self::execute_iterations(node, iterations, heap, context, concurrency)?;
+        Ok(Value::Sequence(
+            outputs.into_iter().map(|(_, output)| output).collect(),
+        ))
+    }
+
+    /// Executes a node once per combination of its matrix
+    ///
+    /// Every run exposes its combination as `matrix`.
     ///
     /// # Returns
     ///
-    /// Returns a `WorkflowResult` containing the parsed workflow or an error
+    /// Returns a mapping from the key of every combination to the output of
+    /// its run, in combination order.
+    fn execute_matrix(
+        node: &WorkflowNode,
+        matrix: &NodeMatrix,
+        heap: &Heap,
+        context: &RunContext,
+        concurrency: usize,
+    ) -> WorkflowResult<Value> {
+        let iterations = matrix
+            .combinations()
+            .into_iter()
+            .map(|combination| {
+                let key = NodeMatrix::key(&combination);
+                let value = Value::Mapping(
+                    combination
+                        .into_iter()
+                        .map(|(axis, value)| (Value::String(axis), value))
+                        .collect(),
+                );
+                (key, vec![("matrix".to_string(), value)])
+            })
+            .collect();
+
+        let outputs = Self::execute_iterations(node, iterations, heap, context, concurrency)?;
+        Ok(Value::Mapping(
+            outputs
+                .into_iter()
+                .map(|(key, output)| (Value::String(key), output))
+                .collect(),
+        ))
+    }
+
+    /// Executes a node once per iteration
     ///
-    /// # Examples
+    /// Every iteration runs in a child scope of the heap holding its
+    /// bindings, so iterations cannot see or modify each other's data. Up to
+    /// `concurrency` iterations run in parallel; once one fails, no further
+    /// iteration is started.
     ///
-    /// ```rust
-    /// use colossus::core::engine::{ExecuteWorkflowOptions, WorkflowExecutor};
-    /// use colossus::core::heap::Heap;
+    /// # Arguments
     ///
-    /// let options = ExecuteWorkflowOptions::new("workflows/simple-log.yml");
-    /// let mut heap = Heap::new();
-    /// match WorkflowExecutor::execute(options, &mut heap) {
-    ///     Ok(workflow) => println!("Workflow loaded: {:?}", workflow),
-    ///     Err(e) => eprintln!("Failed to execute workflow: {}", e),
-    /// }
-    /// ```
+    /// * `node` - The node definition
+    /// * `iterations` - The name and the bindings of every iteration
+    /// * `heap` - The heap the child scopes are created from
+    /// * `context` - Context of the run, providing the registry
+    /// * `concurrency` - Maximum number of iterations executed simultaneously
     ///
-    /// # Errors
+    /// # Returns
     ///
-    /// This function will return an error if:
-    /// - The file does not exist
-    /// - The file format is not supported
-    /// - The file cannot be read
-    /// - The file content cannot be parsed
-    pub fn execute(options: ExecuteWorkflowOptions, heap: &mut Heap) -> WorkflowResult<Workflow> {
-        let path = options.path();
-
-        // Check if file exists
-        if !path.exists() {
-            return Err(WorkflowError::NotFound { path: path.clone() });
+    /// Returns the name and output of every iteration, in order, or the
+    /// error of the first failed iteration.
+    fn execute_iterations(
+        node: &WorkflowNode,
+        iterations: Vec<(String, Vec<(String, Value)>)>,
+        heap: &Heap,
+        context: &RunContext,
+        concurrency: usize,
+    ) -> WorkflowResult<Vec<(String, Value)>> {
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let results: Mutex<Vec<Option<WorkflowResult<Value>>>> =
+            Mutex::new(iterations.iter().map(|_| None).collect());
+
+        std::thread::scope(|scope| {
+            for _ in 0..concurrency.min(iterations.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    if index >= iterations.len() || failed.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    let mut scope_heap = heap.scope(iterations[index].1.iter().cloned());
+                    let result = Self::execute_once(node, &mut scope_heap, context);
+                    if result.is_err() {
+                        failed.store(true, Ordering::SeqCst);
+                    }
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+                });
+            }
+        });
+
+        let results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+        let mut outputs = Vec::with_capacity(results.len());
+        for ((name, _), result) in iterations.into_iter().zip(results) {
+            match result {
+                Some(Ok(output)) => outputs.push((name, output)),
+                Some(Err(e)) => {
+                    return Err(WorkflowError::IterationFailed {
+                        iteration: name,
+                        source: Box::new(e),
+                    })
+                }
+                // Iterations after a failure are not started
+                None => break,
+            }
         }
 
-        // Determine file format
-        let format = FileFormat::from_path(path).ok_or(WorkflowError::UnsupportedFormat)?;
-
-        // Read and parse the workflow file
-        let content = std::fs::read_to_string(path).map_err(WorkflowError::FileRead)?;
-        let workflow = format.parse_content(&content)?;
+        Ok(outputs)
+    }
 
-        // Execute the workflow nodes
-        Self::execute_nodes(&workflow, heap)?;
+    /// Builds and executes a node against the given heap
+    fn execute_once(
+        node: &WorkflowNode,
+        heap: &mut Heap,
+        context: &RunContext,
+    ) -> WorkflowResult<Value> {
+        let node_instance = NodeBuilder::new()
+            .with_workflow_node(node.clone())
+            .with_registry(context.registry().clone())
+            .build(heap)
+            .map_err(|e| WorkflowError::NodeBuilder(e.to_string()))?;
+
+        node_instance
+            .execute(BaseNodeRunOptions::new(heap, node.id.clone()).with_context(context))
+            .map_err(|e| Self::name_node(&node.id, e))
+    }
 
-        Ok(workflow)
+    /// Prefixes the message of an untyped node error with the node id
+    ///
+    /// Typed errors, such as `OutsideWorkflowDir` or `Plugin`, are returned
+    /// unchanged so that callers can match on them.
+    fn name_node(id: &str, error: WorkflowError) -> WorkflowError {
+        match error {
+            WorkflowError::NodeExecutionFailed(message) => {
+                WorkflowError::NodeExecutionFailed(format!("{}: {}", id, message))
+            }
+            error => error,
+        }
     }
 
-    /// Executes all nodes in a workflow
+    /// Evaluates the workflow output definition against the heap
     ///
     /// # Arguments
     ///
-    /// * `workflow` - The workflow to execute
-    /// * `heap` - The heap containing shared data
+    /// * `workflow` - The workflow whose output should be evaluated
+    /// * `heap` - The heap containing node results
     ///
     /// # Returns
     ///
-    /// Returns a `WorkflowResult` indicating success or failure
-    fn execute_nodes(workflow: &Workflow, heap: &mut Heap) -> WorkflowResult<()> {
-        if let Some(nodes) = &workflow.nodes {
-            for node in nodes {
-                let key = node.id.clone();
+    /// Returns the output with variables substituted
+    fn evaluate_output(workflow: &Workflow, heap: &Heap) -> WorkflowOutput {
+        let mut outputs = WorkflowOutput::new();
+
+        if let Some(output) = &workflow.output {
+            for (key, value) in output.iter() {
+                let value = heap.parse(Some(value.clone())).unwrap_or(Value::Null);
+                outputs.insert(key.clone(), value);
+            }
+        }
 
-                let node_instance = NodeBuilder::new()
-                    .with_workflow_node(node.clone())
-                    .build(heap)
-                    .map_err(|e| WorkflowError::NodeBuilder(e.to_string()))?;
+        outputs
+    }
+}
 
-                let output = node_instance.execute(BaseNodeRunOptions::new(heap, key.clone()));
+/// Writes checkpoints of a run into a checkpoint store
+#[derive(Debug)]
+struct Checkpointer {
+    store: CheckpointStore,
+    workflow_path: PathBuf,
+    workflow_hash: String,
+}
 
-                if let Err(e) = &output {
-                    error!("Node '{}' execution failed: {:?}", key, e);
-                }
+impl Checkpointer {
+    /// Records the current state of the run
+    ///
+    /// Failing to write a checkpoint does not fail the run, it only means the
+    /// run cannot be resumed from this point.
+    fn record(&self, run: &WorkflowRun, heap: &Heap) {
+        let checkpoint = Checkpoint::capture(run, &self.workflow_path, &self.workflow_hash, heap);
 
-                heap.insert(key, output.ok());
-            }
+        if let Err(e) = self.store.save(&checkpoint) {
+            warn!("Failed to write checkpoint for run '{}': {}", run.run_id, e);
         }
+    }
 
-        Ok(())
+    /// Records the final state of the run
+    ///
+    /// The checkpoint of a successful run is removed since there is nothing
+    /// left to resume.
+    fn finish(&self, run: &WorkflowRun, heap: &Heap) {
+        if run.status != Status::Done {
+            self.record(run, heap);
+        } else if let Err(e) = self.store.remove(&run.run_id) {
+            warn!(
+                "Failed to remove checkpoint for run '{}': {}",
+                run.run_id, e
+            );
+        }
     }
 }
 
@@ -388,6 +1601,8 @@ pub fn execute_workflow(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::migration::FORMAT_VERSION;
+    use chrono::TimeZone;
     use std::io::Write;
     use std::path::PathBuf;
     use tempfile::NamedTempFile;
@@ -474,6 +1689,38 @@ nodes:
         assert!(workflow.nodes.is_some());
     }
 
+    #[test]
+    fn test_file_format_parse_content_migrates_legacy_format() {
+        let legacy = FileFormat::Yaml
+            .parse_content("name: Legacy\nnodes: []\n")
+            .unwrap();
+        assert_eq!(legacy.colossus, Some(FORMAT_VERSION));
+
+        let current = FileFormat::Json
+            .parse_content(r#"{"colossus": 1, "name": "Current"}"#)
+            .unwrap();
+        assert_eq!(current.colossus, Some(1));
+    }
+
+    #[test]
+    fn test_file_format_parse_content_unsupported_format_version() {
+        let result = FileFormat::Yaml.parse_content("colossus: 99\nnodes: []\n");
+        assert!(matches!(
+            result,
+            Err(WorkflowError::Migration(
+                MigrationError::UnsupportedVersion(99)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_file_format_parse_legacy_error_keeps_location() {
+        let error = FileFormat::Yaml
+            .parse_content("nodes:\n  - type: Log\n")
+            .unwrap_err();
+        assert_eq!(error.span(), Some(Span::new(2, 5, 1)));
+    }
+
     #[test]
     fn test_file_format_parse_invalid_yaml() {
         let invalid_yaml = r#"
@@ -637,6 +1884,578 @@ version: "1.0.0"
         std::fs::remove_file(path).unwrap();
     }
 
+    #[test]
+    fn test_workflow_executor_runs_sub_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("lib")).unwrap();
+        std::fs::write(
+            dir.path().join("lib/double.yml"),
+            r#"
+inputs:
+  - name: value
+    type: number
+nodes:
+  - id: result
+    type: Log
+    input: ${{ value }}${{ value }}
+output:
+  result: ${{ result }}
+"#,
+        )
+        .unwrap();
+        let main = dir.path().join("main.yml");
+        std::fs::write(
+            &main,
+            r#"
+variables:
+  - name: seed
+    value: 21
+nodes:
+  - id: call
+    type: Workflow
+    input:
+      path: lib/double.yml
+      inputs:
+        value: ${{ seed }}
+output:
+  doubled: ${{ call.result }}
+"#,
+        )
+        .unwrap();
+
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(&main),
+            &mut Heap::new(),
+            &ExecutionControl::new(),
+        )
+        .unwrap();
+
+        assert!(run.is_successful(), "{:?}", run.error);
+        assert_eq!(run.outputs.get("doubled"), Some(&Value::from("2121")));
+    }
+
+    /// Node sleeping for 50 milliseconds before returning its input
+    struct SlowNode(Value);
+
+    impl crate::nodes::base::BaseNode for SlowNode {
+        fn execute(&self, _options: BaseNodeRunOptions) -> WorkflowResult<Value> {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            Ok(self.0.clone())
+        }
+    }
+
+    fn run_yaml(yaml: &str) -> WorkflowRun {
+        let mut file = tempfile::Builder::new().suffix(".yml").tempfile().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(file.path()),
+            &mut Heap::new(),
+            &ExecutionControl::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_workflow_executor_for_each() {
+        let run = run_yaml(
+            r#"
+variables:
+  - name: files
+    value: [a.txt, b.txt, c.txt]
+nodes:
+  - id: show
+    type: Log
+    input: ${{ index }}:${{ item }}
+    for_each: ${{ files }}
+output:
+  shown: ${{ show }}
+  item: ${{ item }}
+"#,
+        );
+
+        assert!(run.is_successful(), "{:?}", run.error);
+        assert_eq!(
+            run.outputs.get("shown"),
+            Some(&serde_yml::from_str::<Value>("[0:a.txt, 1:b.txt, 2:c.txt]").unwrap())
+        );
+        // The iteration scope does not leak into the workflow heap
+        assert_eq!(run.outputs.get("item"), Some(&Value::from("${{ item }}")));
+    }
+
+    #[test]
+    fn test_workflow_executor_for_each_concurrently() {
+        let registry = NodeRegistry::new().with_node("Slow", |input| {
+            Ok(Box::new(SlowNode(input.unwrap_or(Value::Null))))
+        });
+        let mut file = tempfile::Builder::new().suffix(".yml").tempfile().unwrap();
+        file.write_all(
+            br#"
+options:
+  concurrency: 4
+nodes:
+  - id: slow
+    type: Slow
+    input: ${{ item }}
+    for_each: [1, 2, 3, 4, 5, 6, 7, 8]
+output:
+  all: ${{ slow }}
+"#,
+        )
+        .unwrap();
+
+        let started = Instant::now();
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(file.path()).with_registry(registry),
+            &mut Heap::new(),
+            &ExecutionControl::new(),
+        )
+        .unwrap();
+
+        assert!(run.is_successful(), "{:?}", run.error);
+        assert!(started.elapsed() < std::time::Duration::from_millis(8 * 50));
+        assert_eq!(
+            run.outputs.get("all"),
+            Some(&serde_yml::from_str::<Value>("[1, 2, 3, 4, 5, 6, 7, 8]").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_workflow_executor_for_each_failures() {
+        let run = run_yaml(
+            r#"
+nodes:
+  - id: each
+    type: Log
+    for_each: not a list ${{ missing }}
+"#,
+        );
+        assert_eq!(run.status, Status::Failed);
+        assert!(run
+            .error
+            .unwrap()
+            .to_string()
+            .contains("for_each must evaluate to a sequence"));
+
+        let run = run_yaml(
+            r#"
+nodes:
+  - id: each
+    type: Workflow
+    input:
+      path: ${{ item }}
+    for_each: [missing.yml]
+"#,
+        );
+        assert!(matches!(
+            run.error,
+            Some(WorkflowError::IterationFailed { ref iteration, .. }) if iteration == "0"
+        ));
+    }
+
+    #[test]
+    fn test_workflow_executor_matrix() {
+        let run = run_yaml(
+            r#"
+options:
+  concurrency: 2
+nodes:
+  - id: build
+    type: Log
+    input: ${{ matrix.os }}-v${{ matrix.version }}
+    matrix:
+      os: [linux, macos]
+      version: [1, 2]
+      exclude:
+        - os: macos
+          version: 1
+      include:
+        - os: windows
+          version: 3
+output:
+  linux: ${{ build["os=linux,version=1"] }}
+  all: ${{ build }}
+"#,
+        );
+
+        assert!(run.is_successful(), "{:?}", run.error);
+        assert_eq!(
+            run.outputs.get("all"),
+            Some(
+                &serde_yml::from_str::<Value>(
+                    "os=linux,version=1: linux-v1\nos=linux,version=2: linux-v2\nos=macos,version=2: macos-v2\nos=windows,version=3: windows-v3\n"
+                )
+                .unwrap()
+            )
+        );
+        assert_eq!(run.outputs.get("linux"), Some(&Value::from("linux-v1")));
+        assert_eq!(run.workflow.expanded_node_count(), 4);
+    }
+
+    #[test]
+    fn test_workflow_executor_for_each_empty_sequence() {
+        let run = run_yaml("nodes:\n  - id: each\n    type: Log\n    for_each: []\n");
+
+        assert!(run.is_successful());
+        assert_eq!(run.nodes[0].status, Status::Done);
+    }
+
+    #[test]
+    fn test_workflow_executor_assert_node() {
+        let run = run_yaml(
+            r#"
+variables:
+  - name: sizes
+    value: [1, 2, 5]
+nodes:
+  - id: positive
+    type: Assert
+    for_each: ${{ sizes }}
+    input:
+      - ${{ item > 0 }}
+  - id: small
+    type: Assert
+    input:
+      - condition: ${{ sizes[2] < 4 }}
+        message: Size ${{ sizes[2] }} is too large
+      - ${{ sizes[0] == 1 }}
+  - id: after
+    type: Log
+"#,
+        );
+
+        assert!(!run.is_successful());
+        assert_eq!(run.node("positive").unwrap().status, Status::Done);
+        assert_eq!(run.node("after").unwrap().status, Status::Done);
+        match &run.error {
+            Some(WorkflowError::AssertionFailed { node, failures }) => {
+                assert_eq!(node, "small");
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].message.as_deref(), Some("Size 5 is too large"));
+                assert_eq!(
+                    failures[0].values,
+                    vec![("sizes[2]".to_string(), Value::from(5))]
+                );
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(
+            run.diagnostic().unwrap().path.as_deref(),
+            Some("/nodes/1/input")
+        );
+    }
+
+    #[test]
+    fn test_workflow_executor_parse_and_serialize_nodes() {
+        let run = run_yaml(
+            r#"
+variables:
+  - name: listing
+    value: "name,size\na.txt,3\nb.txt,12\n"
+nodes:
+  - id: files
+    type: Parse
+    input:
+      format: csv
+      text: ${{ listing }}
+  - id: named
+    type: Assert
+    for_each: ${{ files }}
+    input:
+      - ${{ item.name | length == 5 }}
+  - id: report
+    type: Serialize
+    input:
+      format: json
+      value: ${{ files[1] }}
+  - id: reread
+    type: Parse
+    input:
+      format: json
+      text: ${{ report.content }}
+  - id: check
+    type: Assert
+    input:
+      - ${{ reread.name == "b.txt" && reread.size == "12" }}
+"#,
+        );
+
+        assert!(run.is_successful(), "{:?}", run.error);
+        assert_eq!(run.node("named").unwrap().status, Status::Done);
+    }
+
+    #[test]
+    fn test_workflow_executor_switch_branches() {
+        let run = run_yaml(
+            r#"
+variables:
+  - name: environment
+    value: staging
+nodes:
+  - id: route
+    type: Switch
+    input:
+      value: ${{ environment }}
+      cases:
+        production: prod
+        staging: staging
+  - id: deploy
+    type: Log
+    input: production
+    on_branch: route.production
+  - id: preview
+    type: Log
+    input: staging
+    on_branch: route.staging
+  - id: inner
+    type: Switch
+    input:
+      value: ${{ deploy }}
+      cases:
+        done: production
+    on_branch: route.production
+  - id: nested
+    type: Log
+    on_branch: inner.done
+output:
+  branch: ${{ route.branch }}
+"#,
+        );
+
+        assert!(run.is_successful());
+        assert_eq!(run.outputs.get("branch"), Some(&Value::from("staging")));
+        assert_eq!(run.node("route").unwrap().status, Status::Done);
+        assert_eq!(run.node("deploy").unwrap().status, Status::Skipped);
+        assert_eq!(run.node("preview").unwrap().status, Status::Done);
+        assert_eq!(run.node("inner").unwrap().status, Status::Skipped);
+        assert_eq!(run.node("nested").unwrap().status, Status::Skipped);
+        assert_eq!(run.node("deploy").unwrap().duration_ms, None);
+    }
+
+    #[test]
+    fn test_workflow_executor_runs_failure_handlers() {
+        let run = run_yaml(
+            r#"
+nodes:
+  - id: fetch
+    type: Log
+    input: fetched
+  - id: broken
+    type: Unknown
+  - id: after
+    type: Log
+on_failure:
+  - id: alert
+    type: Log
+    input: "${{ run.failed_node }} failed after ${{ fetch }}: ${{ run.error }}"
+  - id: alert_twice
+    type: Unknown
+finally:
+  - id: cleanup
+    type: Log
+    input: ${{ run.status }}
+  - id: only_on_success
+    type: Log
+    when: run.status == "Done"
+output:
+  alert: ${{ alert }}
+  cleanup: ${{ cleanup }}
+"#,
+        );
+
+        assert_eq!(run.status, Status::Failed);
+        assert!(matches!(run.error, Some(WorkflowError::NodeBuilder(_))));
+        assert_eq!(run.node("after").unwrap().status, Status::Done);
+        let statuses: Vec<Status> = run
+            .on_failure
+            .iter()
+            .chain(&run.finally)
+            .map(|node| node.status)
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![Status::Done, Status::Failed, Status::Done, Status::Skipped]
+        );
+        assert!(run.on_failure[1].error.is_some());
+        assert!(run
+            .outputs
+            .get("alert")
+            .and_then(Value::as_str)
+            .unwrap()
+            .starts_with("broken failed after fetched: Node builder error"));
+        assert_eq!(run.outputs.get("cleanup"), Some(&Value::from("Failed")));
+    }
+
+    #[test]
+    fn test_workflow_executor_finally_failure_fails_successful_run() {
+        let run = run_yaml(
+            r#"
+variables:
+  - name: run
+    value: kept
+nodes:
+  - id: work
+    type: Log
+on_failure:
+  - id: alert
+    type: Log
+finally:
+  - id: cleanup
+    type: Unknown
+output:
+  run: ${{ run }}
+"#,
+        );
+
+        assert_eq!(run.status, Status::Failed);
+        assert_eq!(run.on_failure[0].status, Status::Pending);
+        assert_eq!(run.finally[0].status, Status::Failed);
+        assert!(matches!(run.error, Some(WorkflowError::NodeBuilder(_))));
+        assert_eq!(
+            run.diagnostic().unwrap().path.as_deref(),
+            Some("/finally/0/type")
+        );
+        assert_eq!(run.outputs.get("run"), Some(&Value::from("kept")));
+    }
+
+    #[test]
+    fn test_workflow_executor_uses_injected_clock() {
+        let mut file = tempfile::Builder::new().suffix(".yml").tempfile().unwrap();
+        file.write_all(
+            b"nodes:\n  - id: wait\n    type: WaitUntil\n    input:\n      condition: attempt == 3\n      interval: 10s\n",
+        )
+        .unwrap();
+        let clock = Arc::new(crate::core::clock::ManualClock::new(Utc::now()));
+
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(file.path()).with_clock(clock.clone()),
+            &mut Heap::new(),
+            &ExecutionControl::new(),
+        )
+        .unwrap();
+
+        assert!(run.is_successful(), "{:?}", run.error);
+        assert_eq!(clock.slept(), std::time::Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_workflow_executor_uses_injected_random() {
+        let mut file = tempfile::Builder::new().suffix(".yml").tempfile().unwrap();
+        file.write_all(
+            b"nodes:\n  - id: id\n    type: UUID\n  - id: stamp\n    type: Now\n    input:\n      format: '%Y%m%d'\n",
+        )
+        .unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 5, 17, 8, 0, 0).unwrap();
+
+        let run = |heap: &mut Heap| {
+            let options = ExecuteWorkflowOptions::new(file.path())
+                .with_clock(Arc::new(crate::core::clock::ManualClock::new(start)))
+                .with_random(Arc::new(crate::core::random::SeededRandom::new(7)));
+            let run = WorkflowExecutor::run(options, heap, &ExecutionControl::new()).unwrap();
+            assert!(run.is_successful(), "{:?}", run.error);
+        };
+
+        let (mut first, mut second) = (Heap::new(), Heap::new());
+        run(&mut first);
+        run(&mut second);
+
+        assert_eq!(first.get("id"), second.get("id"));
+        assert_eq!(first.get("stamp"), Some(&Value::from("20240517")));
+    }
+
+    #[test]
+    fn test_workflow_executor_when_condition() {
+        let run = run_yaml(
+            r#"
+variables:
+  - name: debug
+    value: false
+nodes:
+  - id: quiet
+    type: Log
+    when: debug == true
+  - id: loud
+    type: Log
+    when: ${{ !debug }}
+  - id: broken
+    type: Log
+    when: "debug =="
+"#,
+        );
+
+        assert_eq!(run.status, Status::Failed);
+        assert_eq!(run.node("quiet").unwrap().status, Status::Skipped);
+        assert_eq!(run.node("loud").unwrap().status, Status::Done);
+        assert_eq!(run.node("broken").unwrap().status, Status::Failed);
+        assert!(run
+            .node("broken")
+            .unwrap()
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("invalid `when` expression 'debug =='"));
+    }
+
+    #[test]
+    fn test_workflow_executor_runs_imported_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("lib")).unwrap();
+        std::fs::write(
+            dir.path().join("lib/common.yml"),
+            r#"
+variables:
+  - name: greeting
+    value: Hello
+templates:
+  greet:
+    type: Log
+    input:
+      message: ${{ greeting }}
+      target: World
+"#,
+        )
+        .unwrap();
+        let main = dir.path().join("main.yml");
+        std::fs::write(
+            &main,
+            r#"
+imports:
+  - path: lib/common.yml
+nodes:
+  - id: greet
+    uses: greet
+    with:
+      target: Colossus
+  - id: unknown
+    uses: missing
+output:
+  message: ${{ greet.message }} ${{ greet.target }}
+"#,
+        )
+        .unwrap();
+
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(&main),
+            &mut Heap::new(),
+            &ExecutionControl::new(),
+        )
+        .unwrap();
+
+        assert_eq!(run.nodes[0].status, Status::Done);
+        assert!(matches!(
+            run.error,
+            Some(WorkflowError::UnknownTemplate(ref name)) if name == "missing"
+        ));
+        assert_eq!(
+            run.diagnostic().unwrap().path.as_deref(),
+            Some("/nodes/1/uses")
+        );
+        assert_eq!(
+            run.outputs.get("message"),
+            Some(&Value::from("Hello Colossus"))
+        );
+    }
+
     #[test]
     fn test_execute_workflow_from_pathbuf() {
         let yaml_content = r#"
@@ -661,6 +2480,393 @@ version: "1.0.0"
         std::fs::remove_file(path).unwrap();
     }
 
+    fn temp_workflow(content: &str) -> NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(".yml").tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    const THREE_NODES: &str = r#"
+name: "Three Nodes"
+nodes:
+  - id: "first"
+    type: "Log"
+    input: "one"
+  - id: "second"
+    type: "Log"
+    input: "${{ first }} two"
+  - id: "third"
+    type: "Log"
+    input: "three"
+output:
+  combined: "${{ second }}"
+"#;
+
+    #[test]
+    fn test_workflow_executor_run_reports_statuses_and_outputs() {
+        let file = temp_workflow(THREE_NODES);
+        let mut heap = Heap::new();
+
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(file.path()),
+            &mut heap,
+            &ExecutionControl::new(),
+        )
+        .unwrap();
+
+        assert_eq!(run.status, Status::Done);
+        assert_eq!(run.count(Status::Done), 3);
+        assert!(run.error.is_none());
+        assert_eq!(
+            run.outputs.get("combined"),
+            Some(&Value::String("one two".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_workflow_executor_run_failed_node() {
+        let file = temp_workflow(
+            r#"
+nodes:
+  - id: "ok"
+    type: "Log"
+    input: "fine"
+  - id: "broken"
+    type: "Unknown"
+  - id: "after"
+    type: "Log"
+"#,
+        );
+        let mut heap = Heap::new();
+
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(file.path()),
+            &mut heap,
+            &ExecutionControl::new(),
+        )
+        .unwrap();
+
+        assert_eq!(run.status, Status::Failed);
+        assert_eq!(run.node("ok").unwrap().status, Status::Done);
+        assert_eq!(run.node("broken").unwrap().status, Status::Failed);
+        assert!(run.node("broken").unwrap().error.is_some());
+        assert!(heap.contains_key("broken"));
+        assert_eq!(heap.get("broken"), None);
+        assert_eq!(run.node("after").unwrap().status, Status::Done);
+        assert!(heap.contains_key("after"));
+    }
+
+    #[test]
+    fn test_workflow_executor_run_keeps_first_error() {
+        let run = run_yaml(
+            r#"
+nodes:
+  - id: "first"
+    type: "Unknown"
+  - id: "second"
+    type: "Assert"
+    input:
+      - false
+"#,
+        );
+
+        assert_eq!(run.status, Status::Failed);
+        assert_eq!(run.node("first").unwrap().status, Status::Failed);
+        assert_eq!(run.node("second").unwrap().status, Status::Failed);
+        assert!(matches!(run.error, Some(WorkflowError::NodeBuilder(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_workflow_executor_wasm_option_grants() {
+        use crate::nodes::wasm::{tests::echo_module, WasmPlugin};
+
+        let dir = tempfile::tempdir().unwrap();
+        let module = dir.path().join("echo.wasm");
+        echo_module(&module);
+        let registry = NodeRegistry::new().with_wasm_plugin(WasmPlugin::load(&module).unwrap());
+        std::fs::create_dir(dir.path().join("data")).unwrap();
+        std::fs::write(dir.path().join("data").join("secret.txt"), "secret").unwrap();
+
+        let run = |host: &str| {
+            let path = dir.path().join("workflow.yml");
+            std::fs::write(
+                &path,
+                format!(
+                    "options:\n  wasm:\n    dirs:\n      - host: {}\n        guest: /data\nnodes:\n  - id: echo\n    type: Echo\n",
+                    host
+                ),
+            )
+            .unwrap();
+            let mut heap = Heap::new();
+            let run = WorkflowExecutor::run(
+                ExecuteWorkflowOptions::new(&path).with_registry(registry.clone()),
+                &mut heap,
+                &ExecutionControl::new(),
+            )
+            .unwrap();
+            (run, heap)
+        };
+
+        let (granted, heap) = run("data");
+        assert_eq!(granted.status, Status::Done);
+        assert_eq!(heap.get("echo").unwrap()["granted"], Value::from(true));
+
+        let (outside, _) = run("..");
+        assert_eq!(outside.status, Status::Failed);
+        assert!(matches!(
+            outside.error,
+            Some(WorkflowError::OutsideWorkflowDir { .. })
+        ));
+        assert_eq!(outside.node("echo").unwrap().status, Status::Pending);
+    }
+
+    #[test]
+    fn test_workflow_executor_execute_failed_node() {
+        let file = temp_workflow(
+            r#"
+nodes:
+  - id: "broken"
+    type: "Unknown"
+"#,
+        );
+        let mut heap = Heap::new();
+
+        let result = WorkflowExecutor::execute(ExecuteWorkflowOptions::new(file.path()), &mut heap);
+        assert!(matches!(result, Err(WorkflowError::NodeBuilder(_))));
+    }
+
+    #[test]
+    fn test_workflow_executor_node_errors() {
+        let file = temp_workflow(
+            r#"
+nodes:
+  - id: "outside"
+    type: "Parse"
+    input:
+      format: json
+      file: /etc/hostname
+  - id: "invalid"
+    type: "Parse"
+    input:
+      format: json
+      text: "{"
+"#,
+        );
+
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(file.path()),
+            &mut Heap::new(),
+            &ExecutionControl::new(),
+        )
+        .unwrap();
+
+        // Typed errors are passed through, untyped ones name their node once
+        assert!(matches!(
+            run.error,
+            Some(WorkflowError::OutsideWorkflowDir { .. })
+        ));
+        let error = run.node("invalid").unwrap().error.clone().unwrap();
+        assert!(error.starts_with("Node execution failed: invalid: "));
+        assert_eq!(error.matches("Node execution failed").count(), 1);
+    }
+
+    #[test]
+    fn test_workflow_executor_run_stopped_before_start() {
+        let file = temp_workflow(THREE_NODES);
+        let mut heap = Heap::new();
+        let control = ExecutionControl::new();
+        control.stop();
+
+        let run = WorkflowExecutor::run(
+            ExecuteWorkflowOptions::new(file.path()),
+            &mut heap,
+            &control,
+        )
+        .unwrap();
+
+        assert_eq!(run.status, Status::Stopped);
+        assert_eq!(run.count(Status::Stopped), 3);
+        assert!(run.error.is_none());
+        assert!(heap.is_empty());
+        assert_eq!(control.status(), Status::Stopped);
+    }
+
+    #[test]
+    fn test_workflow_executor_run_paused_then_stopped() {
+        let file = temp_workflow(THREE_NODES);
+        let path = file.path().to_path_buf();
+        let control = ExecutionControl::new();
+        control.pause();
+
+        let thread_control = control.clone();
+        let thread = std::thread::spawn(move || {
+            let mut heap = Heap::new();
+            WorkflowExecutor::run(
+                ExecuteWorkflowOptions::new(path),
+                &mut heap,
+                &thread_control,
+            )
+        });
+
+        while control.status() != Status::Paused {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        control.stop();
+
+        let run = thread.join().unwrap().unwrap();
+        assert_eq!(run.status, Status::Stopped);
+        assert_eq!(run.count(Status::Done), 0);
+    }
+
+    #[test]
+    fn test_workflow_executor_spawn() {
+        let file = temp_workflow(THREE_NODES);
+
+        let handle = WorkflowExecutor::spawn(ExecuteWorkflowOptions::new(file.path()), Heap::new());
+        let run = handle.join().unwrap();
+
+        assert_eq!(run.status, Status::Done);
+        assert_eq!(run.count(Status::Done), 3);
+    }
+
+    #[test]
+    fn test_workflow_executor_spawn_file_not_found() {
+        let handle =
+            WorkflowExecutor::spawn(ExecuteWorkflowOptions::new("nonexistent.yml"), Heap::new());
+
+        assert!(matches!(handle.join(), Err(WorkflowError::NotFound { .. })));
+    }
+
+    const FAILING_SECOND: &str = r#"
+nodes:
+  - id: "first"
+    type: "Log"
+    input: "one"
+  - id: "second"
+    type: "Unknown"
+    input: "${{ first }} two"
+"#;
+
+    const FIXED_SECOND: &str = r#"
+nodes:
+  - id: "first"
+    type: "Log"
+    input: "one"
+  - id: "second"
+    type: "Log"
+    input: "${{ first }} two"
+"#;
+
+    #[test]
+    fn test_workflow_executor_writes_checkpoint_on_failure() {
+        let file = temp_workflow(FAILING_SECOND);
+        let state = tempfile::tempdir().unwrap();
+        let options = ExecuteWorkflowOptions::new(file.path())
+            .with_run_id("run-1")
+            .with_state_dir(state.path());
+
+        let run =
+            WorkflowExecutor::run(options, &mut Heap::new(), &ExecutionControl::new()).unwrap();
+        assert_eq!(run.run_id, "run-1");
+
+        let checkpoint = CheckpointStore::new(state.path()).load("run-1").unwrap();
+        assert_eq!(checkpoint.status, Status::Failed);
+        assert!(checkpoint.is_completed("first"));
+        assert!(!checkpoint.is_completed("second"));
+        assert_eq!(
+            checkpoint.heap.get("first"),
+            Some(&Value::String("one".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_workflow_executor_rejects_invalid_run_id() {
+        let file = temp_workflow(FIXED_SECOND);
+        let state = tempfile::tempdir().unwrap();
+        let options = ExecuteWorkflowOptions::new(file.path())
+            .with_run_id("../escape")
+            .with_state_dir(state.path().join("checkpoints"));
+
+        let result = WorkflowExecutor::run(options, &mut Heap::new(), &ExecutionControl::new());
+
+        assert!(matches!(result, Err(WorkflowError::InvalidRunId(_))));
+        assert!(!state.path().join("escape.json").exists());
+    }
+
+    #[test]
+    fn test_workflow_executor_removes_checkpoint_on_success() {
+        let file = temp_workflow(FIXED_SECOND);
+        let state = tempfile::tempdir().unwrap();
+        let options = ExecuteWorkflowOptions::new(file.path())
+            .with_run_id("run-1")
+            .with_state_dir(state.path());
+
+        let run =
+            WorkflowExecutor::run(options, &mut Heap::new(), &ExecutionControl::new()).unwrap();
+
+        assert_eq!(run.status, Status::Done);
+        assert!(!CheckpointStore::new(state.path())
+            .path("run-1")
+            .unwrap()
+            .exists());
+    }
+
+    #[test]
+    fn test_workflow_executor_resume_skips_completed_nodes() {
+        let file = temp_workflow(FAILING_SECOND);
+        let state = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(state.path());
+        let options = ExecuteWorkflowOptions::new(file.path())
+            .with_run_id("run-1")
+            .with_state_dir(state.path());
+        WorkflowExecutor::run(options, &mut Heap::new(), &ExecutionControl::new()).unwrap();
+
+        // Mark the checkpointed heap so that re-running "first" would be detected
+        let mut checkpoint = store.load("run-1").unwrap();
+        checkpoint
+            .heap
+            .insert("first", Some(Value::String("restored".to_string())));
+        store.save(&checkpoint).unwrap();
+
+        // Fix the failing node, which keeps the checkpoint compatible
+        std::fs::write(file.path(), FIXED_SECOND).unwrap();
+
+        let options = ExecuteWorkflowOptions::from_checkpoint(store.load("run-1").unwrap())
+            .with_state_dir(state.path());
+        let mut heap = Heap::new();
+        let run = WorkflowExecutor::run(options, &mut heap, &ExecutionControl::new()).unwrap();
+
+        assert_eq!(run.run_id, "run-1");
+        assert_eq!(run.status, Status::Done);
+        assert_eq!(
+            heap.get("second"),
+            Some(&Value::String("restored two".to_string()))
+        );
+        assert!(!store.path("run-1").unwrap().exists());
+    }
+
+    #[test]
+    fn test_workflow_executor_resume_refuses_incompatible_change() {
+        let file = temp_workflow(FAILING_SECOND);
+        let state = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(state.path());
+        let options = ExecuteWorkflowOptions::new(file.path())
+            .with_run_id("run-1")
+            .with_state_dir(state.path());
+        WorkflowExecutor::run(options, &mut Heap::new(), &ExecutionControl::new()).unwrap();
+
+        std::fs::write(file.path(), FIXED_SECOND.replace("\"one\"", "\"changed\"")).unwrap();
+
+        let options = ExecuteWorkflowOptions::from_checkpoint(store.load("run-1").unwrap());
+        let result = WorkflowExecutor::run(options, &mut Heap::new(), &ExecutionControl::new());
+
+        assert!(matches!(
+            result,
+            Err(WorkflowError::IncompatibleCheckpoint(_))
+        ));
+    }
+
     #[test]
     fn test_workflow_error_display() {
         let error = WorkflowError::InvalidNode("UnknownNode".to_string());
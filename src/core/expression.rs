@@ -0,0 +1,1108 @@
+//! Workflow expressions
+//!
+//! This module parses and evaluates the expressions used by workflows, both
+//! in `when` conditions and inside `${{ ... }}` placeholders.
+//!
+//! # Syntax
+//!
+//! - Literals: `42`, `-1.5`, `"text"`, `'text'`, `true`, `false`, `null`
+//! - References: `name`, `node.field`, `items[0]`, `map["key"]`
+//! - Comparisons: `==`, `!=`, `<`, `<=`, `>`, `>=`
+//! - Logic: `&&`, `||`, `!`, and parentheses
+//! - Filters: `name | upper`, `items | join(", ")`, see [`FILTERS`]
+//!
+//! The root of every reference is looked up in the heap, so it names a
+//! workflow variable, an input, or the output of a previous node.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde_yml::Value;
+
+use crate::core::heap::Heap;
+
+/// Errors that can occur while parsing or evaluating an expression
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ExpressionError {
+    /// The expression is not syntactically valid
+    #[error("{message} at position {position}")]
+    Syntax { message: String, position: usize },
+
+    /// The expression cannot be evaluated against the given values
+    #[error("{0}")]
+    Evaluation(String),
+}
+
+/// Result type for expression operations
+pub type ExpressionResult<T> = Result<T, ExpressionError>;
+
+/// Filters available in expressions, with their minimum and maximum number
+/// of arguments
+///
+/// - `upper`, `lower`, `trim`: change a string
+/// - `replace(from, to)`: replace every occurrence of `from` in a string
+/// - `length`: number of characters, items, or entries
+/// - `first`, `last`: first or last item of a sequence
+/// - `join(separator)`: join the items of a sequence, `""` by default
+/// - `default(value)`: replace `null` with a value
+/// - `json`: serialize a value as compact JSON
+pub const FILTERS: [(&str, usize, usize); 10] = [
+    ("upper", 0, 0),
+    ("lower", 0, 0),
+    ("trim", 0, 0),
+    ("replace", 2, 2),
+    ("length", 0, 0),
+    ("first", 0, 0),
+    ("last", 0, 0),
+    ("join", 0, 1),
+    ("default", 1, 1),
+    ("json", 0, 0),
+];
+
+/// Binary operators supported in expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+/// Parsed expression tree
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Reference(String),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Filter(Box<Expr>, String, Vec<Expr>),
+}
+
+/// A parsed workflow expression
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::expression::Expression;
+/// use colossus::core::heap::Heap;
+/// use serde_yml::Value;
+///
+/// let mut heap = Heap::new();
+/// heap.insert("debug", Some(Value::Bool(true)));
+///
+/// let expression = Expression::parse("debug == true").unwrap();
+/// assert_eq!(expression.references(), vec!["debug"]);
+/// assert!(expression.evaluate_bool(&heap).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Expr,
+}
+
+impl Expression {
+    /// Parses an expression
+    ///
+    /// A condition wrapped in a single `${{ ... }}` placeholder is accepted
+    /// as well, so `when: ${{ debug }}` and `when: debug` are equivalent.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpressionError::Syntax` if the expression is not valid.
+    pub fn parse(source: &str) -> ExpressionResult<Self> {
+        let trimmed = source.trim();
+        let body = trimmed
+            .strip_prefix("${{")
+            .and_then(|rest| rest.strip_suffix("}}"))
+            .filter(|body| !body.contains("${{"))
+            .unwrap_or(trimmed);
+
+        let tokens = tokenize(body)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: body.len(),
+        };
+        let root = parser.parse_expression()?;
+        parser.expect_end()?;
+
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Returns the source text of the expression
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the names looked up in the heap by the expression
+    ///
+    /// Only the root of each reference is returned, in order of first
+    /// appearance and without duplicates.
+    pub fn references(&self) -> Vec<&str> {
+        let mut references = Vec::new();
+        collect_references(&self.root, &mut references);
+        references
+    }
+
+    /// Evaluates the expression against the heap
+    ///
+    /// References to names missing from the heap evaluate to `null`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpressionError::Evaluation` if values cannot be compared
+    /// or indexed.
+    pub fn evaluate(&self, heap: &Heap) -> ExpressionResult<Value> {
+        evaluate(&self.root, heap)
+    }
+
+    /// Returns the value of every reference path of the expression
+    ///
+    /// Each path, such as `fetch.status` or `items[0]`, is paired with the
+    /// value it evaluates to, in order of first appearance. Paths that
+    /// cannot be evaluated are left out. This is used to explain why a
+    /// condition did not hold.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::expression::Expression;
+    /// use colossus::core::heap::Heap;
+    /// use serde_yml::Value;
+    ///
+    /// let mut heap = Heap::new();
+    /// heap.insert("fetch", Some(serde_yml::from_str("status: 404").unwrap()));
+    ///
+    /// let expression = Expression::parse("${{ fetch.status == 200 }}").unwrap();
+    /// assert_eq!(
+    ///     expression.observed_values(&heap),
+    ///     vec![("fetch.status".to_string(), Value::from(404))]
+    /// );
+    /// ```
+    pub fn observed_values(&self, heap: &Heap) -> Vec<(String, Value)> {
+        let mut paths = Vec::new();
+        collect_paths(&self.root, &mut paths);
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                evaluate(path, heap)
+                    .ok()
+                    .map(|value| (path.to_string(), value))
+            })
+            .collect()
+    }
+
+    /// Evaluates the expression and converts the result to a boolean
+    ///
+    /// See [`is_truthy`] for the conversion rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpressionError::Evaluation` if the expression cannot be evaluated.
+    pub fn evaluate_bool(&self, heap: &Heap) -> ExpressionResult<bool> {
+        self.evaluate(heap).map(|value| is_truthy(&value))
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(value) => write!(
+                f,
+                "{}",
+                serde_json::to_string(value).unwrap_or_else(|_| "?".to_string())
+            ),
+            Expr::Reference(name) => write!(f, "{}", name),
+            Expr::Field(target, field) => write!(f, "{}.{}", target, field),
+            Expr::Index(target, index) => write!(f, "{}[{}]", target, index),
+            Expr::Not(inner) => write!(f, "!{}", inner),
+            Expr::Binary(op, left, right) => write!(f, "({} {} {})", left, op, right),
+            Expr::Filter(target, name, arguments) if arguments.is_empty() => {
+                write!(f, "{} | {}", target, name)
+            }
+            Expr::Filter(target, name, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(ToString::to_string).collect();
+                write!(f, "{} | {}({})", target, name, arguments.join(", "))
+            }
+        }
+    }
+}
+
+/// Checks whether a value counts as `true` in a condition
+///
+/// `null`, `false`, `0`, the empty string, and empty sequences and mappings
+/// are falsy; every other value is truthy.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::expression::is_truthy;
+/// use serde_yml::Value;
+///
+/// assert!(is_truthy(&Value::String("yes".to_string())));
+/// assert!(!is_truthy(&Value::Null));
+/// assert!(!is_truthy(&Value::Number(0.into())));
+/// ```
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Sequence(items) => !items.is_empty(),
+        Value::Mapping(map) => !map.is_empty(),
+        Value::Tagged(tagged) => is_truthy(&tagged.value),
+    }
+}
+
+/// Extracts the bodies of the `${{ ... }}` placeholders in a text
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::expression::placeholders;
+///
+/// let found = placeholders("Hello ${{ name }}, you are ${{age}}");
+/// assert_eq!(found, vec!["name", "age"]);
+/// ```
+pub fn placeholders(text: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${{") {
+        let after = &rest[start + 3..];
+        match after.find("}}") {
+            Some(end) => {
+                found.push(after[..end].trim());
+                rest = &after[end + 2..];
+            }
+            None => break,
+        }
+    }
+
+    found
+}
+
+fn collect_references<'a>(expr: &'a Expr, references: &mut Vec<&'a str>) {
+    match expr {
+        Expr::Literal(_) => {}
+        Expr::Reference(name) => {
+            if !references.contains(&name.as_str()) {
+                references.push(name);
+            }
+        }
+        Expr::Field(target, _) | Expr::Not(target) => collect_references(target, references),
+        Expr::Index(target, index) => {
+            collect_references(target, references);
+            collect_references(index, references);
+        }
+        Expr::Binary(_, left, right) => {
+            collect_references(left, references);
+            collect_references(right, references);
+        }
+        Expr::Filter(target, _, arguments) => {
+            collect_references(target, references);
+            for argument in arguments {
+                collect_references(argument, references);
+            }
+        }
+    }
+}
+
+fn collect_paths<'a>(expr: &'a Expr, paths: &mut Vec<&'a Expr>) {
+    if is_path(expr) {
+        if !paths.contains(&expr) {
+            paths.push(expr);
+        }
+        collect_index_paths(expr, paths);
+        return;
+    }
+
+    match expr {
+        Expr::Literal(_) | Expr::Reference(_) => {}
+        Expr::Field(target, _) | Expr::Not(target) => collect_paths(target, paths),
+        Expr::Index(target, index) | Expr::Binary(_, target, index) => {
+            collect_paths(target, paths);
+            collect_paths(index, paths);
+        }
+        Expr::Filter(target, _, arguments) => {
+            collect_paths(target, paths);
+            for argument in arguments {
+                collect_paths(argument, paths);
+            }
+        }
+    }
+}
+
+fn collect_index_paths<'a>(expr: &'a Expr, paths: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::Field(target, _) => collect_index_paths(target, paths),
+        Expr::Index(target, index) => {
+            collect_index_paths(target, paths);
+            collect_paths(index, paths);
+        }
+        _ => {}
+    }
+}
+
+fn is_path(expr: &Expr) -> bool {
+    match expr {
+        Expr::Reference(_) => true,
+        Expr::Field(target, _) | Expr::Index(target, _) => is_path(target),
+        _ => false,
+    }
+}
+
+fn evaluate(expr: &Expr, heap: &Heap) -> ExpressionResult<Value> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Reference(name) => Ok(heap.get(name).cloned().unwrap_or(Value::Null)),
+        Expr::Field(target, field) => {
+            let target = evaluate(target, heap)?;
+            Ok(lookup_field(&target, field))
+        }
+        Expr::Index(target, index) => {
+            let target = evaluate(target, heap)?;
+            let index = evaluate(index, heap)?;
+            lookup_index(&target, &index)
+        }
+        Expr::Not(inner) => Ok(Value::Bool(!is_truthy(&evaluate(inner, heap)?))),
+        Expr::Binary(BinaryOp::And, left, right) => {
+            let left = evaluate(left, heap)?;
+            if !is_truthy(&left) {
+                return Ok(Value::Bool(false));
+            }
+            Ok(Value::Bool(is_truthy(&evaluate(right, heap)?)))
+        }
+        Expr::Binary(BinaryOp::Or, left, right) => {
+            let left = evaluate(left, heap)?;
+            if is_truthy(&left) {
+                return Ok(Value::Bool(true));
+            }
+            Ok(Value::Bool(is_truthy(&evaluate(right, heap)?)))
+        }
+        Expr::Binary(op, left, right) => {
+            let left = evaluate(left, heap)?;
+            let right = evaluate(right, heap)?;
+            compare(*op, &left, &right).map(Value::Bool)
+        }
+        Expr::Filter(target, name, arguments) => {
+            let target = evaluate(target, heap)?;
+            let arguments = arguments
+                .iter()
+                .map(|argument| evaluate(argument, heap))
+                .collect::<ExpressionResult<Vec<_>>>()?;
+            apply_filter(name, target, &arguments)
+        }
+    }
+}
+
+fn apply_filter(name: &str, target: Value, arguments: &[Value]) -> ExpressionResult<Value> {
+    let unsupported = |value: &Value| {
+        ExpressionError::Evaluation(format!(
+            "Filter '{}' cannot be applied to {}",
+            name,
+            type_name(value)
+        ))
+    };
+    let string_argument = |index: usize| match arguments.get(index) {
+        Some(Value::String(text)) => Ok(text.as_str()),
+        Some(other) => Err(ExpressionError::Evaluation(format!(
+            "Filter '{}' expects a string argument, got {}",
+            name,
+            type_name(other)
+        ))),
+        None => Ok(""),
+    };
+
+    match (name, target) {
+        ("default", Value::Null) => Ok(arguments[0].clone()),
+        ("default", value) => Ok(value),
+        ("json", value) => serde_json::to_string(&value)
+            .map(Value::String)
+            .map_err(|e| ExpressionError::Evaluation(e.to_string())),
+        ("length", Value::Null) => Ok(Value::from(0)),
+        ("length", Value::String(text)) => Ok(Value::from(text.chars().count())),
+        ("length", Value::Sequence(items)) => Ok(Value::from(items.len())),
+        ("length", Value::Mapping(map)) => Ok(Value::from(map.len())),
+        (_, Value::Null) => Ok(Value::Null),
+        ("upper", Value::String(text)) => Ok(Value::String(text.to_uppercase())),
+        ("lower", Value::String(text)) => Ok(Value::String(text.to_lowercase())),
+        ("trim", Value::String(text)) => Ok(Value::String(text.trim().to_string())),
+        ("replace", Value::String(text)) => Ok(Value::String(
+            text.replace(string_argument(0)?, string_argument(1)?),
+        )),
+        ("first", Value::Sequence(items)) => Ok(items.first().cloned().unwrap_or(Value::Null)),
+        ("last", Value::Sequence(items)) => Ok(items.last().cloned().unwrap_or(Value::Null)),
+        ("join", Value::Sequence(items)) => {
+            let separator = string_argument(0)?;
+            let parts = items
+                .iter()
+                .map(|item| match item {
+                    Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => {
+                        Err(unsupported(item))
+                    }
+                    scalar => Ok(to_text(scalar)),
+                })
+                .collect::<ExpressionResult<Vec<_>>>()?;
+            Ok(Value::String(parts.join(separator)))
+        }
+        (_, value) => Err(unsupported(&value)),
+    }
+}
+
+/// Converts a value to the text it renders as in a string
+///
+/// Strings are rendered without quotes and other scalars in their literal
+/// form; sequences and mappings are rendered as YAML.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::expression::to_text;
+/// use serde_yml::Value;
+///
+/// assert_eq!(to_text(&Value::from("text")), "text");
+/// assert_eq!(to_text(&Value::from(1.5)), "1.5");
+/// assert_eq!(to_text(&Value::Null), "null");
+/// ```
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "null".to_string(),
+        _ => serde_yml::to_string(value).unwrap_or_default(),
+    }
+}
+
+fn lookup_field(target: &Value, field: &str) -> Value {
+    match target {
+        Value::Mapping(map) => map.get(field).cloned().unwrap_or(Value::Null),
+        Value::Tagged(tagged) => lookup_field(&tagged.value, field),
+        _ => Value::Null,
+    }
+}
+
+fn lookup_index(target: &Value, index: &Value) -> ExpressionResult<Value> {
+    match (target, index) {
+        (Value::Sequence(items), Value::Number(n)) => {
+            let position = n.as_i64().ok_or_else(|| {
+                ExpressionError::Evaluation(format!("Invalid sequence index {}", n))
+            })?;
+            let position = if position < 0 {
+                items.len() as i64 + position
+            } else {
+                position
+            };
+            Ok(usize::try_from(position)
+                .ok()
+                .and_then(|position| items.get(position))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        (Value::Mapping(map), key) => Ok(map.get(key).cloned().unwrap_or(Value::Null)),
+        (Value::Tagged(tagged), index) => lookup_index(&tagged.value, index),
+        (Value::Null, _) => Ok(Value::Null),
+        (target, index) => Err(ExpressionError::Evaluation(format!(
+            "Cannot index {} with {}",
+            type_name(target),
+            type_name(index)
+        ))),
+    }
+}
+
+fn compare(op: BinaryOp, left: &Value, right: &Value) -> ExpressionResult<bool> {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .zip(b.as_f64())
+            .and_then(|(a, b)| a.partial_cmp(&b)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+
+    match op {
+        BinaryOp::Eq => Ok(ordering.map_or_else(|| left == right, |o| o == Ordering::Equal)),
+        BinaryOp::Ne => Ok(ordering.map_or_else(|| left != right, |o| o != Ordering::Equal)),
+        _ => {
+            let ordering = ordering.ok_or_else(|| {
+                ExpressionError::Evaluation(format!(
+                    "Cannot compare {} with {}",
+                    type_name(left),
+                    type_name(right)
+                ))
+            })?;
+            Ok(match op {
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            })
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Sequence(_) => "a sequence",
+        Value::Mapping(_) => "a mapping",
+        Value::Tagged(_) => "a tagged value",
+    }
+}
+
+/// Lexical tokens of the expression language
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64, String),
+    String(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+fn tokenize(source: &str) -> ExpressionResult<Vec<(Token, usize)>> {
+    const OPERATORS: [&str; 17] = [
+        "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")", "[", "]", ".", "-", "|", ",",
+    ];
+
+    let mut tokens = Vec::new();
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (position, c) = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].1.is_ascii_digit()
+                    || (chars[i].1 == '.'
+                        && chars
+                            .get(i + 1)
+                            .is_some_and(|(_, next)| next.is_ascii_digit())))
+            {
+                i += 1;
+            }
+            let end = chars.get(i).map_or(source.len(), |(p, _)| *p);
+            let text = &source[position..end];
+            let number = text.parse::<f64>().map_err(|_| ExpressionError::Syntax {
+                message: format!("Invalid number '{}'", text),
+                position: chars[start].0,
+            })?;
+            tokens.push((Token::Number(number, text.to_string()), position));
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some((_, '\\')) => {
+                        if let Some((_, escaped)) = chars.get(i + 1) {
+                            text.push(match escaped {
+                                'n' => '\n',
+                                't' => '\t',
+                                other => *other,
+                            });
+                        }
+                        i += 2;
+                    }
+                    Some((_, ch)) if *ch == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some((_, ch)) => {
+                        text.push(*ch);
+                        i += 1;
+                    }
+                    None => {
+                        return Err(ExpressionError::Syntax {
+                            message: "Unterminated string".to_string(),
+                            position,
+                        })
+                    }
+                }
+            }
+            tokens.push((Token::String(text), position));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = position;
+            while i < chars.len() && is_identifier_char(&chars, i) {
+                i += 1;
+            }
+            let end = chars.get(i).map_or(source.len(), |(p, _)| *p);
+            tokens.push((Token::Ident(source[start..end].to_string()), position));
+        } else {
+            let rest = &source[position..];
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| ExpressionError::Syntax {
+                    message: format!("Unexpected character '{}'", c),
+                    position,
+                })?;
+            tokens.push((Token::Op(op), position));
+            i += op.chars().count();
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Identifiers may contain dashes, as node ids often do, but only between
+/// alphanumeric characters so that `a-b` is one name.
+fn is_identifier_char(chars: &[(usize, char)], i: usize) -> bool {
+    let c = chars[i].1;
+    if c.is_alphanumeric() || c == '_' {
+        return true;
+    }
+    c == '-'
+        && chars
+            .get(i + 1)
+            .is_some_and(|(_, next)| next.is_alphanumeric() || *next == '_')
+}
+
+/// Recursive descent parser over the token stream
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn parse_expression(&mut self) -> ExpressionResult<Expr> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> ExpressionResult<Expr> {
+        let mut left = self.parse_and()?;
+        while self.eat_op("||") {
+            let right = self.parse_and()?;
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> ExpressionResult<Expr> {
+        let mut left = self.parse_comparison()?;
+        while self.eat_op("&&") {
+            let right = self.parse_comparison()?;
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_comparison(&mut self) -> ExpressionResult<Expr> {
+        let left = self.parse_unary()?;
+
+        let op = [
+            ("==", BinaryOp::Eq),
+            ("!=", BinaryOp::Ne),
+            ("<=", BinaryOp::Le),
+            (">=", BinaryOp::Ge),
+            ("<", BinaryOp::Lt),
+            (">", BinaryOp::Gt),
+        ]
+        .into_iter()
+        .find(|(symbol, _)| self.eat_op(symbol));
+
+        match op {
+            Some((_, op)) => {
+                let right = self.parse_unary()?;
+                Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+            }
+            None => Ok(left),
+        }
+    }
+
+    fn parse_unary(&mut self) -> ExpressionResult<Expr> {
+        if self.eat_op("!") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.eat_op("-") {
+            let position = self.current_position();
+            return match self.next() {
+                Some(Token::Number(n, _)) => Ok(Expr::Literal(number_value(-n))),
+                _ => Err(self.error_at("Expected a number after '-'", position)),
+            };
+        }
+
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> ExpressionResult<Expr> {
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            if self.eat_op(".") {
+                let position = self.current_position();
+                match self.next() {
+                    Some(Token::Ident(field)) => expr = Expr::Field(Box::new(expr), field),
+                    Some(Token::Number(_, text)) if !text.contains('.') => {
+                        expr = Expr::Index(
+                            Box::new(expr),
+                            Box::new(Expr::Literal(Value::Number(
+                                text.parse::<u64>().unwrap_or_default().into(),
+                            ))),
+                        )
+                    }
+                    _ => return Err(self.error_at("Expected a field name after '.'", position)),
+                }
+            } else if self.eat_op("[") {
+                let index = self.parse_expression()?;
+                self.expect_op("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else if self.eat_op("|") {
+                expr = self.parse_filter(expr)?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_filter(&mut self, target: Expr) -> ExpressionResult<Expr> {
+        let position = self.current_position();
+        let name = match self.next() {
+            Some(Token::Ident(name)) => name,
+            _ => return Err(self.error_at("Expected a filter name after '|'", position)),
+        };
+        let (_, min, max) = FILTERS
+            .iter()
+            .find(|(filter, _, _)| *filter == name)
+            .ok_or_else(|| self.error_at(&format!("Unknown filter '{}'", name), position))?;
+
+        let mut arguments = Vec::new();
+        if self.eat_op("(") && !self.eat_op(")") {
+            loop {
+                arguments.push(self.parse_expression()?);
+                if !self.eat_op(",") {
+                    self.expect_op(")")?;
+                    break;
+                }
+            }
+        }
+
+        if arguments.len() < *min || arguments.len() > *max {
+            let expected = if min == max {
+                min.to_string()
+            } else {
+                format!("{} to {}", min, max)
+            };
+            return Err(self.error_at(
+                &format!(
+                    "Filter '{}' takes {} argument(s), got {}",
+                    name,
+                    expected,
+                    arguments.len()
+                ),
+                position,
+            ));
+        }
+
+        Ok(Expr::Filter(Box::new(target), name, arguments))
+    }
+
+    fn parse_primary(&mut self) -> ExpressionResult<Expr> {
+        let position = self.current_position();
+
+        match self.next() {
+            Some(Token::Number(n, _)) => Ok(Expr::Literal(number_value(n))),
+            Some(Token::String(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Ident(name)) => Ok(match name.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                "null" => Expr::Literal(Value::Null),
+                _ => Expr::Reference(name),
+            }),
+            Some(Token::Op("(")) => {
+                let expr = self.parse_expression()?;
+                self.expect_op(")")?;
+                Ok(expr)
+            }
+            Some(Token::Op(op)) => Err(self.error_at(&format!("Unexpected '{}'", op), position)),
+            None => Err(self.error_at("Unexpected end of expression", position)),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        match self.tokens.get(self.position) {
+            Some((Token::Op(found), _)) if *found == op => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> ExpressionResult<()> {
+        if self.eat_op(op) {
+            Ok(())
+        } else {
+            Err(self.error_at(&format!("Expected '{}'", op), self.current_position()))
+        }
+    }
+
+    fn expect_end(&self) -> ExpressionResult<()> {
+        match self.tokens.get(self.position) {
+            None => Ok(()),
+            Some((_, position)) => Err(self.error_at("Unexpected token", *position)),
+        }
+    }
+
+    fn current_position(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end, |(_, position)| *position)
+    }
+
+    fn error_at(&self, message: &str, position: usize) -> ExpressionError {
+        ExpressionError::Syntax {
+            message: message.to_string(),
+            position,
+        }
+    }
+}
+
+fn number_value(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::Number((n as i64).into())
+    } else {
+        Value::Number(n.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heap() -> Heap {
+        let mut heap = Heap::new();
+        heap.insert("debug", Some(Value::Bool(true)));
+        heap.insert("count", Some(Value::Number(3.into())));
+        heap.insert("name", Some(Value::String("Ada".to_string())));
+        heap.insert(
+            "user",
+            Some(serde_yml::from_str("{ name: Ada, roles: [admin, dev] }").unwrap()),
+        );
+        heap
+    }
+
+    fn eval(source: &str) -> Value {
+        Expression::parse(source)
+            .unwrap()
+            .evaluate(&heap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_expression_literals() {
+        assert_eq!(eval("42"), Value::Number(42.into()));
+        assert_eq!(eval("-2"), Value::Number((-2).into()));
+        assert_eq!(eval("'text'"), Value::String("text".to_string()));
+        assert_eq!(eval("\"a\\\"b\""), Value::String("a\"b".to_string()));
+        assert_eq!(eval("true"), Value::Bool(true));
+        assert_eq!(eval("null"), Value::Null);
+    }
+
+    #[test]
+    fn test_expression_references() {
+        assert_eq!(eval("name"), Value::String("Ada".to_string()));
+        assert_eq!(eval("user.name"), Value::String("Ada".to_string()));
+        assert_eq!(eval("user.roles[1]"), Value::String("dev".to_string()));
+        assert_eq!(eval("user.roles.0"), Value::String("admin".to_string()));
+        assert_eq!(eval("user.roles.1.missing"), Value::Null);
+        assert_eq!(eval("user.roles[-1]"), Value::String("dev".to_string()));
+        assert_eq!(eval("user['name']"), Value::String("Ada".to_string()));
+        assert_eq!(eval("missing.field"), Value::Null);
+    }
+
+    #[test]
+    fn test_expression_comparisons() {
+        assert_eq!(eval("debug == true"), Value::Bool(true));
+        assert_eq!(eval("count > 2"), Value::Bool(true));
+        assert_eq!(eval("count <= 2.5"), Value::Bool(false));
+        assert_eq!(eval("count == 3.0"), Value::Bool(true));
+        assert_eq!(eval("name != 'Bob'"), Value::Bool(true));
+        assert_eq!(eval("missing == null"), Value::Bool(true));
+    }
+
+    #[test]
+    fn test_expression_logic() {
+        assert_eq!(eval("debug && count > 5"), Value::Bool(false));
+        assert_eq!(eval("!debug || name == 'Ada'"), Value::Bool(true));
+        assert_eq!(eval("!(count > 1 && debug)"), Value::Bool(false));
+    }
+
+    #[test]
+    fn test_expression_placeholder_wrapper() {
+        let expression = Expression::parse("${{ debug }}").unwrap();
+        assert_eq!(expression.references(), vec!["debug"]);
+        assert_eq!(expression.source(), "${{ debug }}");
+    }
+
+    #[test]
+    fn test_expression_references_deduplicated() {
+        let expression = Expression::parse("a.x == b || a[c] && log-1").unwrap();
+        assert_eq!(expression.references(), vec!["a", "b", "c", "log-1"]);
+    }
+
+    #[test]
+    fn test_expression_syntax_errors() {
+        for source in ["", "a ==", "(a", "a b", "'open", "a @ b", "a.", "-x"] {
+            assert!(
+                matches!(
+                    Expression::parse(source),
+                    Err(ExpressionError::Syntax { .. })
+                ),
+                "expected syntax error for {:?}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_expression_syntax_error_position() {
+        let error = Expression::parse("a == == b").unwrap_err();
+        assert_eq!(
+            error,
+            ExpressionError::Syntax {
+                message: "Unexpected '=='".to_string(),
+                position: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_expression_evaluation_errors() {
+        let expression = Expression::parse("name > 1").unwrap();
+        assert!(matches!(
+            expression.evaluate(&heap()),
+            Err(ExpressionError::Evaluation(_))
+        ));
+    }
+
+    #[test]
+    fn test_expression_observed_values() {
+        let expression = Expression::parse("user.roles[0] == 'x' && !debug || debug").unwrap();
+        assert_eq!(
+            expression.observed_values(&heap()),
+            vec![
+                (
+                    "user.roles[0]".to_string(),
+                    Value::String("admin".to_string())
+                ),
+                ("debug".to_string(), Value::Bool(true)),
+            ]
+        );
+
+        let expression = Expression::parse("user[\"roles\"][count] == missing").unwrap();
+        assert_eq!(
+            expression
+                .observed_values(&heap())
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            vec!["user[\"roles\"][count]", "count", "missing"]
+        );
+    }
+
+    #[test]
+    fn test_expression_filters() {
+        assert_eq!(eval("name | upper"), Value::from("ADA"));
+        assert_eq!(eval("user.name | lower | length"), Value::from(3));
+        assert_eq!(eval("user.roles | join(', ')"), Value::from("admin, dev"));
+        assert_eq!(eval("user.roles | last"), Value::from("dev"));
+        assert_eq!(eval("missing | default('none')"), Value::from("none"));
+        assert_eq!(
+            eval("' a-b ' | trim | replace('-', '+')"),
+            Value::from("a+b")
+        );
+        assert_eq!(
+            eval("user.roles | json"),
+            Value::from("[\"admin\",\"dev\"]")
+        );
+        assert_eq!(eval("name | upper == 'ADA' || false"), Value::Bool(true));
+
+        let expression = Expression::parse("user | join(sep)").unwrap();
+        assert_eq!(expression.references(), vec!["user", "sep"]);
+        assert!(matches!(
+            expression.evaluate(&heap()),
+            Err(ExpressionError::Evaluation(_))
+        ));
+    }
+
+    #[test]
+    fn test_expression_filter_errors() {
+        assert_eq!(
+            Expression::parse("name | shout").unwrap_err().to_string(),
+            "Unknown filter 'shout' at position 7"
+        );
+        assert_eq!(
+            Expression::parse("name | replace('a')")
+                .unwrap_err()
+                .to_string(),
+            "Filter 'replace' takes 2 argument(s), got 1 at position 7"
+        );
+        assert!(Expression::parse("name |").is_err());
+    }
+
+    #[test]
+    fn test_is_truthy() {
+        assert!(is_truthy(&Value::Bool(true)));
+        assert!(is_truthy(&Value::Number(1.into())));
+        assert!(!is_truthy(&Value::String(String::new())));
+        assert!(!is_truthy(&Value::Sequence(Vec::new())));
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders("no placeholders"), Vec::<&str>::new());
+        assert_eq!(placeholders("${{ a }} and ${{b.c}}"), vec!["a", "b.c"]);
+        assert_eq!(placeholders("${{ unterminated"), Vec::<&str>::new());
+    }
+}
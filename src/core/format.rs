@@ -0,0 +1,545 @@
+//! Data formats
+//!
+//! This module converts between text and [`serde_yml::Value`] for the data
+//! formats workflows exchange with other tools: JSON, YAML, TOML and CSV.
+//!
+//! CSV documents are sequences of rows. With a header row, each row becomes
+//! a mapping from the column names to the cells, so that the rows can feed
+//! `for_each` and be used in `${{ ... }}` paths; without one, each row is a
+//! sequence of cells. Cells are always strings.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde_yml::{Mapping, Value};
+
+use crate::core::expression::to_text;
+
+/// Names of the supported formats
+pub const FORMATS: [&str; 4] = ["json", "yaml", "toml", "csv"];
+
+/// Errors that can occur while parsing or serializing data
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum FormatError {
+    /// The format name is not supported
+    #[error("Unknown format '{0}' (expected one of: json, yaml, toml, csv)")]
+    Unknown(String),
+
+    /// The text is not valid in the format
+    #[error("Invalid {format}: {message}")]
+    Parse { format: Format, message: String },
+
+    /// The value cannot be represented in the format
+    #[error("Cannot serialize to {format}: {message}")]
+    Serialize { format: Format, message: String },
+}
+
+/// Result type for format operations
+pub type FormatResult<T> = Result<T, FormatError>;
+
+/// A supported data format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+}
+
+impl Format {
+    /// Returns the format matching the extension of a file, if any
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::format::Format;
+    ///
+    /// assert_eq!(Format::from_path("data/hosts.yml"), Some(Format::Yaml));
+    /// assert_eq!(Format::from_path("Cargo.toml"), Some(Format::Toml));
+    /// assert_eq!(Format::from_path("notes.txt"), None);
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        extension.to_ascii_lowercase().parse().ok()
+    }
+}
+
+impl FromStr for Format {
+    type Err = FormatError;
+
+    fn from_str(name: &str) -> FormatResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            "csv" => Ok(Format::Csv),
+            _ => Err(FormatError::Unknown(name.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::Json => "JSON",
+            Format::Yaml => "YAML",
+            Format::Toml => "TOML",
+            Format::Csv => "CSV",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Header handling of CSV documents
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CsvHeaders {
+    /// The first row holds the column names
+    #[default]
+    FirstRow,
+
+    /// The document has no header row, and rows are sequences
+    Absent,
+
+    /// The column names, with no header row in the document
+    Names(Vec<String>),
+}
+
+/// Options of CSV documents
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::format::{parse, CsvHeaders, CsvOptions, Format};
+/// use serde_yml::Value;
+///
+/// let options = CsvOptions::new()
+///     .with_headers(CsvHeaders::Names(vec!["host".into(), "port".into()]))
+///     .with_delimiter(b';');
+/// let rows = parse("web;80\ndb;5432\n", Format::Csv, &options).unwrap();
+/// assert_eq!(rows[1]["port"], Value::from("5432"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    headers: CsvHeaders,
+    delimiter: u8,
+}
+
+impl CsvOptions {
+    /// Creates options for comma-separated documents with a header row
+    pub fn new() -> Self {
+        Self {
+            headers: CsvHeaders::FirstRow,
+            delimiter: b',',
+        }
+    }
+
+    /// Builder method to set the header handling
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_headers(mut self, headers: CsvHeaders) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Builder method to set the field delimiter
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Returns the header handling
+    pub fn headers(&self) -> &CsvHeaders {
+        &self.headers
+    }
+
+    /// Returns the field delimiter
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses a text in the given format
+///
+/// # Arguments
+///
+/// * `text` - The text to parse
+/// * `format` - The format of the text
+/// * `csv` - Options used when the format is CSV
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::format::{parse, CsvOptions, Format};
+/// use serde_yml::Value;
+///
+/// let value = parse("[server]\nport = 8080", Format::Toml, &CsvOptions::new()).unwrap();
+/// assert_eq!(value["server"]["port"], Value::from(8080));
+///
+/// let rows = parse("name,port\nweb,80\n", Format::Csv, &CsvOptions::new()).unwrap();
+/// assert_eq!(rows[0]["name"], Value::from("web"));
+/// ```
+///
+/// # Errors
+///
+/// Returns `FormatError::Parse` if the text is not valid in the format.
+pub fn parse(text: &str, format: Format, csv: &CsvOptions) -> FormatResult<Value> {
+    let parse_error = |message: String| FormatError::Parse { format, message };
+
+    match format {
+        Format::Json => serde_json::from_str(text).map_err(|e| parse_error(e.to_string())),
+        Format::Yaml => serde_yml::from_str(text).map_err(|e| parse_error(e.to_string())),
+        Format::Toml => text
+            .parse::<toml::Table>()
+            .map(|table| from_toml(toml::Value::Table(table)))
+            .map_err(|e| parse_error(e.message().to_string())),
+        Format::Csv => parse_csv(text, csv).map_err(|e| parse_error(e.to_string())),
+    }
+}
+
+/// Serializes a value in the given format
+///
+/// JSON is pretty-printed, and the serialized text ends with a newline.
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize
+/// * `format` - The format of the text
+/// * `csv` - Options used when the format is CSV
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::format::{serialize, CsvOptions, Format};
+///
+/// let rows = serde_yml::from_str("[{ name: web, port: 80 }, { name: db }]").unwrap();
+/// let text = serialize(&rows, Format::Csv, &CsvOptions::new()).unwrap();
+/// assert_eq!(text, "name,port\nweb,80\ndb,\n");
+/// ```
+///
+/// # Errors
+///
+/// Returns `FormatError::Serialize` if the value cannot be represented in
+/// the format, e.g. a sequence in TOML or nested values in CSV cells.
+pub fn serialize(value: &Value, format: Format, csv: &CsvOptions) -> FormatResult<String> {
+    let serialize_error = |message: String| FormatError::Serialize { format, message };
+
+    let text = match format {
+        Format::Json => serde_json::to_string_pretty(value)
+            .map(|text| text + "\n")
+            .map_err(|e| serialize_error(e.to_string()))?,
+        Format::Yaml => serde_yml::to_string(value).map_err(|e| serialize_error(e.to_string()))?,
+        Format::Toml => {
+            if !matches!(value, Value::Mapping(_)) {
+                return Err(serialize_error(
+                    "the document must be a mapping".to_string(),
+                ));
+            }
+            toml::to_string(value).map_err(|e| serialize_error(e.to_string()))?
+        }
+        Format::Csv => serialize_csv(value, csv).map_err(serialize_error)?,
+    };
+    Ok(text)
+}
+
+/// Converts a TOML value, with dates and times as strings
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Sequence(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(table) => Value::Mapping(
+            table
+                .into_iter()
+                .map(|(key, value)| (Value::String(key), from_toml(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn parse_csv(text: &str, options: &CsvOptions) -> Result<Value, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(options.headers == CsvHeaders::FirstRow)
+        .delimiter(options.delimiter)
+        .from_reader(text.as_bytes());
+
+    let names: Option<Vec<String>> = match &options.headers {
+        CsvHeaders::FirstRow => Some(reader.headers()?.iter().map(str::to_string).collect()),
+        CsvHeaders::Names(names) => Some(names.clone()),
+        CsvHeaders::Absent => None,
+    };
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let row = match &names {
+            Some(names) => Value::Mapping(
+                names
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(name, cell)| (Value::from(name.as_str()), Value::from(cell)))
+                    .collect(),
+            ),
+            None => Value::Sequence(record.iter().map(Value::from).collect()),
+        };
+        rows.push(row);
+    }
+    Ok(Value::Sequence(rows))
+}
+
+fn serialize_csv(value: &Value, options: &CsvOptions) -> Result<String, String> {
+    let Value::Sequence(rows) = value else {
+        return Err("the document must be a sequence of rows".to_string());
+    };
+
+    let names = match &options.headers {
+        CsvHeaders::Names(names) => names.clone(),
+        _ => column_names(rows),
+    };
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .flexible(true)
+        .from_writer(Vec::new());
+    let write_error = |e: csv::Error| e.to_string();
+
+    if options.headers != CsvHeaders::Absent && !names.is_empty() {
+        writer.write_record(&names).map_err(write_error)?;
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let cells = match row {
+            Value::Mapping(row) => names
+                .iter()
+                .map(|name| row.get(name.as_str()).map_or(Ok(String::new()), cell))
+                .collect::<Result<Vec<_>, _>>(),
+            Value::Sequence(row) => row.iter().map(cell).collect(),
+            _ => Err("rows must be mappings or sequences".to_string()),
+        }
+        .map_err(|message| format!("row {}: {}", index + 1, message))?;
+        writer.write_record(&cells).map_err(write_error)?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// Returns the keys of mapping rows, in order of first appearance
+fn column_names(rows: &[Value]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for key in rows
+        .iter()
+        .filter_map(Value::as_mapping)
+        .flat_map(Mapping::keys)
+    {
+        let name = to_text(key);
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Returns the text of a CSV cell
+fn cell(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => {
+            Err("cells must be scalars".to_string())
+        }
+        scalar => Ok(to_text(scalar)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!("JSON".parse::<Format>().unwrap(), Format::Json);
+        assert_eq!("yml".parse::<Format>().unwrap(), Format::Yaml);
+        assert_eq!(
+            "xml".parse::<Format>().unwrap_err().to_string(),
+            "Unknown format 'xml' (expected one of: json, yaml, toml, csv)"
+        );
+        assert_eq!(Format::from_path("report.CSV"), Some(Format::Csv));
+        assert_eq!(Format::from_path("Makefile"), None);
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let options = CsvOptions::new();
+        let expected = yaml("{ name: web, ports: [80, 443], tls: true }");
+
+        assert_eq!(
+            parse(
+                r#"{"name":"web","ports":[80,443],"tls":true}"#,
+                Format::Json,
+                &options
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse(
+                "name: web\nports: [80, 443]\ntls: true",
+                Format::Yaml,
+                &options
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse(
+                "name = \"web\"\nports = [80, 443]\ntls = true",
+                Format::Toml,
+                &options
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse("released = 2024-05-01", Format::Toml, &options).unwrap(),
+            yaml("{ released: '2024-05-01' }")
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_headers() {
+        let text = "name,port\nweb,80\ndb,5432\n";
+
+        assert_eq!(
+            parse(text, Format::Csv, &CsvOptions::new()).unwrap(),
+            yaml("[{ name: web, port: '80' }, { name: db, port: '5432' }]")
+        );
+        assert_eq!(
+            parse(
+                text,
+                Format::Csv,
+                &CsvOptions::new().with_headers(CsvHeaders::Absent)
+            )
+            .unwrap(),
+            yaml("[[name, port], [web, '80'], [db, '5432']]")
+        );
+        assert_eq!(
+            parse(
+                "web\t80\n",
+                Format::Csv,
+                &CsvOptions::new()
+                    .with_headers(CsvHeaders::Names(vec!["host".into(), "port".into()]))
+                    .with_delimiter(b'\t')
+            )
+            .unwrap(),
+            yaml("[{ host: web, port: '80' }]")
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let options = CsvOptions::new();
+        assert!(parse("{", Format::Json, &options)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid JSON: "));
+        assert!(parse("a = ", Format::Toml, &options)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid TOML: "));
+        assert!(parse("a,b\n1,2,3\n", Format::Csv, &options)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid CSV: "));
+    }
+
+    #[test]
+    fn test_serialize_formats() {
+        let options = CsvOptions::new();
+        let value = yaml("{ name: web, ports: [80, 443] }");
+
+        assert_eq!(
+            serialize(&value, Format::Json, &options).unwrap(),
+            "{\n  \"name\": \"web\",\n  \"ports\": [\n    80,\n    443\n  ]\n}\n"
+        );
+        assert_eq!(
+            serialize(&value, Format::Yaml, &options).unwrap(),
+            "name: web\nports:\n- 80\n- 443\n"
+        );
+        assert_eq!(
+            serialize(&value, Format::Toml, &options).unwrap(),
+            "name = \"web\"\nports = [80, 443]\n"
+        );
+        for format in [Format::Json, Format::Yaml, Format::Toml] {
+            let text = serialize(&value, format, &options).unwrap();
+            assert_eq!(parse(&text, format, &options).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_serialize_csv() {
+        let rows = yaml("[{ name: web, port: 80 }, { name: db, tls: true }]");
+        assert_eq!(
+            serialize(&rows, Format::Csv, &CsvOptions::new()).unwrap(),
+            "name,port,tls\nweb,80,\ndb,,true\n"
+        );
+        assert_eq!(
+            serialize(
+                &rows,
+                Format::Csv,
+                &CsvOptions::new().with_headers(CsvHeaders::Names(vec!["name".into()]))
+            )
+            .unwrap(),
+            "name\nweb\ndb\n"
+        );
+        assert_eq!(
+            serialize(
+                &yaml("[[a, 'b,c'], [1, null]]"),
+                Format::Csv,
+                &CsvOptions::new().with_headers(CsvHeaders::Absent)
+            )
+            .unwrap(),
+            "a,\"b,c\"\n1,\n"
+        );
+    }
+
+    #[test]
+    fn test_serialize_errors() {
+        let options = CsvOptions::new();
+        assert_eq!(
+            serialize(&yaml("[1, 2]"), Format::Toml, &options)
+                .unwrap_err()
+                .to_string(),
+            "Cannot serialize to TOML: the document must be a mapping"
+        );
+        assert_eq!(
+            serialize(&yaml("{ a: 1 }"), Format::Csv, &options)
+                .unwrap_err()
+                .to_string(),
+            "Cannot serialize to CSV: the document must be a sequence of rows"
+        );
+        assert_eq!(
+            serialize(&yaml("[{ a: [1] }]"), Format::Csv, &options)
+                .unwrap_err()
+                .to_string(),
+            "Cannot serialize to CSV: row 1: cells must be scalars"
+        );
+    }
+}
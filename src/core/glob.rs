@@ -0,0 +1,236 @@
+//! Glob patterns
+//!
+//! This module matches relative file paths, with `/` separators, against
+//! glob patterns such as `**/*.rs` or `bin/app-{linux,macos}`, compiled
+//! with `globset`:
+//!
+//! - `*` matches any characters except `/`
+//! - `**` as a whole path component matches any number of directories
+//! - `?` matches a single character except `/`
+//! - `[abc]`, `[a-z]` and `[!abc]` match a character of, or not of, a set
+//! - `{a,b}` matches either alternative
+//!
+//! A pattern without `/` matches the name of a file or directory at any
+//! depth, so `*.log` matches `logs/app.log`. A pattern matching a directory
+//! matches everything below it, so `target` matches `target/release/app`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use colossus::core::glob::Glob;
+//!
+//! let glob = Glob::new("src/**/*.rs").unwrap();
+//! assert!(glob.matches("src/main.rs"));
+//! assert!(glob.matches("src/core/engine.rs"));
+//! assert!(!glob.matches("tests/cli.rs"));
+//! ```
+
+use std::fmt;
+
+use globset::{GlobBuilder, GlobMatcher};
+
+/// Error of a malformed glob pattern
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Invalid glob '{pattern}': {message}")]
+pub struct GlobError {
+    pattern: String,
+    message: String,
+}
+
+/// Result type for glob operations
+pub type GlobResult<T> = Result<T, GlobError>;
+
+/// A compiled glob pattern
+#[derive(Clone)]
+pub struct Glob {
+    pattern: String,
+    matcher: GlobMatcher,
+}
+
+impl Glob {
+    /// Compiles a glob pattern
+    ///
+    /// # Errors
+    ///
+    /// Returns `GlobError` if a `[` or `{` is not closed, or if the pattern
+    /// is empty.
+    pub fn new(pattern: &str) -> GlobResult<Self> {
+        let error = |message: String| GlobError {
+            pattern: pattern.to_string(),
+            message,
+        };
+
+        let trimmed = pattern.trim_start_matches("./").trim_end_matches('/');
+        if trimmed.is_empty() {
+            return Err(error("the pattern is empty".to_string()));
+        }
+
+        // A pattern without a separator matches a name at any depth
+        let glob = if trimmed.contains('/') {
+            trimmed.to_string()
+        } else {
+            format!("**/{}", trimmed)
+        };
+        let matcher = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .backslash_escape(true)
+            .build()
+            .map_err(|e| error(e.kind().to_string()))?
+            .compile_matcher();
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            matcher,
+        })
+    }
+
+    /// Returns the pattern of the glob
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns whether a relative path, or one of its parent directories,
+    /// matches the pattern
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        path.match_indices('/')
+            .map(|(end, _)| &path[..end])
+            .chain(std::iter::once(path))
+            .any(|ancestor| self.matcher.is_match(ancestor))
+    }
+}
+
+impl fmt::Debug for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Glob").field(&self.pattern).finish()
+    }
+}
+
+/// Paths selected by include and exclude patterns
+///
+/// A path is selected if it matches any include pattern, or if there are
+/// none, and it matches no exclude pattern.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::glob::{Glob, GlobFilter};
+///
+/// let filter = GlobFilter::new()
+///     .with_include(Glob::new("bin").unwrap())
+///     .with_exclude(Glob::new("*.debug").unwrap());
+/// assert!(filter.selects("bin/app"));
+/// assert!(!filter.selects("bin/app.debug"));
+/// assert!(!filter.selects("README.md"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GlobFilter {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl GlobFilter {
+    /// Creates a filter selecting every path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pattern of the paths to select
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_include(mut self, glob: Glob) -> Self {
+        self.include.push(glob);
+        self
+    }
+
+    /// Adds a pattern of the paths to leave out
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_exclude(mut self, glob: Glob) -> Self {
+        self.exclude.push(glob);
+        self
+    }
+
+    /// Returns whether the filter selects a relative path
+    pub fn selects(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.matches(path)))
+            && !self.exclude.iter().any(|glob| glob.matches(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        Glob::new(pattern).unwrap().matches(path)
+    }
+
+    #[test]
+    fn test_glob_wildcards() {
+        assert!(matches("*.rs", "main.rs"));
+        assert!(matches("*.rs", "src/core/engine.rs"));
+        assert!(!matches("src/*.rs", "src/core/engine.rs"));
+        assert!(matches("src/**/*.rs", "src/main.rs"));
+        assert!(matches("src/**/*.rs", "src/core/engine.rs"));
+        assert!(matches("**/*.rs", "main.rs"));
+        assert!(matches("src/**", "src/core/engine.rs"));
+        assert!(!matches("a/**b", "a/x/yb"));
+        assert!(matches("file?.txt", "file1.txt"));
+        assert!(!matches("file?.txt", "file10.txt"));
+        assert!(!matches("a?b", "a/b"));
+        assert!(matches("v1.0", "v1.0"));
+        assert!(!matches("v1.0", "v1x0"));
+    }
+
+    #[test]
+    fn test_glob_sets_and_alternatives() {
+        assert!(matches("log[0-9].txt", "log7.txt"));
+        assert!(!matches("log[!0-9].txt", "log7.txt"));
+        assert!(matches("log[!0-9].txt", "logs.txt"));
+        assert!(matches("[]x]", "]"));
+        assert!(matches("app-{linux,macos}.tar.gz", "dist/app-macos.tar.gz"));
+        assert!(!matches("app-{linux,macos}.tar.gz", "app-windows.tar.gz"));
+    }
+
+    #[test]
+    fn test_glob_matches_directories() {
+        assert!(matches("target", "target/release/app"));
+        assert!(matches("build/", "build/out.o"));
+        assert!(matches("./docs", "docs/index.md"));
+        assert!(matches("node_modules", "web/node_modules/react/index.js"));
+        assert!(!matches("target", "targets/app"));
+    }
+
+    #[test]
+    fn test_glob_errors() {
+        assert_eq!(
+            Glob::new("log[0-9").unwrap_err().to_string(),
+            "Invalid glob 'log[0-9': unclosed character class; missing ']'"
+        );
+        assert!(Glob::new("{a,b")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid glob '{a,b': unclosed alternate group"));
+        assert!(Glob::new("").is_err());
+        assert!(Glob::new("[z-a]").is_err());
+    }
+
+    #[test]
+    fn test_glob_filter() {
+        let filter = GlobFilter::new()
+            .with_include(Glob::new("dist/**").unwrap())
+            .with_include(Glob::new("README.md").unwrap())
+            .with_exclude(Glob::new("*.map").unwrap());
+
+        assert!(filter.selects("README.md"));
+        assert!(filter.selects("dist/app.js"));
+        assert!(!filter.selects("dist/app.js.map"));
+        assert!(!filter.selects("src/app.ts"));
+        assert!(GlobFilter::new().selects("anything"));
+    }
+}
@@ -1,9 +1,15 @@
 use regex::Regex;
 use std::collections::HashMap;
+use std::sync::LazyLock;
 
 use serde::{Deserialize, Serialize};
 use serde_yml::Value;
 
+use crate::core::expression::{to_text, Expression};
+
+/// Pattern of a `${{ ... }}` placeholder, capturing its body
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$\{\{([^}]+)\}\}").unwrap());
+
 /// A shared data store for workflow execution
 ///
 /// The `Heap` provides a thread-safe way to share data between workflow nodes.
@@ -136,6 +142,29 @@ impl Heap {
         self.data.remove(key).flatten()
     }
 
+    /// Iterates over the entries of the heap that hold a value
+    ///
+    /// Entries are visited in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::heap::Heap;
+    /// use serde_yml::Value;
+    ///
+    /// let mut heap = Heap::new();
+    /// heap.insert("name", Some(Value::from("John")));
+    /// heap.insert("skipped", None);
+    ///
+    /// let entries: Vec<_> = heap.iter().collect();
+    /// assert_eq!(entries, vec![("name", &Value::from("John"))]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.data
+            .iter()
+            .filter_map(|(key, value)| value.as_ref().map(|value| (key.as_str(), value)))
+    }
+
     /// Returns the number of key-value pairs in the heap
     ///
     /// # Returns
@@ -196,7 +225,80 @@ impl Heap {
         self.data.clear();
     }
 
-    /// Parses a string value and replaces variables with values from the heap
+    /// Merges another heap into this one
+    ///
+    /// Entries of `other` overwrite entries with the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The heap whose entries should be copied
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::heap::Heap;
+    /// use serde_yml::Value;
+    ///
+    /// let mut heap = Heap::new();
+    /// heap.insert("name", Some(Value::String("John".to_string())));
+    ///
+    /// let mut other = Heap::new();
+    /// other.insert("name", Some(Value::String("Jane".to_string())));
+    /// other.insert("age", Some(Value::Number(30.into())));
+    ///
+    /// heap.merge(other);
+    /// assert_eq!(heap.get("name"), Some(&Value::String("Jane".to_string())));
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    pub fn merge(&mut self, other: Heap) {
+        self.data.extend(other.data);
+    }
+
+    /// Creates a child scope of the heap
+    ///
+    /// The child starts with every entry of the heap, plus the given
+    /// bindings, which shadow entries with the same key. Changes to the child
+    /// do not affect the heap.
+    ///
+    /// # Arguments
+    ///
+    /// * `bindings` - Values only visible in the child scope
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::heap::Heap;
+    /// use serde_yml::Value;
+    ///
+    /// let mut heap = Heap::new();
+    /// heap.insert("prefix", Some(Value::from("file-")));
+    ///
+    /// let child = heap.scope([("item".to_string(), Value::from("a.txt"))]);
+    /// assert_eq!(
+    ///     child.parse(Some(Value::from("${{ prefix }}${{ item }}"))),
+    ///     Some(Value::from("file-a.txt"))
+    /// );
+    /// assert!(!heap.contains_key("item"));
+    /// ```
+    pub fn scope(&self, bindings: impl IntoIterator<Item = (String, Value)>) -> Heap {
+        let mut child = self.clone();
+        for (key, value) in bindings {
+            child.insert(key, Some(value));
+        }
+        child
+    }
+
+    /// Parses a value and replaces variables with values from the heap
+    ///
+    /// Every `${{ ... }}` placeholder is evaluated as an
+    /// [expression](crate::core::expression), so it can reference fields of
+    /// structured values such as `${{ build.artifacts.0 }}`. A string made of
+    /// a single placeholder is replaced by the value itself, keeping its
+    /// type; placeholders within text are replaced by their string form.
+    /// Sequences and mappings are parsed recursively.
+    ///
+    /// Placeholders referencing names missing from the heap, or that are not
+    /// valid expressions, are left as-is.
     ///
     /// # Arguments
     ///
@@ -219,28 +321,72 @@ impl Heap {
     /// let input = Value::String("Hello ${{name}}, you are ${{age}} years old".to_string());
     /// let result = heap.parse(Some(input));
     /// assert_eq!(result, Some(Value::String("Hello John, you are 30 years old".to_string())));
+    ///
+    /// let result = heap.parse(Some(Value::String("${{ age }}".to_string())));
+    /// assert_eq!(result, Some(Value::Number(30.into())));
     /// ```
     pub fn parse(&self, value: Option<Value>) -> Option<Value> {
+        value.map(|value| self.parse_value(value))
+    }
+
+    /// Substitutes the placeholders of a value, recursively
+    fn parse_value(&self, value: Value) -> Value {
         match value {
-            Some(Value::String(s)) => {
-                let re = Regex::new(r"\$\{\{([^}]+)\}\}").unwrap();
-                let mut result = s.clone();
-
-                for cap in re.captures_iter(&s) {
-                    if let Some(key) = cap.get(1) {
-                        let key = key.as_str().trim();
-                        if let Some(val) = self.get(key) {
-                            let replacement = self.value_to_string(val);
-                            result = result.replace(&cap[0], &replacement);
-                        }
-                    }
-                }
-
-                Some(Value::String(result))
+            Value::String(text) => self.parse_string(text),
+            Value::Sequence(items) => Value::Sequence(
+                items
+                    .into_iter()
+                    .map(|item| self.parse_value(item))
+                    .collect(),
+            ),
+            Value::Mapping(map) => Value::Mapping(
+                map.into_iter()
+                    .map(|(key, item)| (key, self.parse_value(item)))
+                    .collect(),
+            ),
+            Value::Tagged(mut tagged) => {
+                tagged.value = self.parse_value(tagged.value);
+                Value::Tagged(tagged)
+            }
+            other => other,
+        }
+    }
+
+    /// Substitutes the placeholders of a string
+    fn parse_string(&self, text: String) -> Value {
+        if let Some(cap) = PLACEHOLDER.captures(&text) {
+            if cap[0].len() == text.len() {
+                return self
+                    .evaluate_placeholder(&cap[1])
+                    .unwrap_or(Value::String(text));
             }
-            Some(v) => Some(v),
-            None => None,
         }
+
+        let result = PLACEHOLDER.replace_all(&text, |cap: &regex::Captures| {
+            self.evaluate_placeholder(&cap[1])
+                .map(|value| self.value_to_string(&value))
+                .unwrap_or_else(|| cap[0].to_string())
+        });
+
+        Value::String(result.into_owned())
+    }
+
+    /// Evaluates the body of a placeholder
+    ///
+    /// Returns `None` if the body is not a valid expression or references a
+    /// name missing from the heap.
+    fn evaluate_placeholder(&self, body: &str) -> Option<Value> {
+        let expression = Expression::parse(body).ok()?;
+
+        if expression
+            .references()
+            .iter()
+            .any(|name| self.get(name).is_none())
+        {
+            return None;
+        }
+
+        expression.evaluate(self).ok()
     }
 
     /// Converts a value to a string representation
@@ -253,13 +399,7 @@ impl Heap {
     ///
     /// Returns a string representation of the value.
     fn value_to_string(&self, value: &Value) -> String {
-        match value {
-            Value::String(s) => s.clone(),
-            Value::Number(n) => n.to_string(),
-            Value::Bool(b) => b.to_string(),
-            Value::Null => "null".to_string(),
-            _ => serde_yml::to_string(value).unwrap_or_else(|_| "".to_string()),
-        }
+        to_text(value)
     }
 }
 
@@ -275,9 +415,9 @@ impl From<HashMap<String, Option<Value>>> for Heap {
     }
 }
 
-impl Into<HashMap<String, Option<Value>>> for Heap {
-    fn into(self) -> HashMap<String, Option<Value>> {
-        self.data
+impl From<Heap> for HashMap<String, Option<Value>> {
+    fn from(heap: Heap) -> Self {
+        heap.data
     }
 }
 
@@ -377,6 +517,24 @@ mod tests {
         assert!(heap.is_empty());
     }
 
+    #[test]
+    fn test_heap_merge() {
+        let mut heap = Heap::new();
+        heap.insert("name", Some(Value::String("John".to_string())));
+        heap.insert("kept", Some(Value::Bool(true)));
+
+        let mut other = Heap::new();
+        other.insert("name", Some(Value::String("Jane".to_string())));
+        other.insert("empty", None);
+
+        heap.merge(other);
+
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.get("name"), Some(&Value::String("Jane".to_string())));
+        assert_eq!(heap.get("kept"), Some(&Value::Bool(true)));
+        assert!(heap.contains_key("empty"));
+    }
+
     #[test]
     fn test_heap_from_hashmap() {
         let mut data = HashMap::new();
@@ -462,6 +620,66 @@ mod tests {
         assert_eq!(result, Some(null_input));
     }
 
+    #[test]
+    fn test_heap_parse_keeps_type_of_single_placeholder() {
+        let mut heap = Heap::new();
+        heap.insert("count", Some(Value::Number(3.into())));
+        heap.insert(
+            "files",
+            Some(Value::Sequence(vec![Value::String("a.txt".to_string())])),
+        );
+
+        assert_eq!(
+            heap.parse(Some(Value::String("${{ count }}".to_string()))),
+            Some(Value::Number(3.into()))
+        );
+        assert_eq!(
+            heap.parse(Some(Value::String("${{files}}".to_string()))),
+            heap.get("files").cloned()
+        );
+        assert_eq!(
+            heap.parse(Some(Value::String("${{ count }} files".to_string()))),
+            Some(Value::String("3 files".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_heap_parse_expressions() {
+        let mut heap = Heap::new();
+        let build: Value = serde_yml::from_str("artifacts: [app.tar, app.zip]\nok: true").unwrap();
+        heap.insert("build", Some(build));
+        heap.insert("count", Some(Value::Number(3.into())));
+
+        assert_eq!(
+            heap.parse(Some(Value::String("${{ build.artifacts.1 }}".to_string()))),
+            Some(Value::String("app.zip".to_string()))
+        );
+        assert_eq!(
+            heap.parse(Some(Value::String(
+                "${{ count > 2 && build.ok }}".to_string()
+            ))),
+            Some(Value::Bool(true))
+        );
+        assert_eq!(
+            heap.parse(Some(Value::String("${{ count == }}".to_string()))),
+            Some(Value::String("${{ count == }}".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_heap_parse_nested_values() {
+        let mut heap = Heap::new();
+        heap.insert("name", Some(Value::String("John".to_string())));
+
+        let input: Value =
+            serde_yml::from_str("greeting: Hello ${{ name }}\nnames: [\"${{ name }}\", x]\nn: 1")
+                .unwrap();
+        let expected: Value =
+            serde_yml::from_str("greeting: Hello John\nnames: [John, x]\nn: 1").unwrap();
+
+        assert_eq!(heap.parse(Some(input)), Some(expected));
+    }
+
     #[test]
     fn test_heap_parse_none_value() {
         let heap = Heap::new();
@@ -503,4 +721,19 @@ mod tests {
         heap.insert("null_key2", Some(Value::Null));
         assert_eq!(heap.get("null_key2"), Some(&Value::Null));
     }
+
+    #[test]
+    fn test_heap_scope_shadows_without_changing_parent() {
+        let mut heap = Heap::new();
+        heap.insert("item", Some(Value::from("outer")));
+        heap.insert("keep", Some(Value::from(1)));
+
+        let mut child = heap.scope([("item".to_string(), Value::from("inner"))]);
+        child.insert("extra", Some(Value::from(true)));
+
+        assert_eq!(child.get("item"), Some(&Value::from("inner")));
+        assert_eq!(child.get("keep"), Some(&Value::from(1)));
+        assert_eq!(heap.get("item"), Some(&Value::from("outer")));
+        assert!(!heap.contains_key("extra"));
+    }
 }
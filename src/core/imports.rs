@@ -0,0 +1,467 @@
+//! Workflow imports and node templates
+//!
+//! A workflow lists the files it imports under `imports:`, and pulls in
+//! their node templates and variables. Nodes then reference a template with
+//! `uses:`, overriding its input with `with:`:
+//!
+//! ```yaml
+//! imports:
+//!   - path: lib/common.yml
+//! nodes:
+//!   - id: announce
+//!     uses: notify
+//!     with:
+//!       message: Deploying
+//! ```
+//!
+//! Imports are resolved when a workflow file is loaded, so the rest of the
+//! engine only ever sees plain nodes. Relative paths are resolved against
+//! the directory of the importing file, and imported files may import
+//! other files, as long as no file ends up importing itself.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_yml::Value;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::source::SourceFile;
+use crate::shared::types::workflow::import::WorkflowImport;
+use crate::shared::types::workflow::node::WorkflowNode;
+use crate::shared::types::workflow::template::NodeTemplate;
+use crate::shared::types::workflow::variable::WorkflowVariable;
+use crate::shared::types::workflow::workflow::Workflow;
+
+/// Templates and variables collected from imported files
+#[derive(Debug, Default)]
+struct Imported {
+    templates: BTreeMap<String, NodeTemplate>,
+    variables: Vec<WorkflowVariable>,
+}
+
+impl Imported {
+    /// Adds the definitions of a file, replacing those of the same name
+    fn extend(
+        &mut self,
+        templates: BTreeMap<String, NodeTemplate>,
+        variables: Vec<WorkflowVariable>,
+    ) {
+        self.templates.extend(templates);
+        for variable in variables {
+            self.variables
+                .retain(|existing| existing.name != variable.name);
+            self.variables.push(variable);
+        }
+    }
+}
+
+/// Resolves the imports of a workflow and expands the templates its nodes use
+///
+/// Definitions of the workflow itself take precedence over imported ones,
+/// and later imports over earlier ones. After resolution, `templates` holds
+/// every available template, and nodes using a known template are replaced
+/// by the expanded node. Nodes using an unknown template are left as they
+/// are, so that validation can report them.
+///
+/// # Arguments
+///
+/// * `workflow` - The workflow to resolve
+/// * `path` - Path of the workflow file, used to resolve relative imports
+///
+/// # Errors
+///
+/// Returns `WorkflowError::Import` if an imported file cannot be read or
+/// parsed, or `WorkflowError::ImportCycle` if a file imports itself,
+/// directly or through other files.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::engine::FileFormat;
+/// use colossus::core::imports::resolve_imports;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// std::fs::write(
+///     dir.path().join("common.yml"),
+///     "templates:\n  notify:\n    type: Log\n    input:\n      message: Done\n",
+/// )
+/// .unwrap();
+///
+/// let mut workflow = FileFormat::Yaml
+///     .parse_content("imports:\n  - path: common.yml\nnodes:\n  - id: a\n    uses: notify\n")
+///     .unwrap();
+/// resolve_imports(&mut workflow, &dir.path().join("main.yml")).unwrap();
+///
+/// let node = &workflow.nodes().unwrap()[0];
+/// assert_eq!(node.node_type, "Log");
+/// assert!(!node.uses_template());
+/// ```
+pub fn resolve_imports(workflow: &mut Workflow, path: &Path) -> WorkflowResult<()> {
+    let mut imported = Imported::default();
+    let mut stack = vec![canonical(path)];
+    collect(
+        workflow.imports.as_deref().unwrap_or_default(),
+        base_dir(path),
+        &mut stack,
+        &mut imported,
+    )?;
+
+    imported.extend(
+        workflow.templates.take().unwrap_or_default(),
+        workflow.variables.take().unwrap_or_default(),
+    );
+
+    if !imported.templates.is_empty() {
+        let nodes = workflow
+            .nodes
+            .iter_mut()
+            .chain(workflow.on_failure.iter_mut())
+            .chain(workflow.finally.iter_mut())
+            .flatten();
+        for node in nodes {
+            let template = node
+                .uses
+                .as_ref()
+                .and_then(|name| imported.templates.get(name));
+            if let Some(template) = template {
+                expand(node, template);
+            }
+        }
+        workflow.templates = Some(imported.templates);
+    }
+    if !imported.variables.is_empty() {
+        workflow.variables = Some(imported.variables);
+    }
+
+    Ok(())
+}
+
+/// Loads the definitions of imported files, depth first
+///
+/// `stack` holds the canonical paths of the files being imported, outermost
+/// first, to detect cycles.
+fn collect(
+    imports: &[WorkflowImport],
+    dir: &Path,
+    stack: &mut Vec<PathBuf>,
+    imported: &mut Imported,
+) -> WorkflowResult<()> {
+    for import in imports {
+        let path = dir.join(&import.path);
+        let target = canonical(&path);
+
+        if let Some(start) = stack.iter().position(|file| file == &target) {
+            let cycle: Vec<String> = stack[start..]
+                .iter()
+                .chain(std::iter::once(&target))
+                .map(|file| file.display().to_string())
+                .collect();
+            return Err(WorkflowError::ImportCycle(cycle.join(" -> ")));
+        }
+
+        let mut workflow = SourceFile::read(&path)
+            .and_then(|source| source.format().parse_content(source.content()))
+            .map_err(|e| WorkflowError::Import {
+                path: path.clone(),
+                source: Box::new(e),
+            })?;
+
+        stack.push(target);
+        collect(
+            workflow.imports.as_deref().unwrap_or_default(),
+            base_dir(&path),
+            stack,
+            imported,
+        )?;
+        stack.pop();
+
+        imported.extend(
+            workflow.templates.take().unwrap_or_default(),
+            workflow.variables.take().unwrap_or_default(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Replaces a node using a template by the expanded node
+///
+/// The type, input, and condition of the node take precedence over those of
+/// the template, and `with` is merged into the resulting input.
+fn expand(node: &mut WorkflowNode, template: &NodeTemplate) {
+    if node.node_type.trim().is_empty() {
+        node.node_type = template.node_type.clone();
+    }
+    if node.when.is_none() {
+        node.when = template.when.clone();
+    }
+
+    let input = node.input.take().or_else(|| template.input.clone());
+    node.input = match (input, node.with.take()) {
+        (Some(input), Some(with)) => Some(merge(input, with)),
+        (input, with) => input.or(with),
+    };
+
+    node.uses = None;
+}
+
+/// Merges an override into a value
+///
+/// Mappings are merged key by key, recursively; any other override replaces
+/// the value.
+fn merge(base: Value, with: Value) -> Value {
+    match (base, with) {
+        (Value::Mapping(mut base), Value::Mapping(with)) => {
+            for (key, value) in with {
+                match base.get_mut(&key) {
+                    Some(existing) => *existing = merge(std::mem::take(existing), value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+            Value::Mapping(base)
+        }
+        (_, with) => with,
+    }
+}
+
+/// Returns the directory relative imports of a file are resolved against
+fn base_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new(""))
+}
+
+/// Returns the canonical form of a path, or the path itself if it cannot be resolved
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::engine::FileFormat;
+
+    fn files(entries: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in entries {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn resolve(dir: &tempfile::TempDir, yaml: &str) -> WorkflowResult<Workflow> {
+        let mut workflow = FileFormat::Yaml.parse_content(yaml).unwrap();
+        resolve_imports(&mut workflow, &dir.path().join("main.yml"))?;
+        Ok(workflow)
+    }
+
+    fn yaml(text: &str) -> Value {
+        serde_yml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_imports_expands_templates() {
+        let dir = files(&[(
+            "lib/common.yml",
+            "templates:\n  notify:\n    type: Log\n    input: Deploying\n    when: debug == true\n",
+        )]);
+
+        let workflow = resolve(
+            &dir,
+            "imports:\n  - path: lib/common.yml\nnodes:\n  - id: a\n    uses: notify\n",
+        )
+        .unwrap();
+
+        let node = &workflow.nodes().unwrap()[0];
+        assert_eq!(node.node_type, "Log");
+        assert_eq!(node.input, Some(Value::from("Deploying")));
+        assert_eq!(node.when.as_deref(), Some("debug == true"));
+        assert!(node.uses.is_none());
+        assert!(workflow.templates.unwrap().contains_key("notify"));
+    }
+
+    #[test]
+    fn test_resolve_imports_with_overrides() {
+        let dir = files(&[(
+            "common.yml",
+            "templates:\n  call:\n    type: Workflow\n    input:\n      path: build.yml\n      inputs:\n        target: linux\n        release: false\n",
+        )]);
+
+        let workflow = resolve(
+            &dir,
+            "imports:\n  - path: common.yml\nnodes:\n  - id: a\n    uses: call\n    with:\n      inputs:\n        release: true\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            workflow.nodes().unwrap()[0].input,
+            Some(yaml(
+                "path: build.yml\ninputs:\n  target: linux\n  release: true\n"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_resolve_imports_node_fields_take_precedence() {
+        let dir = files(&[(
+            "common.yml",
+            "templates:\n  notify:\n    type: Log\n    input: Template\n    when: 'false'\n",
+        )]);
+
+        let workflow = resolve(
+            &dir,
+            "imports:\n  - path: common.yml\nnodes:\n  - id: a\n    uses: notify\n    input: Node\n    when: 'true'\n",
+        )
+        .unwrap();
+
+        let node = &workflow.nodes().unwrap()[0];
+        assert_eq!(node.input, Some(Value::from("Node")));
+        assert_eq!(node.when.as_deref(), Some("true"));
+    }
+
+    #[test]
+    fn test_resolve_imports_local_templates() {
+        let dir = files(&[]);
+
+        let workflow = resolve(
+            &dir,
+            "templates:\n  hello:\n    type: Log\n    input: Hello\nnodes:\n  - id: a\n    uses: hello\n",
+        )
+        .unwrap();
+
+        assert_eq!(workflow.nodes().unwrap()[0].node_type, "Log");
+    }
+
+    #[test]
+    fn test_resolve_imports_precedence() {
+        let dir = files(&[
+            (
+                "a.yml",
+                "templates:\n  t:\n    type: Log\n    input: a\nvariables:\n  - name: region\n    value: a\n  - name: size\n    value: a\n",
+            ),
+            (
+                "b.yml",
+                "templates:\n  t:\n    type: Log\n    input: b\nvariables:\n  - name: region\n    value: b\n",
+            ),
+        ]);
+
+        let workflow = resolve(
+            &dir,
+            "imports:\n  - path: a.yml\n  - path: b.yml\nvariables:\n  - name: size\n    value: local\nnodes:\n  - id: x\n    uses: t\n",
+        )
+        .unwrap();
+
+        assert_eq!(workflow.nodes().unwrap()[0].input, Some(Value::from("b")));
+        let variables: Vec<(String, Value)> = workflow
+            .variables
+            .unwrap()
+            .into_iter()
+            .map(|variable| (variable.name, variable.value))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                ("region".to_string(), Value::from("b")),
+                ("size".to_string(), Value::from("local")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_imports_nested_relative_paths() {
+        let dir = files(&[
+            ("lib/all.yml", "imports:\n  - path: shared/log.yml\n"),
+            (
+                "lib/shared/log.yml",
+                "templates:\n  log:\n    type: Log\n    input: nested\n",
+            ),
+        ]);
+
+        let workflow = resolve(
+            &dir,
+            "imports:\n  - path: lib/all.yml\nnodes:\n  - id: a\n    uses: log\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            workflow.nodes().unwrap()[0].input,
+            Some(Value::from("nested"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_imports_unknown_template_is_kept() {
+        let dir = files(&[]);
+
+        let workflow = resolve(&dir, "nodes:\n  - id: a\n    uses: missing\n").unwrap();
+
+        let node = &workflow.nodes().unwrap()[0];
+        assert_eq!(node.uses.as_deref(), Some("missing"));
+        assert!(node.node_type.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_imports_missing_file() {
+        let dir = files(&[]);
+
+        match resolve(&dir, "imports:\n  - path: missing.yml\n") {
+            Err(WorkflowError::Import { path, source }) => {
+                assert!(path.ends_with("missing.yml"));
+                assert!(matches!(*source, WorkflowError::NotFound { .. }));
+            }
+            other => panic!("expected an import error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_resolve_imports_detects_cycles() {
+        let dir = files(&[
+            ("main.yml", "imports:\n  - path: a.yml\n"),
+            ("a.yml", "imports:\n  - path: b.yml\n"),
+            ("b.yml", "imports:\n  - path: ./a.yml\n"),
+        ]);
+
+        match resolve(&dir, "imports:\n  - path: a.yml\n") {
+            Err(WorkflowError::ImportCycle(cycle)) => {
+                assert!(cycle.contains("a.yml -> "));
+                assert!(cycle.ends_with("a.yml"));
+                assert_eq!(cycle.matches(" -> ").count(), 2);
+            }
+            other => panic!("expected a cycle, got {:?}", other.map(|_| ())),
+        }
+
+        assert!(matches!(
+            resolve(&dir, "imports:\n  - path: main.yml\n"),
+            Err(WorkflowError::ImportCycle(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_imports_shared_file_is_not_a_cycle() {
+        let dir = files(&[
+            ("a.yml", "imports:\n  - path: common.yml\n"),
+            ("b.yml", "imports:\n  - path: common.yml\n"),
+            ("common.yml", "variables:\n  - name: x\n    value: 1\n"),
+        ]);
+
+        let workflow = resolve(&dir, "imports:\n  - path: a.yml\n  - path: b.yml\n").unwrap();
+        assert_eq!(workflow.variables.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(
+            merge(
+                yaml("a: 1\nb:\n  c: 2\n  d: 3\n"),
+                yaml("b:\n  d: 4\ne: 5\n")
+            ),
+            yaml("a: 1\nb:\n  c: 2\n  d: 4\ne: 5\n")
+        );
+        assert_eq!(
+            merge(yaml("a: 1"), Value::from("text")),
+            Value::from("text")
+        );
+    }
+}
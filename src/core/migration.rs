@@ -0,0 +1,249 @@
+//! Workflow file format versions and migrations
+//!
+//! Workflow files declare the version of the file format they are written
+//! in with a top-level `colossus:` field. Files written for an older format
+//! are upgraded to the current one by applying, in order, every migration
+//! between their version and [`FORMAT_VERSION`]. Files without the field
+//! predate versioning and are treated as version 0.
+//!
+//! Migrations operate on the raw document rather than on [`Workflow`], so
+//! they can handle syntax the current model no longer accepts.
+//!
+//! [`Workflow`]: crate::shared::types::workflow::workflow::Workflow
+
+use serde_yml::{Mapping, Value};
+
+/// Current version of the workflow file format
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Name of the top-level field holding the format version
+pub const VERSION_FIELD: &str = "colossus";
+
+/// Errors that can occur while migrating a workflow document
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MigrationError {
+    /// The document is not a mapping
+    #[error("Workflow document must be a mapping")]
+    NotAMapping,
+
+    /// The format version is not a non-negative integer
+    #[error("Invalid format version '{0}', expected an integer")]
+    InvalidVersion(String),
+
+    /// The format version is newer than this version of colossus supports
+    #[error(
+        "Format version {0} is not supported (latest supported: {FORMAT_VERSION}), upgrade colossus"
+    )]
+    UnsupportedVersion(u32),
+}
+
+/// Result type for migrations
+pub type MigrationResult<T> = Result<T, MigrationError>;
+
+/// An upgrade of a document from one format version to the next
+struct Migration {
+    /// Version the migration upgrades from, to `from + 1`
+    from: u32,
+
+    /// Human-readable summary of the change
+    description: &'static str,
+
+    /// Function rewriting the document in place
+    apply: fn(&mut Mapping),
+}
+
+/// Every migration, ordered by source version
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "Declare the file format version with `colossus: 1`",
+    apply: |_| {},
+}];
+
+/// Summary of the migrations applied to a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Format version of the document before migrating
+    pub from: u32,
+
+    /// Format version of the document after migrating
+    pub to: u32,
+
+    /// Descriptions of the applied migrations, in order
+    pub applied: Vec<&'static str>,
+}
+
+impl MigrationReport {
+    /// Checks if the document was changed
+    pub fn is_migrated(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// Reads the format version declared by a document
+///
+/// # Returns
+///
+/// Returns 0 for documents without a `colossus` field.
+///
+/// # Errors
+///
+/// Returns an error if the document is not a mapping or the version is not
+/// a non-negative integer.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::migration::format_version;
+///
+/// let document = serde_yml::from_str("colossus: 1\nnodes: []").unwrap();
+/// assert_eq!(format_version(&document), Ok(1));
+///
+/// let document = serde_yml::from_str("nodes: []").unwrap();
+/// assert_eq!(format_version(&document), Ok(0));
+/// ```
+pub fn format_version(document: &Value) -> MigrationResult<u32> {
+    let Value::Mapping(map) = document else {
+        return Err(MigrationError::NotAMapping);
+    };
+
+    match map.get(VERSION_FIELD) {
+        None => Ok(0),
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| {
+                MigrationError::InvalidVersion(
+                    serde_yml::to_string(value)
+                        .map(|text| text.trim_end().to_string())
+                        .unwrap_or_default(),
+                )
+            }),
+    }
+}
+
+/// Upgrades a document to the current format version
+///
+/// The `colossus` field of a migrated document is set to the current
+/// version and moved first.
+///
+/// # Errors
+///
+/// Returns an error if the document is not a mapping, or declares an
+/// invalid or unsupported format version.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::migration::{migrate, FORMAT_VERSION};
+///
+/// let mut document = serde_yml::from_str("name: Legacy\nnodes: []").unwrap();
+/// let report = migrate(&mut document).unwrap();
+///
+/// assert!(report.is_migrated());
+/// assert_eq!(report.to, FORMAT_VERSION);
+/// assert_eq!(document["colossus"], serde_yml::Value::from(FORMAT_VERSION));
+/// ```
+pub fn migrate(document: &mut Value) -> MigrationResult<MigrationReport> {
+    let from = format_version(document)?;
+    if from > FORMAT_VERSION {
+        return Err(MigrationError::UnsupportedVersion(from));
+    }
+
+    let mut report = MigrationReport {
+        from,
+        to: from,
+        applied: Vec::new(),
+    };
+    if from == FORMAT_VERSION {
+        return Ok(report);
+    }
+
+    let Value::Mapping(map) = document else {
+        return Err(MigrationError::NotAMapping);
+    };
+
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from) {
+        (migration.apply)(map);
+        report.applied.push(migration.description);
+        report.to = migration.from + 1;
+    }
+
+    let mut migrated = Mapping::new();
+    migrated.insert(Value::from(VERSION_FIELD), Value::from(report.to));
+    for (key, value) in std::mem::take(map) {
+        if key.as_str() != Some(VERSION_FIELD) {
+            migrated.insert(key, value);
+        }
+    }
+    *map = migrated;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(yaml: &str) -> Value {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_migrations_are_contiguous() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.from, index as u32);
+        }
+        assert_eq!(MIGRATIONS.len() as u32, FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_format_version() {
+        assert_eq!(format_version(&document("colossus: 1")), Ok(1));
+        assert_eq!(format_version(&document("name: Legacy")), Ok(0));
+        assert_eq!(
+            format_version(&document("colossus: one")),
+            Err(MigrationError::InvalidVersion("one".to_string()))
+        );
+        assert_eq!(
+            format_version(&document("colossus: -1")),
+            Err(MigrationError::InvalidVersion("-1".to_string()))
+        );
+        assert_eq!(
+            format_version(&document("- a")),
+            Err(MigrationError::NotAMapping)
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_document() {
+        let mut doc = document("name: Legacy\nnodes:\n  - id: a\n    type: Log\n");
+        let report = migrate(&mut doc).unwrap();
+
+        assert_eq!(report.from, 0);
+        assert_eq!(report.to, FORMAT_VERSION);
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(
+            serde_yml::to_string(&doc).unwrap(),
+            "colossus: 1\nname: Legacy\nnodes:\n- id: a\n  type: Log\n"
+        );
+    }
+
+    #[test]
+    fn test_migrate_current_document_is_unchanged() {
+        let mut doc = document("name: Current\ncolossus: 1\n");
+        let report = migrate(&mut doc).unwrap();
+
+        assert!(!report.is_migrated());
+        assert!(report.applied.is_empty());
+        assert_eq!(doc, document("name: Current\ncolossus: 1\n"));
+    }
+
+    #[test]
+    fn test_migrate_unsupported_version() {
+        let mut doc = document("colossus: 99");
+        assert_eq!(
+            migrate(&mut doc),
+            Err(MigrationError::UnsupportedVersion(99))
+        );
+    }
+}
@@ -8,7 +8,25 @@
 //! The core module provides the fundamental components needed to execute workflows:
 //!
 //! - **Engine**: Main workflow execution logic and file parsing
+//! - **Context**: Run context shared with nodes, including the sub-workflow call stack
+//! - **Control**: Pause, resume, and stop handles for running workflows
+//! - **Checkpoint**: Persisted run state used to resume failed or interrupted runs
+//! - **Clock**: Injectable source of the current time
+//! - **Archive**: Reading and writing tar, tar.gz and zip archives
+//! - **Digest**: SHA-256, SHA-1 and MD5 digests of data
+//! - **Expression**: Parsing and evaluation of `when` conditions and placeholders
+//! - **Format**: Conversion between text and values for JSON, YAML, TOML and CSV
+//! - **Glob**: Glob patterns selecting relative file paths
+//! - **Imports**: Imported files and expansion of node templates
 //! - **Heap**: Shared data store for workflow variables and state
+//! - **Migration**: Workflow file format versions and upgrades of older files
+//! - **Query**: jq filters picking values out of structured data
+//! - **Random**: Injectable source of random numbers
+//! - **Source**: Workflow files and the location of their elements
+//! - **Run**: Outcome of a workflow run, including per-node statuses
+//! - **Schema**: JSON Schema of the workflow file format
+//! - **Template**: Jinja-style text templates rendered against the heap
+//! - **Validation**: Static checks of workflow definitions
 //! - **Error Handling**: Comprehensive error types and result handling
 //!
 //! # Examples
@@ -25,5 +43,23 @@
 //! }
 //! ```
 
+pub mod archive;
+pub mod checkpoint;
+pub mod clock;
+pub mod context;
+pub mod control;
+pub mod digest;
 pub mod engine;
+pub mod expression;
+pub mod format;
+pub mod glob;
 pub mod heap;
+pub mod imports;
+pub mod migration;
+pub mod query;
+pub mod random;
+pub mod run;
+pub mod schema;
+pub mod source;
+pub mod template;
+pub mod validation;
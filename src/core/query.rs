@@ -0,0 +1,232 @@
+//! jq queries
+//!
+//! This module parses and runs [jq](https://jqlang.github.io/jq/manual/)
+//! filters against workflow values, to pick fields out of large structured
+//! outputs. Filters have access to the jq standard library, e.g. `select`,
+//! `map` and `to_entries`, and produce any number of results.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use colossus::core::query::Query;
+//! use serde_yml::Value;
+//!
+//! let pods: Value = serde_yml::from_str(
+//!     "items: [{ name: api, ready: true }, { name: db, ready: false }]",
+//! )
+//! .unwrap();
+//!
+//! let query = Query::parse(".items[] | select(.ready | not) | .name").unwrap();
+//! assert_eq!(query.run(&pods).unwrap(), vec![Value::from("db")]);
+//! ```
+
+use std::fmt;
+
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, Native, RcIter};
+use jaq_json::Val;
+use serde_json::Value as JsonValue;
+use serde_yml::Value;
+
+/// Errors that can occur while parsing or running a query
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum QueryError {
+    /// The query is not a valid jq filter
+    #[error("{message} at position {position}")]
+    Syntax { message: String, position: usize },
+
+    /// The query failed on its input
+    #[error("{0}")]
+    Run(String),
+}
+
+/// Result type for query operations
+pub type QueryResult<T> = Result<T, QueryError>;
+
+/// A compiled jq filter
+#[derive(Clone)]
+pub struct Query {
+    source: String,
+    filter: jaq_core::Filter<Native<Val>>,
+}
+
+impl Query {
+    /// Parses and compiles a jq filter
+    ///
+    /// # Errors
+    ///
+    /// Returns `QueryError::Syntax` with the position of the first error if
+    /// the filter does not parse, or uses undefined filters or variables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::query::Query;
+    ///
+    /// assert!(Query::parse(".items[].name").is_ok());
+    /// assert_eq!(
+    ///     Query::parse(".items[] | nme").unwrap_err().to_string(),
+    ///     "Undefined filter 'nme' at position 11"
+    /// );
+    /// ```
+    pub fn parse(source: &str) -> QueryResult<Self> {
+        let program = File {
+            code: source,
+            path: (),
+        };
+        let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+        let arena = Arena::default();
+
+        let modules = loader.load(&arena, program).map_err(|errors| {
+            let error = errors.into_iter().next().map(|(_, error)| error);
+            match error {
+                Some(jaq_core::load::Error::Lex(errors)) => errors.first().map(|(expect, rest)| {
+                    syntax_error(source, rest, format!("Expected {}", expect.as_str()))
+                }),
+                Some(jaq_core::load::Error::Parse(errors)) => {
+                    errors.first().map(|(expect, found)| {
+                        let message = match found.is_empty() {
+                            true => format!("Expected {}", expect.as_str()),
+                            false => format!("Expected {}, found '{}'", expect.as_str(), found),
+                        };
+                        syntax_error(source, found, message)
+                    })
+                }
+                Some(jaq_core::load::Error::Io(errors)) => errors
+                    .first()
+                    .map(|(path, message)| syntax_error(source, path, message.clone())),
+                None => None,
+            }
+            .unwrap_or_else(|| QueryError::Syntax {
+                message: "Invalid query".to_string(),
+                position: 0,
+            })
+        })?;
+
+        let filter = Compiler::default()
+            .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+            .compile(modules)
+            .map_err(|errors| {
+                errors
+                    .into_iter()
+                    .flat_map(|(_, errors)| errors)
+                    .next()
+                    .map(|(name, undefined)| {
+                        let message = format!("Undefined {} '{}'", undefined.as_str(), name);
+                        syntax_error(source, name, message)
+                    })
+                    .unwrap_or_else(|| QueryError::Syntax {
+                        message: "Invalid query".to_string(),
+                        position: 0,
+                    })
+            })?;
+
+        Ok(Self {
+            source: source.to_string(),
+            filter,
+        })
+    }
+
+    /// Returns the source of the query
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Runs the query against a value, returning every result
+    ///
+    /// # Errors
+    ///
+    /// Returns `QueryError::Run` if the query fails on the value, e.g. when
+    /// iterating over a number.
+    pub fn run(&self, input: &Value) -> QueryResult<Vec<Value>> {
+        let input = serde_json::to_value(input)
+            .map_err(|e| QueryError::Run(format!("Input cannot be converted to JSON: {}", e)))?;
+
+        let inputs = RcIter::new(core::iter::empty());
+        let results = self.filter.run((Ctx::new([], &inputs), Val::from(input)));
+
+        results
+            .map(|result| {
+                let value = result.map_err(|e| QueryError::Run(e.to_string()))?;
+                serde_yml::to_value(JsonValue::from(value))
+                    .map_err(|e| QueryError::Run(e.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Query").field(&self.source).finish()
+    }
+}
+
+/// Creates a syntax error located at a slice of the query source
+fn syntax_error(source: &str, at: &str, message: String) -> QueryError {
+    let offset = (at.as_ptr() as usize).wrapping_sub(source.as_ptr() as usize);
+    let position = if offset <= source.len() {
+        source[..offset].chars().count()
+    } else {
+        0
+    };
+    QueryError::Syntax { message, position }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yml::from_str(text).unwrap()
+    }
+
+    fn run(query: &str, input: &str) -> Vec<Value> {
+        Query::parse(query).unwrap().run(&yaml(input)).unwrap()
+    }
+
+    #[test]
+    fn test_query_results() {
+        let input = "{ services: [{ name: api, port: 80 }, { name: db, port: 5432 }] }";
+
+        assert_eq!(run(".services[0].name", input), vec![Value::from("api")]);
+        assert_eq!(
+            run(".services[] | select(.port > 100) | .name", input),
+            vec![Value::from("db")]
+        );
+        assert_eq!(
+            run("[.services[].port] | add", input),
+            vec![Value::from(5512)]
+        );
+        assert_eq!(
+            run(".services | map({(.name): .port}) | add", input),
+            vec![yaml("{ api: 80, db: 5432 }")]
+        );
+        assert!(run(".services[] | select(.port > 10000)", input).is_empty());
+    }
+
+    #[test]
+    fn test_query_syntax_errors() {
+        let error = |query: &str| Query::parse(query).unwrap_err().to_string();
+
+        assert_eq!(error(".a | nme"), "Undefined filter 'nme' at position 5");
+        assert_eq!(error(".a | $x"), "Undefined variable '$x' at position 5");
+        assert_eq!(error(".a["), "Expected closing bracket at position 3");
+        assert_eq!(error(".a | | .b"), "Expected term, found '|' at position 5");
+    }
+
+    #[test]
+    fn test_query_run_error() {
+        let query = Query::parse(".[]").unwrap();
+        assert!(matches!(
+            query.run(&Value::from(1)),
+            Err(QueryError::Run(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_clone() {
+        let query = Query::parse(".a").unwrap();
+        assert_eq!(query.clone().source(), ".a");
+        assert_eq!(format!("{:?}", query), "Query(\".a\")");
+    }
+}
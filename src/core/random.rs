@@ -0,0 +1,128 @@
+//! Randomness source of workflow runs
+//!
+//! Nodes producing random values go through the [`RandomSource`] of the run
+//! context instead of the operating system, so that tests can substitute a
+//! [`SeededRandom`] and get the same values on every run.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use rand::rngs::{OsRng, StdRng};
+use rand::{RngCore, SeedableRng};
+
+/// Source of random numbers
+pub trait RandomSource: fmt::Debug + Send + Sync {
+    /// Returns a random 64-bit number
+    fn next_u64(&self) -> u64;
+
+    /// Fills a buffer with random bytes
+    fn fill_bytes(&self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            let random = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&random[..chunk.len()]);
+        }
+    }
+
+    /// Returns a uniformly distributed number below `bound`
+    ///
+    /// Returns 0 when `bound` is 0.
+    fn below(&self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        // Rejects the numbers of the last incomplete range to avoid bias
+        let zone = u64::MAX - (u64::MAX % bound);
+        loop {
+            let random = self.next_u64();
+            if random < zone {
+                return random % bound;
+            }
+        }
+    }
+}
+
+/// Randomness source reading the random number generator of the system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemRandom;
+
+impl RandomSource for SystemRandom {
+    fn next_u64(&self) -> u64 {
+        OsRng.next_u64()
+    }
+
+    fn fill_bytes(&self, bytes: &mut [u8]) {
+        OsRng.fill_bytes(bytes);
+    }
+}
+
+/// Randomness source producing the same sequence for the same seed
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::random::{RandomSource, SeededRandom};
+///
+/// let first = SeededRandom::new(7);
+/// let second = SeededRandom::new(7);
+/// assert_eq!(first.next_u64(), second.next_u64());
+/// assert!(first.below(6) < 6);
+/// ```
+#[derive(Debug)]
+pub struct SeededRandom {
+    rng: Mutex<StdRng>,
+}
+
+impl SeededRandom {
+    /// Creates a source seeded with the given number
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn next_u64(&self) -> u64 {
+        self.rng
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .next_u64()
+    }
+}
+
+/// Returns the randomness source used when none is injected
+pub fn system_random() -> Arc<dyn RandomSource> {
+    Arc::new(SystemRandom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_random_is_deterministic() {
+        let first = SeededRandom::new(42);
+        let second = SeededRandom::new(42);
+
+        let mut a = [0u8; 13];
+        let mut b = [0u8; 13];
+        first.fill_bytes(&mut a);
+        second.fill_bytes(&mut b);
+        assert_eq!(a, b);
+        assert_ne!(a, [0u8; 13]);
+
+        assert_ne!(
+            SeededRandom::new(1).next_u64(),
+            SeededRandom::new(2).next_u64()
+        );
+    }
+
+    #[test]
+    fn test_random_below() {
+        let random = SeededRandom::new(3);
+        assert_eq!(random.below(0), 0);
+        assert_eq!(random.below(1), 0);
+        assert!((0..1000).all(|_| random.below(10) < 10));
+        assert!((0..1000).any(|_| random.below(10) == 9));
+    }
+}
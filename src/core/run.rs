@@ -0,0 +1,391 @@
+//! Workflow run results
+//!
+//! This module contains the types describing the outcome of a workflow
+//! execution: the overall status, the status of every node, and the
+//! evaluated workflow output.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+
+use crate::core::engine::WorkflowError;
+use crate::core::validation::Diagnostic;
+use crate::shared::types::workflow::node::WorkflowNode;
+use crate::shared::types::workflow::output::WorkflowOutput;
+use crate::shared::types::workflow::status::Status;
+use crate::shared::types::workflow::workflow::Workflow;
+
+/// Generates a new, unique run identifier
+///
+/// Identifiers start with the creation time in milliseconds, so sorting them
+/// lexicographically sorts runs chronologically.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::run::generate_run_id;
+///
+/// let first = generate_run_id();
+/// let second = generate_run_id();
+/// assert_ne!(first, second);
+/// ```
+pub fn generate_run_id() -> String {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let sequence = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let suffix = (std::process::id() ^ sequence.rotate_left(16)) & 0xffff;
+
+    format!("{:013}-{:04x}{:02x}", millis, suffix, sequence & 0xff)
+}
+
+/// Checks that a string can be used as a run identifier
+///
+/// Run identifiers name files and directories in the checkpoint and run
+/// stores, so they may only contain ASCII letters, digits, `-` and `_`.
+/// Identifiers from [`generate_run_id`] are always valid.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::run::{generate_run_id, is_valid_run_id};
+///
+/// assert!(is_valid_run_id(&generate_run_id()));
+/// assert!(is_valid_run_id("nightly-build_2"));
+/// assert!(!is_valid_run_id("../../etc/passwd"));
+/// assert!(!is_valid_run_id(""));
+/// ```
+pub fn is_valid_run_id(run_id: &str) -> bool {
+    !run_id.is_empty()
+        && run_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Outcome of a single node within a workflow run
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::run::NodeRun;
+/// use colossus::shared::types::workflow::status::Status;
+///
+/// let node = NodeRun::new("log1");
+/// assert_eq!(node.status, Status::Pending);
+/// assert!(node.error.is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeRun {
+    /// Identifier of the node
+    pub id: String,
+
+    /// Status reached by the node
+    pub status: Status,
+
+    /// Error message if the node failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Time spent executing the node, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl NodeRun {
+    /// Creates a pending node run for the given node id
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            status: Status::Pending,
+            error: None,
+            duration_ms: None,
+        }
+    }
+}
+
+/// Outcome of a workflow run
+///
+/// A run is returned even when the workflow failed or was stopped, so that
+/// callers can report partial results.
+#[derive(Debug)]
+pub struct WorkflowRun {
+    /// Unique identifier of the run
+    pub run_id: String,
+
+    /// The workflow definition that was executed
+    pub workflow: Workflow,
+
+    /// Hash of the workflow file content, when loaded from a file
+    pub workflow_hash: Option<String>,
+
+    /// Effective workflow inputs, including defaults
+    pub inputs: HashMap<String, Value>,
+
+    /// Time at which the run started
+    pub started_at: DateTime<Utc>,
+
+    /// Time at which the run finished
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// Overall status of the run
+    pub status: Status,
+
+    /// Status of every node, in definition order
+    pub nodes: Vec<NodeRun>,
+
+    /// Status of every `on_failure` node, in definition order
+    pub on_failure: Vec<NodeRun>,
+
+    /// Status of every `finally` node, in definition order
+    pub finally: Vec<NodeRun>,
+
+    /// Workflow output evaluated against the final heap
+    pub outputs: WorkflowOutput,
+
+    /// Error that caused the run to fail, if any
+    ///
+    /// This is the error of the first failed main node. Failures of
+    /// `on_failure` and `finally` nodes are only reported by their node
+    /// runs, except when the main nodes all succeeded, in which case the
+    /// first of them fails the run.
+    pub error: Option<WorkflowError>,
+}
+
+impl WorkflowRun {
+    /// Creates a pending run with one pending entry per workflow node
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::run::WorkflowRun;
+    /// use colossus::shared::types::workflow::{node::WorkflowNode, workflow::Workflow};
+    ///
+    /// let mut workflow = Workflow::new("Example");
+    /// workflow.add_node(WorkflowNode::new("log1", "Log", None));
+    ///
+    /// let run = WorkflowRun::new(workflow);
+    /// assert_eq!(run.nodes.len(), 1);
+    /// assert_eq!(run.nodes[0].id, "log1");
+    /// ```
+    pub fn new(workflow: Workflow) -> Self {
+        let pending = |nodes: &Option<Vec<_>>| -> Vec<NodeRun> {
+            nodes
+                .iter()
+                .flatten()
+                .map(|node: &WorkflowNode| NodeRun::new(node.id.clone()))
+                .collect()
+        };
+        let nodes = pending(&workflow.nodes);
+        let on_failure = pending(&workflow.on_failure);
+        let finally = pending(&workflow.finally);
+
+        Self {
+            run_id: generate_run_id(),
+            workflow,
+            workflow_hash: None,
+            inputs: HashMap::new(),
+            started_at: Utc::now(),
+            finished_at: None,
+            status: Status::Pending,
+            nodes,
+            on_failure,
+            finally,
+            outputs: WorkflowOutput::new(),
+            error: None,
+        }
+    }
+
+    /// Sets the run identifier
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = run_id.into();
+        self
+    }
+
+    /// Gets the run of a node by its id
+    pub fn node(&self, id: &str) -> Option<&NodeRun> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Returns the number of nodes that reached the given status
+    pub fn count(&self, status: Status) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| node.status == status)
+            .count()
+    }
+
+    /// Checks if the run completed successfully
+    pub fn is_successful(&self) -> bool {
+        self.status.is_successful()
+    }
+
+    /// Describes the error that failed the run as a diagnostic
+    ///
+    /// The diagnostic points at the failed node: at its `type` field when the
+    /// node could not be built, or at the whole node otherwise. The failed
+    /// node is looked up in the main nodes first, then in the `on_failure`
+    /// and `finally` nodes. Use [`Diagnostic::locate`] to resolve it to a
+    /// file location.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the run has no error.
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        let error = self.error.as_ref()?;
+        let mut diagnostic = Diagnostic::from(error);
+
+        let sections = [
+            ("nodes", &self.nodes),
+            ("on_failure", &self.on_failure),
+            ("finally", &self.finally),
+        ];
+        let failed = sections.iter().find_map(|(section, nodes)| {
+            nodes
+                .iter()
+                .position(|node| node.status == Status::Failed)
+                .map(|index| (*section, index, &nodes[index]))
+        });
+
+        if let Some((section, index, node)) = failed {
+            let field = match error {
+                WorkflowError::NodeBuilder(_) | WorkflowError::InvalidNode(_) => "/type",
+                WorkflowError::UnknownTemplate(_) => "/uses",
+                WorkflowError::AssertionFailed { .. } => "/input",
+                _ => "",
+            };
+            diagnostic = diagnostic
+                .with_node(&node.id)
+                .with_path(format!("/{}/{}{}", section, index, field));
+        }
+
+        Some(diagnostic)
+    }
+
+    /// Returns the duration of the run in milliseconds, once it has finished
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.finished_at
+            .map(|finished_at| (finished_at - self.started_at).num_milliseconds().max(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow_with_nodes(ids: &[&str]) -> Workflow {
+        let mut workflow = Workflow::new("Test Workflow");
+        for id in ids {
+            workflow.add_node(WorkflowNode::new(*id, "Log", None));
+        }
+        workflow
+    }
+
+    #[test]
+    fn test_generate_run_id_is_unique_and_sortable() {
+        let first = generate_run_id();
+        let second = generate_run_id();
+
+        assert_ne!(first, second);
+        assert_eq!(first.len(), second.len());
+        assert!(first[..13] <= second[..13]);
+    }
+
+    #[test]
+    fn test_workflow_run_with_run_id() {
+        let run = WorkflowRun::new(Workflow::new("Test")).with_run_id("run-1");
+        assert_eq!(run.run_id, "run-1");
+    }
+
+    #[test]
+    fn test_node_run_new() {
+        let node = NodeRun::new("node1");
+        assert_eq!(node.id, "node1");
+        assert_eq!(node.status, Status::Pending);
+        assert_eq!(node.error, None);
+    }
+
+    #[test]
+    fn test_workflow_run_new() {
+        let run = WorkflowRun::new(workflow_with_nodes(&["a", "b"]));
+        assert_eq!(run.status, Status::Pending);
+        assert_eq!(run.nodes.len(), 2);
+        assert_eq!(run.count(Status::Pending), 2);
+        assert!(run.outputs.is_empty());
+        assert!(run.error.is_none());
+    }
+
+    #[test]
+    fn test_workflow_run_without_nodes() {
+        let run = WorkflowRun::new(Workflow::new("Empty"));
+        assert!(run.nodes.is_empty());
+    }
+
+    #[test]
+    fn test_workflow_run_diagnostic() {
+        let mut run = WorkflowRun::new(workflow_with_nodes(&["a", "b"]));
+        assert!(run.diagnostic().is_none());
+
+        run.nodes[1].status = Status::Failed;
+        run.error = Some(WorkflowError::InvalidNode("Foo".to_string()));
+
+        let diagnostic = run.diagnostic().unwrap();
+        assert_eq!(diagnostic.node.as_deref(), Some("b"));
+        assert_eq!(diagnostic.path.as_deref(), Some("/nodes/1/type"));
+        assert_eq!(diagnostic.message, "Invalid node type: Foo");
+    }
+
+    #[test]
+    fn test_workflow_run_diagnostic_in_finally() {
+        let mut workflow = workflow_with_nodes(&["a"]);
+        workflow.finally = Some(vec![WorkflowNode::new("cleanup", "Foo", None)]);
+        let mut run = WorkflowRun::new(workflow);
+        assert_eq!(run.finally.len(), 1);
+
+        run.nodes[0].status = Status::Done;
+        run.finally[0].status = Status::Failed;
+        run.error = Some(WorkflowError::InvalidNode("Foo".to_string()));
+
+        let diagnostic = run.diagnostic().unwrap();
+        assert_eq!(diagnostic.node.as_deref(), Some("cleanup"));
+        assert_eq!(diagnostic.path.as_deref(), Some("/finally/0/type"));
+    }
+
+    #[test]
+    fn test_workflow_run_node_lookup() {
+        let mut run = WorkflowRun::new(workflow_with_nodes(&["a", "b"]));
+        run.nodes[1].status = Status::Done;
+
+        assert_eq!(run.node("b").unwrap().status, Status::Done);
+        assert!(run.node("c").is_none());
+        assert_eq!(run.count(Status::Done), 1);
+    }
+
+    #[test]
+    fn test_workflow_run_duration() {
+        let mut run = WorkflowRun::new(workflow_with_nodes(&["a"]));
+        assert_eq!(run.duration_ms(), None);
+
+        run.finished_at = Some(run.started_at + chrono::Duration::milliseconds(1500));
+        assert_eq!(run.duration_ms(), Some(1500));
+    }
+
+    #[test]
+    fn test_workflow_run_is_successful() {
+        let mut run = WorkflowRun::new(workflow_with_nodes(&["a"]));
+        assert!(!run.is_successful());
+
+        run.status = Status::Done;
+        assert!(run.is_successful());
+    }
+}
@@ -0,0 +1,498 @@
+//! JSON Schema of the workflow file format
+//!
+//! This module describes workflow files as a JSON Schema (draft-07), so
+//! editors such as the YAML language server can offer completion and inline
+//! errors. The schema of every node type registered in a [`NodeRegistry`] is
+//! embedded, so the `input` of a node is checked against the schema of its
+//! `type`.
+
+use serde_json::{json, Map, Value};
+
+use crate::core::migration::FORMAT_VERSION;
+use crate::nodes::registry::NodeRegistry;
+
+/// JSON Schema dialect used by the generated schema
+pub const SCHEMA_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Generates the JSON Schema of workflow files
+///
+/// # Arguments
+///
+/// * `registry` - Registry providing the node types and their input schemas
+///
+/// # Returns
+///
+/// Returns the schema as a JSON value.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::schema::workflow_schema;
+/// use colossus::nodes::registry::NodeRegistry;
+///
+/// let schema = workflow_schema(&NodeRegistry::new());
+/// assert_eq!(schema["title"], "Colossus workflow");
+/// assert_eq!(schema["definitions"]["node"]["properties"]["type"]["enum"][0], "Archive");
+/// ```
+pub fn workflow_schema(registry: &NodeRegistry) -> Value {
+    json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": "Colossus workflow",
+        "description": "A workflow definition executed by colossus",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "colossus": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": FORMAT_VERSION,
+                "description": "Version of the workflow file format"
+            },
+            "id": {
+                "type": "string",
+                "description": "Unique identifier of the workflow"
+            },
+            "name": {
+                "type": "string",
+                "description": "Human-readable name of the workflow"
+            },
+            "version": {
+                "type": "string",
+                "description": "Version of the workflow definition, e.g. 1.0.0"
+            },
+            "imports": {
+                "type": "array",
+                "description": "Files whose templates and variables are available to the workflow",
+                "items": { "$ref": "#/definitions/import" }
+            },
+            "variables": {
+                "type": "array",
+                "description": "Global variables available to every node",
+                "items": { "$ref": "#/definitions/variable" }
+            },
+            "inputs": {
+                "type": "array",
+                "description": "Input parameters provided when executing the workflow",
+                "items": { "$ref": "#/definitions/input" }
+            },
+            "templates": {
+                "type": "object",
+                "description": "Reusable node definitions, referenced by nodes with `uses`",
+                "additionalProperties": { "$ref": "#/definitions/template" }
+            },
+            "nodes": {
+                "type": "array",
+                "description": "Steps of the workflow, executed in order",
+                "items": { "$ref": "#/definitions/node" }
+            },
+            "on_failure": {
+                "type": "array",
+                "description": "Steps executed after a node failed, seeing the failure as ${{ run.error }} and ${{ run.failed_node }}",
+                "items": { "$ref": "#/definitions/node" }
+            },
+            "finally": {
+                "type": "array",
+                "description": "Steps executed after the other steps, whatever the outcome of the run",
+                "items": { "$ref": "#/definitions/node" }
+            },
+            "output": {
+                "type": "object",
+                "description": "Values produced by the workflow, evaluated after the last node",
+                "additionalProperties": true
+            },
+            "options": { "$ref": "#/definitions/options" }
+        },
+        "definitions": {
+            "import": {
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["path"],
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Imported file, relative to the importing workflow"
+                    }
+                }
+            },
+            "template": template_schema(registry),
+            "variable": {
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["name", "value"],
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Name used to reference the variable"
+                    },
+                    "value": {
+                        "description": "Value of the variable"
+                    }
+                }
+            },
+            "input": {
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["name", "type"],
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Name used to reference the input"
+                    },
+                    "type": {
+                        "type": "string",
+                        "description": "Expected data type, e.g. string, number, or boolean"
+                    },
+                    "default": {
+                        "description": "Value used when the input is not provided"
+                    }
+                }
+            },
+            "options": {
+                "type": "object",
+                "description": "Execution options",
+                "additionalProperties": false,
+                "properties": {
+                    "concurrency": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Maximum number of nodes executed simultaneously"
+                    },
+                    "wasm": {
+                        "type": "object",
+                        "description": "Host resources granted to WebAssembly nodes, which have none by default",
+                        "additionalProperties": false,
+                        "properties": {
+                            "dirs": {
+                                "type": "array",
+                                "description": "Directories visible to the modules",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["host", "guest"],
+                                    "additionalProperties": false,
+                                    "properties": {
+                                        "host": {
+                                            "type": "string",
+                                            "description": "Path of the directory, relative to the workflow file"
+                                        },
+                                        "guest": {
+                                            "type": "string",
+                                            "description": "Path of the directory as seen by the modules"
+                                        },
+                                        "writable": {
+                                            "type": "boolean",
+                                            "default": false,
+                                            "description": "Allow the modules to create, modify and remove files"
+                                        }
+                                    }
+                                }
+                            },
+                            "env": {
+                                "type": "array",
+                                "description": "Names of the host environment variables visible to the modules",
+                                "items": { "type": "string" }
+                            }
+                        }
+                    }
+                }
+            },
+            "node": node_schema(registry)
+        }
+    })
+}
+
+/// Generates the schema of a node template
+///
+/// Templates are checked like nodes, except that they have no id and
+/// require a type.
+fn template_schema(registry: &NodeRegistry) -> Value {
+    let mut schema = node_schema(registry);
+
+    if let Some(properties) = schema["properties"].as_object_mut() {
+        for field in ["id", "on_branch", "uses", "with", "for_each", "matrix"] {
+            properties.remove(field);
+        }
+    }
+    if let Some(schema) = schema.as_object_mut() {
+        schema.remove("anyOf");
+    }
+    schema["required"] = json!(["type"]);
+
+    schema
+}
+
+/// Generates the schema of a workflow node
+///
+/// The `type` field is restricted to the registered node types, and the
+/// `input` of each type with a schema is checked with an `if`/`then` rule.
+/// Nodes based on a template get their type from it, so they need either a
+/// `type` or `uses`.
+fn node_schema(registry: &NodeRegistry) -> Value {
+    let node_types = registry.node_types();
+
+    let rules: Vec<Value> = node_types
+        .iter()
+        .filter_map(|node_type| {
+            registry.schema(node_type).map(|schema| {
+                json!({
+                    "if": {
+                        "required": ["type"],
+                        "properties": { "type": { "const": node_type } }
+                    },
+                    "then": {
+                        "properties": { "input": schema }
+                    }
+                })
+            })
+        })
+        .collect();
+
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("additionalProperties".to_string(), json!(false));
+    schema.insert("required".to_string(), json!(["id"]));
+    schema.insert(
+        "anyOf".to_string(),
+        json!([{ "required": ["type"] }, { "required": ["uses"] }]),
+    );
+    schema.insert(
+        "properties".to_string(),
+        json!({
+            "id": {
+                "type": "string",
+                "description": "Unique identifier of the node, used to reference its output"
+            },
+            "type": {
+                "type": "string",
+                "description": "Type of the node",
+                "enum": node_types
+            },
+            "input": {
+                "description": "Input of the node; strings may contain ${{ ... }} placeholders"
+            },
+            "when": {
+                "type": "string",
+                "description": "Condition deciding whether the node runs"
+            },
+            "on_branch": {
+                "type": "string",
+                "description": "Branch of a Switch node, as <switch id>.<branch>, the node runs on",
+                "pattern": "^[^.]+\\..+$"
+            },
+            "uses": {
+                "type": "string",
+                "description": "Name of the template the node is based on"
+            },
+            "with": {
+                "description": "Overrides merged into the input of the template"
+            },
+            "for_each": {
+                "description": "Sequence, or ${{ ... }} placeholder evaluating to one, to run the node for once per item",
+                "oneOf": [{ "type": "array" }, { "type": "string" }]
+            },
+            "matrix": {
+                "type": "object",
+                "description": "Values of every axis, to run the node for once per combination",
+                "properties": {
+                    "include": {
+                        "type": "array",
+                        "description": "Extra combinations to run",
+                        "items": { "type": "object" }
+                    },
+                    "exclude": {
+                        "type": "array",
+                        "description": "Partial combinations not to run",
+                        "items": { "type": "object" }
+                    }
+                },
+                "additionalProperties": { "type": "array" }
+            }
+        }),
+    );
+    if !rules.is_empty() {
+        schema.insert("allOf".to_string(), Value::Array(rules));
+    }
+
+    Value::Object(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::log::LogNode;
+
+    #[test]
+    fn test_workflow_schema_top_level() {
+        let schema = workflow_schema(&NodeRegistry::new());
+
+        assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["additionalProperties"], false);
+        for field in [
+            "colossus",
+            "id",
+            "name",
+            "version",
+            "imports",
+            "variables",
+            "inputs",
+            "templates",
+            "nodes",
+            "on_failure",
+            "finally",
+            "output",
+            "options",
+        ] {
+            assert!(
+                schema["properties"].get(field).is_some(),
+                "missing {}",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn test_workflow_schema_node_types() {
+        let registry =
+            NodeRegistry::new().with_node("Print", |input| Ok(Box::new(LogNode::new(input))));
+        let schema = workflow_schema(&registry);
+
+        assert_eq!(
+            schema["definitions"]["node"]["properties"]["type"]["enum"],
+            json!([
+                "Archive",
+                "Assert",
+                "Base64",
+                "DateFormat",
+                "Extract",
+                "Hash",
+                "Log",
+                "Now",
+                "Parse",
+                "Print",
+                "Query",
+                "Random",
+                "Regex",
+                "Script",
+                "Serialize",
+                "Switch",
+                "Template",
+                "UUID",
+                "WaitUntil",
+                "Workflow"
+            ])
+        );
+        assert_eq!(schema["definitions"]["node"]["required"], json!(["id"]));
+        assert_eq!(
+            schema["definitions"]["template"]["properties"]["type"]["enum"],
+            json!([
+                "Archive",
+                "Assert",
+                "Base64",
+                "DateFormat",
+                "Extract",
+                "Hash",
+                "Log",
+                "Now",
+                "Parse",
+                "Print",
+                "Query",
+                "Random",
+                "Regex",
+                "Script",
+                "Serialize",
+                "Switch",
+                "Template",
+                "UUID",
+                "WaitUntil",
+                "Workflow"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_workflow_schema_node_input_rules() {
+        let registry = NodeRegistry::new()
+            .with_node("Print", |input| Ok(Box::new(LogNode::new(input))))
+            .with_schema("Print", json!({ "type": "string" }));
+        let schema = workflow_schema(&registry);
+
+        let rules = schema["definitions"]["node"]["allOf"].as_array().unwrap();
+        assert_eq!(rules.len(), 20);
+        assert_eq!(rules[9]["if"]["properties"]["type"]["const"], "Print");
+        assert_eq!(
+            rules[9]["then"]["properties"]["input"],
+            json!({ "type": "string" })
+        );
+    }
+
+    #[test]
+    fn test_workflow_schema_without_node_schemas() {
+        let registry =
+            NodeRegistry::empty().with_node("Echo", |input| Ok(Box::new(LogNode::new(input))));
+        let schema = workflow_schema(&registry);
+
+        assert!(schema["definitions"]["node"].get("allOf").is_none());
+    }
+
+    #[test]
+    fn test_workflow_schema_references_resolve() {
+        let schema = workflow_schema(&NodeRegistry::new());
+        let text = schema.to_string();
+
+        for name in ["import", "template", "variable", "input", "node", "options"] {
+            assert!(text.contains(&format!("#/definitions/{}", name)));
+            assert!(schema["definitions"].get(name).is_some());
+        }
+    }
+
+    #[test]
+    fn test_workflow_schema_matches_model_fields() {
+        use crate::shared::types::workflow::{
+            import::WorkflowImport,
+            input::WorkflowInput,
+            node::WorkflowNode,
+            options::{WasmDirOption, WasmOptions, WorkflowOptions},
+            template::NodeTemplate,
+            variable::WorkflowVariable,
+            workflow::Workflow,
+        };
+
+        let schema = workflow_schema(&NodeRegistry::new());
+        let keys = |value: &Value| {
+            let mut keys: Vec<String> = value["properties"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect();
+            keys.sort();
+            keys
+        };
+        let fields = |fields: &[&str]| {
+            let mut fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+            fields.sort();
+            fields
+        };
+
+        assert_eq!(keys(&schema), fields(Workflow::FIELDS));
+        let definitions = &schema["definitions"];
+        assert_eq!(keys(&definitions["node"]), fields(WorkflowNode::FIELDS));
+        assert_eq!(keys(&definitions["input"]), fields(WorkflowInput::FIELDS));
+        assert_eq!(keys(&definitions["import"]), fields(WorkflowImport::FIELDS));
+        assert_eq!(keys(&definitions["template"]), fields(NodeTemplate::FIELDS));
+        assert_eq!(
+            keys(&definitions["variable"]),
+            fields(WorkflowVariable::FIELDS)
+        );
+        assert_eq!(
+            keys(&definitions["options"]),
+            fields(WorkflowOptions::FIELDS)
+        );
+        let wasm = &definitions["options"]["properties"]["wasm"];
+        assert_eq!(keys(wasm), fields(WasmOptions::FIELDS));
+        assert_eq!(
+            keys(&wasm["properties"]["dirs"]["items"]),
+            fields(WasmDirOption::FIELDS)
+        );
+    }
+}
@@ -0,0 +1,495 @@
+//! Workflow source files and locations
+//!
+//! This module keeps the text of a workflow file next to a map from document
+//! paths to their position in the file, so that diagnostics can point at the
+//! exact line and column of a problem and show the offending snippet.
+//!
+//! Document paths use the JSON Pointer syntax: `/nodes/1/type` is the `type`
+//! field of the second node. JSON files are parsed as YAML flow documents,
+//! so the same map serves both formats.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_yml::libyml::parser::{Event, Parser};
+use serde_yml::Value;
+
+use crate::core::engine::{FileFormat, WorkflowError, WorkflowResult};
+use crate::core::imports::resolve_imports;
+use crate::shared::types::workflow::workflow::Workflow;
+
+/// Position of a piece of text within a source file
+///
+/// Lines and columns start at 1. A length of 0 spans to the end of the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    /// Line number, starting at 1
+    pub line: usize,
+
+    /// Column number, starting at 1
+    pub column: usize,
+
+    /// Number of characters covered by the span
+    pub length: usize,
+}
+
+impl Span {
+    /// Creates a span
+    pub fn new(line: usize, column: usize, length: usize) -> Self {
+        Self {
+            line,
+            column,
+            length,
+        }
+    }
+}
+
+impl Default for Span {
+    fn default() -> Self {
+        Self::new(1, 1, 0)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Location of a document element, as a key and a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    key: Option<Span>,
+    value: Span,
+}
+
+/// Map from document paths to their position in the source text
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::source::{SourceMap, Span};
+///
+/// let map = SourceMap::parse("nodes:\n  - id: greet\n    type: Foo\n");
+/// assert_eq!(map.value("/nodes/0/type"), Some(Span::new(3, 11, 3)));
+/// assert_eq!(map.key("/nodes/0/type"), Some(Span::new(3, 5, 4)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    entries: HashMap<String, Entry>,
+}
+
+/// Parent collection of the element being parsed
+enum Frame {
+    Mapping {
+        path: String,
+        key: Option<(String, Span)>,
+    },
+    Sequence {
+        path: String,
+        index: usize,
+    },
+}
+
+impl SourceMap {
+    /// Builds the map of a YAML or JSON document
+    ///
+    /// Parsing stops at the first syntax error; the elements located up to
+    /// that point are kept.
+    pub fn parse(content: &str) -> Self {
+        let mut map = Self::default();
+        let mut parser = Parser::new(Cow::Borrowed(content.as_bytes()));
+        let mut stack: Vec<Frame> = Vec::new();
+
+        while let Ok((event, mark)) = parser.parse_next_event() {
+            let start = Span::new(mark.line() as usize + 1, mark.column() as usize + 1, 0);
+
+            match event {
+                Event::StreamEnd => break,
+                Event::Scalar(scalar) => {
+                    let text = String::from_utf8_lossy(&scalar.value).to_string();
+                    let length = scalar
+                        .repr
+                        .map(|repr| String::from_utf8_lossy(repr).chars().count())
+                        .unwrap_or_else(|| text.chars().count());
+                    let span = Span { length, ..start };
+
+                    if let Some(Frame::Mapping {
+                        key: key @ None, ..
+                    }) = stack.last_mut()
+                    {
+                        *key = Some((text, span));
+                    } else {
+                        map.record(&mut stack, span);
+                    }
+                }
+                Event::Alias(_) => {
+                    if let Some(Frame::Mapping {
+                        key: key @ None, ..
+                    }) = stack.last_mut()
+                    {
+                        *key = Some((String::new(), start));
+                    } else {
+                        map.record(&mut stack, start);
+                    }
+                }
+                Event::MappingStart(_) => {
+                    let path = map.record(&mut stack, start);
+                    stack.push(Frame::Mapping { path, key: None });
+                }
+                Event::SequenceStart(_) => {
+                    let path = map.record(&mut stack, start);
+                    stack.push(Frame::Sequence { path, index: 0 });
+                }
+                Event::MappingEnd | Event::SequenceEnd => {
+                    stack.pop();
+                }
+                Event::StreamStart | Event::DocumentStart | Event::DocumentEnd => {}
+            }
+        }
+
+        map
+    }
+
+    /// Records a value in the current collection and returns its path
+    fn record(&mut self, stack: &mut [Frame], value: Span) -> String {
+        let (path, key) = match stack.last_mut() {
+            Some(Frame::Mapping { path, key }) => {
+                let (name, span) = key.take().unwrap_or_default();
+                (format!("{}/{}", path, escape(&name)), Some(span))
+            }
+            Some(Frame::Sequence { path, index }) => {
+                *index += 1;
+                (format!("{}/{}", path, *index - 1), None)
+            }
+            None => (String::new(), None),
+        };
+
+        self.entries.insert(path.clone(), Entry { key, value });
+        path
+    }
+
+    /// Returns the span of the value at the given path
+    pub fn value(&self, path: &str) -> Option<Span> {
+        self.entries.get(path).map(|entry| entry.value)
+    }
+
+    /// Returns the span of the key of the value at the given path
+    pub fn key(&self, path: &str) -> Option<Span> {
+        self.entries.get(path).and_then(|entry| entry.key)
+    }
+
+    /// Returns the span that best locates the given path
+    ///
+    /// Scalars are located by their value. When a path does not exist, for
+    /// example because a field is missing, its closest existing parent is
+    /// used instead.
+    pub fn locate(&self, path: &str) -> Option<Span> {
+        let mut current = path;
+
+        loop {
+            if let Some(entry) = self.entries.get(current) {
+                return Some(match (entry.key, entry.value.length) {
+                    (Some(key), 0) => key,
+                    _ => entry.value,
+                });
+            }
+
+            current = &current[..current.rfind('/')?];
+        }
+    }
+}
+
+/// Escapes a mapping key for use in a document path
+pub(crate) fn escape(key: &str) -> Cow<'_, str> {
+    if key.contains(['~', '/']) {
+        Cow::Owned(key.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+/// A workflow file loaded into memory
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::engine::FileFormat;
+/// use colossus::core::source::{SourceFile, Span};
+///
+/// let source = SourceFile::new(
+///     "workflow.yml",
+///     FileFormat::Yaml,
+///     "nodes:\n  - id: greet\n    type: Foo\n",
+/// );
+///
+/// let snippet = source.snippet(Span::new(3, 11, 3));
+/// assert!(snippet.contains("--> workflow.yml:3:11"));
+/// assert!(snippet.contains("3 |     type: Foo"));
+/// assert!(snippet.contains("^^^"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    path: PathBuf,
+    format: FileFormat,
+    content: String,
+    map: SourceMap,
+}
+
+impl SourceFile {
+    /// Creates a source file from its content
+    pub fn new(path: impl Into<PathBuf>, format: FileFormat, content: impl Into<String>) -> Self {
+        let content = content.into();
+        let map = SourceMap::parse(&content);
+
+        Self {
+            path: path.into(),
+            format,
+            content,
+            map,
+        }
+    }
+
+    /// Reads a workflow file from disk
+    ///
+    /// The format is detected from the file extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file does not exist, has an unsupported
+    /// extension, or cannot be read.
+    pub fn read(path: impl AsRef<Path>) -> WorkflowResult<Self> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            return Err(WorkflowError::NotFound {
+                path: path.to_path_buf(),
+            });
+        }
+
+        let format = FileFormat::from_path(path).ok_or(WorkflowError::UnsupportedFormat)?;
+        let content = std::fs::read_to_string(path).map_err(WorkflowError::FileRead)?;
+
+        Ok(Self::new(path, format, content))
+    }
+
+    /// Returns the path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the format of the file
+    pub fn format(&self) -> FileFormat {
+        self.format
+    }
+
+    /// Returns the content of the file
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns the map of document paths to positions
+    pub fn map(&self) -> &SourceMap {
+        &self.map
+    }
+
+    /// Parses the workflow defined by the file
+    ///
+    /// The imports of the workflow are resolved against the file path, and
+    /// the templates its nodes use are expanded, see
+    /// [`resolve_imports`](crate::core::imports::resolve_imports).
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::JsonParse` or `WorkflowError::YamlParse` if the
+    /// content is not a valid workflow, or an import error if an imported
+    /// file cannot be loaded.
+    pub fn parse(&self) -> WorkflowResult<Workflow> {
+        let mut workflow = self.format.parse_content(&self.content)?;
+        resolve_imports(&mut workflow, &self.path)?;
+        Ok(workflow)
+    }
+
+    /// Parses the file as a generic document, keeping every field
+    ///
+    /// Unlike [`SourceFile::parse`], fields that are not part of the
+    /// workflow model are preserved, so they can be reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::JsonParse` or `WorkflowError::YamlParse` if the
+    /// content is not valid JSON or YAML.
+    pub fn document(&self) -> WorkflowResult<Value> {
+        match self.format {
+            FileFormat::Json => {
+                serde_json::from_str(&self.content).map_err(WorkflowError::JsonParse)
+            }
+            FileFormat::Yaml => {
+                serde_yml::from_str(&self.content).map_err(WorkflowError::YamlParse)
+            }
+        }
+    }
+
+    /// Returns the text of a line, starting at 1
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.content.lines().nth(line.checked_sub(1)?)
+    }
+
+    /// Renders the location of a span with the offending line highlighted
+    ///
+    /// The output follows the usual compiler layout:
+    ///
+    /// ```text
+    ///   --> workflow.yml:3:11
+    ///    |
+    ///  3 |     type: Foo
+    ///    |           ^^^
+    /// ```
+    pub fn snippet(&self, span: Span) -> String {
+        let location = format!("{}:{}", self.path.display(), span);
+        let Some(text) = self.line(span.line) else {
+            return format!("  --> {}", location);
+        };
+
+        let gutter = span.line.to_string().len();
+        let start = span.column.saturating_sub(1).min(text.chars().count());
+        let remaining = text.chars().count() - start;
+        let length = match span.length {
+            0 => remaining,
+            length => length.min(remaining),
+        }
+        .max(1);
+
+        format!(
+            "{pad} --> {location}\n{pad} |\n{line} | {text}\n{pad} | {indent}{marker}",
+            pad = " ".repeat(gutter),
+            location = location,
+            line = span.line,
+            text = text,
+            indent = " ".repeat(start),
+            marker = "^".repeat(length),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = "name: Example
+nodes:
+  - id: greet
+    type: Log
+    input:
+      message: \"Hello\"
+  - id: other
+    type: Foo
+output:
+  result: ${{ greet }}
+";
+
+    #[test]
+    fn test_source_map_yaml_paths() {
+        let map = SourceMap::parse(WORKFLOW);
+
+        assert_eq!(map.value("/name"), Some(Span::new(1, 7, 7)));
+        assert_eq!(map.value("/nodes/0/id"), Some(Span::new(3, 9, 5)));
+        assert_eq!(map.value("/nodes/1/type"), Some(Span::new(8, 11, 3)));
+        assert_eq!(map.key("/nodes/1/type"), Some(Span::new(8, 5, 4)));
+        assert_eq!(
+            map.value("/nodes/0/input/message"),
+            Some(Span::new(6, 16, 7))
+        );
+        assert_eq!(map.value("/output/result"), Some(Span::new(10, 11, 12)));
+    }
+
+    #[test]
+    fn test_source_map_json_paths() {
+        let map = SourceMap::parse(
+            r#"{
+  "nodes": [
+    { "id": "a", "type": "Foo" }
+  ]
+}"#,
+        );
+
+        assert_eq!(map.value("/nodes/0/type"), Some(Span::new(3, 26, 5)));
+        assert_eq!(map.key("/nodes/0/id"), Some(Span::new(3, 7, 4)));
+    }
+
+    #[test]
+    fn test_source_map_locate_falls_back_to_parent() {
+        let map = SourceMap::parse(WORKFLOW);
+
+        assert_eq!(map.locate("/nodes/1/type"), Some(Span::new(8, 11, 3)));
+        assert_eq!(map.locate("/nodes/1/when"), map.value("/nodes/1"));
+        assert_eq!(map.locate("/nodes/0/input"), map.key("/nodes/0/input"));
+        assert_eq!(map.locate("/missing"), map.value(""));
+    }
+
+    #[test]
+    fn test_source_map_escapes_keys() {
+        let map = SourceMap::parse("a/b: 1\n");
+        assert!(map.value("/a~1b").is_some());
+    }
+
+    #[test]
+    fn test_source_map_invalid_document() {
+        let map = SourceMap::parse("nodes:\n  - id: a\n  bad: [\n");
+        assert!(map.value("/nodes/0/id").is_some());
+    }
+
+    #[test]
+    fn test_source_file_snippet() {
+        let source = SourceFile::new("wf.yml", FileFormat::Yaml, WORKFLOW);
+
+        assert_eq!(
+            source.snippet(Span::new(8, 11, 3)),
+            "  --> wf.yml:8:11\n  |\n8 |     type: Foo\n  |           ^^^"
+        );
+        assert_eq!(
+            source.snippet(Span::new(10, 3, 0)),
+            "   --> wf.yml:10:3\n   |\n10 |   result: ${{ greet }}\n   |   ^^^^^^^^^^^^^^^^^^^^"
+        );
+        assert_eq!(source.snippet(Span::new(99, 1, 1)), "  --> wf.yml:99:1");
+    }
+
+    #[test]
+    fn test_source_file_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workflow.yml");
+        std::fs::write(&path, WORKFLOW).unwrap();
+
+        let source = SourceFile::read(&path).unwrap();
+        assert_eq!(source.format(), FileFormat::Yaml);
+        assert_eq!(source.parse().unwrap().node_count(), 2);
+        assert_eq!(source.line(8), Some("    type: Foo"));
+
+        assert!(matches!(
+            SourceFile::read(dir.path().join("missing.yml")),
+            Err(WorkflowError::NotFound { .. })
+        ));
+        assert!(matches!(
+            SourceFile::read(dir.path()),
+            Err(WorkflowError::UnsupportedFormat)
+        ));
+    }
+
+    #[test]
+    fn test_source_file_document_keeps_unknown_fields() {
+        let yaml = SourceFile::new("wf.yml", FileFormat::Yaml, "nodes: []\nwen: true\n");
+        assert_eq!(yaml.document().unwrap()["wen"], Value::Bool(true));
+
+        let json = SourceFile::new("wf.json", FileFormat::Json, r#"{"wen": true}"#);
+        assert_eq!(json.document().unwrap()["wen"], Value::Bool(true));
+
+        let invalid = SourceFile::new("wf.json", FileFormat::Json, "{");
+        assert!(matches!(
+            invalid.document(),
+            Err(WorkflowError::JsonParse(_))
+        ));
+    }
+}
@@ -0,0 +1,584 @@
+//! Text templates
+//!
+//! This module parses and renders the Jinja-style templates used by the
+//! `Template` node to generate files with repeated or optional sections,
+//! which `${{ ... }}` placeholders cannot express.
+//!
+//! # Syntax
+//!
+//! - `{{ expression }}`: the value of an [expression](crate::core::expression),
+//!   filters included, such as `{{ name | upper }}`
+//! - `{% if condition %} ... {% elif condition %} ... {% else %} ... {% endif %}`
+//! - `{% for item in expression %} ... {% endfor %}`, over a sequence, or
+//!   over a mapping whose entries have a `key` and a `value`; the body also
+//!   sees `loop.index` (from 0), `loop.first`, `loop.last` and `loop.length`
+//! - `{# comment #}`
+//!
+//! A line holding nothing but a `{% ... %}` or `{# ... #}` tag is removed
+//! entirely, so block tags can sit on their own lines without leaving blank
+//! lines behind. A `-` just inside a delimiter, as in `{{- name -}}`, trims
+//! the whitespace before or after the tag.
+
+use serde_yml::{Mapping, Value};
+
+use crate::core::expression::{to_text, Expression, ExpressionError};
+use crate::core::heap::Heap;
+
+/// Errors that can occur while parsing or rendering a template
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TemplateError {
+    /// The template is not syntactically valid
+    #[error("{message} on line {line}")]
+    Syntax { message: String, line: usize },
+
+    /// An expression of the template cannot be evaluated
+    #[error("{message} on line {line}")]
+    Render { message: String, line: usize },
+}
+
+/// Result type for template operations
+pub type TemplateResult<T> = Result<T, TemplateError>;
+
+/// Lexical tokens of a template
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Output(String, usize),
+    Tag(String, usize),
+    Comment,
+}
+
+/// Parsed template tree
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Output(Expression, usize),
+    If {
+        branches: Vec<(Expression, Vec<Segment>, usize)>,
+        otherwise: Vec<Segment>,
+    },
+    For {
+        variable: String,
+        iterable: Expression,
+        body: Vec<Segment>,
+        line: usize,
+    },
+}
+
+/// A parsed template
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::core::template::Template;
+/// use serde_yml::Value;
+///
+/// let mut heap = Heap::new();
+/// heap.insert("items", Some(serde_yml::from_str("[a, b]").unwrap()));
+///
+/// let template = Template::parse(
+///     "{% for item in items %}\n- {{ item | upper }}\n{% endfor %}\n",
+/// )
+/// .unwrap();
+/// assert_eq!(template.render(&heap).unwrap(), "- A\n- B\n");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parses a template
+    ///
+    /// # Errors
+    ///
+    /// Returns `TemplateError::Syntax` if a tag is not closed, a block is
+    /// not terminated, or an expression is not valid.
+    pub fn parse(source: &str) -> TemplateResult<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+
+        let (segments, end) = parser.parse_block(&[])?;
+        if let Some((tag, line)) = end {
+            return Err(syntax(format!("Unexpected {{% {} %}}", tag), line));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Renders the template with the heap as context
+    ///
+    /// # Errors
+    ///
+    /// Returns `TemplateError::Render` if an expression cannot be evaluated
+    /// or a loop is over a value that is neither a sequence nor a mapping.
+    pub fn render(&self, heap: &Heap) -> TemplateResult<String> {
+        let mut output = String::new();
+        let mut scope = heap.clone();
+        render(&self.segments, &mut scope, &mut output)?;
+        Ok(output)
+    }
+}
+
+fn syntax(message: String, line: usize) -> TemplateError {
+    TemplateError::Syntax { message, line }
+}
+
+fn render(segments: &[Segment], heap: &mut Heap, output: &mut String) -> TemplateResult<()> {
+    let failure = |line: usize| {
+        move |e: ExpressionError| TemplateError::Render {
+            message: e.to_string(),
+            line,
+        }
+    };
+
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Output(expression, line) => {
+                let value = expression.evaluate(heap).map_err(failure(*line))?;
+                output.push_str(&to_text(&value));
+            }
+            Segment::If {
+                branches,
+                otherwise,
+            } => {
+                let mut chosen = otherwise;
+                for (condition, body, line) in branches {
+                    if condition.evaluate_bool(heap).map_err(failure(*line))? {
+                        chosen = body;
+                        break;
+                    }
+                }
+                render(chosen, heap, output)?;
+            }
+            Segment::For {
+                variable,
+                iterable,
+                body,
+                line,
+            } => {
+                let items = match iterable.evaluate(heap).map_err(failure(*line))? {
+                    Value::Null => Vec::new(),
+                    Value::Sequence(items) => items,
+                    Value::Mapping(map) => map
+                        .into_iter()
+                        .map(|(key, value)| {
+                            let mut entry = Mapping::new();
+                            entry.insert(Value::from("key"), key);
+                            entry.insert(Value::from("value"), value);
+                            Value::Mapping(entry)
+                        })
+                        .collect(),
+                    other => {
+                        return Err(TemplateError::Render {
+                            message: format!(
+                                "Cannot loop over `{}`, which is not a sequence or a mapping",
+                                to_text(&other).trim_end()
+                            ),
+                            line: *line,
+                        })
+                    }
+                };
+
+                let length = items.len();
+                let shadowed_item = heap.get(variable).cloned();
+                let shadowed_loop = heap.get("loop").cloned();
+
+                for (index, item) in items.into_iter().enumerate() {
+                    let mut state = Mapping::new();
+                    state.insert(Value::from("index"), Value::from(index));
+                    state.insert(Value::from("first"), Value::from(index == 0));
+                    state.insert(Value::from("last"), Value::from(index + 1 == length));
+                    state.insert(Value::from("length"), Value::from(length));
+
+                    heap.insert(variable.clone(), Some(item));
+                    heap.insert("loop", Some(Value::Mapping(state)));
+                    render(body, heap, output)?;
+                }
+
+                restore(heap, variable, shadowed_item);
+                restore(heap, "loop", shadowed_loop);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Puts back a value shadowed by a loop binding
+fn restore(heap: &mut Heap, key: &str, value: Option<Value>) {
+    match value {
+        Some(value) => {
+            heap.insert(key, Some(value));
+        }
+        None => {
+            heap.remove(key);
+        }
+    }
+}
+
+fn tokenize(source: &str) -> TemplateResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut standalone = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+    let mut trim_next = false;
+
+    while let Some(start) = find_tag(rest) {
+        let (open, close) = match &rest[start..start + 2] {
+            "{{" => ("{{", "}}"),
+            "{%" => ("{%", "%}"),
+            _ => ("{#", "#}"),
+        };
+
+        let mut text = rest[..start].to_string();
+        if trim_next {
+            text = text.trim_start().to_string();
+        }
+        line += rest[..start].matches('\n').count();
+
+        let after = &rest[start + 2..];
+        let end = after
+            .find(close)
+            .ok_or_else(|| syntax(format!("Unclosed '{}', expected '{}'", open, close), line))?;
+        let mut body = &after[..end];
+        let trim_before = body.starts_with('-');
+        trim_next = body.len() > 1 && body.ends_with('-');
+
+        if trim_before {
+            body = &body[1..];
+            text = text.trim_end().to_string();
+        }
+        if trim_next {
+            body = &body[..body.len() - 1];
+        }
+
+        tokens.push(Token::Text(text));
+        tokens.push(match open {
+            "{{" => Token::Output(body.trim().to_string(), line),
+            "{%" => Token::Tag(body.trim().to_string(), line),
+            _ => Token::Comment,
+        });
+        standalone.push(open != "{{" && !trim_before && !trim_next);
+
+        line += after[..end].matches('\n').count();
+        rest = &after[end + 2..];
+    }
+
+    let mut text = rest.to_string();
+    if trim_next {
+        text = text.trim_start().to_string();
+    }
+    tokens.push(Token::Text(text));
+
+    remove_standalone_lines(&mut tokens, &standalone);
+    Ok(tokens)
+}
+
+/// Finds the start of the next `{{`, `{%` or `{#` delimiter
+fn find_tag(text: &str) -> Option<usize> {
+    text.match_indices('{')
+        .map(|(index, _)| index)
+        .find(|index| matches!(text.as_bytes().get(index + 1), Some(b'{' | b'%' | b'#')))
+}
+
+/// Removes the lines holding nothing but a block tag or a comment
+///
+/// Tokens alternate between text and tags, starting and ending with text.
+fn remove_standalone_lines(tokens: &mut [Token], standalone: &[bool]) {
+    for (tag, candidate) in standalone.iter().enumerate() {
+        if !candidate {
+            continue;
+        }
+        let before = tag * 2;
+        let after = before + 2;
+        let is_first = before == 0;
+        let is_last = after == tokens.len() - 1;
+
+        let line_start = match &tokens[before] {
+            Token::Text(text) => match text.rfind('\n') {
+                Some(newline) => text[newline + 1..].trim().is_empty().then_some(newline + 1),
+                None if is_first && text.trim().is_empty() => Some(0),
+                None => None,
+            },
+            _ => None,
+        };
+        let line_end = match &tokens[after] {
+            Token::Text(text) => {
+                let indent = text.len() - text.trim_start_matches([' ', '\t']).len();
+                match &text[indent..] {
+                    rest if rest.starts_with("\r\n") => Some(indent + 2),
+                    rest if rest.starts_with('\n') => Some(indent + 1),
+                    "" if is_last => Some(indent),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let (Some(line_start), Some(line_end)) = (line_start, line_end) {
+            if let Token::Text(text) = &mut tokens[before] {
+                text.truncate(line_start);
+            }
+            if let Token::Text(text) = &mut tokens[after] {
+                text.replace_range(..line_end, "");
+            }
+        }
+    }
+}
+
+/// Tag ending a block, with its line
+type BlockEnd = (String, usize);
+
+/// Recursive descent parser over the token stream
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    /// Parses segments until one of the given tags, which is returned
+    /// together with its line
+    fn parse_block(
+        &mut self,
+        terminators: &[&str],
+    ) -> TemplateResult<(Vec<Segment>, Option<BlockEnd>)> {
+        let mut segments = Vec::new();
+
+        while let Some(token) = self.tokens.get(self.position).cloned() {
+            self.position += 1;
+            match token {
+                Token::Text(text) if text.is_empty() => {}
+                Token::Text(text) => segments.push(Segment::Text(text)),
+                Token::Comment => {}
+                Token::Output(source, line) => {
+                    segments.push(Segment::Output(expression(&source, line)?, line));
+                }
+                Token::Tag(tag, line) => {
+                    let keyword = tag.split_whitespace().next().unwrap_or("");
+                    if terminators.contains(&keyword) {
+                        return Ok((segments, Some((tag, line))));
+                    }
+                    segments.push(match keyword {
+                        "if" => self.parse_if(&tag, line)?,
+                        "for" => self.parse_for(&tag, line)?,
+                        _ => return Err(syntax(format!("Unexpected {{% {} %}}", tag), line)),
+                    });
+                }
+            }
+        }
+
+        Ok((segments, None))
+    }
+
+    fn parse_if(&mut self, tag: &str, line: usize) -> TemplateResult<Segment> {
+        let mut branches = Vec::new();
+        let mut condition = (expression(argument(tag, "if", line)?, line)?, line);
+
+        loop {
+            let (body, end) = self.parse_block(&["elif", "else", "endif"])?;
+            branches.push((condition.0, body, condition.1));
+
+            let keyword = end
+                .as_ref()
+                .and_then(|(end, _)| end.split_whitespace().next())
+                .unwrap_or("");
+            match (keyword, end.clone()) {
+                ("elif", Some((end, end_line))) => {
+                    condition = (
+                        expression(argument(&end, "elif", end_line)?, end_line)?,
+                        end_line,
+                    );
+                }
+                ("else", _) => {
+                    let (otherwise, end) = self.parse_block(&["endif"])?;
+                    return match end {
+                        Some(_) => Ok(Segment::If {
+                            branches,
+                            otherwise,
+                        }),
+                        None => Err(unterminated("if", "endif", line)),
+                    };
+                }
+                ("endif", _) => {
+                    return Ok(Segment::If {
+                        branches,
+                        otherwise: Vec::new(),
+                    })
+                }
+                _ => return Err(unterminated("if", "endif", line)),
+            }
+        }
+    }
+
+    fn parse_for(&mut self, tag: &str, line: usize) -> TemplateResult<Segment> {
+        let rest = argument(tag, "for", line)?;
+        let (variable, iterable) = rest
+            .split_once(" in ")
+            .map(|(variable, iterable)| (variable.trim(), iterable.trim()))
+            .filter(|(variable, _)| {
+                !variable.is_empty()
+                    && variable
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            })
+            .ok_or_else(|| {
+                syntax(
+                    format!(
+                        "Expected {{% for <name> in <expression> %}}, got {{% {} %}}",
+                        tag
+                    ),
+                    line,
+                )
+            })?;
+
+        let iterable = expression(iterable, line)?;
+        let (body, end) = self.parse_block(&["endfor"])?;
+        if end.is_none() {
+            return Err(unterminated("for", "endfor", line));
+        }
+
+        Ok(Segment::For {
+            variable: variable.to_string(),
+            iterable,
+            body,
+            line,
+        })
+    }
+}
+
+/// Returns what follows the keyword of a tag, which must not be empty
+fn argument<'a>(tag: &'a str, keyword: &str, line: usize) -> TemplateResult<&'a str> {
+    let rest = tag[keyword.len()..].trim();
+    if rest.is_empty() {
+        return Err(syntax(
+            format!("{{% {} %}} requires an expression", keyword),
+            line,
+        ));
+    }
+    Ok(rest)
+}
+
+fn expression(source: &str, line: usize) -> TemplateResult<Expression> {
+    Expression::parse(source)
+        .map_err(|e| syntax(format!("Invalid expression `{}`: {}", source, e), line))
+}
+
+fn unterminated(keyword: &str, end: &str, line: usize) -> TemplateError {
+    syntax(
+        format!("{{% {} %}} is not closed by {{% {} %}}", keyword, end),
+        line,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heap() -> Heap {
+        let mut heap = Heap::new();
+        heap.insert("name", Some(Value::from("Ada")));
+        heap.insert(
+            "services",
+            Some(serde_yml::from_str("[{ name: api, port: 80 }, { name: db }]").unwrap()),
+        );
+        heap.insert(
+            "labels",
+            Some(serde_yml::from_str("{ team: core, tier: backend }").unwrap()),
+        );
+        heap
+    }
+
+    fn render(source: &str) -> String {
+        Template::parse(source).unwrap().render(&heap()).unwrap()
+    }
+
+    #[test]
+    fn test_template_output() {
+        assert_eq!(render("Hello {{ name }}!"), "Hello Ada!");
+        assert_eq!(
+            render("{{ name | lower }} {{ services | length }}"),
+            "ada 2"
+        );
+        assert_eq!(render("no tags"), "no tags");
+        assert_eq!(render("{# hidden #}shown"), "shown");
+    }
+
+    #[test]
+    fn test_template_loops() {
+        let output = render(
+            "services:\n{% for service in services %}\n  - {{ service.name }}{% if service.port %}:{{ service.port }}{% endif %}\n{% endfor %}\n",
+        );
+        assert_eq!(output, "services:\n  - api:80\n  - db\n");
+
+        let output = render("{% for label in labels %}{{ label.key }}={{ label.value }}{% if !loop.last %},{% endif %}{% endfor %}");
+        assert_eq!(output, "team=core,tier=backend");
+
+        assert_eq!(render("{% for x in missing %}{{ x }}{% endfor %}"), "");
+    }
+
+    #[test]
+    fn test_template_loop_restores_bindings() {
+        let output = render("{% for name in services %}{{ loop.index }}{% endfor %}{{ name }}");
+        assert_eq!(output, "01Ada");
+    }
+
+    #[test]
+    fn test_template_conditionals() {
+        let template =
+            "{% if name == 'Bob' %}bob{% elif name == 'Ada' %}ada{% else %}other{% endif %}";
+        assert_eq!(render(template), "ada");
+        assert_eq!(render("{% if missing %}yes{% else %}no{% endif %}"), "no");
+    }
+
+    #[test]
+    fn test_template_whitespace_control() {
+        assert_eq!(render("a\n  {%- if true -%}\n  b\n{%- endif %}"), "ab");
+        assert_eq!(
+            render("  {% if true %}\nkept\n  {% endif %}\nend"),
+            "kept\nend"
+        );
+        assert_eq!(render("x {% if true %}y{% endif %}\n"), "x y\n");
+    }
+
+    #[test]
+    fn test_template_syntax_errors() {
+        let error = |source: &str| Template::parse(source).unwrap_err().to_string();
+
+        assert_eq!(
+            error("a\n{{ name"),
+            "Unclosed '{{', expected '}}' on line 2"
+        );
+        assert_eq!(
+            error("{% if name %}\nyes"),
+            "{% if %} is not closed by {% endif %} on line 1"
+        );
+        assert_eq!(error("{% endfor %}"), "Unexpected {% endfor %} on line 1");
+        assert_eq!(
+            error("\n\n{{ name | shout }}"),
+            "Invalid expression `name | shout`: Unknown filter 'shout' at position 7 on line 3"
+        );
+        assert!(Template::parse("{% for in items %}{% endfor %}").is_err());
+        assert!(Template::parse("{% if %}{% endif %}").is_err());
+    }
+
+    #[test]
+    fn test_template_render_errors() {
+        let error = Template::parse("a\n{% for x in name %}{% endfor %}")
+            .unwrap()
+            .render(&heap())
+            .unwrap_err();
+        assert_eq!(
+            error,
+            TemplateError::Render {
+                message: "Cannot loop over `Ada`, which is not a sequence or a mapping".to_string(),
+                line: 2,
+            }
+        );
+    }
+}
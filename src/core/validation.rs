@@ -0,0 +1,1971 @@
+//! Static workflow validation
+//!
+//! This module checks a workflow definition without executing any node.
+//! Every problem found is collected into a [`ValidationReport`] so that all
+//! of them can be reported at once.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde_yml::Value;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult, RUN_BINDING};
+use crate::core::expression::{placeholders, Expression};
+use crate::core::imports::resolve_imports;
+use crate::core::migration::{FORMAT_VERSION, VERSION_FIELD};
+use crate::core::source::{escape, SourceFile, Span};
+use crate::nodes::registry::NodeRegistry;
+use crate::shared::types::workflow::import::WorkflowImport;
+use crate::shared::types::workflow::input::WorkflowInput;
+use crate::shared::types::workflow::node::WorkflowNode;
+use crate::shared::types::workflow::options::{WasmDirOption, WasmOptions, WorkflowOptions};
+use crate::shared::types::workflow::template::NodeTemplate;
+use crate::shared::types::workflow::variable::WorkflowVariable;
+use crate::shared::types::workflow::workflow::Workflow;
+use crate::shared::utils::suggest::did_you_mean;
+
+/// Severity of a validation diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The workflow cannot run correctly
+    Error,
+    /// The workflow can run but is likely not doing what was intended
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A single problem found while validating a workflow
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::validation::{Diagnostic, Severity};
+///
+/// let diagnostic = Diagnostic::error("Unknown node type 'Lgo'").with_node("greet");
+/// assert_eq!(diagnostic.severity, Severity::Error);
+/// assert_eq!(diagnostic.to_string(), "error: node 'greet': Unknown node type 'Lgo'");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Severity of the problem
+    pub severity: Severity,
+
+    /// Description of the problem
+    pub message: String,
+
+    /// Identifier of the node the problem relates to, if any
+    pub node: Option<String>,
+
+    /// Document path of the offending element, e.g. `/nodes/1/type`
+    pub path: Option<String>,
+
+    /// Location of the offending element in the workflow file
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    /// Creates an error diagnostic
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            node: None,
+            path: None,
+            span: None,
+        }
+    }
+
+    /// Creates a warning diagnostic
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            node: None,
+            path: None,
+            span: None,
+        }
+    }
+
+    /// Sets the node the diagnostic relates to
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_node(mut self, node: impl Into<String>) -> Self {
+        self.node = Some(node.into());
+        self
+    }
+
+    /// Sets the document path of the offending element
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets the location of the offending element
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Checks if the diagnostic is an error
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Resolves the document path of the diagnostic to a file location
+    ///
+    /// A location that is already known is kept.
+    pub fn locate(&mut self, source: &SourceFile) {
+        if self.span.is_none() {
+            self.span = self
+                .path
+                .as_deref()
+                .and_then(|path| source.map().locate(path));
+        }
+    }
+
+    /// Renders the diagnostic with the offending source snippet
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::FileFormat;
+    /// use colossus::core::source::SourceFile;
+    /// use colossus::core::validation::Diagnostic;
+    ///
+    /// let source = SourceFile::new("wf.yml", FileFormat::Yaml, "nodes:\n  - id: a\n    type: Foo\n");
+    /// let mut diagnostic = Diagnostic::error("Unknown node type 'Foo'").with_path("/nodes/0/type");
+    /// diagnostic.locate(&source);
+    ///
+    /// assert_eq!(
+    ///     diagnostic.render(&source),
+    ///     "error: Unknown node type 'Foo'\n  --> wf.yml:3:11\n  |\n3 |     type: Foo\n  |           ^^^"
+    /// );
+    /// ```
+    pub fn render(&self, source: &SourceFile) -> String {
+        match self.span {
+            Some(span) => format!("{}\n{}", self, source.snippet(span)),
+            None => format!("{}\n  --> {}", self, source.path().display()),
+        }
+    }
+}
+
+impl From<&WorkflowError> for Diagnostic {
+    /// Converts an error into a diagnostic, keeping its location if known
+    ///
+    /// Import errors point at the `imports` section.
+    fn from(error: &WorkflowError) -> Self {
+        let mut diagnostic = Diagnostic::error(error.to_string());
+        if matches!(
+            error,
+            WorkflowError::Import { .. } | WorkflowError::ImportCycle(_)
+        ) {
+            diagnostic = diagnostic.with_path("/imports");
+        }
+
+        match error.span() {
+            Some(span) => diagnostic.with_span(span),
+            None => diagnostic,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.node {
+            Some(node) => write!(f, "{}: node '{}': {}", self.severity, node, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// All diagnostics found while validating a workflow
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Creates an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a diagnostic to the report
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Returns all diagnostics, in the order they were found
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the error diagnostics
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| d.is_error())
+    }
+
+    /// Returns the warning diagnostics
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| !d.is_error())
+    }
+
+    /// Checks if the workflow has no errors; warnings are allowed
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Checks if the report contains no diagnostic at all
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Resolves the location of every diagnostic in the given source file
+    pub fn locate(&mut self, source: &SourceFile) {
+        for diagnostic in &mut self.diagnostics {
+            diagnostic.locate(source);
+        }
+    }
+
+    /// Converts the report into a result
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::Validation` if the report contains errors.
+    pub fn into_result(self) -> WorkflowResult<Self> {
+        if self.is_valid() {
+            Ok(self)
+        } else {
+            Err(WorkflowError::Validation(self))
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self.errors().map(|d| d.to_string()).collect();
+        write!(f, "{} error(s): {}", errors.len(), errors.join("; "))
+    }
+}
+
+/// Validates workflows against a node registry without executing them
+///
+/// The validator checks:
+///
+/// - required fields and duplicate node, input, and variable names
+/// - node types unknown to the registry, and node inputs rejected by the
+///   input check of their type
+/// - `when` expressions and `${{ ... }}` placeholders that do not parse
+/// - references to unknown names or to nodes that have not run yet
+/// - reference cycles between nodes
+/// - unknown fields, when validating a file or raw document
+/// - missing sub-workflow files and call cycles between files, when
+///   validating a file
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::validation::WorkflowValidator;
+/// use colossus::shared::types::workflow::{node::WorkflowNode, workflow::Workflow};
+/// use serde_yml::Value;
+///
+/// let mut workflow = Workflow::new("Example");
+/// workflow.add_node(WorkflowNode::new("a", "Log", Some(Value::String("${{ b }}".to_string()))));
+/// workflow.add_node(WorkflowNode::new("b", "Lgo", None));
+///
+/// let report = WorkflowValidator::new().validate(&workflow);
+/// assert!(!report.is_valid());
+/// assert_eq!(report.errors().count(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowValidator {
+    registry: NodeRegistry,
+    lenient: bool,
+}
+
+impl WorkflowValidator {
+    /// Creates a validator knowing the built-in node types
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the registry of known node types
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_registry(mut self, registry: NodeRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Sets whether unknown fields are reported as warnings instead of errors
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Validates a workflow file
+    ///
+    /// The file is checked both for unknown fields, see
+    /// [`WorkflowValidator::validate_document`], and as a workflow, see
+    /// [`WorkflowValidator::validate`], once its imports are resolved. Every
+    /// diagnostic is located in the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::JsonParse` or `WorkflowError::YamlParse` if
+    /// the file is not a valid workflow.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::core::engine::FileFormat;
+    /// use colossus::core::source::SourceFile;
+    /// use colossus::core::validation::WorkflowValidator;
+    ///
+    /// let source = SourceFile::new("wf.yml", FileFormat::Yaml, "nodes:\n  - id: a\n    type: Log\n    wen: true\n");
+    ///
+    /// let report = WorkflowValidator::new().validate_source(&source).unwrap();
+    /// assert_eq!(report.errors().next().unwrap().message, "Unknown field 'wen', did you mean 'when'?");
+    ///
+    /// let report = WorkflowValidator::new().with_lenient(true).validate_source(&source).unwrap();
+    /// assert!(report.is_valid());
+    /// ```
+    pub fn validate_source(&self, source: &SourceFile) -> WorkflowResult<ValidationReport> {
+        let document = source.document()?;
+        let mut workflow = source.format().parse_content(source.content())?;
+
+        let mut report = self.validate_document(&document);
+        for diagnostic in &mut report.diagnostics {
+            diagnostic.span = diagnostic
+                .path
+                .as_deref()
+                .and_then(|path| source.map().key(path));
+        }
+
+        // Nodes cannot be checked without the templates they use
+        if let Err(e) = resolve_imports(&mut workflow, source.path()) {
+            report.push(Diagnostic::from(&e));
+            report.locate(source);
+            return Ok(report);
+        }
+
+        report
+            .diagnostics
+            .extend(self.validate(&workflow).diagnostics);
+
+        let context = RunContext::new(source.path()).with_registry(self.registry.clone());
+        let wasm = workflow
+            .options
+            .as_ref()
+            .and_then(|options| options.wasm.as_ref());
+        for (index, dir) in wasm
+            .and_then(|wasm| wasm.dirs.as_ref())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            if let Err(e) = context.resolve_within(&dir.host) {
+                report.push(
+                    Diagnostic::error(e.to_string())
+                        .with_path(format!("/options/wasm/dirs/{}/host", index)),
+                );
+            }
+        }
+
+        for (_, base, node) in Self::located_nodes(&workflow) {
+            let Some((field, path)) = self.sub_workflow_path(node) else {
+                continue;
+            };
+
+            if let Some(e) = self.check_sub_workflow(&context, path) {
+                report.push(
+                    Diagnostic::error(e.to_string())
+                        .with_node(&node.id)
+                        .with_path(format!("{}/input/{}", base, escape(field))),
+                );
+            }
+        }
+
+        report.locate(source);
+
+        Ok(report)
+    }
+
+    /// Returns the path of the workflow called by a node, if it is static
+    ///
+    /// Paths computed from placeholders are only known when running.
+    ///
+    /// # Returns
+    ///
+    /// Returns the input field holding the path, and the path.
+    fn sub_workflow_path<'a>(&'a self, node: &'a WorkflowNode) -> Option<(&'a str, &'a str)> {
+        let field = self.registry.workflow_call(&node.node_type)?;
+
+        node.input
+            .as_ref()
+            .and_then(|input| input.get(field))
+            .and_then(Value::as_str)
+            .filter(|path| placeholders(path).is_empty())
+            .map(|path| (field, path))
+    }
+
+    /// Follows the static calls of a sub-workflow, looking for a missing
+    /// file, a call cycle, or too deep a nesting
+    fn check_sub_workflow(&self, context: &RunContext, path: &str) -> Option<WorkflowError> {
+        let child = match context.enter(path) {
+            Ok(child) => child,
+            Err(e) => return Some(e),
+        };
+
+        // Files that cannot be parsed are reported when validating them
+        let workflow = SourceFile::read(child.workflow_path())
+            .and_then(|source| source.parse())
+            .ok()?;
+
+        workflow
+            .nodes()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node| self.sub_workflow_path(node))
+            .find_map(|(_, path)| self.check_sub_workflow(&child, path))
+    }
+
+    /// Checks a raw workflow document for unknown fields
+    ///
+    /// Fields that are not part of the workflow format would otherwise be
+    /// silently ignored, so a typo such as `wen:` instead of `when:` is
+    /// reported, with a suggestion when a known field is close enough.
+    /// Unknown fields are errors, or warnings in lenient mode.
+    ///
+    /// # Returns
+    ///
+    /// Returns a report with one diagnostic per unknown field.
+    pub fn validate_document(&self, document: &Value) -> ValidationReport {
+        let mut report = ValidationReport::new();
+
+        self.check_fields(document, "", Workflow::FIELDS, None, &mut report);
+
+        if document.get(VERSION_FIELD).is_none() {
+            report.push(
+                Diagnostic::warning(format!(
+                    "Workflow does not declare its file format version, add `{}: {}` or run `colossus migrate`",
+                    VERSION_FIELD, FORMAT_VERSION
+                ))
+                .with_path(""),
+            );
+        }
+
+        let sections: [(&str, &[&str]); 6] = [
+            ("imports", WorkflowImport::FIELDS),
+            ("variables", WorkflowVariable::FIELDS),
+            ("inputs", WorkflowInput::FIELDS),
+            ("nodes", WorkflowNode::FIELDS),
+            ("on_failure", WorkflowNode::FIELDS),
+            ("finally", WorkflowNode::FIELDS),
+        ];
+        for (section, fields) in sections {
+            let Some(Value::Sequence(entries)) = document.get(section) else {
+                continue;
+            };
+
+            for (index, entry) in entries.iter().enumerate() {
+                let node = if fields == WorkflowNode::FIELDS {
+                    entry.get("id").and_then(Value::as_str)
+                } else {
+                    None
+                };
+                let path = format!("/{}/{}", section, index);
+                self.check_fields(entry, &path, fields, node, &mut report);
+            }
+        }
+
+        if let Some(Value::Mapping(templates)) = document.get("templates") {
+            for (name, template) in templates {
+                let path = format!("/templates/{}", escape(name.as_str().unwrap_or_default()));
+                self.check_fields(template, &path, NodeTemplate::FIELDS, None, &mut report);
+            }
+        }
+
+        if let Some(options) = document.get("options") {
+            self.check_fields(
+                options,
+                "/options",
+                WorkflowOptions::FIELDS,
+                None,
+                &mut report,
+            );
+
+            if let Some(wasm) = options.get("wasm") {
+                let path = "/options/wasm";
+                self.check_fields(wasm, path, WasmOptions::FIELDS, None, &mut report);
+                if let Some(Value::Sequence(dirs)) = wasm.get("dirs") {
+                    for (index, dir) in dirs.iter().enumerate() {
+                        let path = format!("{}/dirs/{}", path, index);
+                        self.check_fields(dir, &path, WasmDirOption::FIELDS, None, &mut report);
+                    }
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Reports the keys of a mapping that are not in the known fields
+    fn check_fields(
+        &self,
+        value: &Value,
+        path: &str,
+        fields: &[&str],
+        node: Option<&str>,
+        report: &mut ValidationReport,
+    ) {
+        let Value::Mapping(map) = value else {
+            return;
+        };
+
+        for key in map.keys() {
+            let name = match key {
+                Value::String(name) => name.clone(),
+                other => serde_yml::to_string(other)
+                    .map(|text| text.trim_end().to_string())
+                    .unwrap_or_default(),
+            };
+            if fields.contains(&name.as_str()) {
+                continue;
+            }
+
+            let message = match did_you_mean(&name, fields.iter().copied()) {
+                Some(suggestion) => {
+                    format!("Unknown field '{}', did you mean '{}'?", name, suggestion)
+                }
+                None => format!(
+                    "Unknown field '{}' (expected one of: {})",
+                    name,
+                    fields.join(", ")
+                ),
+            };
+            let mut diagnostic = if self.lenient {
+                Diagnostic::warning(message)
+            } else {
+                Diagnostic::error(message)
+            }
+            .with_path(format!("{}/{}", path, escape(&name)));
+            if let Some(node) = node {
+                diagnostic = diagnostic.with_node(node);
+            }
+
+            report.push(diagnostic);
+        }
+    }
+
+    /// Validates a workflow
+    ///
+    /// Every diagnostic carries the document path of the element it relates
+    /// to, which can be turned into a file location with
+    /// [`ValidationReport::locate`].
+    ///
+    /// # Returns
+    ///
+    /// Returns a report with every problem found.
+    pub fn validate(&self, workflow: &Workflow) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        let nodes = Self::located_nodes(workflow);
+
+        let globals = Self::check_globals(workflow, &mut report);
+        let positions = Self::check_nodes(workflow, &self.registry, &mut report);
+
+        let mut edges: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+
+        for (index, (section, base, node)) in nodes.iter().enumerate() {
+            let mut references = Vec::new();
+
+            if let Some(input) = &node.input {
+                Self::collect_value_references(
+                    input,
+                    &format!("input of node '{}'", node.id),
+                    &format!("{}/input", base),
+                    &mut references,
+                    &mut report,
+                );
+            }
+
+            // The outcome of the run is only defined for handler nodes
+            if *section != "nodes" {
+                references.retain(|(name, _)| name != RUN_BINDING);
+            }
+
+            // Names bound by the node itself are not in the heap
+            let bindings = self.registry.bindings(&node.node_type);
+            references.retain(|(name, _)| !bindings.contains(name));
+
+            // Iteration bindings are only defined within the iterations
+            if node.matrix.is_some() {
+                references.retain(|(name, _)| name != "matrix");
+            }
+
+            if let Some(matrix) = &node.matrix {
+                let path = format!("{}/matrix", base);
+                if node.for_each.is_some() {
+                    report.push(
+                        Diagnostic::error("`for_each` and `matrix` cannot be combined")
+                            .with_node(&node.id)
+                            .with_path(&path),
+                    );
+                }
+                if matrix.combinations().is_empty() {
+                    report.push(
+                        Diagnostic::warning("Matrix has no combinations, the node never runs")
+                            .with_node(&node.id)
+                            .with_path(&path),
+                    );
+                }
+                for (entry, combination) in matrix.exclude.iter().enumerate() {
+                    for axis in combination.keys() {
+                        if !matrix.axes.contains_key(axis) {
+                            report.push(
+                                Diagnostic::warning(format!(
+                                    "Excluded combination refers to unknown axis '{}'",
+                                    axis
+                                ))
+                                .with_node(&node.id)
+                                .with_path(format!(
+                                    "{}/exclude/{}/{}",
+                                    path,
+                                    entry,
+                                    escape(axis)
+                                )),
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Some(items) = &node.for_each {
+                references.retain(|(name, _)| name != "item" && name != "index");
+
+                Self::collect_value_references(
+                    items,
+                    &format!("for_each of node '{}'", node.id),
+                    &format!("{}/for_each", base),
+                    &mut references,
+                    &mut report,
+                );
+                let is_sequence = match items {
+                    Value::String(text) => !placeholders(text).is_empty(),
+                    other => other.is_sequence(),
+                };
+                if !is_sequence {
+                    report.push(
+                        Diagnostic::error(
+                            "`for_each` must be a sequence or a ${{ ... }} placeholder",
+                        )
+                        .with_node(&node.id)
+                        .with_path(format!("{}/for_each", base)),
+                    );
+                }
+            }
+
+            if let Some(on_branch) = &node.on_branch {
+                let path = format!("{}/on_branch", base);
+                match node.branch() {
+                    Some((switch, branch)) => match positions.get(switch) {
+                        Some(&target) => {
+                            references.push((switch.to_string(), path.clone()));
+                            if let Some(message) = self.check_branch(nodes[target].2, branch) {
+                                report.push(
+                                    Diagnostic::error(message)
+                                        .with_node(&node.id)
+                                        .with_path(&path),
+                                );
+                            }
+                        }
+                        None => report.push(
+                            Diagnostic::error(format!(
+                                "`on_branch` refers to unknown node '{}'",
+                                switch
+                            ))
+                            .with_node(&node.id)
+                            .with_path(&path),
+                        ),
+                    },
+                    None => report.push(
+                        Diagnostic::error(format!(
+                            "`on_branch` '{}' must be of the form <switch id>.<branch>",
+                            on_branch
+                        ))
+                        .with_node(&node.id)
+                        .with_path(&path),
+                    ),
+                }
+            }
+
+            if let Some(when) = &node.when {
+                let path = format!("{}/when", base);
+                match Expression::parse(when) {
+                    Ok(expression) => references.extend(
+                        expression
+                            .references()
+                            .into_iter()
+                            .map(|name| (name.to_string(), path.clone())),
+                    ),
+                    Err(e) => report.push(
+                        Diagnostic::error(format!("Invalid `when` expression '{}': {}", when, e))
+                            .with_node(&node.id)
+                            .with_path(path),
+                    ),
+                }
+            }
+
+            let mut seen = HashSet::new();
+            for (name, path) in references {
+                if !seen.insert(name.clone()) || globals.contains(name.as_str()) {
+                    continue;
+                }
+
+                match positions.get(name.as_str()) {
+                    Some(&target) => {
+                        edges[index].push(target);
+                        if target > index {
+                            report.push(
+                                Diagnostic::error(format!(
+                                    "References node '{}' before it runs",
+                                    name
+                                ))
+                                .with_node(&node.id)
+                                .with_path(path),
+                            );
+                        }
+                    }
+                    None => report.push(
+                        Diagnostic::error(format!("References unknown name '{}'", name))
+                            .with_node(&node.id)
+                            .with_path(path),
+                    ),
+                }
+            }
+        }
+
+        for (key, value) in workflow.output.iter().flat_map(|output| output.iter()) {
+            let mut references = Vec::new();
+            Self::collect_value_references(
+                value,
+                &format!("output '{}'", key),
+                &format!("/output/{}", escape(key)),
+                &mut references,
+                &mut report,
+            );
+
+            for (name, path) in references {
+                if !globals.contains(name.as_str()) && !positions.contains_key(name.as_str()) {
+                    report.push(
+                        Diagnostic::error(format!(
+                            "Output '{}' references unknown name '{}'",
+                            key, name
+                        ))
+                        .with_path(path),
+                    );
+                }
+            }
+        }
+
+        for cycle in Self::find_cycles(&edges) {
+            let mut names: Vec<&str> = cycle.iter().map(|&i| nodes[i].2.id.as_str()).collect();
+            names.push(names[0]);
+            report.push(
+                Diagnostic::error(format!("Reference cycle: {}", names.join(" -> ")))
+                    .with_node(names[0])
+                    .with_path(&nodes[cycle[0]].1),
+            );
+        }
+
+        report
+    }
+
+    /// Checks that a node takes branches, including the given one
+    ///
+    /// The branches of a node are only known when its node type can list
+    /// them from the input as written, so branches computed from a
+    /// placeholder are not checked.
+    ///
+    /// # Returns
+    ///
+    /// Returns the error message, or `None` if the branch may be taken.
+    fn check_branch(&self, switch: &WorkflowNode, branch: &str) -> Option<String> {
+        if !self.registry.has_branches(&switch.node_type) {
+            return Some(format!(
+                "`on_branch` refers to node '{}', which has no branches",
+                switch.id
+            ));
+        }
+
+        let branches = self.registry.branches(
+            &switch.node_type,
+            switch.input.as_ref().unwrap_or(&Value::Null),
+        )?;
+        if branches.iter().any(|name| name == branch) {
+            return None;
+        }
+
+        Some(
+            match did_you_mean(branch, branches.iter().map(String::as_str)) {
+                Some(suggestion) => format!(
+                    "Node '{}' has no branch '{}', did you mean '{}'?",
+                    switch.id, branch, suggestion
+                ),
+                None => format!(
+                    "Node '{}' has no branch '{}' (available: {})",
+                    switch.id,
+                    branch,
+                    branches.join(", ")
+                ),
+            },
+        )
+    }
+
+    /// Lists the nodes of every section in the order they run
+    ///
+    /// Each node is returned with the field of its section and its document
+    /// path, such as `/finally/0`.
+    fn located_nodes(workflow: &Workflow) -> Vec<(&'static str, String, &WorkflowNode)> {
+        workflow
+            .sections()
+            .into_iter()
+            .flat_map(|(section, nodes)| {
+                nodes
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, node)| (section, format!("/{}/{}", section, index), node))
+            })
+            .collect()
+    }
+
+    /// Checks inputs and variables, returning their names
+    fn check_globals<'a>(
+        workflow: &'a Workflow,
+        report: &mut ValidationReport,
+    ) -> HashSet<&'a str> {
+        let mut globals = HashSet::new();
+
+        let names = workflow
+            .variables
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, variable)| ("variable", "variables", index, variable.name.as_str()))
+            .chain(
+                workflow
+                    .inputs
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .map(|(index, input)| ("input", "inputs", index, input.name.as_str())),
+            );
+
+        for (kind, section, index, name) in names {
+            let path = format!("/{}/{}/name", section, index);
+            if name.trim().is_empty() {
+                report.push(
+                    Diagnostic::error(format!("A {} has an empty name", kind)).with_path(path),
+                );
+            } else if !globals.insert(name) {
+                report.push(
+                    Diagnostic::error(format!("Duplicate {} name '{}'", kind, name))
+                        .with_path(path),
+                );
+            }
+        }
+
+        globals
+    }
+
+    /// Checks node ids and types, returning the position of every node id
+    fn check_nodes<'a>(
+        workflow: &'a Workflow,
+        registry: &NodeRegistry,
+        report: &mut ValidationReport,
+    ) -> HashMap<&'a str, usize> {
+        let mut positions = HashMap::new();
+
+        if workflow.nodes().unwrap_or_default().is_empty() {
+            report.push(Diagnostic::warning("Workflow has no nodes").with_path("/nodes"));
+        }
+
+        for (index, (section, base, node)) in Self::located_nodes(workflow).into_iter().enumerate()
+        {
+            let id_path = format!("{}/id", base);
+            let type_path = format!("{}/type", base);
+
+            if node.id.trim().is_empty() {
+                let message = match section {
+                    "nodes" => format!("Node at position {} has an empty id", index + 1),
+                    _ => format!("Node in `{}` has an empty id", section),
+                };
+                report.push(Diagnostic::error(message).with_path(id_path));
+                continue;
+            }
+
+            if positions.contains_key(node.id.as_str()) {
+                report.push(
+                    Diagnostic::error("Duplicate node id")
+                        .with_node(&node.id)
+                        .with_path(&id_path),
+                );
+            } else {
+                positions.insert(node.id.as_str(), index);
+            }
+
+            if let Some(template) = &node.uses {
+                let available: Vec<&str> = workflow
+                    .templates
+                    .iter()
+                    .flat_map(|templates| templates.keys())
+                    .map(String::as_str)
+                    .collect();
+                let message = match did_you_mean(template, available.iter().copied()) {
+                    Some(suggestion) => format!(
+                        "Unknown template '{}', did you mean '{}'?",
+                        template, suggestion
+                    ),
+                    None if available.is_empty() => format!(
+                        "Unknown template '{}', no templates are defined or imported",
+                        template
+                    ),
+                    None => format!(
+                        "Unknown template '{}' (available: {})",
+                        template,
+                        available.join(", ")
+                    ),
+                };
+                report.push(
+                    Diagnostic::error(message)
+                        .with_node(&node.id)
+                        .with_path(format!("{}/uses", base)),
+                );
+            } else if node.node_type.trim().is_empty() {
+                report.push(
+                    Diagnostic::error("Missing node type")
+                        .with_node(&node.id)
+                        .with_path(type_path),
+                );
+            } else if !registry.contains(&node.node_type) {
+                report.push(
+                    Diagnostic::error(format!(
+                        "Unknown node type '{}' (available: {})",
+                        node.node_type,
+                        registry.node_types().join(", ")
+                    ))
+                    .with_node(&node.id)
+                    .with_path(type_path),
+                );
+            } else if let Some(input) = &node.input {
+                if let Err(message) = registry.check_input(&node.node_type, input) {
+                    report.push(
+                        Diagnostic::error(message)
+                            .with_node(&node.id)
+                            .with_path(format!("{}/input", base)),
+                    );
+                }
+            }
+
+            let shadowed = workflow
+                .variables
+                .iter()
+                .flatten()
+                .map(|variable| &variable.name)
+                .chain(workflow.inputs.iter().flatten().map(|input| &input.name))
+                .any(|name| name == &node.id);
+            if shadowed {
+                report.push(
+                    Diagnostic::warning(
+                        "Node output overwrites an input or variable of the same name",
+                    )
+                    .with_node(&node.id)
+                    .with_path(id_path),
+                );
+            }
+        }
+
+        positions
+    }
+
+    /// Collects the names referenced by the placeholders of a value
+    ///
+    /// Each name is returned with the document path of the string that
+    /// references it.
+    fn collect_value_references(
+        value: &Value,
+        location: &str,
+        path: &str,
+        references: &mut Vec<(String, String)>,
+        report: &mut ValidationReport,
+    ) {
+        match value {
+            Value::String(text) => {
+                for body in placeholders(text) {
+                    match Expression::parse(body) {
+                        Ok(expression) => references.extend(
+                            expression
+                                .references()
+                                .into_iter()
+                                .map(|name| (name.to_string(), path.to_string())),
+                        ),
+                        Err(e) => report.push(
+                            Diagnostic::error(format!(
+                                "Invalid expression '${{{{ {} }}}}' in {}: {}",
+                                body, location, e
+                            ))
+                            .with_path(path),
+                        ),
+                    }
+                }
+            }
+            Value::Sequence(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    let path = format!("{}/{}", path, index);
+                    Self::collect_value_references(item, location, &path, references, report);
+                }
+            }
+            Value::Mapping(map) => {
+                for (key, item) in map {
+                    let path = match key {
+                        Value::String(key) => format!("{}/{}", path, escape(key)),
+                        _ => path.to_string(),
+                    };
+                    Self::collect_value_references(key, location, &path, references, report);
+                    Self::collect_value_references(item, location, &path, references, report);
+                }
+            }
+            Value::Tagged(tagged) => {
+                Self::collect_value_references(&tagged.value, location, path, references, report)
+            }
+            _ => {}
+        }
+    }
+
+    /// Finds the distinct cycles of a dependency graph
+    fn find_cycles(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            Active,
+            Done,
+        }
+
+        fn visit(
+            node: usize,
+            edges: &[Vec<usize>],
+            marks: &mut [Mark],
+            stack: &mut Vec<usize>,
+            cycles: &mut Vec<Vec<usize>>,
+        ) {
+            marks[node] = Mark::Active;
+            stack.push(node);
+
+            for &next in &edges[node] {
+                match marks[next] {
+                    Mark::New => visit(next, edges, marks, stack, cycles),
+                    Mark::Active => {
+                        let start = stack.iter().position(|&n| n == next).unwrap_or(0);
+                        cycles.push(stack[start..].to_vec());
+                    }
+                    Mark::Done => {}
+                }
+            }
+
+            stack.pop();
+            marks[node] = Mark::Done;
+        }
+
+        let mut marks = vec![Mark::New; edges.len()];
+        let mut cycles = Vec::new();
+
+        for node in 0..edges.len() {
+            if marks[node] == Mark::New {
+                visit(node, edges, &mut marks, &mut Vec::new(), &mut cycles);
+            }
+        }
+
+        cycles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(yaml: &str) -> ValidationReport {
+        let workflow: Workflow = serde_yml::from_str(yaml).unwrap();
+        WorkflowValidator::new().validate(&workflow)
+    }
+
+    fn messages(report: &ValidationReport) -> Vec<String> {
+        report.diagnostics().iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_valid_workflow() {
+        let report = validate(
+            r#"
+name: Valid
+variables:
+  - name: greeting
+    value: Hello
+inputs:
+  - name: debug
+    type: boolean
+nodes:
+  - id: first
+    type: Log
+    input: ${{ greeting }}
+  - id: second
+    type: Log
+    input:
+      message: ${{ first }} again
+    when: debug == true
+output:
+  result: ${{ second.message }}
+"#,
+        );
+
+        assert!(report.is_empty(), "{:?}", messages(&report));
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_validate_empty_workflow_warns() {
+        let report = validate("name: Empty");
+
+        assert!(report.is_valid());
+        assert_eq!(messages(&report), vec!["warning: Workflow has no nodes"]);
+    }
+
+    #[test]
+    fn test_validate_duplicate_ids() {
+        let report = validate(
+            r#"
+nodes:
+  - id: a
+    type: Log
+  - id: a
+    type: Log
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec!["error: node 'a': Duplicate node id"]
+        );
+    }
+
+    #[test]
+    fn test_validate_missing_id_and_type() {
+        let report = validate(
+            r#"
+nodes:
+  - id: ""
+    type: Log
+  - id: b
+    type: ""
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: Node at position 1 has an empty id",
+                "error: node 'b': Missing node type",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_node_type() {
+        let report = validate(
+            r#"
+nodes:
+  - id: a
+    type: Lgo
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec!["error: node 'a': Unknown node type 'Lgo' (available: Archive, Assert, Base64, DateFormat, Extract, Hash, Log, Now, Parse, Query, Random, Regex, Script, Serialize, Switch, Template, UUID, WaitUntil, Workflow)"]
+        );
+    }
+
+    #[test]
+    fn test_validate_node_input_check() {
+        let report = validate(
+            r#"
+nodes:
+  - id: names
+    type: Query
+    input:
+      from: ${{ items }}
+      query: ".[] | .nme | lenght"
+  - id: dynamic
+    type: Query
+    input:
+      from: ${{ items }}
+      query: ".${{ field }}"
+variables:
+  - name: items
+    value: []
+  - name: field
+    value: name
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec!["error: node 'names': Invalid query: Undefined filter 'lenght' at position 13"]
+        );
+        assert_eq!(
+            report.errors().next().unwrap().path.as_deref(),
+            Some("/nodes/0/input")
+        );
+    }
+
+    #[test]
+    fn test_validate_uses_registry() {
+        let workflow: Workflow = serde_yml::from_str("nodes: [{ id: a, type: Custom }]").unwrap();
+        let registry = NodeRegistry::new().with_node("Custom", |input| {
+            Ok(Box::new(crate::nodes::log::LogNode::new(input)))
+        });
+
+        let report = WorkflowValidator::new()
+            .with_registry(registry)
+            .validate(&workflow);
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_validate_uses_registry_declarations() {
+        let workflow: Workflow = serde_yml::from_str(
+            r#"
+nodes:
+  - id: toss
+    type: Toss
+  - id: heads
+    type: Log
+    on_branch: toss.heads
+  - id: edge
+    type: Log
+    on_branch: toss.edge
+  - id: retry
+    type: Retry
+    input: "${{ attempt }} ${{ result }}"
+"#,
+        )
+        .unwrap();
+        let log = |input| -> WorkflowResult<Box<dyn crate::nodes::base::BaseNode>> {
+            Ok(Box::new(crate::nodes::log::LogNode::new(input)))
+        };
+        let registry = NodeRegistry::new()
+            .with_node("Toss", log)
+            .with_branches("Toss", |_| {
+                Some(vec!["heads".to_string(), "tails".to_string()])
+            })
+            .with_node("Retry", log)
+            .with_bindings("Retry", &["attempt"]);
+
+        let report = WorkflowValidator::new()
+            .with_registry(registry)
+            .validate(&workflow);
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: node 'edge': Node 'toss' has no branch 'edge' (available: heads, tails)",
+                "error: node 'retry': References unknown name 'result'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_unresolved_and_forward_references() {
+        let report = validate(
+            r#"
+nodes:
+  - id: a
+    type: Log
+    input: "${{ b }} ${{ missing }}"
+  - id: b
+    type: Log
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: node 'a': References node 'b' before it runs",
+                "error: node 'a': References unknown name 'missing'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_cycles() {
+        let report = validate(
+            r#"
+nodes:
+  - id: a
+    type: Log
+    input: ${{ b }}
+  - id: b
+    type: Log
+    input: ${{ a }}
+  - id: c
+    type: Log
+    when: c
+"#,
+        );
+
+        let messages = messages(&report);
+        assert!(messages.contains(&"error: node 'a': Reference cycle: a -> b -> a".to_string()));
+        assert!(messages.contains(&"error: node 'c': Reference cycle: c -> c".to_string()));
+    }
+
+    #[test]
+    fn test_validate_bad_expressions() {
+        let report = validate(
+            r#"
+nodes:
+  - id: a
+    type: Log
+    input: ${{ a == }}
+    when: "debug =="
+"#,
+        );
+
+        assert_eq!(report.errors().count(), 2);
+        assert!(
+            messages(&report)[0].contains("Invalid expression '${{ a == }}' in input of node 'a'")
+        );
+        assert!(messages(&report)[1].contains("Invalid `when` expression 'debug =='"));
+    }
+
+    #[test]
+    fn test_validate_output_references() {
+        let report = validate(
+            r#"
+nodes:
+  - id: a
+    type: Log
+output:
+  ok: ${{ a }}
+  bad: ${{ nope }}
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec!["error: Output 'bad' references unknown name 'nope'"]
+        );
+    }
+
+    #[test]
+    fn test_validate_duplicate_globals_and_shadowing() {
+        let report = validate(
+            r#"
+variables:
+  - name: x
+    value: 1
+inputs:
+  - name: x
+    type: number
+nodes:
+  - id: x
+    type: Log
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: Duplicate input name 'x'",
+                "warning: node 'x': Node output overwrites an input or variable of the same name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validation_report_into_result() {
+        assert!(validate("nodes: [{ id: a, type: Log }]")
+            .into_result()
+            .is_ok());
+
+        let error = validate("nodes: [{ id: a, type: Nope }]")
+            .into_result()
+            .unwrap_err();
+        assert!(matches!(error, WorkflowError::Validation(_)));
+        assert!(error
+            .to_string()
+            .starts_with("Workflow is invalid: 1 error(s)"));
+    }
+
+    #[test]
+    fn test_validate_diagnostic_paths() {
+        let report = validate(
+            r#"
+nodes:
+  - id: a
+    type: Foo
+  - id: b
+    type: Log
+    input:
+      message: ${{ missing }}
+    when: ${{ a == }}
+output:
+  result: ${{ nothing }}
+"#,
+        );
+
+        let paths: Vec<Option<&str>> = report
+            .diagnostics()
+            .iter()
+            .map(|d| d.path.as_deref())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                Some("/nodes/0/type"),
+                Some("/nodes/1/when"),
+                Some("/nodes/1/input/message"),
+                Some("/output/result"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_locate_and_render() {
+        let content = "nodes:\n  - id: a\n    type: Foo\n";
+        let source = SourceFile::new("wf.yml", crate::core::engine::FileFormat::Yaml, content);
+        let workflow = source.parse().unwrap();
+
+        let mut report = WorkflowValidator::new().validate(&workflow);
+        report.locate(&source);
+
+        let diagnostic = report.errors().next().unwrap();
+        assert_eq!(diagnostic.span, Some(Span::new(3, 11, 3)));
+        assert_eq!(
+            diagnostic.render(&source),
+            "error: node 'a': Unknown node type 'Foo' (available: Archive, Assert, Base64, DateFormat, Extract, Hash, Log, Now, Parse, Query, Random, Regex, Script, Serialize, Switch, Template, UUID, WaitUntil, Workflow)\n  --> wf.yml:3:11\n  |\n3 |     type: Foo\n  |           ^^^"
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_from_parse_error() {
+        let source = SourceFile::new(
+            "wf.yml",
+            crate::core::engine::FileFormat::Yaml,
+            "nodes:\n  - type: Log\n",
+        );
+        let error = source.parse().unwrap_err();
+
+        let diagnostic = Diagnostic::from(&error);
+        assert!(diagnostic.span.is_some());
+        assert!(diagnostic.render(&source).contains("--> wf.yml:2:5"));
+    }
+
+    #[test]
+    fn test_diagnostic_render_without_span() {
+        let source = SourceFile::new("wf.yml", crate::core::engine::FileFormat::Yaml, "");
+        let diagnostic = Diagnostic::warning("Workflow has no nodes");
+        assert_eq!(
+            diagnostic.render(&source),
+            "warning: Workflow has no nodes\n  --> wf.yml"
+        );
+    }
+
+    fn validate_fields(yaml: &str, lenient: bool) -> ValidationReport {
+        let document: Value = serde_yml::from_str(yaml).unwrap();
+        WorkflowValidator::new()
+            .with_lenient(lenient)
+            .validate_document(&document)
+    }
+
+    #[test]
+    fn test_validate_document_unknown_fields() {
+        let report = validate_fields(
+            r#"
+colossus: 1
+name: Typos
+nmae: Typo
+variables:
+  - name: a
+    valeu: 1
+inputs:
+  - name: b
+    type: string
+    defualt: x
+nodes:
+  - id: first
+    type: Log
+    wen: ${{ b }}
+    depends-on: [a]
+options:
+  concurency: 2
+"#,
+            false,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: Unknown field 'nmae', did you mean 'name'?",
+                "error: Unknown field 'valeu', did you mean 'value'?",
+                "error: Unknown field 'defualt', did you mean 'default'?",
+                "error: node 'first': Unknown field 'wen', did you mean 'when'?",
+                "error: node 'first': Unknown field 'depends-on' (expected one of: id, type, input, when, on_branch, uses, with, for_each, matrix)",
+                "error: Unknown field 'concurency', did you mean 'concurrency'?",
+            ]
+        );
+
+        let paths: Vec<&str> = report
+            .diagnostics()
+            .iter()
+            .filter_map(|d| d.path.as_deref())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/nmae",
+                "/variables/0/valeu",
+                "/inputs/0/defualt",
+                "/nodes/0/wen",
+                "/nodes/0/depends-on",
+                "/options/concurency",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_document_lenient() {
+        let report = validate_fields(
+            "colossus: 1\nnodes:\n  - id: a\n    type: Log\n    wen: x\n",
+            true,
+        );
+
+        assert!(report.is_valid());
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[test]
+    fn test_validate_document_known_fields() {
+        let report = validate_fields(
+            r#"
+colossus: 1
+id: wf
+name: Known
+version: "1.0"
+variables:
+  - name: a
+    value: 1
+inputs:
+  - name: b
+    type: string
+    default: x
+nodes:
+  - id: first
+    type: Log
+    input:
+      anything: goes
+    when: ${{ b }}
+output:
+  free: form
+options:
+  concurrency: 2
+  wasm:
+    dirs:
+      - host: assets
+        guest: /assets
+        writable: false
+    env: [API_URL]
+"#,
+            false,
+        );
+
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_validate_document_wasm_options() {
+        let report = validate_fields(
+            "colossus: 1\noptions:\n  wasm:\n    dir: []\n    dirs:\n      - host: a\n        guest: /a\n        writeable: true\n",
+            false,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: Unknown field 'dir', did you mean 'dirs'?",
+                "error: Unknown field 'writeable', did you mean 'writable'?",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_source_wasm_dirs_outside_workflow_dir() {
+        let content = "colossus: 1\noptions:\n  wasm:\n    dirs:\n      - host: assets\n        guest: /assets\n      - host: ../secrets\n        guest: /secrets\n";
+        let source = SourceFile::new("wf.yml", crate::core::engine::FileFormat::Yaml, content);
+
+        let report = WorkflowValidator::new().validate_source(&source).unwrap();
+
+        assert_eq!(report.errors().count(), 1);
+        let error = report.errors().next().unwrap();
+        assert!(error.message.contains("../secrets"), "{}", error.message);
+        assert_eq!(error.path.as_deref(), Some("/options/wasm/dirs/1/host"));
+    }
+
+    #[test]
+    fn test_validate_document_missing_format_version() {
+        let report = validate_fields("nodes: []\n", false);
+
+        assert!(report.is_valid());
+        assert_eq!(
+            messages(&report),
+            vec!["warning: Workflow does not declare its file format version, add `colossus: 1` or run `colossus migrate`"]
+        );
+    }
+
+    #[test]
+    fn test_validate_source_locates_unknown_field_keys() {
+        let content = "colossus: 1\nnodes:\n  - id: a\n    type: Log\n    wen: true\n";
+        let source = SourceFile::new("wf.yml", crate::core::engine::FileFormat::Yaml, content);
+
+        let report = WorkflowValidator::new().validate_source(&source).unwrap();
+
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(report.diagnostics()[0].span, Some(Span::new(5, 5, 3)));
+    }
+
+    #[test]
+    fn test_validate_source_sub_workflows() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let call = |target: &str| {
+            format!(
+                "colossus: 1\nnodes:\n  - id: call\n    type: Workflow\n    input:\n      path: {}\n",
+                target
+            )
+        };
+
+        write("leaf.yml", "colossus: 1\nnodes: []\n");
+        let ok = write("ok.yml", &call("leaf.yml"));
+        let missing = write("missing.yml", &call("nowhere.yml"));
+        let dynamic = write("dynamic.yml", &call("${{ target }}"));
+        let a = write("a.yml", &call("b.yml"));
+        write("b.yml", &call("a.yml"));
+
+        let validate = |path: &std::path::Path| {
+            let source = SourceFile::read(path).unwrap();
+            WorkflowValidator::new().validate_source(&source).unwrap()
+        };
+
+        assert!(validate(&ok).is_empty());
+
+        let report = validate(&missing);
+        let error = report.errors().next().unwrap();
+        assert!(error.message.starts_with("Workflow file not found"));
+        assert_eq!(error.span, Some(Span::new(6, 13, 11)));
+
+        assert!(validate(&dynamic)
+            .errors()
+            .all(|d| !d.message.contains("not found")));
+
+        let report = validate(&a);
+        let error = report.errors().next().unwrap();
+        assert!(error.message.starts_with("Workflow call cycle:"));
+        assert!(error.message.ends_with("a.yml"));
+    }
+
+    #[test]
+    fn test_validate_unknown_template() {
+        let report = validate(
+            r#"
+templates:
+  notify:
+    type: Log
+nodes:
+  - id: a
+    uses: notfy
+  - id: b
+    uses: deploy
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: node 'a': Unknown template 'notfy', did you mean 'notify'?",
+                "error: node 'b': Unknown template 'deploy' (available: notify)",
+            ]
+        );
+        assert_eq!(
+            report.diagnostics()[0].path.as_deref(),
+            Some("/nodes/0/uses")
+        );
+
+        let report = validate("nodes:\n  - id: a\n    uses: notify\n");
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: node 'a': Unknown template 'notify', no templates are defined or imported"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_source_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+
+        write(
+            "common.yml",
+            "colossus: 1\ntemplates:\n  notify:\n    type: Log\n    input: ${{ target }}\n    wen: 'true'\n",
+        );
+        let ok = write(
+            "ok.yml",
+            "colossus: 1\nimports:\n  - path: common.yml\nvariables:\n  - name: target\n    value: prod\nnodes:\n  - id: a\n    uses: notify\n",
+        );
+        let broken = write(
+            "broken.yml",
+            "colossus: 1\nimports:\n  - path: nowhere.yml\n    as: lib\nnodes:\n  - id: a\n    uses: notify\n",
+        );
+
+        let validate = |path: &std::path::Path| {
+            let source = SourceFile::read(path).unwrap();
+            WorkflowValidator::new().validate_source(&source).unwrap()
+        };
+
+        assert!(validate(&ok).is_empty(), "{:?}", messages(&validate(&ok)));
+        let report = validate(&dir.path().join("common.yml"));
+        let error = report.errors().next().unwrap();
+        assert_eq!(error.message, "Unknown field 'wen', did you mean 'when'?");
+        assert_eq!(error.path.as_deref(), Some("/templates/notify/wen"));
+
+        let report = validate(&broken);
+        let errors: Vec<&Diagnostic> = report.errors().collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].message,
+            "Unknown field 'as' (expected one of: path)"
+        );
+        assert!(errors[1].message.starts_with("Failed to import"));
+        assert_eq!(errors[1].span, Some(Span::new(2, 1, 7)));
+    }
+
+    #[test]
+    fn test_validate_for_each() {
+        let report = validate(
+            r#"
+variables:
+  - name: files
+    value: [a.txt, b.txt]
+nodes:
+  - id: show
+    type: Log
+    input: ${{ index }} ${{ item }}
+    for_each: ${{ files }}
+  - id: literal
+    type: Log
+    input: ${{ item }}
+    for_each: [1, 2]
+  - id: outside
+    type: Log
+    input: ${{ item }}
+  - id: missing
+    type: Log
+    for_each: ${{ folders }}
+  - id: plain
+    type: Log
+    for_each: files
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: node 'outside': References unknown name 'item'",
+                "error: node 'missing': References unknown name 'folders'",
+                "error: node 'plain': `for_each` must be a sequence or a ${{ ... }} placeholder",
+            ]
+        );
+        assert_eq!(
+            report.diagnostics()[1].path.as_deref(),
+            Some("/nodes/3/for_each")
+        );
+    }
+
+    #[test]
+    fn test_validate_matrix() {
+        let report = validate(
+            r#"
+nodes:
+  - id: build
+    type: Log
+    input: ${{ matrix.os }}
+    matrix:
+      os: [linux, macos]
+      exclude:
+        - arch: arm
+  - id: never
+    type: Log
+    matrix:
+      os: []
+  - id: both
+    type: Log
+    for_each: [1]
+    matrix:
+      os: [linux]
+  - id: outside
+    type: Log
+    input: ${{ matrix.os }}
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "warning: node 'build': Excluded combination refers to unknown axis 'arch'",
+                "warning: node 'never': Matrix has no combinations, the node never runs",
+                "error: node 'both': `for_each` and `matrix` cannot be combined",
+                "error: node 'outside': References unknown name 'matrix'",
+            ]
+        );
+        assert_eq!(
+            report.diagnostics()[0].path.as_deref(),
+            Some("/nodes/0/matrix/exclude/0/arch")
+        );
+    }
+
+    #[test]
+    fn test_validate_on_branch() {
+        let report = validate(
+            r#"
+nodes:
+  - id: early
+    type: Log
+    on_branch: route.prod
+  - id: route
+    type: Switch
+    input:
+      value: prod
+      cases:
+        production: prod
+      default: other
+  - id: deploy
+    type: Log
+    on_branch: route.production
+  - id: fallback
+    type: Log
+    on_branch: route.other
+  - id: typo
+    type: Log
+    on_branch: route.prodution
+  - id: plain
+    type: Log
+    on_branch: deploy.done
+  - id: missing
+    type: Log
+    on_branch: nowhere.done
+  - id: malformed
+    type: Log
+    on_branch: route
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: node 'early': Node 'route' has no branch 'prod' (available: production, other)",
+                "error: node 'early': References node 'route' before it runs",
+                "error: node 'typo': Node 'route' has no branch 'prodution', did you mean 'production'?",
+                "error: node 'plain': `on_branch` refers to node 'deploy', which has no branches",
+                "error: node 'missing': `on_branch` refers to unknown node 'nowhere'",
+                "error: node 'malformed': `on_branch` 'route' must be of the form <switch id>.<branch>",
+            ]
+        );
+        assert_eq!(
+            report.diagnostics()[0].path.as_deref(),
+            Some("/nodes/0/on_branch")
+        );
+    }
+
+    #[test]
+    fn test_validate_handlers() {
+        let report = validate(
+            r#"
+nodes:
+  - id: work
+    type: Log
+    input: ${{ run.status }} ${{ alert }}
+on_failure:
+  - id: alert
+    type: Log
+    input: ${{ run.failed_node }} ${{ work }}
+  - id: work
+    type: Lgo
+finally:
+  - id: cleanup
+    type: Log
+    input: ${{ alert }} ${{ missing }}
+"#,
+        );
+
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error: node 'work': Duplicate node id",
+                "error: node 'work': Unknown node type 'Lgo' (available: Archive, Assert, Base64, DateFormat, Extract, Hash, Log, Now, Parse, Query, Random, Regex, Script, Serialize, Switch, Template, UUID, WaitUntil, Workflow)",
+                "error: node 'work': References unknown name 'run'",
+                "error: node 'work': References node 'alert' before it runs",
+                "error: node 'cleanup': References unknown name 'missing'",
+                "error: node 'work': Reference cycle: work -> alert -> work",
+            ]
+        );
+        assert_eq!(
+            report.diagnostics()[0].path.as_deref(),
+            Some("/on_failure/1/id")
+        );
+        assert_eq!(
+            report.diagnostics()[4].path.as_deref(),
+            Some("/finally/0/input")
+        );
+    }
+}
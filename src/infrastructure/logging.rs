@@ -0,0 +1,112 @@
+//! Per-run log capture
+//!
+//! This module provides a `tracing` writer that copies log output into the
+//! log file of the current run, in addition to the terminal. A single run log
+//! can be active at a time; when none is active, the writer discards output.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use tracing_subscriber::fmt::MakeWriter;
+
+static RUN_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+fn sink() -> MutexGuard<'static, Option<File>> {
+    RUN_LOG
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Starts capturing log output into the given file
+///
+/// Output is appended, so a resumed run keeps the logs of previous attempts.
+/// Any previously active run log is closed.
+///
+/// # Errors
+///
+/// Returns an error if the file or its parent directory cannot be created.
+pub fn start(path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *sink() = Some(file);
+
+    Ok(())
+}
+
+/// Stops capturing log output and closes the run log
+pub fn stop() {
+    if let Some(mut file) = sink().take() {
+        let _ = file.flush();
+    }
+}
+
+/// Writer forwarding log output to the active run log
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::logging::RunLogWriter;
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let subscriber = tracing_subscriber::registry().with(
+///     tracing_subscriber::fmt::layer()
+///         .with_ansi(false)
+///         .with_writer(RunLogWriter),
+/// );
+/// # drop(subscriber);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunLogWriter;
+
+impl Write for RunLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match sink().as_mut() {
+            Some(file) => file.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match sink().as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for RunLogWriter {
+    type Writer = RunLogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_log_writer_captures_while_started() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run-1/run.log");
+
+        RunLogWriter.write_all(b"discarded\n").unwrap();
+
+        start(&path).unwrap();
+        RunLogWriter.write_all(b"first\n").unwrap();
+        stop();
+        RunLogWriter.write_all(b"discarded\n").unwrap();
+
+        start(&path).unwrap();
+        RunLogWriter.write_all(b"second\n").unwrap();
+        stop();
+
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "first\nsecond\n");
+    }
+}
@@ -8,7 +8,10 @@
 //!
 //! The infrastructure module provides cross-cutting concerns:
 //!
-//! - **Logging**: Application logging and tracing
+//! - **Paths**: Local directories used for run state
+//! - **Plugins**: Discovery of plugin executables providing node types
+//! - **Run Store**: History of past workflow runs
+//! - **Logging**: Per-run log capture
 //! - **Configuration**: Application configuration management
 //! - **Monitoring**: Health checks and metrics
 //! - **Security**: Authentication and authorization
@@ -16,15 +19,22 @@
 //! # Examples
 //!
 //! ```rust
-//! // Future infrastructure usage examples will be added here
-//! // as the infrastructure components are implemented
+//! use colossus::infrastructure::paths::checkpoint_dir;
+//!
+//! let dir = checkpoint_dir();
+//! println!("Checkpoints are stored in {:?}", dir);
 //! ```
 //!
 //! # TODO
 //!
-//! - [ ] Add logging infrastructure
+//! - [x] Add local state directories
+//! - [x] Add run history
+//! - [x] Add logging infrastructure
 //! - [ ] Add configuration management
 //! - [ ] Add monitoring and metrics
 //! - [ ] Add security components
 
-// TODO: Add infrastructure implementations (logging, config, etc.)
+pub mod logging;
+pub mod paths;
+pub mod plugins;
+pub mod run_store;
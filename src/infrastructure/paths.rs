@@ -0,0 +1,137 @@
+//! Filesystem locations used by the application
+//!
+//! This module resolves the directories where Colossus keeps its local
+//! state and caches, following the XDG base directory conventions.
+
+use std::path::PathBuf;
+
+/// Returns the directory where Colossus keeps persistent data
+///
+/// Resolution order:
+/// 1. `$XDG_DATA_HOME/colossus`
+/// 2. `$HOME/.local/share/colossus`
+/// 3. `.colossus/data` relative to the current directory
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::paths::data_dir;
+///
+/// println!("Data is stored in {:?}", data_dir());
+/// ```
+pub fn data_dir() -> PathBuf {
+    resolve("XDG_DATA_HOME", ".local/share", "data")
+}
+
+/// Returns the directory where the run history is stored
+pub fn runs_dir() -> PathBuf {
+    data_dir().join("runs")
+}
+
+/// Returns the directory where Colossus keeps run state
+///
+/// Resolution order:
+/// 1. `$XDG_STATE_HOME/colossus`
+/// 2. `$HOME/.local/state/colossus`
+/// 3. `.colossus/state` relative to the current directory
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::paths::state_dir;
+///
+/// println!("Run state is stored in {:?}", state_dir());
+/// ```
+pub fn state_dir() -> PathBuf {
+    resolve("XDG_STATE_HOME", ".local/state", "state")
+}
+
+/// Returns the directory where run checkpoints are stored
+pub fn checkpoint_dir() -> PathBuf {
+    state_dir().join("checkpoints")
+}
+
+/// Returns the directory where Colossus keeps data it can recompute
+///
+/// Resolution order:
+/// 1. `$XDG_CACHE_HOME/colossus`
+/// 2. `$HOME/.cache/colossus`
+/// 3. `.colossus/cache` relative to the current directory
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::paths::cache_dir;
+///
+/// println!("Cached data is stored in {:?}", cache_dir());
+/// ```
+pub fn cache_dir() -> PathBuf {
+    resolve("XDG_CACHE_HOME", ".cache", "cache")
+}
+
+/// Returns the file where the descriptions of plugins are cached
+pub fn plugin_cache_file() -> PathBuf {
+    cache_dir().join("plugins.json")
+}
+
+/// Resolves an XDG base directory for Colossus
+///
+/// # Arguments
+///
+/// * `variable` - XDG environment variable to honor
+/// * `home_fallback` - Path relative to `$HOME` used when the variable is unset
+/// * `local_fallback` - Directory under `.colossus` used when `$HOME` is unset
+fn resolve(variable: &str, home_fallback: &str, local_fallback: &str) -> PathBuf {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+
+    if let Some(base) = non_empty(variable) {
+        PathBuf::from(base).join("colossus")
+    } else if let Some(home) = non_empty("HOME") {
+        PathBuf::from(home).join(home_fallback).join("colossus")
+    } else {
+        PathBuf::from(".colossus").join(local_fallback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_dir_is_inside_data_dir() {
+        assert!(runs_dir().starts_with(data_dir()));
+        assert!(runs_dir().ends_with("runs"));
+    }
+
+    #[test]
+    fn test_checkpoint_dir_is_inside_state_dir() {
+        assert!(checkpoint_dir().starts_with(state_dir()));
+        assert!(checkpoint_dir().ends_with("checkpoints"));
+    }
+
+    #[test]
+    fn test_plugin_cache_file_is_inside_cache_dir() {
+        assert!(plugin_cache_file().starts_with(cache_dir()));
+        assert!(plugin_cache_file().ends_with("plugins.json"));
+    }
+
+    #[test]
+    fn test_resolve_uses_variable_when_set() {
+        std::env::set_var("COLOSSUS_TEST_XDG_DIR", "/tmp/xdg");
+        assert_eq!(
+            resolve("COLOSSUS_TEST_XDG_DIR", ".local/test", "test"),
+            PathBuf::from("/tmp/xdg/colossus")
+        );
+        std::env::remove_var("COLOSSUS_TEST_XDG_DIR");
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_home() {
+        let resolved = resolve("COLOSSUS_TEST_UNSET_DIR", ".local/test", "test");
+        if let Some(home) = std::env::var_os("HOME").filter(|home| !home.is_empty()) {
+            assert_eq!(resolved, PathBuf::from(home).join(".local/test/colossus"));
+        } else {
+            assert_eq!(resolved, PathBuf::from(".colossus/test"));
+        }
+    }
+}
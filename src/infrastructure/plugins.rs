@@ -0,0 +1,546 @@
+//! Discovery of plugin executables
+//!
+//! This module finds the executables implementing node types through the
+//! [external node protocol](crate::nodes::external), and registers them
+//! next to the built-in node types.
+//!
+//! Plugins are looked up, in order:
+//! 1. in the `plugins/` directory next to the workflow file, where every
+//!    executable file is a plugin, and every `.wasm` file a
+//!    [WebAssembly module](crate::nodes::wasm)
+//! 2. on `PATH`, where executables named `colossus-node-<type>` are plugins
+//!
+//! A node type is provided by the first plugin declaring it, and built-in
+//! node types cannot be replaced.
+//!
+//! # Cache
+//!
+//! The `describe` handshake runs a plugin executable, so its answers are
+//! cached in [`plugin_cache_file`], keyed on the path, modification time and
+//! size of each plugin. Commands that must not have side effects, such as
+//! `validate` and `schema`, use [`cached_registry`]: it never runs plugin
+//! executables, and skips those whose description is not cached yet.
+//! WebAssembly modules are sandboxed, so they are described either way.
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tracing::{debug, warn};
+
+use crate::core::engine::WorkflowResult;
+use crate::infrastructure::paths::plugin_cache_file;
+use crate::nodes::external::{Plugin, DEFAULT_TIMEOUT};
+use crate::nodes::registry::NodeRegistry;
+use crate::nodes::wasm::WasmPlugin;
+
+/// Directory holding the plugins of a workflow, next to the workflow file
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// Prefix of the names of plugin executables looked up on `PATH`
+pub const PLUGIN_PREFIX: &str = "colossus-node-";
+
+/// Extension of the WebAssembly modules of the plugins directory
+pub const WASM_EXTENSION: &str = "wasm";
+
+/// A plugin found by discovery
+#[derive(Debug, Clone)]
+pub enum Discovered {
+    /// A plugin executable
+    Executable(Plugin),
+
+    /// A WebAssembly module
+    Wasm(WasmPlugin),
+}
+
+impl Discovered {
+    /// Returns the type name used in workflow files
+    pub fn node_type(&self) -> &str {
+        match self {
+            Discovered::Executable(plugin) => plugin.node_type(),
+            Discovered::Wasm(plugin) => plugin.node_type(),
+        }
+    }
+
+    /// Returns the path of the plugin executable or module
+    pub fn path(&self) -> &Path {
+        match self {
+            Discovered::Executable(plugin) => plugin.program(),
+            Discovered::Wasm(plugin) => plugin.path(),
+        }
+    }
+
+    /// Returns the JSON Schema of the node input, if the plugin provides one
+    pub fn schema(&self) -> Option<&JsonValue> {
+        match self {
+            Discovered::Executable(plugin) => plugin.schema(),
+            Discovered::Wasm(plugin) => plugin.schema(),
+        }
+    }
+
+    /// Returns the time an execution may take
+    pub fn timeout(&self) -> Duration {
+        match self {
+            Discovered::Executable(plugin) => plugin.timeout(),
+            Discovered::Wasm(plugin) => plugin.timeout(),
+        }
+    }
+
+    /// Loads the plugin at a path, depending on its extension
+    fn load(path: &Path) -> WorkflowResult<Self> {
+        if is_wasm(path) {
+            WasmPlugin::load(path).map(Discovered::Wasm)
+        } else {
+            Plugin::describe(path).map(Discovered::Executable)
+        }
+    }
+}
+
+/// Cached `describe` answers of plugins, keyed on their path
+///
+/// A description is only used while the modification time and size of the
+/// plugin file are unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::plugins::PluginCache;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let cache = PluginCache::load(dir.path().join("plugins.json"));
+/// assert!(cache.is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct PluginCache {
+    file: PathBuf,
+    plugins: BTreeMap<PathBuf, Description>,
+    changed: bool,
+}
+
+/// The cached `describe` answer of a plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Description {
+    modified: SystemTime,
+    size: u64,
+    #[serde(rename = "type")]
+    node_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input_schema: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout: Option<f64>,
+}
+
+impl PluginCache {
+    /// Loads the cache stored in a file
+    ///
+    /// A missing or unreadable file yields an empty cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Path of the cache file
+    pub fn load(file: impl Into<PathBuf>) -> Self {
+        let file = file.into();
+        let plugins = match std::fs::read_to_string(&file) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring plugin cache {}: {}", file.display(), e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+
+        Self {
+            file,
+            plugins,
+            changed: false,
+        }
+    }
+
+    /// Returns `true` if no plugin is described
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Writes the cache to its file, if a description was added
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> std::io::Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        if let Some(dir) = self.file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let temp = self.file.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(&self.plugins)?)?;
+        std::fs::rename(&temp, &self.file)
+    }
+
+    /// Returns the plugin at a path, if its description is cached and the
+    /// file did not change since
+    fn get(&self, path: &Path) -> Option<Discovered> {
+        let description = self.plugins.get(path)?;
+        if fingerprint(path)? != (description.modified, description.size) {
+            return None;
+        }
+
+        let node_type = description.node_type.clone();
+        let timeout = match description.timeout {
+            Some(timeout) => Duration::try_from_secs_f64(timeout).ok()?,
+            None => DEFAULT_TIMEOUT,
+        };
+        let schema = description.input_schema.clone();
+        Some(if is_wasm(path) {
+            let mut plugin = WasmPlugin::new(node_type, path).with_timeout(timeout);
+            if let Some(schema) = schema {
+                plugin = plugin.with_schema(schema);
+            }
+            Discovered::Wasm(plugin)
+        } else {
+            let mut plugin = Plugin::new(node_type, path).with_timeout(timeout);
+            if let Some(schema) = schema {
+                plugin = plugin.with_schema(schema);
+            }
+            Discovered::Executable(plugin)
+        })
+    }
+
+    /// Caches the description of a plugin
+    fn insert(&mut self, plugin: &Discovered) {
+        let Some((modified, size)) = fingerprint(plugin.path()) else {
+            return;
+        };
+
+        self.plugins.insert(
+            plugin.path().to_path_buf(),
+            Description {
+                modified,
+                size,
+                node_type: plugin.node_type().to_string(),
+                input_schema: plugin.schema().cloned(),
+                timeout: Some(plugin.timeout())
+                    .filter(|timeout| *timeout != DEFAULT_TIMEOUT)
+                    .map(|timeout| timeout.as_secs_f64()),
+            },
+        );
+        self.changed = true;
+    }
+}
+
+/// Discovers the plugins available to workflows in a directory
+///
+/// Plugins missing from the cache are described, which runs plugin
+/// executables, and plugins failing the `describe` handshake are skipped
+/// with a warning.
+///
+/// # Arguments
+///
+/// * `base_dir` - Directory of the workflow file
+pub fn discover(base_dir: &Path) -> Vec<Discovered> {
+    discover_with(base_dir, true)
+}
+
+/// Discovers the plugins available to workflows in a directory, without
+/// running any plugin executable
+///
+/// Plugin executables whose description is not cached are skipped with a
+/// warning.
+///
+/// # Arguments
+///
+/// * `base_dir` - Directory of the workflow file
+pub fn discover_cached(base_dir: &Path) -> Vec<Discovered> {
+    discover_with(base_dir, false)
+}
+
+/// Creates a registry of the built-in node types and the discovered plugins
+///
+/// # Arguments
+///
+/// * `base_dir` - Directory of the workflow file
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::plugins::registry;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// assert!(registry(dir.path()).contains("Log"));
+/// ```
+pub fn registry(base_dir: &Path) -> NodeRegistry {
+    with_plugins(NodeRegistry::new(), discover(base_dir))
+}
+
+/// Creates a registry of the built-in node types and the plugins whose
+/// description is cached, without running any plugin executable
+///
+/// # Arguments
+///
+/// * `base_dir` - Directory of the workflow file
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::plugins::cached_registry;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// assert!(cached_registry(dir.path()).contains("Log"));
+/// ```
+pub fn cached_registry(base_dir: &Path) -> NodeRegistry {
+    with_plugins(NodeRegistry::new(), discover_cached(base_dir))
+}
+
+/// Discovers the plugins of a workflow directory with the plugin cache
+fn discover_with(base_dir: &Path, describe: bool) -> Vec<Discovered> {
+    let mut cache = PluginCache::load(plugin_cache_file());
+    let plugins = discover_in(
+        &base_dir.join(PLUGINS_DIR),
+        std::env::var_os("PATH").as_deref(),
+        &mut cache,
+        describe,
+    );
+
+    if let Err(e) = cache.save() {
+        warn!("Cannot save plugin cache: {}", e);
+    }
+    plugins
+}
+
+/// Registers plugins whose node type is not taken yet
+fn with_plugins(registry: NodeRegistry, plugins: Vec<Discovered>) -> NodeRegistry {
+    plugins.into_iter().fold(registry, |registry, plugin| {
+        if registry.contains(plugin.node_type()) {
+            warn!(
+                "Ignoring plugin {}: node type '{}' is already defined",
+                plugin.path().display(),
+                plugin.node_type()
+            );
+            return registry;
+        }
+
+        debug!(
+            "Registered plugin {} for node type '{}'",
+            plugin.path().display(),
+            plugin.node_type()
+        );
+        match plugin {
+            Discovered::Executable(plugin) => registry.with_plugin(plugin),
+            Discovered::Wasm(plugin) => registry.with_wasm_plugin(plugin),
+        }
+    })
+}
+
+/// Discovers the plugins of a plugins directory and a search path
+///
+/// Plugin executables missing from the cache are only described when
+/// `describe` is set.
+fn discover_in(
+    plugins_dir: &Path,
+    search_path: Option<&OsStr>,
+    cache: &mut PluginCache,
+    describe: bool,
+) -> Vec<Discovered> {
+    let mut candidates = plugin_files(plugins_dir, |_| true);
+
+    let mut names = Vec::new();
+    for dir in search_path.map(std::env::split_paths).into_iter().flatten() {
+        let programs = plugin_files(&dir, |name| name.starts_with(PLUGIN_PREFIX));
+        for program in programs.into_iter().filter(|program| !is_wasm(program)) {
+            let name = program.file_name().map(OsStr::to_os_string);
+            if !names.contains(&name) {
+                names.push(name);
+                candidates.push(program);
+            }
+        }
+    }
+
+    candidates
+        .into_iter()
+        .filter_map(|path| {
+            if let Some(plugin) = cache.get(&path) {
+                return Some(plugin);
+            }
+            if !describe && !is_wasm(&path) {
+                warn!(
+                    "Skipping plugin {}: not described yet, run `colossus plugins` to describe it",
+                    path.display()
+                );
+                return None;
+            }
+
+            match Discovered::load(&path) {
+                Ok(plugin) => {
+                    cache.insert(&plugin);
+                    Some(plugin)
+                }
+                Err(e) => {
+                    warn!("Skipping plugin: {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the modification time and size of a file
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = path.metadata().ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Lists the executables and modules of a directory whose name is accepted
+///
+/// Files are sorted by name, and a missing directory has no files.
+fn plugin_files(dir: &Path, accept: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut programs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(&accept)
+        })
+        .filter(|path| is_wasm(path) || is_executable(path))
+        .map(|path| path.canonicalize().unwrap_or(path))
+        .collect();
+    programs.sort();
+    programs
+}
+
+fn is_wasm(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new(WASM_EXTENSION)) && path.is_file()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::nodes::external::tests::echo_plugin;
+    use crate::nodes::wasm::tests::echo_module;
+
+    #[test]
+    fn test_discover_plugins_dir_and_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugins = dir.path().join(PLUGINS_DIR);
+        let bin = dir.path().join("bin");
+        let other_bin = dir.path().join("other");
+        for path in [&plugins, &bin, &other_bin] {
+            std::fs::create_dir(path).unwrap();
+        }
+
+        echo_plugin(&plugins.join("echo"), "Echo");
+        echo_module(&plugins.join("b.wasm"));
+        std::fs::write(plugins.join("README"), "not a plugin").unwrap();
+        echo_plugin(&bin.join("colossus-node-http"), "Http");
+        echo_plugin(&bin.join("unrelated"), "Unrelated");
+        echo_plugin(&other_bin.join("colossus-node-http"), "Shadowed");
+
+        let search_path = std::env::join_paths([&bin, &other_bin]).unwrap();
+        let mut cache = PluginCache::load(dir.path().join("plugins.json"));
+        let discovered = discover_in(&plugins, Some(&search_path), &mut cache, true);
+        let types: Vec<&str> = discovered.iter().map(Discovered::node_type).collect();
+        assert_eq!(types, vec!["Echo", "Echo", "Http"]);
+        assert!(matches!(discovered[0], Discovered::Wasm(_)));
+
+        let registry = with_plugins(NodeRegistry::new(), discovered);
+        assert!(registry.contains("Echo") && registry.contains("Http"));
+    }
+
+    #[test]
+    fn test_discover_skips_broken_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let broken = dir.path().join("broken");
+        std::fs::write(&broken, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&broken, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+        std::fs::write(dir.path().join("broken.wasm"), "not a module").unwrap();
+
+        let mut cache = PluginCache::load(dir.path().join("plugins.json"));
+        assert!(discover_in(dir.path(), None, &mut cache, true).is_empty());
+        assert!(discover_in(&dir.path().join("missing"), None, &mut cache, true).is_empty());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_plugins_cannot_replace_builtin_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("log");
+        echo_plugin(&program, "Log");
+
+        let plugins = vec![Discovered::load(&program).unwrap()];
+        let registry = with_plugins(NodeRegistry::new(), plugins);
+        assert_eq!(registry.schema("Log"), NodeRegistry::new().schema("Log"));
+    }
+
+    #[test]
+    fn test_cached_discovery_never_runs_executables() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugins = dir.path().join(PLUGINS_DIR);
+        std::fs::create_dir(&plugins).unwrap();
+        let runs = dir.path().join("runs");
+        let program = plugins.join("http");
+        std::fs::write(
+            &program,
+            format!(
+                "#!/bin/sh\necho run >> '{}'\necho '{{\"type\":\"Http\",\"timeout\":30}}'\n",
+                runs.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(
+            &program,
+            std::os::unix::fs::PermissionsExt::from_mode(0o755),
+        )
+        .unwrap();
+        echo_module(&plugins.join("echo.wasm"));
+        let cache_file = dir.path().join("cache").join("plugins.json");
+        let count_runs = || std::fs::read_to_string(&runs).map_or(0, |runs| runs.lines().count());
+
+        let mut cache = PluginCache::load(&cache_file);
+        let discovered = discover_in(&plugins, None, &mut cache, false);
+        let types: Vec<&str> = discovered.iter().map(Discovered::node_type).collect();
+        assert_eq!(types, vec!["Echo"]);
+        assert_eq!(count_runs(), 0);
+
+        let discovered = discover_in(&plugins, None, &mut cache, true);
+        assert_eq!(discovered.len(), 2);
+        assert_eq!(count_runs(), 1);
+        cache.save().unwrap();
+
+        let mut cache = PluginCache::load(&cache_file);
+        let discovered = discover_in(&plugins, None, &mut cache, false);
+        let types: Vec<&str> = discovered.iter().map(Discovered::node_type).collect();
+        assert_eq!(types, vec!["Echo", "Http"]);
+        assert_eq!(
+            discovered[0].schema(),
+            Some(&serde_json::json!({ "type": "object" }))
+        );
+        assert_eq!(count_runs(), 1);
+
+        let script = std::fs::read_to_string(&program).unwrap();
+        std::fs::write(&program, script.replace("Http", "Changed")).unwrap();
+        assert_eq!(discover_in(&plugins, None, &mut cache, false).len(), 1);
+        assert_eq!(count_runs(), 1);
+    }
+}
@@ -0,0 +1,517 @@
+//! Local run history
+//!
+//! This module records every workflow run on disk so that it can be listed,
+//! inspected, and pruned later. Each run is stored in its own directory,
+//! named after the run id, containing a `run.json` record and a `run.log`
+//! file with the log output of the run.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+use tracing::warn;
+
+use crate::core::run::{is_valid_run_id, NodeRun, WorkflowRun};
+use crate::shared::types::workflow::output::WorkflowOutput;
+use crate::shared::types::workflow::status::Status;
+
+/// Errors that can occur while accessing the run store
+#[derive(Debug, thiserror::Error)]
+pub enum RunStoreError {
+    /// No run with the given id has been recorded
+    #[error("Run not found: {0}")]
+    NotFound(String),
+
+    /// The run id contains characters that are not allowed
+    #[error("Invalid run id '{0}': only letters, digits, '-' and '_' are allowed")]
+    InvalidRunId(String),
+
+    /// The store could not be read or written
+    #[error("Failed to access run store: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A run record could not be serialized or parsed
+    #[error("Invalid run record: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Result type for run store operations
+pub type RunStoreResult<T> = Result<T, RunStoreError>;
+
+/// Persisted summary of a workflow run
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use colossus::infrastructure::run_store::RunRecord;
+/// use colossus::shared::types::workflow::status::Status;
+///
+/// let record = RunRecord::start("run-1", "workflow.yml", HashMap::new());
+/// assert_eq!(record.status, Status::Running);
+/// assert!(record.finished_at.is_none());
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Identifier of the run
+    pub run_id: String,
+
+    /// Path of the executed workflow file
+    pub workflow_path: PathBuf,
+
+    /// Name of the executed workflow
+    #[serde(default)]
+    pub workflow_name: Option<String>,
+
+    /// Hash of the workflow file content
+    #[serde(default)]
+    pub workflow_hash: Option<String>,
+
+    /// Effective workflow inputs
+    #[serde(default)]
+    pub inputs: HashMap<String, Value>,
+
+    /// Status of the run
+    pub status: Status,
+
+    /// Time at which the run started
+    pub started_at: DateTime<Utc>,
+
+    /// Time at which the run finished
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// Total duration of the run, in milliseconds
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+
+    /// Status of every node
+    #[serde(default)]
+    pub nodes: Vec<NodeRun>,
+
+    /// Status of every `on_failure` node
+    #[serde(default)]
+    pub on_failure: Vec<NodeRun>,
+
+    /// Status of every `finally` node
+    #[serde(default)]
+    pub finally: Vec<NodeRun>,
+
+    /// Evaluated workflow output
+    #[serde(default)]
+    pub outputs: WorkflowOutput,
+
+    /// Error that made the run fail, if any
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl RunRecord {
+    /// Creates the record of a run that is starting
+    ///
+    /// # Arguments
+    ///
+    /// * `run_id` - Identifier of the run
+    /// * `workflow_path` - Path of the workflow file
+    /// * `inputs` - Input values provided for the run
+    pub fn start(
+        run_id: impl Into<String>,
+        workflow_path: impl Into<PathBuf>,
+        inputs: HashMap<String, Value>,
+    ) -> Self {
+        Self {
+            run_id: run_id.into(),
+            workflow_path: workflow_path.into(),
+            workflow_name: None,
+            workflow_hash: None,
+            inputs,
+            status: Status::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            duration_ms: None,
+            nodes: Vec::new(),
+            on_failure: Vec::new(),
+            finally: Vec::new(),
+            outputs: WorkflowOutput::new(),
+            error: None,
+        }
+    }
+
+    /// Updates the record with the outcome of a run
+    ///
+    /// The start time and provided inputs of the record are kept, so that a
+    /// resumed run reports its original inputs and its total duration across
+    /// attempts.
+    pub fn complete(&mut self, run: &WorkflowRun) {
+        let finished_at = run.finished_at.unwrap_or_else(Utc::now);
+
+        self.workflow_name = run.workflow.name.clone();
+        self.workflow_hash = run.workflow_hash.clone();
+        for (name, value) in &run.inputs {
+            self.inputs
+                .entry(name.clone())
+                .or_insert_with(|| value.clone());
+        }
+        self.status = run.status;
+        self.finished_at = Some(finished_at);
+        self.duration_ms = Some(self.elapsed_ms(finished_at));
+        self.nodes = run.nodes.clone();
+        self.on_failure = run.on_failure.clone();
+        self.finally = run.finally.clone();
+        self.outputs = run.outputs.clone();
+        self.error = run.error.as_ref().map(|e| e.to_string());
+    }
+
+    /// Marks the run as failed before any node could execute
+    pub fn fail(&mut self, error: impl ToString) {
+        let finished_at = Utc::now();
+
+        self.status = Status::Failed;
+        self.finished_at = Some(finished_at);
+        self.duration_ms = Some(self.elapsed_ms(finished_at));
+        self.error = Some(error.to_string());
+    }
+
+    fn elapsed_ms(&self, finished_at: DateTime<Utc>) -> u64 {
+        (finished_at - self.started_at).num_milliseconds().max(0) as u64
+    }
+}
+
+/// Directory-backed store of run records
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::infrastructure::run_store::RunStore;
+///
+/// let store = RunStore::new("/tmp/colossus/runs");
+/// assert!(store.record_path("run-1").unwrap().ends_with("run-1/run.json"));
+/// assert!(store.log_path("run-1").unwrap().ends_with("run-1/run.log"));
+/// assert!(store.run_dir("../run-1").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunStore {
+    dir: PathBuf,
+}
+
+impl RunStore {
+    /// Creates a store rooted at the given directory
+    ///
+    /// The directory is created when the first record is saved.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory of the store
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the directory holding the files of a run
+    ///
+    /// # Errors
+    ///
+    /// Returns `RunStoreError::InvalidRunId` if the run id could name a
+    /// directory outside the store, see [`is_valid_run_id`].
+    pub fn run_dir(&self, run_id: &str) -> RunStoreResult<PathBuf> {
+        if !is_valid_run_id(run_id) {
+            return Err(RunStoreError::InvalidRunId(run_id.to_string()));
+        }
+
+        Ok(self.dir.join(run_id))
+    }
+
+    /// Returns the path of the record of a run
+    ///
+    /// # Errors
+    ///
+    /// Returns `RunStoreError::InvalidRunId` if the run id is not valid.
+    pub fn record_path(&self, run_id: &str) -> RunStoreResult<PathBuf> {
+        Ok(self.run_dir(run_id)?.join("run.json"))
+    }
+
+    /// Returns the path of the log file of a run
+    ///
+    /// # Errors
+    ///
+    /// Returns `RunStoreError::InvalidRunId` if the run id is not valid.
+    pub fn log_path(&self, run_id: &str) -> RunStoreResult<PathBuf> {
+        Ok(self.run_dir(run_id)?.join("run.log"))
+    }
+
+    /// Saves a run record, replacing any previous record of the same run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record cannot be written.
+    pub fn save(&self, record: &RunRecord) -> RunStoreResult<()> {
+        std::fs::create_dir_all(self.run_dir(&record.run_id)?)?;
+
+        let path = self.record_path(&record.run_id)?;
+        let temp = path.with_extension("json.tmp");
+
+        std::fs::write(&temp, serde_json::to_string_pretty(record)?)?;
+        std::fs::rename(&temp, &path)?;
+
+        Ok(())
+    }
+
+    /// Loads the record of a run
+    ///
+    /// # Errors
+    ///
+    /// Returns `RunStoreError::NotFound` if the run was never recorded.
+    pub fn load(&self, run_id: &str) -> RunStoreResult<RunRecord> {
+        let path = self.record_path(run_id)?;
+
+        if !path.exists() {
+            return Err(RunStoreError::NotFound(run_id.to_string()));
+        }
+
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Lists all recorded runs, most recent first
+    ///
+    /// Records that cannot be parsed, or that are stored under the
+    /// directory of another run, are skipped with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store directory cannot be read.
+    pub fn list(&self) -> RunStoreResult<Vec<RunRecord>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut records = Vec::new();
+
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if !entry.path().is_dir() {
+                continue;
+            }
+
+            let run_id = entry.file_name().to_string_lossy().to_string();
+            match self.load(&run_id) {
+                Ok(record) if record.run_id == run_id => records.push(record),
+                Ok(record) => warn!(
+                    "Skipping run '{}': its record belongs to run '{}'",
+                    run_id, record.run_id
+                ),
+                Err(e) => warn!("Skipping run '{}': {}", run_id, e),
+            }
+        }
+
+        records.sort_by(|a, b| {
+            b.started_at
+                .cmp(&a.started_at)
+                .then_with(|| b.run_id.cmp(&a.run_id))
+        });
+        Ok(records)
+    }
+
+    /// Reads the log output of a run
+    ///
+    /// # Errors
+    ///
+    /// Returns `RunStoreError::NotFound` if the run was never recorded.
+    pub fn read_logs(&self, run_id: &str) -> RunStoreResult<String> {
+        if !self.record_path(run_id)?.exists() {
+            return Err(RunStoreError::NotFound(run_id.to_string()));
+        }
+
+        match std::fs::read_to_string(self.log_path(run_id)?) {
+            Ok(logs) => Ok(logs),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Removes the runs that finished before the given time
+    ///
+    /// Runs that never finished are compared using their start time.
+    ///
+    /// # Arguments
+    ///
+    /// * `cutoff` - Runs older than this time are removed
+    ///
+    /// # Returns
+    ///
+    /// Returns the ids of the removed runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read or a run cannot be removed.
+    pub fn prune(&self, cutoff: DateTime<Utc>) -> RunStoreResult<Vec<String>> {
+        let mut removed = Vec::new();
+
+        for record in self.list()? {
+            if record.finished_at.unwrap_or(record.started_at) < cutoff {
+                std::fs::remove_dir_all(self.run_dir(&record.run_id)?)?;
+                removed.push(record.run_id);
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::types::workflow::node::WorkflowNode;
+    use crate::shared::types::workflow::workflow::Workflow;
+
+    fn record(run_id: &str, age_days: i64) -> RunRecord {
+        let mut record = RunRecord::start(run_id, "workflow.yml", HashMap::new());
+        record.started_at = Utc::now() - chrono::Duration::days(age_days);
+        record
+    }
+
+    #[test]
+    fn test_run_record_complete() {
+        let mut workflow = Workflow::new("Recorded");
+        workflow.add_node(WorkflowNode::new("a", "Log", None));
+
+        let mut run = WorkflowRun::new(workflow).with_run_id("run-1");
+        run.status = Status::Done;
+        run.workflow_hash = Some("hash".to_string());
+        run.inputs
+            .insert("name".to_string(), Value::String("Ada".to_string()));
+        run.nodes[0].status = Status::Done;
+        run.finished_at = Some(Utc::now());
+
+        let mut record = RunRecord::start("run-1", "workflow.yml", HashMap::new());
+        record.complete(&run);
+
+        assert_eq!(record.status, Status::Done);
+        assert_eq!(record.workflow_name, Some("Recorded".to_string()));
+        assert_eq!(record.workflow_hash, Some("hash".to_string()));
+        assert_eq!(record.inputs.len(), 1);
+        assert_eq!(record.nodes, run.nodes);
+        assert!(record.finished_at.is_some());
+        assert!(record.duration_ms.is_some());
+        assert!(record.error.is_none());
+    }
+
+    #[test]
+    fn test_run_record_fail() {
+        let mut record = RunRecord::start("run-1", "missing.yml", HashMap::new());
+        record.fail("Workflow file not found");
+
+        assert_eq!(record.status, Status::Failed);
+        assert_eq!(record.error, Some("Workflow file not found".to_string()));
+        assert!(record.finished_at.is_some());
+    }
+
+    #[test]
+    fn test_run_store_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path());
+
+        store.save(&record("run-1", 0)).unwrap();
+        let loaded = store.load("run-1").unwrap();
+
+        assert_eq!(loaded.run_id, "run-1");
+        assert_eq!(loaded.workflow_path, PathBuf::from("workflow.yml"));
+        assert!(matches!(
+            store.load("missing"),
+            Err(RunStoreError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_run_store_list_most_recent_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path());
+
+        store.save(&record("old", 3)).unwrap();
+        store.save(&record("new", 0)).unwrap();
+        store.save(&record("middle", 1)).unwrap();
+        std::fs::create_dir_all(dir.path().join("garbage")).unwrap();
+        std::fs::write(dir.path().join("garbage/run.json"), "not json").unwrap();
+
+        let ids: Vec<_> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|record| record.run_id)
+            .collect();
+        assert_eq!(ids, vec!["new", "middle", "old"]);
+    }
+
+    #[test]
+    fn test_run_store_list_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path().join("missing"));
+
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_run_store_read_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path());
+        store.save(&record("run-1", 0)).unwrap();
+
+        assert_eq!(store.read_logs("run-1").unwrap(), "");
+
+        std::fs::write(store.log_path("run-1").unwrap(), "INFO hello\n").unwrap();
+        assert_eq!(store.read_logs("run-1").unwrap(), "INFO hello\n");
+        assert!(store.read_logs("missing").is_err());
+    }
+
+    #[test]
+    fn test_run_store_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path());
+
+        store.save(&record("old", 10)).unwrap();
+        store.save(&record("new", 0)).unwrap();
+
+        let removed = store.prune(Utc::now() - chrono::Duration::days(7)).unwrap();
+
+        assert_eq!(removed, vec!["old"]);
+        assert!(!store.run_dir("old").unwrap().exists());
+        assert!(store.load("new").is_ok());
+    }
+
+    #[test]
+    fn test_run_store_rejects_invalid_run_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path().join("runs"));
+        std::fs::create_dir_all(dir.path().join("outside")).unwrap();
+
+        for run_id in ["../outside", "a/b", "a\\b", "..", ""] {
+            assert!(matches!(
+                store.load(run_id),
+                Err(RunStoreError::InvalidRunId(_))
+            ));
+            assert!(store.read_logs(run_id).is_err());
+        }
+        assert!(store.save(&record("../outside", 10)).is_err());
+    }
+
+    #[test]
+    fn test_run_store_prune_skips_mismatched_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path());
+
+        store.save(&record("kept", 0)).unwrap();
+        std::fs::create_dir_all(dir.path().join("moved")).unwrap();
+        std::fs::write(
+            dir.path().join("moved/run.json"),
+            serde_json::to_string(&record("kept", 10)).unwrap(),
+        )
+        .unwrap();
+
+        let removed = store.prune(Utc::now() - chrono::Duration::days(7)).unwrap();
+
+        assert!(removed.is_empty());
+        assert!(store.load("kept").is_ok());
+        assert!(dir.path().join("moved").exists());
+    }
+}
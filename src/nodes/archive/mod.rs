@@ -0,0 +1,498 @@
+//! Archive node implementation
+//!
+//! This module contains the `Archive` node type, which packs the files of a
+//! directory into a tar, tar.gz or zip [archive](crate::core::archive), e.g.
+//! to ship the output of a build.
+//!
+//! ```yaml
+//! - id: package
+//!   type: Archive
+//!   input:
+//!     source: build/release
+//!     path: dist/app.tar.gz
+//!     include: ["bin/**", "README.md"]
+//!     exclude: "*.debug"
+//! ```
+//!
+//! The format is inferred from the extension of the archive when not given.
+//! Files are selected by [glob patterns](crate::core::glob) relative to the
+//! source directory, and every file is kept when no `include` is given.
+//! Directories are created implicitly by the files they contain, so empty
+//! directories and symbolic links are left out.
+//!
+//! Paths are relative to the directory of the workflow file, and both the
+//! source and the archive must be inside it.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde_yml::{Mapping, Value};
+use tracing::info;
+
+use crate::core::archive::{
+    ArchiveError, ArchiveFormat, ArchiveResult, ArchiveWriter, Entry, ARCHIVE_FORMATS,
+};
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::placeholders;
+use crate::core::glob::{Glob, GlobFilter};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::parse::string_field;
+
+/// A node packing the files of a directory into an archive
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::context::RunContext;
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::archive::ArchiveNode;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use serde_yml::Value;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// std::fs::create_dir_all(dir.path().join("build/bin")).unwrap();
+/// std::fs::write(dir.path().join("build/bin/app"), "app").unwrap();
+/// std::fs::write(dir.path().join("build/app.log"), "log").unwrap();
+///
+/// let input: Value = serde_yml::from_str(
+///     "{ source: build, path: dist/app.zip, exclude: '*.log' }",
+/// ).unwrap();
+/// let node = ArchiveNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let context = RunContext::new(dir.path().join("workflow.yml"));
+/// let output = node
+///     .execute(BaseNodeRunOptions::new(&heap, "package").with_context(&context))
+///     .unwrap();
+/// assert_eq!(output["files"][0], Value::from("bin/app"));
+/// assert!(dir.path().join("dist/app.zip").exists());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArchiveNode {
+    source: PathBuf,
+    path: PathBuf,
+    format: ArchiveFormat,
+    filter: GlobFilter,
+}
+
+impl ArchiveNode {
+    /// Creates an archive node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the `source` directory, the archive `path`,
+    ///   and optionally its `format` and the `include` and `exclude` globs
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if the source or the path is
+    /// missing, if the format is unknown or cannot be inferred, or if a glob
+    /// is invalid.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let source = required_path("Archive", &input, "source")?;
+        let path = required_path("Archive", &input, "path")?;
+        let format = archive_format("Archive", &input, &path)?;
+        let filter = glob_filter("Archive", &input)?;
+
+        Ok(Self {
+            source,
+            path,
+            format,
+            filter,
+        })
+    }
+
+    /// Returns the format of the archive
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// Checks the globs of a node input, as written in the workflow file
+    ///
+    /// Globs containing `${{ ... }}` placeholders are only known when the
+    /// workflow runs, and are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message of the first invalid glob.
+    pub fn check_input(input: &Value) -> Result<(), String> {
+        for field in ["include", "exclude"] {
+            let patterns = match input.get(field) {
+                Some(Value::Sequence(items)) => items.iter().collect(),
+                Some(value) => vec![value],
+                None => vec![],
+            };
+            for pattern in patterns {
+                if let Value::String(pattern) = pattern {
+                    if placeholders(pattern).is_empty() {
+                        Glob::new(pattern).map_err(|e| e.to_string())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        let mut schema = serde_json::json!({
+            "type": "object",
+            "required": ["source", "path"],
+            "additionalProperties": false,
+            "properties": {
+                "source": {
+                    "type": "string",
+                    "description": "Directory whose files are archived, relative to the workflow file"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Archive file to write, relative to the workflow file"
+                }
+            }
+        });
+        schema["properties"]
+            .as_object_mut()
+            .unwrap()
+            .extend(filter_schema());
+        schema
+    }
+}
+
+impl BaseNode for ArchiveNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let source = context.resolve_within(&self.source)?;
+        let path = context.resolve_within(&self.path)?;
+        if !source.is_dir() {
+            return Err(WorkflowError::NodeExecutionFailed(format!(
+                "Archive source {} is not a directory",
+                source.display()
+            )));
+        }
+
+        let mut entries = Vec::new();
+        collect(&source, "", &path, &self.filter, &mut entries).map_err(|e| {
+            WorkflowError::NodeExecutionFailed(format!(
+                "Failed to read {}: {}",
+                source.display(),
+                e
+            ))
+        })?;
+
+        let size = write(self.format, &path, &entries).map_err(|e| {
+            // A partly written archive is not left behind
+            let _ = std::fs::remove_file(&path);
+            WorkflowError::NodeExecutionFailed(format!("Failed to write {}: {}", path.display(), e))
+        })?;
+
+        info!(
+            "Archive '{}' wrote {} files to {}",
+            options.prefix(),
+            entries.len(),
+            path.display()
+        );
+
+        let files: Vec<Value> = entries
+            .into_iter()
+            .map(|(entry, _)| Value::from(entry.path))
+            .collect();
+        let mut output = Mapping::new();
+        output.insert(Value::from("path"), Value::from(path.display().to_string()));
+        output.insert(Value::from("files"), Value::Sequence(files));
+        output.insert(Value::from("size"), Value::from(size));
+        Ok(Value::Mapping(output))
+    }
+}
+
+/// Writes an archive, streaming the content of its files from disk
+///
+/// # Returns
+///
+/// Returns the size of the archive, in bytes.
+fn write(format: ArchiveFormat, path: &Path, entries: &[(Entry, PathBuf)]) -> ArchiveResult<u64> {
+    let io = |e: std::io::Error| ArchiveError::Io(e.to_string());
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io)?;
+    }
+    let output = BufWriter::new(File::create(path).map_err(io)?);
+    let mut writer = ArchiveWriter::new(format, output);
+    for (entry, file) in entries {
+        let content =
+            File::open(file).map_err(|e| ArchiveError::Io(format!("{}: {}", file.display(), e)))?;
+        writer.append(entry, content)?;
+    }
+    let output = writer
+        .finish()?
+        .into_inner()
+        .map_err(|e| io(e.into_error()))?;
+    output.metadata().map(|metadata| metadata.len()).map_err(io)
+}
+
+/// Adds the selected files below a directory to the entries, sorted by path
+///
+/// # Arguments
+///
+/// * `dir` - Directory to read
+/// * `prefix` - Path of the directory relative to the source, with `/`
+/// * `archive` - Archive being written, left out if it is in the source
+/// * `filter` - Filter selecting the files
+/// * `entries` - Entries the files are added to, with their paths on disk
+fn collect(
+    dir: &Path,
+    prefix: &str,
+    archive: &Path,
+    filter: &GlobFilter,
+    entries: &mut Vec<(Entry, PathBuf)>,
+) -> std::io::Result<()> {
+    let mut children = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    children.sort_by_key(|child| child.file_name());
+
+    for child in children {
+        let name = child.file_name().to_string_lossy().into_owned();
+        let relative = match prefix.is_empty() {
+            true => name,
+            false => format!("{}/{}", prefix, name),
+        };
+        let metadata = std::fs::symlink_metadata(child.path())?;
+
+        if metadata.is_dir() {
+            collect(&child.path(), &relative, archive, filter, entries)?;
+        } else if metadata.is_file() && child.path() != archive && filter.selects(&relative) {
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_secs() as i64);
+            let entry = Entry::file(relative, metadata.len())
+                .with_mode(mode(&metadata))
+                .with_modified(modified);
+            entries.push((entry, child.path()));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the Unix permissions of a file
+#[cfg(unix)]
+fn mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode()
+}
+
+/// Returns the Unix permissions of a file, from its read-only flag
+#[cfg(not(unix))]
+fn mode(metadata: &std::fs::Metadata) -> u32 {
+    match metadata.permissions().readonly() {
+        true => 0o444,
+        false => 0o644,
+    }
+}
+
+/// Returns a required, non-empty path field of the input
+pub(crate) fn required_path(node: &str, input: &Value, field: &str) -> WorkflowResult<PathBuf> {
+    match string_field(node, input, field)? {
+        Some(path) if !path.trim().is_empty() => Ok(PathBuf::from(path)),
+        _ => Err(WorkflowError::NodeBuilder(format!(
+            "{} node requires a `{}`",
+            node, field
+        ))),
+    }
+}
+
+/// Returns the `format` of the input, or the format inferred from the path
+pub(crate) fn archive_format(
+    node: &str,
+    input: &Value,
+    path: &Path,
+) -> WorkflowResult<ArchiveFormat> {
+    match string_field(node, input, "format")? {
+        Some(format) => format
+            .parse()
+            .map_err(|e| WorkflowError::NodeBuilder(format!("{} node: {}", node, e))),
+        None => ArchiveFormat::from_path(path).ok_or_else(|| {
+            WorkflowError::NodeBuilder(format!(
+                "{} node cannot infer the format of {}, set `format`",
+                node,
+                path.display()
+            ))
+        }),
+    }
+}
+
+/// Returns the filter of the `include` and `exclude` globs of the input
+pub(crate) fn glob_filter(node: &str, input: &Value) -> WorkflowResult<GlobFilter> {
+    let mut filter = GlobFilter::new();
+    for glob in globs(node, input, "include")? {
+        filter = filter.with_include(glob);
+    }
+    for glob in globs(node, input, "exclude")? {
+        filter = filter.with_exclude(glob);
+    }
+    Ok(filter)
+}
+
+/// Returns the globs of a field holding a glob or a list of globs
+fn globs(node: &str, input: &Value, field: &str) -> WorkflowResult<Vec<Glob>> {
+    let patterns = match input.get(field) {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(Value::Sequence(items)) => items.iter().collect(),
+        Some(value) => vec![value],
+    };
+
+    patterns
+        .into_iter()
+        .map(|pattern| match pattern {
+            Value::String(pattern) => Glob::new(pattern)
+                .map_err(|e| WorkflowError::NodeBuilder(format!("{} node: {}", node, e))),
+            _ => Err(WorkflowError::NodeBuilder(format!(
+                "{} node `{}` must be a glob or a list of globs",
+                node, field
+            ))),
+        })
+        .collect()
+}
+
+/// Returns the JSON Schema of the `format`, `include` and `exclude` fields
+pub(crate) fn filter_schema() -> serde_json::Map<String, serde_json::Value> {
+    let globs = |description: &str| {
+        serde_json::json!({
+            "oneOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "string" } }
+            ],
+            "description": description
+        })
+    };
+
+    let mut properties = serde_json::Map::new();
+    properties.insert(
+        "format".to_string(),
+        serde_json::json!({
+            "enum": ARCHIVE_FORMATS,
+            "description": "Archive format, inferred from the extension of the archive by default"
+        }),
+    );
+    properties.insert(
+        "include".to_string(),
+        globs("Globs of the files to keep, e.g. bin/**; all files by default"),
+    );
+    properties.insert(
+        "exclude".to_string(),
+        globs("Globs of the files to leave out, e.g. *.log"),
+    );
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::archive::ArchiveReader;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<ArchiveNode> {
+        ArchiveNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("build/bin/app", "app"),
+            ("build/bin/app.debug", "symbols"),
+            ("build/README.md", "# App"),
+            ("build/logs/run.log", "log"),
+        ] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn execute(node: &ArchiveNode, dir: &Path) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        let context = RunContext::new(dir.join("workflow.yml"));
+        node.execute(BaseNodeRunOptions::new(&heap, "package").with_context(&context))
+    }
+
+    #[test]
+    fn test_archive_node_writes_selected_files() {
+        let dir = project();
+        let node = node(
+            "{ source: build, path: dist/app.tar.gz, include: [bin, README.md], exclude: '*.debug' }",
+        )
+        .unwrap();
+        assert_eq!(node.format(), ArchiveFormat::TarGz);
+
+        let output = execute(&node, dir.path()).unwrap();
+        assert_eq!(
+            output["files"],
+            serde_yml::from_str::<Value>("[README.md, bin/app]").unwrap()
+        );
+
+        let file = File::open(dir.path().join("dist/app.tar.gz")).unwrap();
+        assert_eq!(output["size"], Value::from(file.metadata().unwrap().len()));
+        let mut contents = Vec::new();
+        ArchiveReader::new(ArchiveFormat::TarGz, file)
+            .for_each(|entry, content| {
+                contents.push((entry.path.clone(), std::io::read_to_string(content)?));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(contents[1], ("bin/app".to_string(), "app".to_string()));
+    }
+
+    #[test]
+    fn test_archive_node_skips_itself() {
+        let dir = project();
+        let node = node("{ source: build, path: build/all.zip }").unwrap();
+
+        execute(&node, dir.path()).unwrap();
+        let output = execute(&node, dir.path()).unwrap();
+        assert_eq!(output["files"].as_sequence().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_archive_node_confined_to_workflow_dir() {
+        let dir = project();
+
+        for yaml in [
+            "{ source: .., path: all.zip }",
+            "{ source: build, path: ../app.zip }",
+        ] {
+            assert!(matches!(
+                execute(&node(yaml).unwrap(), dir.path()),
+                Err(WorkflowError::OutsideWorkflowDir { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_archive_node_invalid_input() {
+        assert!(node("path: app.zip").is_err());
+        assert!(node("{ source: build, path: app.bin }").is_err());
+        assert!(node("{ source: build, path: app.bin, format: tar }").is_ok());
+        assert!(node("{ source: build, path: app.zip, format: rar }").is_err());
+        assert!(node("{ source: build, path: app.zip, include: '[a' }").is_err());
+        assert!(node("{ source: build, path: app.zip, exclude: [1] }").is_err());
+    }
+
+    #[test]
+    fn test_archive_node_check_input() {
+        let check = |yaml: &str| ArchiveNode::check_input(&serde_yml::from_str(yaml).unwrap());
+
+        assert!(check("include: [bin/**, '*.md']").is_ok());
+        assert!(check("exclude: '${{ pattern }}'").is_ok());
+        assert_eq!(
+            check("exclude: ['*.log', 'log[0-9']").unwrap_err(),
+            "Invalid glob 'log[0-9': unclosed character class; missing ']'"
+        );
+    }
+}
@@ -0,0 +1,387 @@
+//! Assert node implementation
+//!
+//! This module contains the `Assert` node type, which checks a list of
+//! conditions and fails the run when any of them does not hold. Every check
+//! is evaluated, so a single failure reports all the broken conditions
+//! together with the values they saw.
+//!
+//! ```yaml
+//! - id: check
+//!   type: Assert
+//!   input:
+//!     - condition: ${{ fetch.status == 200 }}
+//!       message: Fetch should succeed
+//!     - ${{ fetch.body.items != null }}
+//! ```
+//!
+//! The input of the node is not substituted when the node is built: the
+//! conditions are evaluated, and the placeholders of the messages are
+//! substituted, when the node executes.
+
+use std::fmt;
+
+use serde_yml::{Mapping, Value};
+use tracing::info;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::{is_truthy, Expression};
+use crate::core::heap::Heap;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// A check of an `Assert` node that did not hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertionFailure {
+    /// The condition, without its `${{ ... }}` wrapper
+    pub condition: String,
+    /// The message of the check, with its placeholders substituted
+    pub message: Option<String>,
+    /// The values of the references of the condition
+    pub values: Vec<(String, Value)>,
+    /// Why the condition could not be evaluated, if it could not
+    pub error: Option<String>,
+}
+
+impl AssertionFailure {
+    /// Joins failures into a single line
+    pub fn join(failures: &[AssertionFailure]) -> String {
+        failures
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl fmt::Display for AssertionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(message) = &self.message {
+            write!(f, "{}: ", message)?;
+        }
+        write!(f, "expected {}", self.condition)?;
+
+        if !self.values.is_empty() {
+            let values = self
+                .values
+                .iter()
+                .map(|(path, value)| format!("{} = {}", path, render(value)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, ", got {}", values)?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, " ({})", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Condition of a check
+#[derive(Debug, Clone)]
+enum Condition {
+    Expression(Expression),
+    Literal(Value),
+}
+
+/// A single check of an `Assert` node
+#[derive(Debug, Clone)]
+struct Check {
+    condition: Condition,
+    message: Option<String>,
+}
+
+/// A node failing when one of its conditions does not hold
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::assert::AssertNode;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("- ${{ count > 2 }}").unwrap();
+/// let node = AssertNode::new(Some(input)).unwrap();
+///
+/// let mut heap = Heap::new();
+/// heap.insert("count", Some(Value::from(1)));
+/// let error = node.execute(BaseNodeRunOptions::new(&heap, "check")).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Assertion failed in node 'check': expected count > 2, got count = 1"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct AssertNode {
+    checks: Vec<Check>,
+}
+
+impl AssertNode {
+    /// Creates an assert node from its input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Sequence of checks, each either a condition or a mapping
+    ///   with a `condition` and an optional `message`
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if the input is not a non-empty
+    /// sequence of checks, or if a condition is not a valid expression.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let items = match input {
+            Some(Value::Sequence(items)) if !items.is_empty() => items,
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Assert node requires a non-empty sequence of checks".to_string(),
+                ))
+            }
+        };
+
+        let checks = items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| Self::check(index, item))
+            .collect::<WorkflowResult<_>>()?;
+
+        Ok(Self { checks })
+    }
+
+    /// Parses the check at the given position of the input
+    fn check(index: usize, item: Value) -> WorkflowResult<Check> {
+        let (condition, message) = match item {
+            Value::Mapping(mut map) => {
+                let condition = map.remove("condition").ok_or_else(|| {
+                    WorkflowError::NodeBuilder(format!(
+                        "Assert check {} requires a `condition`",
+                        index
+                    ))
+                })?;
+                let message = match map.remove("message") {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(message)) => Some(message),
+                    Some(_) => {
+                        return Err(WorkflowError::NodeBuilder(format!(
+                            "Assert check {} `message` must be a string",
+                            index
+                        )))
+                    }
+                };
+                (condition, message)
+            }
+            other => (other, None),
+        };
+
+        let condition = match condition {
+            Value::String(source) => Expression::parse(&source)
+                .map(Condition::Expression)
+                .map_err(|e| {
+                    WorkflowError::NodeBuilder(format!("Invalid Assert condition {}: {}", index, e))
+                })?,
+            value => Condition::Literal(value),
+        };
+
+        Ok(Check { condition, message })
+    }
+
+    /// Evaluates every check against the heap
+    ///
+    /// # Returns
+    ///
+    /// Returns the checks that did not hold, in definition order.
+    pub fn failures(&self, heap: &Heap) -> Vec<AssertionFailure> {
+        self.checks
+            .iter()
+            .filter_map(|check| Self::evaluate(check, heap))
+            .collect()
+    }
+
+    /// Evaluates a check, returning its failure if it did not hold
+    fn evaluate(check: &Check, heap: &Heap) -> Option<AssertionFailure> {
+        let (condition, values, error) = match &check.condition {
+            Condition::Literal(value) if is_truthy(value) => return None,
+            Condition::Literal(value) => (render(value), Vec::new(), None),
+            Condition::Expression(expression) => {
+                let error = match expression.evaluate_bool(heap) {
+                    Ok(true) => return None,
+                    Ok(false) => None,
+                    Err(e) => Some(e.to_string()),
+                };
+                (
+                    condition_text(expression),
+                    expression.observed_values(heap),
+                    error,
+                )
+            }
+        };
+
+        let message = check.message.as_ref().map(|message| {
+            match heap.parse(Some(Value::String(message.clone()))) {
+                Some(Value::String(message)) => message,
+                Some(value) => render(&value),
+                None => message.clone(),
+            }
+        });
+
+        Some(AssertionFailure {
+            condition,
+            message,
+            values,
+            error,
+        })
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "array",
+            "minItems": 1,
+            "items": {
+                "anyOf": [
+                    {
+                        "description": "Condition that must hold, usually a ${{ ... }} placeholder"
+                    },
+                    {
+                        "type": "object",
+                        "additionalProperties": false,
+                        "required": ["condition"],
+                        "properties": {
+                            "condition": {
+                                "description": "Condition that must hold, usually a ${{ ... }} placeholder"
+                            },
+                            "message": {
+                                "type": "string",
+                                "description": "Explanation reported when the condition does not hold"
+                            }
+                        }
+                    }
+                ]
+            }
+        })
+    }
+}
+
+impl BaseNode for AssertNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let failures = self.failures(options.heap());
+        if !failures.is_empty() {
+            return Err(WorkflowError::AssertionFailed {
+                node: options.prefix().to_string(),
+                failures,
+            });
+        }
+
+        info!(
+            "Assert '{}' passed {} check(s)",
+            options.prefix(),
+            self.checks.len()
+        );
+
+        let mut output = Mapping::new();
+        output.insert(Value::from("passed"), Value::from(self.checks.len()));
+        Ok(Value::Mapping(output))
+    }
+}
+
+/// Returns the text of a condition without its `${{ ... }}` wrapper
+fn condition_text(expression: &Expression) -> String {
+    let source = expression.source().trim();
+    source
+        .strip_prefix("${{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .unwrap_or(source)
+        .trim()
+        .to_string()
+}
+
+/// Renders a value on a single line
+fn render(value: &Value) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "?".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(yaml: &str) -> WorkflowResult<AssertNode> {
+        AssertNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn heap() -> Heap {
+        let mut heap = Heap::new();
+        heap.insert(
+            "fetch",
+            Some(serde_yml::from_str("{ status: 404, body: { items: [] } }").unwrap()),
+        );
+        heap.insert("expected", Some(Value::from(200)));
+        heap
+    }
+
+    #[test]
+    fn test_assert_node_passes() {
+        let node = node("- ${{ fetch.status == 404 }}\n- condition: fetch.body\n- true").unwrap();
+        let output = node
+            .execute(BaseNodeRunOptions::new(&heap(), "check"))
+            .unwrap();
+        assert_eq!(output.get("passed"), Some(&Value::from(3)));
+    }
+
+    #[test]
+    fn test_assert_node_reports_every_failure() {
+        let node = node(
+            "- condition: ${{ fetch.status == expected }}\n  message: Fetch returned ${{ fetch.status }}\n\
+             - ${{ fetch.status > 0 }}\n\
+             - ${{ fetch.body.items[0] != null }}",
+        )
+        .unwrap();
+
+        let failures = node.failures(&heap());
+        assert_eq!(failures.len(), 2);
+        assert_eq!(
+            failures[0].to_string(),
+            "Fetch returned 404: expected fetch.status == expected, got fetch.status = 404, expected = 200"
+        );
+        assert_eq!(
+            failures[1].to_string(),
+            "expected fetch.body.items[0] != null, got fetch.body.items[0] = null"
+        );
+    }
+
+    #[test]
+    fn test_assert_node_error() {
+        let error = node("- ${{ fetch.status == 200 }}\n- false")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap(), "check"))
+            .unwrap_err();
+
+        match &error {
+            WorkflowError::AssertionFailed { node, failures } => {
+                assert_eq!(node, "check");
+                assert_eq!(failures.len(), 2);
+                assert_eq!(failures[1].condition, "false");
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert_eq!(
+            error.to_string(),
+            "Assertion failed in node 'check': expected fetch.status == 200, got fetch.status = 404; expected false"
+        );
+    }
+
+    #[test]
+    fn test_assert_node_evaluation_error() {
+        let failures = node("- fetch.status < 'a'").unwrap().failures(&heap());
+        assert_eq!(
+            failures[0].error.as_deref(),
+            Some("Cannot compare a number with a string")
+        );
+    }
+
+    #[test]
+    fn test_assert_node_invalid_input() {
+        assert!(node("condition: true").is_err());
+        assert!(node("[]").is_err());
+        assert!(node("- message: missing condition").is_err());
+        assert!(node("- condition: a ==").is_err());
+        assert!(node("- condition: a\n  message: [b]").is_err());
+    }
+}
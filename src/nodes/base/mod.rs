@@ -1,6 +1,6 @@
 use serde_yml::Value;
 
-use crate::core::{engine::WorkflowError, heap::Heap};
+use crate::core::{context::RunContext, engine::WorkflowError, heap::Heap};
 
 /// Options for running a base node
 ///
@@ -10,6 +10,7 @@ use crate::core::{engine::WorkflowError, heap::Heap};
 pub struct BaseNodeRunOptions<'a> {
     heap: &'a Heap,
     prefix: String,
+    context: Option<&'a RunContext>,
 }
 
 impl<'a> BaseNodeRunOptions<'a> {
@@ -33,6 +34,7 @@ impl<'a> BaseNodeRunOptions<'a> {
         Self {
             heap,
             prefix: prefix.into(),
+            context: None,
         }
     }
 
@@ -54,6 +56,27 @@ impl<'a> BaseNodeRunOptions<'a> {
         &self.prefix
     }
 
+    /// Returns the context of the run executing the node, if any
+    ///
+    /// The context is set when the node is executed by the workflow engine.
+    pub fn context(&self) -> Option<&'a RunContext> {
+        self.context
+    }
+
+    /// Builder method to set the context of the run executing the node
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The run context
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_context(mut self, context: &'a RunContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+
     /// Builder method to set the heap
     ///
     /// # Arguments
@@ -204,4 +227,19 @@ mod tests {
             Some(&Value::String("test_value".to_string()))
         );
     }
+
+    #[test]
+    fn test_base_node_run_options_context() {
+        let heap = Heap::new();
+        let context = RunContext::new("dir/main.yml");
+
+        let options = BaseNodeRunOptions::new(&heap, "test_node");
+        assert!(options.context().is_none());
+
+        let options = options.with_context(&context);
+        assert_eq!(
+            options.context().unwrap().workflow_path(),
+            std::path::Path::new("dir/main.yml")
+        );
+    }
 }
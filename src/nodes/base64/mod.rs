@@ -0,0 +1,215 @@
+//! Base64 node implementation
+//!
+//! This module contains the `Base64` node type, which encodes a string as
+//! Base64 or decodes Base64 back to a string, e.g. to build basic
+//! authentication headers or read Kubernetes secrets.
+//!
+//! ```yaml
+//! - id: secret
+//!   type: Base64
+//!   input:
+//!     operation: decode
+//!     text: "${{ kubectl.stdout }}"
+//! ```
+//!
+//! With `url_safe`, the URL and filename safe alphabet of RFC 4648 is used
+//! instead of the standard one. Padding is optional when decoding.
+
+use base64::alphabet::{STANDARD, URL_SAFE};
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use serde_yml::Value;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::to_text;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::parse::string_field;
+
+/// A node encoding or decoding Base64
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::base64::Base64Node;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("{ text: 'admin:secret' }").unwrap();
+/// let node = Base64Node::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "auth")).unwrap();
+/// assert_eq!(output, Value::from("YWRtaW46c2VjcmV0"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Base64Node {
+    text: String,
+    decode: bool,
+    url_safe: bool,
+}
+
+impl Base64Node {
+    /// Creates a Base64 node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the `text`, the `operation` (`encode` or
+    ///   `decode`) and whether to use the `url_safe` alphabet
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if `text` is missing or if the
+    /// operation is unknown.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let text = match input.get("text") {
+            None | Some(Value::Null | Value::Sequence(_) | Value::Mapping(_)) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Base64 node requires a `text` string".to_string(),
+                ))
+            }
+            Some(text) => to_text(text),
+        };
+
+        let decode = match string_field("Base64", &input, "operation")? {
+            None | Some("encode") => false,
+            Some("decode") => true,
+            Some(operation) => {
+                return Err(WorkflowError::NodeBuilder(format!(
+                    "Unknown Base64 operation '{}' (available: encode, decode)",
+                    operation
+                )))
+            }
+        };
+
+        let url_safe = match input.get("url_safe") {
+            None | Some(Value::Null) => false,
+            Some(Value::Bool(url_safe)) => *url_safe,
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Base64 node `url_safe` must be a boolean".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            text,
+            decode,
+            url_safe,
+        })
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["text"],
+            "additionalProperties": false,
+            "properties": {
+                "text": {
+                    "type": ["string", "number", "boolean"],
+                    "description": "Text to encode, or Base64 to decode"
+                },
+                "operation": {
+                    "enum": ["encode", "decode"],
+                    "description": "Operation to apply, defaults to encode"
+                },
+                "url_safe": {
+                    "type": "boolean",
+                    "description": "Use the URL and filename safe alphabet"
+                }
+            }
+        })
+    }
+
+    /// Returns the Base64 engine of the node
+    fn engine(&self) -> GeneralPurpose {
+        let alphabet = match self.url_safe {
+            true => &URL_SAFE,
+            false => &STANDARD,
+        };
+        let config = GeneralPurposeConfig::new()
+            .with_encode_padding(!self.url_safe)
+            .with_decode_padding_mode(DecodePaddingMode::Indifferent);
+        GeneralPurpose::new(alphabet, config)
+    }
+}
+
+impl BaseNode for Base64Node {
+    fn execute(&self, _options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        if !self.decode {
+            return Ok(Value::from(self.engine().encode(&self.text)));
+        }
+
+        let text: String = self.text.split_whitespace().collect();
+        let bytes = self
+            .engine()
+            .decode(text)
+            .map_err(|e| WorkflowError::NodeExecutionFailed(format!("Invalid Base64: {}", e)))?;
+        String::from_utf8(bytes).map(Value::from).map_err(|_| {
+            WorkflowError::NodeExecutionFailed("Decoded Base64 is not UTF-8 text".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<Base64Node> {
+        Base64Node::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn execute(yaml: &str) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        node(yaml)?.execute(BaseNodeRunOptions::new(&heap, "base64"))
+    }
+
+    #[test]
+    fn test_base64_node_encode() {
+        assert_eq!(execute("text: 'hi?>'").unwrap(), Value::from("aGk/Pg=="));
+        assert_eq!(
+            execute("{ text: 'hi?>', url_safe: true }").unwrap(),
+            Value::from("aGk_Pg")
+        );
+        assert_eq!(execute("text: 42").unwrap(), Value::from("NDI="));
+    }
+
+    #[test]
+    fn test_base64_node_decode() {
+        assert_eq!(
+            execute("{ text: 'aGk/Pg==', operation: decode }").unwrap(),
+            Value::from("hi?>")
+        );
+        assert_eq!(
+            execute("{ text: 'aGk_Pg', operation: decode, url_safe: true }").unwrap(),
+            Value::from("hi?>")
+        );
+        assert_eq!(
+            execute("{ text: \"aGVs\\nbG8=\\n\", operation: decode }").unwrap(),
+            Value::from("hello")
+        );
+
+        assert!(execute("{ text: 'a!b', operation: decode }")
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid Base64"));
+        assert_eq!(
+            execute("{ text: '/w==', operation: decode }")
+                .unwrap_err()
+                .to_string(),
+            "Node execution failed: Decoded Base64 is not UTF-8 text"
+        );
+    }
+
+    #[test]
+    fn test_base64_node_invalid_input() {
+        assert!(node("operation: encode").is_err());
+        assert!(node("{ text: a, operation: rot13 }").is_err());
+        assert!(node("{ text: a, url_safe: maybe }").is_err());
+    }
+}
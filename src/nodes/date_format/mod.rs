@@ -0,0 +1,305 @@
+//! DateFormat node implementation
+//!
+//! This module contains the `DateFormat` node type, which reads a date and
+//! formats it in another timezone or format, e.g. to turn the timestamp of
+//! an API response into a readable date.
+//!
+//! ```yaml
+//! - id: released
+//!   type: DateFormat
+//!   input:
+//!     date: "${{ release.published_at }}"
+//!     timezone: "America/New_York"
+//!     format: "%B %-d, %Y"
+//! ```
+//!
+//! Dates are RFC 3339 or RFC 2822 strings, `YYYY-MM-DD` dates, or Unix
+//! timestamps in seconds, unless `input_format` gives the strftime format to
+//! read them with. Dates without an offset are read as UTC. The timezone and
+//! format of the output are the same as for the [`Now`](crate::nodes::now)
+//! node.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde_yml::Value;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::placeholders;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::now::{check_format, time_options, time_schema, NowNode, Timezone};
+use crate::nodes::parse::string_field;
+
+/// A node formatting a date
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::date_format::DateFormatNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str(
+///     "{ date: '2024-06-30T22:00:00Z', timezone: '+02:00', format: '%A %d %B' }",
+/// )
+/// .unwrap();
+/// let node = DateFormatNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "day")).unwrap();
+/// assert_eq!(output, Value::from("Monday 01 July"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DateFormatNode {
+    date: DateTime<Utc>,
+    timezone: Timezone,
+    format: Option<String>,
+}
+
+impl DateFormatNode {
+    /// Creates a date format node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the `date` to format, the `input_format` it
+    ///   is read with, and the `timezone` and `format` of the output
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if the date is missing or cannot
+    /// be read, or if a format or the timezone is invalid.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let input_format = string_field("DateFormat", &input, "input_format")?;
+        if let Some(input_format) = input_format {
+            check_format(input_format)
+                .map_err(|e| WorkflowError::NodeBuilder(format!("DateFormat node: {}", e)))?;
+        }
+
+        let date = match input.get("date") {
+            Some(Value::Number(seconds)) => seconds
+                .as_i64()
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+                .or_else(|| {
+                    let seconds = seconds.as_f64()?;
+                    let nanos = (seconds.fract() * 1e9).round() as u32;
+                    DateTime::from_timestamp(seconds.floor() as i64, nanos)
+                }),
+            Some(Value::String(date)) => match input_format {
+                Some(input_format) => parse_with(date.trim(), input_format),
+                None => parse_date(date.trim()),
+            },
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "DateFormat node requires a `date`".to_string(),
+                ))
+            }
+        };
+        let date = date.ok_or_else(|| {
+            let date = input.get("date").map(to_display).unwrap_or_default();
+            WorkflowError::NodeBuilder(match input_format {
+                Some(input_format) => format!(
+                    "DateFormat node cannot read date '{}' with format '{}'",
+                    date, input_format
+                ),
+                None => format!("DateFormat node cannot read date '{}'", date),
+            })
+        })?;
+
+        let (timezone, format) = time_options("DateFormat", &input)?;
+
+        Ok(Self {
+            date,
+            timezone,
+            format,
+        })
+    }
+
+    /// Returns the date read from the input, in UTC
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    /// Checks the formats and timezone of a node input, as written in the
+    /// workflow file
+    ///
+    /// Fields containing `${{ ... }}` placeholders are only known when the
+    /// workflow runs, and are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if a format or the timezone is invalid.
+    pub fn check_input(input: &Value) -> Result<(), String> {
+        if let Some(Value::String(input_format)) = input.get("input_format") {
+            if placeholders(input_format).is_empty() {
+                check_format(input_format)?;
+            }
+        }
+        NowNode::check_input(input)
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        let mut schema = time_schema();
+        schema["required"] = serde_json::json!(["date"]);
+        schema["properties"]["date"] = serde_json::json!({
+            "type": ["string", "number"],
+            "description": "Date to format, e.g. 2024-06-30T22:00:00Z or a Unix timestamp"
+        });
+        schema["properties"]["input_format"] = serde_json::json!({
+            "type": "string",
+            "description": "strftime format the date is read with, e.g. %d/%m/%Y"
+        });
+        schema
+    }
+}
+
+impl BaseNode for DateFormatNode {
+    fn execute(&self, _options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        Ok(Value::from(
+            self.timezone.format(self.date, self.format.as_deref()),
+        ))
+    }
+}
+
+/// Reads a date in one of the formats recognised without an input format
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .or_else(|_| DateTime::parse_from_rfc2822(date))
+        .map(|date| date.to_utc())
+        .ok()
+        .or_else(|| {
+            date.parse::<i64>()
+                .ok()
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        })
+        .or_else(|| parse_with(date, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|| parse_with(date, "%Y-%m-%d %H:%M:%S%.f"))
+        .or_else(|| parse_with(date, "%Y-%m-%d"))
+}
+
+/// Reads a date with a strftime format, as UTC when it has no offset
+fn parse_with(date: &str, format: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(date, format)
+        .map(|date| date.to_utc())
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(date, format)
+                .ok()
+                .map(|date| date.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(date, format)
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
+
+/// Formats a date input for error messages
+fn to_display(date: &Value) -> String {
+    match date {
+        Value::String(date) => date.clone(),
+        Value::Number(number) => number.to_string(),
+        other => serde_yml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<DateFormatNode> {
+        DateFormatNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn execute(yaml: &str) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        node(yaml)?.execute(BaseNodeRunOptions::new(&heap, "date"))
+    }
+
+    #[test]
+    fn test_date_format_node_reads_dates() {
+        let expected = Value::from("2024-02-29T12:30:00Z");
+
+        for date in [
+            "'2024-02-29T12:30:00Z'",
+            "'2024-02-29T14:30:00+02:00'",
+            "'Thu, 29 Feb 2024 12:30:00 +0000'",
+            "'2024-02-29 12:30:00'",
+            "1709209800",
+            "'1709209800'",
+        ] {
+            assert_eq!(
+                execute(&format!("{{ date: {} }}", date)).unwrap(),
+                expected,
+                "{}",
+                date
+            );
+        }
+
+        assert_eq!(
+            execute("{ date: '2024-02-29' }").unwrap(),
+            Value::from("2024-02-29T00:00:00Z")
+        );
+        assert_eq!(
+            execute("{ date: 1709209800.25 }").unwrap(),
+            Value::from("2024-02-29T12:30:00.250Z")
+        );
+        assert_eq!(
+            execute("{ date: '29/02/2024 12h30', input_format: '%d/%m/%Y %Hh%M' }").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_date_format_node_formats() {
+        assert_eq!(
+            execute("{ date: '2024-02-29T23:30:00Z', timezone: '+01:00', format: '%Y-%m-%d' }")
+                .unwrap(),
+            Value::from("2024-03-01")
+        );
+        assert_eq!(
+            execute("{ date: '2024-02-29T12:30:00+02:00', format: '%H:%M %Z' }").unwrap(),
+            Value::from("10:30 UTC")
+        );
+        assert_eq!(
+            execute(
+                "{ date: '2024-07-04T16:00:00Z', timezone: America/New_York, format: '%H:%M %Z' }"
+            )
+            .unwrap(),
+            Value::from("12:00 EDT")
+        );
+    }
+
+    #[test]
+    fn test_date_format_node_invalid_input() {
+        assert!(node("{ format: '%F' }").is_err());
+        assert!(node("{ date: [2024] }").is_err());
+        assert_eq!(
+            node("{ date: yesterday }").unwrap_err().to_string(),
+            "Node builder error: DateFormat node cannot read date 'yesterday'"
+        );
+        assert_eq!(
+            node("{ date: '2024', input_format: '%d/%m' }")
+                .unwrap_err()
+                .to_string(),
+            "Node builder error: DateFormat node cannot read date '2024' with format '%d/%m'"
+        );
+        assert!(node("{ date: 0, input_format: '%Q' }").is_err());
+        assert!(node("{ date: 0, timezone: Mars }").is_err());
+    }
+
+    #[test]
+    fn test_date_format_node_check_input() {
+        let input = |yaml: &str| serde_yml::from_str::<Value>(yaml).unwrap();
+
+        assert!(DateFormatNode::check_input(&input("{ date: '${{ d }}', format: '%F' }")).is_ok());
+        assert!(DateFormatNode::check_input(&input("{ date: 0, input_format: '%Q' }")).is_err());
+        assert!(DateFormatNode::check_input(&input("{ date: 0, timezone: Mars }")).is_err());
+    }
+}
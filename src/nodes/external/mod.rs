@@ -0,0 +1,514 @@
+//! External node implementation
+//!
+//! This module contains the protocol used to run nodes implemented as
+//! separate executables, so that node types can be written in any language.
+//! For every request, colossus spawns the plugin executable, writes a single
+//! line of JSON to its standard input, and reads a JSON response from its
+//! standard output.
+//!
+//! # Protocol
+//!
+//! The `describe` handshake runs once, when the plugin is discovered:
+//!
+//! ```text
+//! > {"action":"describe"}
+//! < {"type":"Http","input_schema":{"type":"object","required":["url"]}}
+//! ```
+//!
+//! `type` names the node type, and the optional `input_schema` is the JSON
+//! Schema of its input, embedded in the workflow schema like the schemas of
+//! the built-in nodes. A plugin whose executions take long may also declare
+//! a `timeout` in seconds, replacing the default of [`DEFAULT_TIMEOUT`].
+//!
+//! Each execution then sends the node id and type, the path of the workflow
+//! file, and the input with its placeholders substituted:
+//!
+//! ```text
+//! > {"action":"execute","node":{"id":"fetch","type":"Http"},"workflow":"deploy.yml","input":{"url":"https://example.com"}}
+//! < {"output":{"status":200}}
+//! ```
+//!
+//! A plugin reports a failure with `{"error": "message"}`. Executions run in
+//! the directory of the workflow file. A plugin exiting unsuccessfully fails,
+//! whatever it answered, with what it wrote to its standard error, and a
+//! plugin still running after its timeout is killed.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value as JsonValue};
+use serde_yml::Value;
+use tracing::debug;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// Time a plugin may take to answer an `execute` request, unless it
+/// declares its own timeout
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Time a plugin may take to answer the `describe` handshake
+pub const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interval between checks of whether a plugin exited
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A node type provided by a plugin executable
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use colossus::nodes::external::Plugin;
+/// use colossus::nodes::registry::NodeRegistry;
+///
+/// let plugin = Plugin::describe("plugins/http").unwrap();
+/// let registry = NodeRegistry::new().with_plugin(plugin);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plugin {
+    node_type: String,
+    program: PathBuf,
+    schema: Option<JsonValue>,
+    timeout: Duration,
+}
+
+impl Plugin {
+    /// Creates a plugin without running its `describe` handshake
+    ///
+    /// # Arguments
+    ///
+    /// * `node_type` - The type name used in workflow files
+    /// * `program` - Path of the plugin executable
+    pub fn new(node_type: impl Into<String>, program: impl Into<PathBuf>) -> Self {
+        Self {
+            node_type: node_type.into(),
+            program: program.into(),
+            schema: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Builder method to set the JSON Schema of the node input
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_schema(mut self, schema: JsonValue) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Builder method to set the time an execution may take
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs the `describe` handshake of a plugin executable
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::Plugin` if the executable cannot be run, does
+    /// not answer in time, or does not answer with a node type.
+    pub fn describe(program: impl Into<PathBuf>) -> WorkflowResult<Self> {
+        let program = program.into();
+        let response = call(&program, None, &describe_request(), DESCRIBE_TIMEOUT)?;
+        let (node_type, schema) = describe_response(&program, &response)?;
+
+        let mut plugin = Self::new(node_type, &program);
+        if let Some(schema) = schema {
+            plugin = plugin.with_schema(schema);
+        }
+        if let Some(timeout) = describe_timeout(&program, &response)? {
+            plugin = plugin.with_timeout(timeout);
+        }
+        Ok(plugin)
+    }
+
+    /// Returns the type name used in workflow files
+    pub fn node_type(&self) -> &str {
+        &self.node_type
+    }
+
+    /// Returns the path of the plugin executable
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Returns the JSON Schema of the node input, if the plugin provides one
+    pub fn schema(&self) -> Option<&JsonValue> {
+        self.schema.as_ref()
+    }
+
+    /// Returns the time an execution may take
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+/// A node executed by a plugin
+#[derive(Debug, Clone)]
+pub struct ExternalNode {
+    plugin: Plugin,
+    input: Option<Value>,
+}
+
+impl ExternalNode {
+    /// Creates a node executed by the given plugin
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin` - The plugin providing the node type
+    /// * `input` - The evaluated input sent to the plugin
+    pub fn new(plugin: Plugin, input: Option<Value>) -> Self {
+        Self { plugin, input }
+    }
+}
+
+impl BaseNode for ExternalNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let request = execute_request(&options, context, &self.plugin.node_type, &self.input)?;
+        let response = call(
+            &self.plugin.program,
+            Some(context.base_dir()),
+            &request,
+            self.plugin.timeout,
+        )
+        .map_err(|e| WorkflowError::NodeExecutionFailed(e.to_string()))?;
+
+        execute_output(&response)
+    }
+}
+
+/// Builds the `execute` request of a node
+pub(crate) fn execute_request(
+    options: &BaseNodeRunOptions,
+    context: &RunContext,
+    node_type: &str,
+    input: &Option<Value>,
+) -> WorkflowResult<JsonValue> {
+    let input = serde_json::to_value(input).map_err(|e| {
+        WorkflowError::NodeExecutionFailed(format!("Cannot send input to plugin: {}", e))
+    })?;
+
+    Ok(json!({
+        "action": "execute",
+        "node": { "id": options.prefix(), "type": node_type },
+        "workflow": context.workflow_path().display().to_string(),
+        "input": input,
+    }))
+}
+
+/// Returns the node output of an `execute` response
+pub(crate) fn execute_output(response: &JsonValue) -> WorkflowResult<Value> {
+    if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+        let message = error
+            .as_str()
+            .map_or_else(|| error.to_string(), str::to_string);
+        return Err(WorkflowError::NodeExecutionFailed(message));
+    }
+
+    serde_yml::to_value(response.get("output").unwrap_or(&JsonValue::Null))
+        .map_err(|e| WorkflowError::NodeExecutionFailed(format!("Invalid plugin output: {}", e)))
+}
+
+/// Returns the node type and input schema of a `describe` response
+pub(crate) fn describe_response(
+    program: &Path,
+    response: &JsonValue,
+) -> WorkflowResult<(String, Option<JsonValue>)> {
+    let node_type = response
+        .get("type")
+        .and_then(JsonValue::as_str)
+        .filter(|node_type| !node_type.trim().is_empty())
+        .ok_or_else(|| plugin_error(program, "`describe` did not return a `type`"))?;
+    let schema = response
+        .get("input_schema")
+        .filter(|schema| !schema.is_null())
+        .cloned();
+
+    Ok((node_type.to_string(), schema))
+}
+
+/// Returns the execution timeout declared by a `describe` response, if any
+pub(crate) fn describe_timeout(
+    program: &Path,
+    response: &JsonValue,
+) -> WorkflowResult<Option<Duration>> {
+    let Some(timeout) = response.get("timeout").filter(|timeout| !timeout.is_null()) else {
+        return Ok(None);
+    };
+
+    timeout
+        .as_f64()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .filter(|timeout| !timeout.is_zero())
+        .map(Some)
+        .ok_or_else(|| plugin_error(program, "`timeout` must be a positive number of seconds"))
+}
+
+/// Builds the `describe` request of the handshake
+pub(crate) fn describe_request() -> JsonValue {
+    json!({ "action": "describe" })
+}
+
+/// Sends a request to a plugin executable and reads its response
+///
+/// The request is written and the output read on separate threads, so that
+/// a plugin answering before reading its whole request cannot block, and
+/// the plugin is killed when it outlives the timeout.
+fn call(
+    program: &Path,
+    dir: Option<&Path>,
+    request: &JsonValue,
+    timeout: Duration,
+) -> WorkflowResult<JsonValue> {
+    debug!(
+        "Calling plugin {}: {}",
+        program.display(),
+        request["action"]
+    );
+
+    let mut command = Command::new(program);
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = dir.filter(|dir| !dir.as_os_str().is_empty()) {
+        command.current_dir(dir);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| plugin_error(program, &format!("cannot be run: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let request = format!("{}\n", request);
+        // A plugin may exit without reading its request, which is not an error
+        thread::spawn(move || stdin.write_all(request.as_bytes()));
+    }
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let status = wait(&mut child, timeout)
+        .map_err(|e| plugin_error(program, &format!("did not complete: {}", e)))?
+        .ok_or_else(|| {
+            plugin_error(
+                program,
+                &format!("did not answer within {} seconds", timeout.as_secs_f64()),
+            )
+        })?;
+    let stdout = String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned();
+
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr.join().unwrap_or_default()).into_owned();
+        return Err(plugin_error(
+            program,
+            &format!("exited with {}: {}", status, stderr.trim()),
+        ));
+    }
+
+    match serde_json::from_str::<JsonValue>(stdout.trim()) {
+        Ok(response) if response.is_object() => Ok(response),
+        _ => Err(plugin_error(
+            program,
+            &format!("did not answer with a JSON object: {}", stdout.trim()),
+        )),
+    }
+}
+
+/// Reads a pipe of a plugin to its end on a separate thread
+fn read_to_end(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut content = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut content);
+        }
+        content
+    })
+}
+
+/// Waits for a plugin to exit, killing it once the timeout is over
+///
+/// Returns `None` if the plugin was killed.
+fn wait(child: &mut Child, timeout: Duration) -> std::io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+pub(crate) fn plugin_error(program: &Path, message: &str) -> WorkflowError {
+    WorkflowError::Plugin {
+        program: program.to_path_buf(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(all(test, unix))]
+pub(crate) mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Writes an executable plugin echoing its execute requests
+    pub(crate) fn echo_plugin(path: &Path, node_type: &str) {
+        let script = format!(
+            r#"#!/bin/sh
+read request
+case "$request" in
+  *'"describe"'*) echo '{{"type":"{}","input_schema":{{"type":"object"}}}}' ;;
+  *'"fail"'*) echo '{{"error":"asked to fail"}}' ;;
+  *'"crash"'*) echo 'crashed' >&2; exit 3 ;;
+  *) echo "{{\"output\": $request}}" ;;
+esac
+"#,
+            node_type
+        );
+        std::fs::write(path, script).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn execute(plugin: &Plugin, input: &str) -> WorkflowResult<Value> {
+        let dir = tempfile::tempdir().unwrap();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+        let heap = Heap::new();
+        ExternalNode::new(plugin.clone(), Some(serde_yml::from_str(input).unwrap()))
+            .execute(BaseNodeRunOptions::new(&heap, "call").with_context(&context))
+    }
+
+    #[test]
+    fn test_plugin_describe() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("echo");
+        echo_plugin(&program, "Echo");
+
+        let plugin = Plugin::describe(&program).unwrap();
+        assert_eq!(plugin.node_type(), "Echo");
+        assert_eq!(plugin.program(), program);
+        assert_eq!(plugin.schema(), Some(&json!({ "type": "object" })));
+    }
+
+    #[test]
+    fn test_plugin_describe_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = Plugin::describe(dir.path().join("missing")).unwrap_err();
+        assert!(missing.to_string().contains("cannot be run"), "{}", missing);
+
+        let program = dir.path().join("silent");
+        std::fs::write(&program, "#!/bin/sh\necho '{}'\n").unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(Plugin::describe(&program)
+            .unwrap_err()
+            .to_string()
+            .ends_with("`describe` did not return a `type`"));
+    }
+
+    #[test]
+    fn test_external_node_execute() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("echo");
+        echo_plugin(&program, "Echo");
+        let plugin = Plugin::describe(&program).unwrap();
+
+        let output = execute(&plugin, "url: https://example.com").unwrap();
+        assert_eq!(output["action"], Value::from("execute"));
+        assert_eq!(output["node"]["id"], Value::from("call"));
+        assert_eq!(output["node"]["type"], Value::from("Echo"));
+        assert_eq!(output["input"]["url"], Value::from("https://example.com"));
+
+        assert_eq!(
+            execute(&plugin, "mode: fail").unwrap_err().to_string(),
+            "Node execution failed: asked to fail"
+        );
+        let error = execute(&plugin, "mode: crash").unwrap_err().to_string();
+        assert!(error.contains("crashed"), "{}", error);
+    }
+
+    /// Writes an executable plugin running a shell script
+    fn script_plugin(path: &Path, body: &str) {
+        std::fs::write(path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_describe_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("slow");
+        script_plugin(&program, r#"echo '{"type":"Slow","timeout":0.5}'"#);
+        assert_eq!(
+            Plugin::describe(&program).unwrap().timeout(),
+            Duration::from_millis(500)
+        );
+
+        script_plugin(&program, r#"echo '{"type":"Slow","timeout":-1}'"#);
+        let error = Plugin::describe(&program).unwrap_err().to_string();
+        assert!(error.contains("`timeout` must be a positive"), "{}", error);
+    }
+
+    #[test]
+    fn test_external_node_killed_after_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("sleep");
+        script_plugin(&program, "sleep 10");
+        let plugin = Plugin::new("Sleep", &program).with_timeout(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let error = execute(&plugin, "{}").unwrap_err().to_string();
+        assert!(
+            error.contains("did not answer within 0.2 seconds"),
+            "{}",
+            error
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_external_node_fails_on_unsuccessful_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("partial");
+        script_plugin(
+            &program,
+            r#"echo '{"output":"partial"}'; echo 'disk full' >&2; exit 1"#,
+        );
+        let plugin = Plugin::new("Partial", &program);
+
+        let error = execute(&plugin, "{}").unwrap_err().to_string();
+        assert!(error.contains("exited with"), "{}", error);
+        assert!(error.ends_with("disk full"), "{}", error);
+    }
+
+    #[test]
+    fn test_external_node_large_request_and_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("chatty");
+        // Writes more than a pipe buffer before reading its request
+        script_plugin(
+            &program,
+            r#"head -c 200000 /dev/zero >&2; read request; echo "{\"output\": ${#request}}""#,
+        );
+        let plugin = Plugin::new("Chatty", &program).with_timeout(Duration::from_secs(30));
+
+        let input = format!("text: {}", "a".repeat(200_000));
+        let output = execute(&plugin, &input).unwrap();
+        assert!(output.as_u64().unwrap() > 200_000);
+    }
+}
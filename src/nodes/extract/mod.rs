@@ -0,0 +1,524 @@
+//! Extract node implementation
+//!
+//! This module contains the `Extract` node type, which unpacks the files of
+//! a tar, tar.gz or zip [archive](crate::core::archive) into a directory,
+//! e.g. to install a downloaded release.
+//!
+//! ```yaml
+//! - id: unpack
+//!   type: Extract
+//!   input:
+//!     path: downloads/tool.zip
+//!     destination: tools
+//!     include: bin/**
+//! ```
+//!
+//! The format is inferred from the extension of the archive when not given,
+//! and entries are selected by [glob patterns](crate::core::glob) like in the
+//! `Archive` node. Existing files are overwritten, and Unix permissions are
+//! restored.
+//!
+//! Paths are relative to the directory of the workflow file, and both the
+//! archive and the destination must be inside it. Archives with absolute
+//! entries or entries climbing out of the destination with `..` are rejected
+//! before anything is written.
+//!
+//! Entries are streamed to disk. The decompressed size of the archive and of
+//! each entry is limited by `max_size` (4 GiB by default) and
+//! `max_entry_size` (1 GiB by default), so that zip and gzip bombs fail the
+//! node instead of filling the disk.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use serde_yml::{Mapping, Value};
+use tracing::info;
+
+use crate::core::archive::{
+    ArchiveError, ArchiveFormat, ArchiveReader, Entry, EntryKind, SizeLimits,
+};
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::glob::GlobFilter;
+use crate::nodes::archive::{
+    archive_format, filter_schema, glob_filter, required_path, ArchiveNode,
+};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// A node unpacking an archive into a directory
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::archive::{ArchiveFormat, ArchiveWriter, Entry};
+/// use colossus::core::context::RunContext;
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::extract::ExtractNode;
+/// use serde_yml::Value;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let file = std::fs::File::create(dir.path().join("tool.tar")).unwrap();
+/// let mut writer = ArchiveWriter::new(ArchiveFormat::Tar, file);
+/// writer
+///     .append(&Entry::file("bin/tool", 4).with_mode(0o755), &b"tool"[..])
+///     .unwrap();
+/// writer.finish().unwrap();
+///
+/// let input: Value = serde_yml::from_str("{ path: tool.tar, destination: tools }").unwrap();
+/// let node = ExtractNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let context = RunContext::new(dir.path().join("workflow.yml"));
+/// let output = node
+///     .execute(BaseNodeRunOptions::new(&heap, "unpack").with_context(&context))
+///     .unwrap();
+/// assert_eq!(output["files"][0], Value::from("bin/tool"));
+/// assert_eq!(std::fs::read(dir.path().join("tools/bin/tool")).unwrap(), b"tool");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractNode {
+    path: PathBuf,
+    destination: PathBuf,
+    format: ArchiveFormat,
+    filter: GlobFilter,
+    limits: SizeLimits,
+}
+
+impl ExtractNode {
+    /// Creates an extract node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the archive `path`, the `destination`
+    ///   directory, and optionally the `format` of the archive, the
+    ///   `include` and `exclude` globs, and the `max_size` and
+    ///   `max_entry_size` limits in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if the path or the destination is
+    /// missing, if the format is unknown or cannot be inferred, if a glob is
+    /// invalid, or if a limit is not a positive integer.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let path = required_path("Extract", &input, "path")?;
+        let destination = required_path("Extract", &input, "destination")?;
+        let format = archive_format("Extract", &input, &path)?;
+        let filter = glob_filter("Extract", &input)?;
+
+        let mut limits = SizeLimits::new();
+        if let Some(total) = size_limit(&input, "max_size")? {
+            limits = limits.with_total(total);
+        }
+        if let Some(entry) = size_limit(&input, "max_entry_size")? {
+            limits = limits.with_entry(entry);
+        }
+
+        Ok(Self {
+            path,
+            destination,
+            format,
+            filter,
+            limits,
+        })
+    }
+
+    /// Returns the format of the archive
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// Returns the limits on the decompressed size of the archive
+    pub fn limits(&self) -> SizeLimits {
+        self.limits
+    }
+
+    /// Checks the globs of a node input, as written in the workflow file
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message of the first invalid glob.
+    pub fn check_input(input: &Value) -> Result<(), String> {
+        ArchiveNode::check_input(input)
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        let mut schema = serde_json::json!({
+            "type": "object",
+            "required": ["path", "destination"],
+            "additionalProperties": false,
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Archive file to unpack, relative to the workflow file"
+                },
+                "destination": {
+                    "type": "string",
+                    "description": "Directory the files are written to, relative to the workflow file"
+                },
+                "max_size": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Largest decompressed size of the archive, in bytes; 4 GiB by default"
+                },
+                "max_entry_size": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Largest decompressed size of each entry, in bytes; 1 GiB by default"
+                }
+            }
+        });
+        schema["properties"]
+            .as_object_mut()
+            .unwrap()
+            .extend(filter_schema());
+        schema
+    }
+}
+
+impl BaseNode for ExtractNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let path = context.resolve_within(&self.path)?;
+        let destination = context.resolve_within(&self.destination)?;
+
+        let file = File::open(&path).map_err(|e| {
+            WorkflowError::NodeExecutionFailed(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let failed = |e: ArchiveError| {
+            WorkflowError::NodeExecutionFailed(format!("{}: {}", path.display(), e))
+        };
+        let mut reader =
+            ArchiveReader::new(self.format, BufReader::new(file)).with_limits(self.limits);
+        let entries = reader.entries().map_err(failed)?;
+
+        // Every entry is checked before anything is written
+        let mut targets = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            let relative = relative_path(&entry.path).ok_or_else(|| {
+                WorkflowError::NodeExecutionFailed(format!(
+                    "{}: entry {} would be written outside the destination",
+                    path.display(),
+                    entry.path
+                ))
+            })?;
+            let name = relative.to_string_lossy().replace('\\', "/");
+            if relative.as_os_str().is_empty() || !self.filter.selects(&name) {
+                targets.push(None);
+                continue;
+            }
+            let target = context.resolve_within(destination.join(&relative))?;
+            targets.push(Some((name, target)));
+        }
+
+        // The entries are read again in the same order, streaming each to disk
+        let mut files = Vec::new();
+        let mut targets = targets.into_iter();
+        reader
+            .for_each(|entry, content| {
+                let Some((name, target)) = targets.next().flatten() else {
+                    return Ok(());
+                };
+                extract(entry, content, &target).map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!("Failed to write {}: {}", target.display(), e),
+                    )
+                })?;
+                if entry.kind == EntryKind::File {
+                    files.push(Value::from(name));
+                }
+                Ok(())
+            })
+            .map_err(failed)?;
+
+        info!(
+            "Extract '{}' wrote {} files to {}",
+            options.prefix(),
+            files.len(),
+            destination.display()
+        );
+
+        let mut output = Mapping::new();
+        output.insert(
+            Value::from("destination"),
+            Value::from(destination.display().to_string()),
+        );
+        output.insert(Value::from("files"), Value::Sequence(files));
+        Ok(Value::Mapping(output))
+    }
+}
+
+/// Writes an entry to its target, streaming the content of files
+fn extract(entry: &Entry, content: &mut dyn Read, target: &Path) -> io::Result<()> {
+    match entry.kind {
+        // Directories stay writable, so the files in them can be extracted
+        EntryKind::Directory => {
+            std::fs::create_dir_all(target)?;
+            set_mode(target, entry.mode | 0o700)
+        }
+        EntryKind::File => {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            io::copy(content, &mut File::create(target)?)?;
+            set_mode(target, entry.mode)
+        }
+    }
+}
+
+/// Returns an optional size limit of the input, a positive integer
+fn size_limit(input: &Value, field: &str) -> WorkflowResult<Option<u64>> {
+    match input.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .filter(|limit| *limit > 0)
+            .map(Some)
+            .ok_or_else(|| {
+                WorkflowError::NodeBuilder(format!(
+                    "Extract node `{}` must be a positive integer",
+                    field
+                ))
+            }),
+    }
+}
+
+/// Returns the path of an archive entry relative to the destination
+///
+/// Returns `None` if the entry is absolute or climbs out with `..`.
+fn relative_path(entry: &str) -> Option<PathBuf> {
+    let entry = entry.replace('\\', "/");
+    let mut relative = PathBuf::new();
+    for component in Path::new(&entry).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(relative)
+}
+
+/// Sets the Unix permissions of an extracted file or directory
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode & 0o777))
+}
+
+/// Sets the permissions of an extracted file, which only exist on Unix
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::archive::ArchiveWriter;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<ExtractNode> {
+        ExtractNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn execute(node: &ExtractNode, dir: &Path) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        let context = RunContext::new(dir.join("workflow.yml"));
+        node.execute(BaseNodeRunOptions::new(&heap, "unpack").with_context(&context))
+    }
+
+    fn write_archive(dir: &Path, name: &str, entries: &[(Entry, &[u8])]) {
+        let format = ArchiveFormat::from_path(name).unwrap();
+        let mut writer = ArchiveWriter::new(format, File::create(dir.join(name)).unwrap());
+        for (entry, content) in entries {
+            writer.append(entry, *content).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    /// Writes a tar archive without checking its paths, like a malicious one
+    fn write_raw_tar(dir: &Path, name: &str, entries: &[(&str, &[u8])]) {
+        let mut builder = tar::Builder::new(File::create(dir.join(name)).unwrap());
+        for (path, content) in entries {
+            let mut header = tar::Header::new_ustar();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_mode(0o644);
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            builder.append(&header, *content).unwrap();
+        }
+        builder.finish().unwrap();
+    }
+
+    #[test]
+    fn test_extract_node_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in [("build/bin/app", "app"), ("build/README.md", "# App")] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let heap = Heap::new();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+
+        for name in ["app.tar", "app.tar.gz", "app.zip"] {
+            let input = format!("{{ source: build, path: {} }}", name);
+            ArchiveNode::new(Some(serde_yml::from_str(&input).unwrap()))
+                .unwrap()
+                .execute(BaseNodeRunOptions::new(&heap, "package").with_context(&context))
+                .unwrap();
+
+            let input = format!("{{ path: {}, destination: out/{} }}", name, name);
+            let output = execute(&node(&input).unwrap(), dir.path()).unwrap();
+            assert_eq!(
+                output["files"],
+                serde_yml::from_str::<Value>("[README.md, bin/app]").unwrap()
+            );
+            let extracted = dir.path().join("out").join(name).join("bin/app");
+            assert_eq!(std::fs::read_to_string(extracted).unwrap(), "app");
+        }
+    }
+
+    #[test]
+    fn test_extract_node_filters_and_restores_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        write_archive(
+            dir.path(),
+            "tool.zip",
+            &[
+                (Entry::directory("tool"), b""),
+                (Entry::file("tool/bin/tool", 4).with_mode(0o755), b"tool"),
+                (Entry::file("tool/docs/guide.md", 5), b"guide"),
+            ],
+        );
+
+        let output = execute(
+            &node("{ path: tool.zip, destination: tools, exclude: docs }").unwrap(),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(
+            output["files"],
+            Value::Sequence(vec![Value::from("tool/bin/tool")])
+        );
+        assert!(!dir.path().join("tools/tool/docs").exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = std::fs::metadata(dir.path().join("tools/tool/bin/tool")).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o755);
+        }
+    }
+
+    #[test]
+    fn test_extract_node_rejects_entries_leaving_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, entry) in [
+            ("climb.tar", "../../escaped.txt"),
+            ("climb.zip", "../../escaped.txt"),
+            ("absolute.zip", "/tmp/escaped.txt"),
+        ] {
+            let entries: [(&str, &[u8]); 2] = [("safe.txt", b"safe"), (entry, b"escaped")];
+            match ArchiveFormat::from_path(name).unwrap() {
+                ArchiveFormat::Zip => write_archive(
+                    dir.path(),
+                    name,
+                    &entries
+                        .map(|(path, content)| (Entry::file(path, content.len() as u64), content)),
+                ),
+                _ => write_raw_tar(dir.path(), name, &entries),
+            }
+
+            let input = format!("{{ path: {}, destination: out }}", name);
+            let error = execute(&node(&input).unwrap(), dir.path()).unwrap_err();
+            assert!(error.to_string().contains("outside the destination"));
+            assert!(!dir.path().join("out/safe.txt").exists());
+        }
+    }
+
+    #[test]
+    fn test_extract_node_confined_to_workflow_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        write_archive(dir.path(), "tool.tar", &[(Entry::file("a", 0), b"")]);
+
+        for yaml in [
+            "{ path: tool.tar, destination: ../out }",
+            "{ path: ../tool.tar, destination: out }",
+        ] {
+            assert!(matches!(
+                execute(&node(yaml).unwrap(), dir.path()),
+                Err(WorkflowError::OutsideWorkflowDir { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_extract_node_invalid_input() {
+        assert!(node("path: tool.zip").is_err());
+        assert!(node("{ path: tool.bin, destination: out }").is_err());
+        assert!(node("{ path: tool.bin, destination: out, format: zip }").is_ok());
+        assert!(node("{ path: tool.zip, destination: out, max_size: 0 }").is_err());
+        assert!(node("{ path: tool.zip, destination: out, max_entry_size: 1GiB }").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tool.zip"), "not a zip").unwrap();
+        let error = execute(
+            &node("{ path: tool.zip, destination: out }").unwrap(),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Invalid zip archive"));
+    }
+
+    #[test]
+    fn test_extract_node_size_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let zeros = vec![0; 1 << 20];
+        for name in ["bomb.tar.gz", "bomb.zip"] {
+            write_archive(
+                dir.path(),
+                name,
+                &[
+                    (Entry::file("small.txt", 5), b"small"),
+                    (Entry::file("zeros", 1 << 20), &zeros),
+                ],
+            );
+
+            let input = format!(
+                "{{ path: {}, destination: limited, max_entry_size: 1000 }}",
+                name
+            );
+            let limited = node(&input).unwrap();
+            assert_eq!(limited.limits().entry(), 1000);
+            let error = execute(&limited, dir.path()).unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("entry zeros is larger than 1000 bytes once decompressed"));
+            assert!(!dir.path().join("limited").exists());
+
+            let input = format!(
+                "{{ path: {}, destination: limited, max_size: 100000 }}",
+                name
+            );
+            let error = execute(&node(&input).unwrap(), dir.path()).unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("archive is larger than 100000 bytes once decompressed"));
+            assert!(!dir.path().join("limited").exists());
+
+            let input = format!("{{ path: {}, destination: out/{} }}", name, name);
+            execute(&node(&input).unwrap(), dir.path()).unwrap();
+            let extracted = dir.path().join("out").join(name).join("zeros");
+            assert_eq!(std::fs::metadata(extracted).unwrap().len(), 1 << 20);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Hash node implementation
+//!
+//! This module contains the `Hash` node type, which outputs the
+//! [digest](crate::core::digest) of a string or of a file as lowercase
+//! hexadecimal, e.g. to fingerprint a build artifact or to check a download
+//! against its published checksum.
+//!
+//! ```yaml
+//! - id: checksum
+//!   type: Hash
+//!   input:
+//!     file: dist/app.tar.gz
+//!     algorithm: sha256
+//! ```
+//!
+//! The algorithms are `sha256` (the default), `sha1` and `md5`, and paths are
+//! relative to the directory of the workflow file and must stay inside it.
+
+use std::path::PathBuf;
+
+use serde_yml::Value;
+
+use crate::core::context::RunContext;
+use crate::core::digest::{digest, to_hex, HashAlgorithm, ALGORITHMS};
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::to_text;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::parse::string_field;
+
+/// What a node hashes
+#[derive(Debug, Clone)]
+enum Source {
+    Text(String),
+    File(PathBuf),
+}
+
+/// A node computing the digest of a string or a file
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::hash::HashNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("{ text: abc, algorithm: sha1 }").unwrap();
+/// let node = HashNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "digest")).unwrap();
+/// assert_eq!(output, Value::from("a9993e364706816aba3e25717850c26c9cd0d89d"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashNode {
+    source: Source,
+    algorithm: HashAlgorithm,
+}
+
+impl HashNode {
+    /// Creates a hash node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with either the `text` to hash or the `file` it is
+    ///   read from, and the `algorithm`
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if neither or both of `text` and
+    /// `file` are set, or if the algorithm is unknown.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let text = match input.get("text") {
+            None | Some(Value::Null) => None,
+            Some(Value::Sequence(_) | Value::Mapping(_)) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Hash node `text` must be a string".to_string(),
+                ))
+            }
+            Some(text) => Some(to_text(text)),
+        };
+        let source = match (text, string_field("Hash", &input, "file")?) {
+            (Some(text), None) => Source::Text(text),
+            (None, Some(file)) if !file.trim().is_empty() => Source::File(PathBuf::from(file)),
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Hash node requires either a `text` or a `file`".to_string(),
+                ))
+            }
+        };
+
+        let algorithm = match string_field("Hash", &input, "algorithm")? {
+            Some(algorithm) => algorithm
+                .parse()
+                .map_err(|e| WorkflowError::NodeBuilder(format!("Hash node: {}", e)))?,
+            None => HashAlgorithm::default(),
+        };
+
+        Ok(Self { source, algorithm })
+    }
+
+    /// Returns the algorithm of the node
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "oneOf": [
+                { "required": ["text"] },
+                { "required": ["file"] }
+            ],
+            "properties": {
+                "text": {
+                    "type": ["string", "number", "boolean"],
+                    "description": "Text to hash"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "File to hash, relative to the workflow file"
+                },
+                "algorithm": {
+                    "type": "string",
+                    "enum": ALGORITHMS,
+                    "description": "Digest algorithm, sha256 by default"
+                }
+            }
+        })
+    }
+}
+
+impl BaseNode for HashNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let bytes = match &self.source {
+            Source::Text(text) => text.as_bytes().to_vec(),
+            Source::File(file) => {
+                let path = context.resolve_within(file)?;
+                std::fs::read(&path).map_err(|e| {
+                    WorkflowError::NodeExecutionFailed(format!(
+                        "Failed to read {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+            }
+        };
+
+        Ok(Value::from(to_hex(&digest(self.algorithm, &bytes))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<HashNode> {
+        HashNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    #[test]
+    fn test_hash_node_text_and_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("data.bin"), b"abc").unwrap();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+        let heap = Heap::new();
+        let execute = |node: HashNode| {
+            node.execute(BaseNodeRunOptions::new(&heap, "hash").with_context(&context))
+        };
+
+        let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(
+            execute(node("text: abc").unwrap()).unwrap(),
+            Value::from(sha256)
+        );
+        assert_eq!(
+            execute(node("file: data.bin").unwrap()).unwrap(),
+            Value::from(sha256)
+        );
+        assert_eq!(
+            execute(node("{ file: data.bin, algorithm: md5 }").unwrap()).unwrap(),
+            Value::from("900150983cd24fb0d6963f7d28e17f72")
+        );
+
+        let error = execute(node("file: missing.bin").unwrap()).unwrap_err();
+        assert!(error.to_string().contains("Failed to read"));
+    }
+
+    #[test]
+    fn test_hash_node_file_confined_to_workflow_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("flows")).unwrap();
+        std::fs::write(dir.path().join("secret.bin"), b"abc").unwrap();
+        let context = RunContext::new(dir.path().join("flows/workflow.yml"));
+        let heap = Heap::new();
+
+        assert!(matches!(
+            node("file: ../secret.bin")
+                .unwrap()
+                .execute(BaseNodeRunOptions::new(&heap, "hash").with_context(&context)),
+            Err(WorkflowError::OutsideWorkflowDir { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hash_node_invalid_input() {
+        assert!(node("algorithm: md5").is_err());
+        assert!(node("{ text: a, file: b }").is_err());
+        assert!(node("text: [a]").is_err());
+        assert_eq!(
+            node("{ text: a, algorithm: crc32 }")
+                .unwrap_err()
+                .to_string(),
+            "Node builder error: Hash node: Unknown hash algorithm 'crc32' (available: sha256, sha1, md5)"
+        );
+    }
+}
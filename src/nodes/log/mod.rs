@@ -7,7 +7,7 @@ use crate::{core::engine::WorkflowError, nodes::base::BaseNode};
 ///
 /// This node is useful for debugging and providing feedback during
 /// workflow execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct LogNode {
     input: Option<Value>,
 }
@@ -39,6 +39,15 @@ impl LogNode {
     pub fn input(&self) -> Option<&Value> {
         self.input.as_ref()
     }
+
+    /// Returns the JSON Schema of the node input
+    ///
+    /// Any value can be logged, so the schema only documents the input.
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "description": "Value to log; strings may contain ${{ ... }} placeholders"
+        })
+    }
 }
 
 impl BaseNode for LogNode {
@@ -50,12 +59,6 @@ impl BaseNode for LogNode {
     }
 }
 
-impl Default for LogNode {
-    fn default() -> Self {
-        Self { input: None }
-    }
-}
-
 impl From<Value> for LogNode {
     fn from(input: Value) -> Self {
         Self::new(Some(input))
@@ -7,8 +7,29 @@
 //!
 //! The nodes module provides the building blocks for workflow execution:
 //!
+//! - **Archive**: File node packing a directory into a tar, tar.gz or zip archive
+//! - **Assert**: Checking node failing when a condition does not hold
 //! - **Base**: Core node trait and execution options
+//! - **Base64**: Encoding node converting text to and from Base64
+//! - **DateFormat**: Time node formatting a date in a timezone and format
+//! - **External**: Nodes implemented by plugin executables
+//! - **Extract**: File node unpacking a tar, tar.gz or zip archive into a directory
+//! - **Hash**: Digest node computing the SHA-256, SHA-1 or MD5 of text or a file
 //! - **Log**: Logging node for debugging and output
+//! - **Now**: Time node outputting the current time of the run
+//! - **Parse**: Conversion node parsing JSON, YAML, TOML or CSV text into a value
+//! - **Query**: Selection node applying a jq query to a value
+//! - **Random**: Node drawing a random integer or list element
+//! - **Regex**: Text node matching, extracting or replacing with a regular expression
+//! - **Registry**: Node types available to workflows
+//! - **Script**: Sandboxed Rhai script computing a value from the heap
+//! - **Serialize**: Conversion node writing a value as JSON, YAML, TOML or CSV
+//! - **Switch**: Branching node choosing which downstream nodes run
+//! - **Template**: Rendering node generating text or files from a template
+//! - **UUID**: Node generating a random UUID
+//! - **WaitUntil**: Polling node retrying a node or condition until it succeeds
+//! - **Wasm**: Nodes implemented by sandboxed WebAssembly modules
+//! - **Workflow**: Sub-workflow node running another workflow file
 //! - **Builder**: Fluent interface for creating nodes
 //!
 //! # Examples
@@ -29,14 +50,35 @@
 
 use crate::{
     core::{engine::WorkflowResult, heap::Heap},
-    nodes::{base::BaseNode, log::LogNode},
+    nodes::{base::BaseNode, registry::NodeRegistry},
 };
 use serde_yml::Value;
 
 use crate::shared::types::workflow::node::WorkflowNode;
 
+pub mod archive;
+pub mod assert;
 pub mod base;
+pub mod base64;
+pub mod date_format;
+pub mod external;
+pub mod extract;
+pub mod hash;
 pub mod log;
+pub mod now;
+pub mod parse;
+pub mod query;
+pub mod random;
+pub mod regex;
+pub mod registry;
+pub mod script;
+pub mod serialize;
+pub mod switch;
+pub mod template;
+pub mod uuid;
+pub mod wait_until;
+pub mod wasm;
+pub mod workflow;
 
 /// Builder for creating workflow nodes
 ///
@@ -46,6 +88,7 @@ pub mod log;
 pub struct NodeBuilder {
     workflow_node: Option<WorkflowNode>,
     input: Option<Value>,
+    registry: Option<NodeRegistry>,
 }
 
 impl NodeBuilder {
@@ -62,6 +105,7 @@ impl NodeBuilder {
         Self {
             workflow_node: None,
             input: None,
+            registry: None,
         }
     }
 
@@ -114,8 +158,35 @@ impl NodeBuilder {
         self
     }
 
+    /// Sets the registry used to resolve the node type
+    ///
+    /// When no registry is set, only the built-in node types are available.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry of available node types
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::nodes::{NodeBuilder, registry::NodeRegistry};
+    ///
+    /// let builder = NodeBuilder::new().with_registry(NodeRegistry::new());
+    /// ```
+    pub fn with_registry(mut self, registry: NodeRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
     /// Builds a node instance from the configuration
     ///
+    /// The placeholders of the input are substituted from the heap, unless
+    /// the registry defers the input of the node type.
+    ///
     /// # Arguments
     ///
     /// * `heap` - The heap containing shared data
@@ -138,8 +209,6 @@ impl NodeBuilder {
     /// let node_instance = builder.build(&mut heap).expect("Failed to build node");
     /// ```
     pub fn build(self, heap: &mut Heap) -> WorkflowResult<Box<dyn BaseNode>> {
-        let input = heap.parse(self.input);
-
         let node_type = self
             .workflow_node
             .as_ref()
@@ -151,10 +220,14 @@ impl NodeBuilder {
             .node_type
             .clone();
 
-        match node_type.as_str() {
-            "Log" => Ok(Box::new(LogNode::new(input))),
-            _ => Err(crate::core::engine::WorkflowError::InvalidNode(node_type)),
-        }
+        let registry = self.registry.unwrap_or_default();
+        let input = if registry.defers_input(&node_type) {
+            self.input
+        } else {
+            heap.parse(self.input)
+        };
+
+        registry.create(&node_type, input)
     }
 
     /// Gets a reference to the workflow node configuration
@@ -258,6 +331,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_node_builder_build_with_registry() {
+        let mut heap = Heap::new();
+        let registry = NodeRegistry::empty().with_node("Echo", |input| {
+            Ok(Box::new(crate::nodes::log::LogNode::new(input)))
+        });
+
+        let echo = NodeBuilder::new()
+            .with_workflow_node(WorkflowNode::new("echo", "Echo", None))
+            .with_registry(registry.clone());
+        assert!(echo.build(&mut heap).is_ok());
+
+        let log = NodeBuilder::new()
+            .with_workflow_node(WorkflowNode::new("log", "Log", None))
+            .with_registry(registry);
+        assert!(log.build(&mut heap).is_err());
+    }
+
     #[test]
     fn test_node_builder_build_no_workflow_node() {
         let mut heap = Heap::new();
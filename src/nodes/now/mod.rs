@@ -0,0 +1,371 @@
+//! Now node implementation
+//!
+//! This module contains the `Now` node type, which outputs the current time
+//! of the run as text, e.g. to stamp release notes or name backups.
+//!
+//! ```yaml
+//! - id: stamp
+//!   type: Now
+//!   input:
+//!     timezone: "+02:00"
+//!     format: "%Y-%m-%d %H:%M"
+//! ```
+//!
+//! The time is read from the [clock](crate::core::clock) of the run, so tests
+//! injecting a manual clock get a fixed time. Times are formatted with
+//! [strftime](https://docs.rs/chrono/latest/chrono/format/strftime/index.html)
+//! specifiers, as RFC 3339 by default, in UTC unless a timezone is given.
+//! Timezones are `UTC`, `local`, an IANA name such as `Europe/Paris`, whose
+//! offset follows daylight saving time, or a fixed offset such as `-05:00`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use serde_yml::Value;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::placeholders;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::parse::string_field;
+
+/// Timezone a time is shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timezone {
+    /// Coordinated Universal Time, the default
+    #[default]
+    Utc,
+    /// Timezone of the machine running the workflow
+    Local,
+    /// Timezone of the IANA database, such as `Europe/Paris`
+    Named(Tz),
+    /// Fixed offset from UTC
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    /// Formats a time in this timezone
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time to format
+    /// * `format` - strftime format, RFC 3339 when not given
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::{TimeZone, Utc};
+    /// use colossus::nodes::now::Timezone;
+    ///
+    /// let time = Utc.with_ymd_and_hms(2024, 3, 1, 22, 30, 0).unwrap();
+    /// let timezone: Timezone = "+02:00".parse().unwrap();
+    /// assert_eq!(timezone.format(time, None), "2024-03-02T00:30:00+02:00");
+    /// assert_eq!(timezone.format(time, Some("%d/%m %H:%M")), "02/03 00:30");
+    ///
+    /// let timezone: Timezone = "Europe/Paris".parse().unwrap();
+    /// assert_eq!(timezone.format(time, None), "2024-03-01T23:30:00+01:00");
+    /// ```
+    pub fn format(&self, time: DateTime<Utc>, format: Option<&str>) -> String {
+        match self {
+            Self::Utc => format_in(time, format),
+            Self::Local => format_in(time.with_timezone(&Local), format),
+            Self::Named(tz) => format_in(time.with_timezone(tz), format),
+            Self::Fixed(offset) => format_in(time.with_timezone(offset), format),
+        }
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "UTC" | "utc" | "Z" => Ok(Self::Utc),
+            "local" => Ok(Self::Local),
+            _ => parse_offset(name)
+                .map(Self::Fixed)
+                .or_else(|| name.parse().ok().map(Self::Named))
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown timezone '{}', expected UTC, local, a name such as Europe/Paris or an offset such as +02:00",
+                        name
+                    )
+                }),
+        }
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => write!(f, "UTC"),
+            Self::Local => write!(f, "local"),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+            Self::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+/// A node outputting the current time
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use chrono::{TimeZone, Utc};
+/// use colossus::core::clock::ManualClock;
+/// use colossus::core::context::RunContext;
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::now::NowNode;
+/// use serde_yml::Value;
+///
+/// let time = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+/// let context = RunContext::new("workflow.yml").with_clock(Arc::new(ManualClock::new(time)));
+///
+/// let node = NowNode::new(None).unwrap();
+/// let heap = Heap::new();
+/// let output = node
+///     .execute(BaseNodeRunOptions::new(&heap, "stamp").with_context(&context))
+///     .unwrap();
+/// assert_eq!(output, Value::from("2024-01-01T12:00:00Z"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NowNode {
+    timezone: Timezone,
+    format: Option<String>,
+}
+
+impl NowNode {
+    /// Creates a now node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Optional mapping with the `timezone` and `format` of the
+    ///   output
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if the timezone or the format is
+    /// invalid.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+        let (timezone, format) = time_options("Now", &input)?;
+        Ok(Self { timezone, format })
+    }
+
+    /// Checks the timezone and format of a node input, as written in the
+    /// workflow file
+    ///
+    /// Fields containing `${{ ... }}` placeholders are only known when the
+    /// workflow runs, and are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if the timezone or the format is invalid.
+    pub fn check_input(input: &Value) -> Result<(), String> {
+        if let Some(Value::String(timezone)) = input.get("timezone") {
+            if placeholders(timezone).is_empty() {
+                timezone.parse::<Timezone>()?;
+            }
+        }
+        if let Some(Value::String(format)) = input.get("format") {
+            if placeholders(format).is_empty() {
+                check_format(format)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        time_schema()
+    }
+}
+
+impl BaseNode for NowNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let now = context.clock().now();
+        Ok(Value::from(
+            self.timezone.format(now, self.format.as_deref()),
+        ))
+    }
+}
+
+/// Returns the timezone and format of a `Now` or `DateFormat` input
+pub(crate) fn time_options(
+    node: &str,
+    input: &Value,
+) -> WorkflowResult<(Timezone, Option<String>)> {
+    let timezone = match string_field(node, input, "timezone")? {
+        Some(timezone) => timezone
+            .parse()
+            .map_err(|e| WorkflowError::NodeBuilder(format!("{} node: {}", node, e)))?,
+        None => Timezone::Utc,
+    };
+    let format = string_field(node, input, "format")?
+        .map(|format| {
+            check_format(format)
+                .map(|_| format.to_string())
+                .map_err(|e| WorkflowError::NodeBuilder(format!("{} node: {}", node, e)))
+        })
+        .transpose()?;
+    Ok((timezone, format))
+}
+
+/// Returns the JSON Schema shared by the `Now` and `DateFormat` inputs
+pub(crate) fn time_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "timezone": {
+                "type": "string",
+                "description": "Timezone of the output: UTC (default), local, a name such as Europe/Paris or an offset such as +02:00"
+            },
+            "format": {
+                "type": "string",
+                "description": "strftime format of the output, e.g. %Y-%m-%d, RFC 3339 by default"
+            }
+        }
+    })
+}
+
+/// Checks that a strftime format only uses known specifiers
+pub(crate) fn check_format(format: &str) -> Result<(), String> {
+    match StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        true => Err(format!("Invalid time format '{}'", format)),
+        false => Ok(()),
+    }
+}
+
+/// Formats a time, as RFC 3339 when no format is given
+fn format_in<Tz>(time: DateTime<Tz>, format: Option<&str>) -> String
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: fmt::Display,
+{
+    match format {
+        Some(format) => time.format(format).to_string(),
+        None => time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+    }
+}
+
+/// Parses an offset such as `+02:00`, `-0530` or `+01`
+fn parse_offset(text: &str) -> Option<FixedOffset> {
+    let (sign, digits) = match text.as_bytes().first()? {
+        b'+' => (1, &text[1..]),
+        b'-' => (-1, &text[1..]),
+        _ => return None,
+    };
+    let digits = digits.replace(':', "");
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (digits[..2].parse::<i32>().ok()?, digits[2..].parse().ok()?),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::ManualClock;
+    use crate::core::heap::Heap;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    fn node(yaml: &str) -> WorkflowResult<NowNode> {
+        NowNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    #[test]
+    fn test_now_node_reads_clock() {
+        let time = Utc.with_ymd_and_hms(2024, 12, 31, 23, 59, 30).unwrap();
+        let context = RunContext::new("workflow.yml").with_clock(Arc::new(ManualClock::new(time)));
+        let heap = Heap::new();
+        let execute = |node: NowNode| {
+            node.execute(BaseNodeRunOptions::new(&heap, "now").with_context(&context))
+                .unwrap()
+        };
+
+        assert_eq!(
+            execute(NowNode::new(None).unwrap()),
+            Value::from("2024-12-31T23:59:30Z")
+        );
+        assert_eq!(
+            execute(node("{ timezone: '+01:00' }").unwrap()),
+            Value::from("2025-01-01T00:59:30+01:00")
+        );
+        assert_eq!(
+            execute(node("{ timezone: '-0530', format: '%Y-%m-%d %H:%M %z' }").unwrap()),
+            Value::from("2024-12-31 18:29 -0530")
+        );
+        assert_eq!(
+            execute(node("{ format: '%s' }").unwrap()),
+            Value::from(time.timestamp().to_string())
+        );
+    }
+
+    #[test]
+    fn test_timezone_names() {
+        assert_eq!("UTC".parse(), Ok(Timezone::Utc));
+        assert_eq!("local".parse(), Ok(Timezone::Local));
+        assert_eq!("+01".parse::<Timezone>().unwrap().to_string(), "+01:00");
+        assert!("+25:00".parse::<Timezone>().is_err());
+        assert!("+01:60".parse::<Timezone>().is_err());
+        assert_eq!(
+            "Europe/Paris".parse::<Timezone>().unwrap().to_string(),
+            "Europe/Paris"
+        );
+        assert_eq!(
+            "Mars/Olympus".parse::<Timezone>(),
+            Err(
+                "Unknown timezone 'Mars/Olympus', expected UTC, local, a name such as Europe/Paris or an offset such as +02:00"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_timezone_follows_daylight_saving_time() {
+        let timezone: Timezone = "America/New_York".parse().unwrap();
+        let winter = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let summer = Utc.with_ymd_and_hms(2024, 7, 15, 12, 0, 0).unwrap();
+
+        assert_eq!(timezone.format(winter, None), "2024-01-15T07:00:00-05:00");
+        assert_eq!(timezone.format(summer, None), "2024-07-15T08:00:00-04:00");
+    }
+
+    #[test]
+    fn test_now_node_invalid_input() {
+        assert!(node("{ timezone: Mars }").is_err());
+        assert!(node("{ format: 3 }").is_err());
+        assert_eq!(
+            node("{ format: '%Y-%Q' }").unwrap_err().to_string(),
+            "Node builder error: Now node: Invalid time format '%Y-%Q'"
+        );
+    }
+
+    #[test]
+    fn test_now_node_check_input() {
+        let input = |yaml: &str| serde_yml::from_str::<Value>(yaml).unwrap();
+
+        assert!(NowNode::check_input(&input("{ timezone: UTC, format: '%F' }")).is_ok());
+        assert!(NowNode::check_input(&input("{ format: '${{ format }}%Q' }")).is_ok());
+        assert!(NowNode::check_input(&input("{ timezone: Mars }")).is_err());
+        assert!(NowNode::check_input(&input("{ format: '%Q' }")).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! Parse node implementation
+//!
+//! This module contains the `Parse` node type, which parses JSON, YAML,
+//! TOML or CSV text into a value, e.g. the output of a command or the
+//! content of a file. The parsed value is the output of the node.
+//!
+//! ```yaml
+//! - id: hosts
+//!   type: Parse
+//!   input:
+//!     file: inventory/hosts.csv
+//!
+//! - id: ping
+//!   type: Log
+//!   for_each: "${{ hosts }}"
+//!   input:
+//!     message: "Pinging ${{ item.name }} on port ${{ item.port }}"
+//! ```
+//!
+//! The format is inferred from the file extension when not given, and paths
+//! are relative to the directory of the workflow file and must stay inside
+//! it.
+
+use std::path::PathBuf;
+
+use serde_yml::Value;
+use tracing::debug;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::to_text;
+use crate::core::format::{self, CsvHeaders, CsvOptions, Format, FORMATS};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// Where the text of a node comes from
+#[derive(Debug, Clone)]
+enum Source {
+    Text(String),
+    File(PathBuf),
+}
+
+/// A node parsing text into a value
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::parse::ParseNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str(r#"{ format: json, text: '{"port": 8080}' }"#).unwrap();
+/// let node = ParseNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "config")).unwrap();
+/// assert_eq!(output["port"], Value::from(8080));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseNode {
+    source: Source,
+    format: Format,
+    csv: CsvOptions,
+}
+
+impl ParseNode {
+    /// Creates a parse node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with either the `text` to parse or the `file` it
+    ///   is read from, its `format`, and the CSV `headers` and `delimiter`
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if neither or both of `text` and
+    /// `file` are set, or if the format is missing or unknown.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let text = match input.get("text") {
+            None | Some(Value::Null) => None,
+            Some(Value::Sequence(_) | Value::Mapping(_)) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Parse node `text` must be a string".to_string(),
+                ))
+            }
+            Some(text) => Some(to_text(text)),
+        };
+        let file = string_field("Parse", &input, "file")?;
+
+        let (source, path) = match (text, file) {
+            (Some(text), None) => (Source::Text(text), None),
+            (None, Some(file)) if !file.trim().is_empty() => {
+                (Source::File(PathBuf::from(file)), Some(file))
+            }
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Parse node requires either a `text` or a `file`".to_string(),
+                ))
+            }
+        };
+        let (format, csv) = format_options("Parse", &input, path)?;
+
+        Ok(Self {
+            source,
+            format,
+            csv,
+        })
+    }
+
+    /// Returns the format of the parsed text
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        let mut schema = format_schema("Format of the text, inferred from the file extension");
+        schema["oneOf"] = serde_json::json!([
+            { "required": ["text"] },
+            { "required": ["file"] }
+        ]);
+        schema["properties"]["text"] = serde_json::json!({
+            "type": "string",
+            "description": "Text to parse, e.g. the output of a command"
+        });
+        schema["properties"]["file"] = serde_json::json!({
+            "type": "string",
+            "description": "File to parse, relative to the workflow file"
+        });
+        schema
+    }
+}
+
+impl BaseNode for ParseNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let text = match &self.source {
+            Source::Text(text) => text.clone(),
+            Source::File(file) => {
+                let path = context.resolve_within(file)?;
+                std::fs::read_to_string(&path).map_err(|e| {
+                    WorkflowError::NodeExecutionFailed(format!(
+                        "Failed to read {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+            }
+        };
+
+        debug!(
+            "Parse '{}' parsing {} bytes of {}",
+            options.prefix(),
+            text.len(),
+            self.format
+        );
+        format::parse(&text, self.format, &self.csv)
+            .map_err(|e| WorkflowError::NodeExecutionFailed(e.to_string()))
+    }
+}
+
+/// Returns the format and CSV options of a `Parse` or `Serialize` input
+///
+/// The format is inferred from the extension of `path` when not given.
+pub(crate) fn format_options(
+    node: &str,
+    input: &Value,
+    path: Option<&str>,
+) -> WorkflowResult<(Format, CsvOptions)> {
+    let format = match string_field(node, input, "format")? {
+        Some(name) => name
+            .parse::<Format>()
+            .map_err(|e| WorkflowError::NodeBuilder(format!("{} node: {}", node, e)))?,
+        None => path.and_then(Format::from_path).ok_or_else(|| {
+            WorkflowError::NodeBuilder(format!("{} node requires a `format`", node))
+        })?,
+    };
+
+    let mut csv = CsvOptions::new();
+    match input.get("headers") {
+        None | Some(Value::Null) | Some(Value::Bool(true)) => {}
+        Some(Value::Bool(false)) => csv = csv.with_headers(CsvHeaders::Absent),
+        Some(Value::Sequence(names)) if !names.is_empty() => {
+            csv = csv.with_headers(CsvHeaders::Names(names.iter().map(to_text).collect()))
+        }
+        Some(_) => {
+            return Err(WorkflowError::NodeBuilder(format!(
+                "{} node `headers` must be a boolean or a list of column names",
+                node
+            )))
+        }
+    }
+    if let Some(delimiter) = string_field(node, input, "delimiter")? {
+        match delimiter.as_bytes() {
+            [delimiter] => csv = csv.with_delimiter(*delimiter),
+            _ => {
+                return Err(WorkflowError::NodeBuilder(format!(
+                    "{} node `delimiter` must be a single ASCII character",
+                    node
+                )))
+            }
+        }
+    }
+
+    Ok((format, csv))
+}
+
+/// Returns the JSON Schema shared by the `Parse` and `Serialize` inputs
+pub(crate) fn format_schema(format_description: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "format": {
+                "type": "string",
+                "enum": FORMATS,
+                "description": format_description
+            },
+            "headers": {
+                "oneOf": [
+                    { "type": "boolean" },
+                    { "type": "array", "items": { "type": "string" }, "minItems": 1 }
+                ],
+                "description": "CSV only: whether the first row holds the column names, or the column names"
+            },
+            "delimiter": {
+                "type": "string",
+                "minLength": 1,
+                "maxLength": 1,
+                "description": "CSV only: field delimiter, a comma by default"
+            }
+        }
+    })
+}
+
+/// Returns an optional string field of the input
+pub(crate) fn string_field<'a>(
+    node: &str,
+    input: &'a Value,
+    field: &str,
+) -> WorkflowResult<Option<&'a str>> {
+    match input.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(text)) => Ok(Some(text)),
+        Some(_) => Err(WorkflowError::NodeBuilder(format!(
+            "{} node `{}` must be a string",
+            node, field
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<ParseNode> {
+        ParseNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn execute(node: &ParseNode, context: &RunContext) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        node.execute(BaseNodeRunOptions::new(&heap, "parse").with_context(context))
+    }
+
+    #[test]
+    fn test_parse_node_text() {
+        let context = RunContext::default();
+        let output = execute(
+            &node("format: toml\ntext: \"[server]\\nport = 8080\"").unwrap(),
+            &context,
+        )
+        .unwrap();
+        assert_eq!(output["server"]["port"], Value::from(8080));
+
+        let output = execute(
+            &node("format: csv\nheaders: false\ndelimiter: ';'\ntext: \"a;b\\n1;2\"").unwrap(),
+            &context,
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            serde_yml::from_str::<Value>("[[a, b], ['1', '2']]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_node_file_infers_format() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("hosts.csv"), "name,port\nweb,80\n").unwrap();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+
+        let parse = node("file: hosts.csv").unwrap();
+        assert_eq!(parse.format(), Format::Csv);
+        assert_eq!(
+            execute(&parse, &context).unwrap(),
+            serde_yml::from_str::<Value>("[{ name: web, port: '80' }]").unwrap()
+        );
+
+        let missing = node("file: missing.json").unwrap();
+        assert!(execute(&missing, &context)
+            .unwrap_err()
+            .to_string()
+            .contains("Failed to read"));
+    }
+
+    #[test]
+    fn test_parse_node_file_confined_to_workflow_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("flows")).unwrap();
+        std::fs::write(dir.path().join("secret.json"), "{}").unwrap();
+        let context = RunContext::new(dir.path().join("flows/workflow.yml"));
+
+        assert!(matches!(
+            execute(&node("file: ../secret.json").unwrap(), &context),
+            Err(WorkflowError::OutsideWorkflowDir { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_node_invalid_text() {
+        let error = execute(
+            &node("format: json\ntext: '{'").unwrap(),
+            &RunContext::default(),
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Node execution failed: Invalid JSON: "));
+    }
+
+    #[test]
+    fn test_parse_node_invalid_input() {
+        assert!(node("format: json").is_err());
+        assert!(node("text: a\nfile: b.json").is_err());
+        assert!(node("text: a").is_err());
+        assert!(node("file: notes.txt").is_err());
+        assert!(node("format: xml\ntext: a").is_err());
+        assert!(node("format: csv\ntext: a\nheaders: []").is_err());
+        assert!(node("format: csv\ntext: a\ndelimiter: ';;'").is_err());
+        assert!(node("format: json\ntext: [1]").is_err());
+    }
+}
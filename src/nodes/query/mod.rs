@@ -0,0 +1,237 @@
+//! Query node implementation
+//!
+//! This module contains the `Query` node type, which applies a
+//! [jq query](crate::core::query) to a value, typically the output of another
+//! node, and outputs what it selects.
+//!
+//! ```yaml
+//! - id: unhealthy
+//!   type: Query
+//!   input:
+//!     from: "${{ status }}"
+//!     query: ".services[] | select(.healthy | not) | .name"
+//!     all: true
+//! ```
+//!
+//! A query selecting a single value outputs that value, and a query
+//! selecting several values outputs them as a sequence. With `all`, the
+//! output is always a sequence, which suits `for_each`. The query is checked
+//! when the workflow is validated, unless it contains placeholders.
+
+use serde_yml::Value;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::placeholders;
+use crate::core::query::Query;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// A node selecting values with a jq query
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::query::QueryNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str(
+///     "{ from: { items: [{ id: 1 }, { id: 2 }] }, query: '[.items[].id] | max' }",
+/// )
+/// .unwrap();
+/// let node = QueryNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "latest")).unwrap();
+/// assert_eq!(output, Value::from(2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryNode {
+    from: Value,
+    query: Query,
+    all: bool,
+}
+
+impl QueryNode {
+    /// Creates a query node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the value to query `from`, the jq `query`,
+    ///   and whether to output `all` results as a sequence
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if `from` or `query` is missing,
+    /// or if the query is invalid.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let from = input
+            .as_mapping()
+            .and_then(|input| input.get("from"))
+            .cloned()
+            .ok_or_else(|| {
+                WorkflowError::NodeBuilder("Query node requires a `from`".to_string())
+            })?;
+
+        let query = match input.get("query") {
+            Some(Value::String(query)) if !query.trim().is_empty() => Query::parse(query)
+                .map_err(|e| WorkflowError::NodeBuilder(format!("Invalid query: {}", e)))?,
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Query node requires a `query` string".to_string(),
+                ))
+            }
+        };
+
+        let all = match input.get("all") {
+            None | Some(Value::Null) => false,
+            Some(Value::Bool(all)) => *all,
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Query node `all` must be a boolean".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self { from, query, all })
+    }
+
+    /// Returns the query of the node
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Checks the query of a node input, as written in the workflow file
+    ///
+    /// Queries containing `${{ ... }}` placeholders are only known when the
+    /// workflow runs, and are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if the query is invalid.
+    pub fn check_input(input: &Value) -> Result<(), String> {
+        match input.get("query") {
+            Some(Value::String(query)) if placeholders(query).is_empty() => Query::parse(query)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid query: {}", e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["from", "query"],
+            "additionalProperties": false,
+            "properties": {
+                "from": {
+                    "description": "Value to query, e.g. ${{ node_id }}"
+                },
+                "query": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "jq query selecting values, e.g. .items[] | .name"
+                },
+                "all": {
+                    "type": "boolean",
+                    "description": "Output a sequence even when the query selects a single value"
+                }
+            }
+        })
+    }
+}
+
+impl BaseNode for QueryNode {
+    fn execute(&self, _options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let mut results = self.query.run(&self.from).map_err(|e| {
+            WorkflowError::NodeExecutionFailed(format!(
+                "Query '{}' failed: {}",
+                self.query.source(),
+                e
+            ))
+        })?;
+
+        Ok(match results.len() {
+            _ if self.all => Value::Sequence(results),
+            0 => Value::Null,
+            1 => results.remove(0),
+            _ => Value::Sequence(results),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<QueryNode> {
+        QueryNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn execute(yaml: &str) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        node(yaml)?.execute(BaseNodeRunOptions::new(&heap, "query"))
+    }
+
+    #[test]
+    fn test_query_node_results() {
+        let from = "from: { items: [{ name: a, size: 3 }, { name: b, size: 12 }] }";
+
+        assert_eq!(
+            execute(&format!("{}\nquery: .items[0].name", from)).unwrap(),
+            Value::from("a")
+        );
+        assert_eq!(
+            execute(&format!("{}\nquery: .items[].name", from)).unwrap(),
+            serde_yml::from_str::<Value>("[a, b]").unwrap()
+        );
+        assert_eq!(
+            execute(&format!("{}\nquery: .items[] | select(.size > 100)", from)).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            execute(&format!(
+                "{}\nquery: .items[] | select(.size > 10) | .name\nall: true",
+                from
+            ))
+            .unwrap(),
+            serde_yml::from_str::<Value>("[b]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_query_node_run_error() {
+        let error = execute("from: 1\nquery: '.[]'").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Node execution failed: Query '.[]' failed: "));
+    }
+
+    #[test]
+    fn test_query_node_invalid_input() {
+        assert!(node("query: .a").is_err());
+        assert!(node("from: 1").is_err());
+        assert!(node("from: 1\nquery: ''").is_err());
+        assert!(node("from: 1\nquery: .a\nall: yes please").is_err());
+        assert_eq!(
+            node("from: 1\nquery: '.a | nme'").unwrap_err().to_string(),
+            "Node builder error: Invalid query: Undefined filter 'nme' at position 5"
+        );
+    }
+
+    #[test]
+    fn test_query_node_check_input() {
+        let input = |yaml: &str| serde_yml::from_str::<Value>(yaml).unwrap();
+
+        assert!(QueryNode::check_input(&input("{ from: 1, query: .a }")).is_ok());
+        assert!(QueryNode::check_input(&input("{ from: 1, query: '.${{ field }}' }")).is_ok());
+        assert_eq!(
+            QueryNode::check_input(&input("{ from: 1, query: '.a[' }")),
+            Err("Invalid query: Expected closing bracket at position 3".to_string())
+        );
+    }
+}
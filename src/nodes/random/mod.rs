@@ -0,0 +1,222 @@
+//! Random node implementation
+//!
+//! This module contains the `Random` node type, which outputs a random
+//! integer in a range, or a random element of a list, e.g. to pick a port or
+//! spread jobs over a set of hosts.
+//!
+//! ```yaml
+//! - id: port
+//!   type: Random
+//!   input:
+//!     min: 20000
+//!     max: 29999
+//!
+//! - id: host
+//!   type: Random
+//!   input:
+//!     choices: "${{ hosts }}"
+//! ```
+//!
+//! Ranges include both bounds, and `min` is 0 by default. Values are drawn
+//! from the [randomness source](crate::core::random) of the run, so tests
+//! injecting a seeded source get the same values on every run.
+
+use serde_yml::Value;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// What a random node draws from
+#[derive(Debug, Clone)]
+enum Draw {
+    Range { min: i64, max: i64 },
+    Choices(Vec<Value>),
+}
+
+/// A node outputting a random integer or list element
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::random::RandomNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("{ min: 1, max: 6 }").unwrap();
+/// let node = RandomNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "dice")).unwrap();
+/// assert!((1..=6).contains(&output.as_i64().unwrap()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomNode {
+    draw: Draw,
+}
+
+impl RandomNode {
+    /// Creates a random node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with either the `min` and `max` of an integer
+    ///   range, or the `choices` to pick from
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if neither a range nor choices
+    /// are given, if the range is empty, or if there are no choices.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let draw = match (input.get("choices"), input.get("max")) {
+            (Some(Value::Sequence(choices)), None) if input.get("min").is_none() => {
+                if choices.is_empty() {
+                    return Err(WorkflowError::NodeBuilder(
+                        "Random node `choices` must not be empty".to_string(),
+                    ));
+                }
+                Draw::Choices(choices.clone())
+            }
+            (None, Some(max)) => {
+                let min = match input.get("min") {
+                    None | Some(Value::Null) => Some(0),
+                    Some(min) => min.as_i64(),
+                };
+                match (min, max.as_i64()) {
+                    (Some(min), Some(max)) if min <= max => Draw::Range { min, max },
+                    (Some(_), Some(_)) => {
+                        return Err(WorkflowError::NodeBuilder(
+                            "Random node `min` must not be greater than `max`".to_string(),
+                        ))
+                    }
+                    _ => {
+                        return Err(WorkflowError::NodeBuilder(
+                            "Random node `min` and `max` must be integers".to_string(),
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Random node requires either a `max` or a list of `choices`".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self { draw })
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "oneOf": [
+                { "required": ["max"] },
+                { "required": ["choices"] }
+            ],
+            "properties": {
+                "min": {
+                    "type": "integer",
+                    "description": "Smallest integer drawn, 0 by default"
+                },
+                "max": {
+                    "type": "integer",
+                    "description": "Largest integer drawn"
+                },
+                "choices": {
+                    "type": "array",
+                    "minItems": 1,
+                    "description": "Values to pick one from"
+                }
+            }
+        })
+    }
+}
+
+impl BaseNode for RandomNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+        let random = context.random();
+
+        Ok(match &self.draw {
+            Draw::Range { min, max } => {
+                let span = max.abs_diff(*min);
+                let offset = match span.checked_add(1) {
+                    Some(bound) => random.below(bound),
+                    None => random.next_u64(),
+                };
+                Value::from(min.wrapping_add_unsigned(offset))
+            }
+            Draw::Choices(choices) => {
+                let index = random.below(choices.len() as u64) as usize;
+                choices[index].clone()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+    use crate::core::random::SeededRandom;
+    use std::sync::Arc;
+
+    fn node(yaml: &str) -> WorkflowResult<RandomNode> {
+        RandomNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn draws(yaml: &str, seed: u64) -> Vec<Value> {
+        let context =
+            RunContext::new("workflow.yml").with_random(Arc::new(SeededRandom::new(seed)));
+        let heap = Heap::new();
+        let node = node(yaml).unwrap();
+        (0..200)
+            .map(|_| {
+                node.execute(BaseNodeRunOptions::new(&heap, "random").with_context(&context))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_random_node_range() {
+        let values = draws("{ min: -2, max: 2 }", 1);
+        assert_eq!(values, draws("{ min: -2, max: 2 }", 1));
+        for expected in -2..=2 {
+            assert!(values.contains(&Value::from(expected)));
+        }
+        assert!(values
+            .iter()
+            .all(|value| (-2..=2).contains(&value.as_i64().unwrap())));
+
+        assert!(draws("max: 0", 1)
+            .iter()
+            .all(|value| value == &Value::from(0)));
+        let extremes = format!("{{ min: {}, max: {} }}", i64::MIN, i64::MAX);
+        assert_eq!(draws(&extremes, 1).len(), 200);
+    }
+
+    #[test]
+    fn test_random_node_choices() {
+        let values = draws("choices: [web, db, { name: cache }]", 2);
+        assert_eq!(values, draws("choices: [web, db, { name: cache }]", 2));
+        assert!(values.contains(&Value::from("db")));
+        assert!(values.contains(&serde_yml::from_str("{ name: cache }").unwrap()));
+    }
+
+    #[test]
+    fn test_random_node_invalid_input() {
+        assert!(RandomNode::new(None).is_err());
+        assert!(node("min: 1").is_err());
+        assert!(node("{ min: 3, max: 2 }").is_err());
+        assert!(node("{ max: 2.5 }").is_err());
+        assert!(node("choices: []").is_err());
+        assert!(node("{ choices: [a], max: 2 }").is_err());
+    }
+}
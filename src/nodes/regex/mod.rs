@@ -0,0 +1,383 @@
+//! Regex node implementation
+//!
+//! This module contains the `Regex` node type, which applies a regular
+//! expression to a string, typically the output of a command, to test it,
+//! pull values out of it, or rewrite it.
+//!
+//! ```yaml
+//! - id: version
+//!   type: Regex
+//!   input:
+//!     text: "${{ build.stdout }}"
+//!     pattern: 'version (?P<major>\d+)\.(?P<minor>\d+)'
+//!     operation: captures
+//! ```
+//!
+//! The operations are:
+//!
+//! - **match**: Outputs whether the pattern matches the text (the default)
+//! - **captures**: Outputs the named groups of the first match as a mapping,
+//!   or null when the pattern does not match
+//! - **find_all**: Outputs every match as a sequence, of strings or, when the
+//!   pattern has named groups, of mappings
+//! - **replace**: Outputs the text with every match replaced by the
+//!   `replacement`, which may refer to groups as `$name` or `${1}`
+//!
+//! The pattern uses the syntax of the [regex](https://docs.rs/regex) crate,
+//! and is checked when the workflow is validated, unless it contains
+//! placeholders.
+
+use regex::{Captures, Regex};
+use serde_yml::{Mapping, Value};
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::placeholders;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// Operations of the regex node
+pub const OPERATIONS: [&str; 4] = ["match", "captures", "find_all", "replace"];
+
+/// Operation applied by a regex node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexOperation {
+    /// Tests whether the pattern matches
+    Match,
+    /// Extracts the named groups of the first match
+    Captures,
+    /// Extracts every match
+    FindAll,
+    /// Replaces every match
+    Replace(String),
+}
+
+/// A node matching, extracting or replacing text with a regular expression
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::regex::RegexNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str(
+///     r"{ text: 'ids: 17, 42', pattern: '\d+', operation: find_all }",
+/// )
+/// .unwrap();
+/// let node = RegexNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "ids")).unwrap();
+/// assert_eq!(output, serde_yml::from_str::<Value>("['17', '42']").unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegexNode {
+    text: String,
+    regex: Regex,
+    operation: RegexOperation,
+}
+
+impl RegexNode {
+    /// Creates a regex node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the `text` to search, the `pattern`, the
+    ///   `operation` to apply and, for `replace`, the `replacement`
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if `text` or `pattern` is
+    /// missing, if the pattern is invalid, if the operation is unknown, if
+    /// `replace` has no replacement, or if `captures` is used with a pattern
+    /// without named groups.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let text = match input.get("text") {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Number(number)) => number.to_string(),
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Regex node requires a `text` string".to_string(),
+                ))
+            }
+        };
+
+        let regex = match input.get("pattern") {
+            Some(Value::String(pattern)) => {
+                Regex::new(pattern).map_err(|e| WorkflowError::NodeBuilder(pattern_error(&e)))?
+            }
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Regex node requires a `pattern` string".to_string(),
+                ))
+            }
+        };
+
+        let operation = match input.get("operation") {
+            None | Some(Value::Null) => "match",
+            Some(Value::String(operation)) => operation.as_str(),
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Regex node `operation` must be a string".to_string(),
+                ))
+            }
+        };
+        let operation = match operation {
+            "match" => RegexOperation::Match,
+            "captures" => RegexOperation::Captures,
+            "find_all" => RegexOperation::FindAll,
+            "replace" => match input.get("replacement") {
+                Some(Value::String(replacement)) => RegexOperation::Replace(replacement.clone()),
+                _ => {
+                    return Err(WorkflowError::NodeBuilder(
+                        "Regex node `replace` requires a `replacement` string".to_string(),
+                    ))
+                }
+            },
+            operation => {
+                return Err(WorkflowError::NodeBuilder(format!(
+                    "Unknown regex operation '{}' (available: {})",
+                    operation,
+                    OPERATIONS.join(", ")
+                )))
+            }
+        };
+
+        if operation == RegexOperation::Captures && group_names(&regex).next().is_none() {
+            return Err(WorkflowError::NodeBuilder(
+                "Regex node `captures` requires a pattern with named groups, e.g. (?P<name>...)"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Self {
+            text,
+            regex,
+            operation,
+        })
+    }
+
+    /// Returns the operation of the node
+    pub fn operation(&self) -> &RegexOperation {
+        &self.operation
+    }
+
+    /// Checks the pattern of a node input, as written in the workflow file
+    ///
+    /// Patterns containing `${{ ... }}` placeholders are only known when the
+    /// workflow runs, and are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if the pattern is invalid.
+    pub fn check_input(input: &Value) -> Result<(), String> {
+        match input.get("pattern") {
+            Some(Value::String(pattern)) if placeholders(pattern).is_empty() => Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| pattern_error(&e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["text", "pattern"],
+            "additionalProperties": false,
+            "properties": {
+                "text": {
+                    "type": ["string", "number"],
+                    "description": "Text to search, e.g. ${{ node_id.stdout }}"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression, e.g. v(?P<version>\\d+\\.\\d+)"
+                },
+                "operation": {
+                    "enum": OPERATIONS,
+                    "description": "Operation to apply, defaults to match"
+                },
+                "replacement": {
+                    "type": "string",
+                    "description": "Replacement of each match for replace, may refer to groups as $name"
+                }
+            }
+        })
+    }
+
+    /// Converts the named groups of a match to a mapping
+    fn groups(&self, captures: &Captures) -> Value {
+        let groups = group_names(&self.regex)
+            .map(|name| {
+                let value = captures
+                    .name(name)
+                    .map_or(Value::Null, |group| Value::from(group.as_str()));
+                (Value::from(name), value)
+            })
+            .collect::<Mapping>();
+        Value::Mapping(groups)
+    }
+}
+
+impl BaseNode for RegexNode {
+    fn execute(&self, _options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        Ok(match &self.operation {
+            RegexOperation::Match => Value::Bool(self.regex.is_match(&self.text)),
+            RegexOperation::Captures => self
+                .regex
+                .captures(&self.text)
+                .map_or(Value::Null, |captures| self.groups(&captures)),
+            RegexOperation::FindAll if group_names(&self.regex).next().is_some() => {
+                Value::Sequence(
+                    self.regex
+                        .captures_iter(&self.text)
+                        .map(|captures| self.groups(&captures))
+                        .collect(),
+                )
+            }
+            RegexOperation::FindAll => Value::Sequence(
+                self.regex
+                    .find_iter(&self.text)
+                    .map(|found| Value::from(found.as_str()))
+                    .collect(),
+            ),
+            RegexOperation::Replace(replacement) => Value::from(
+                self.regex
+                    .replace_all(&self.text, replacement.as_str())
+                    .as_ref(),
+            ),
+        })
+    }
+}
+
+/// Returns the names of the named groups of a pattern
+fn group_names(regex: &Regex) -> impl Iterator<Item = &str> {
+    regex.capture_names().flatten()
+}
+
+/// Formats a pattern error on a single line
+///
+/// Syntax errors of the regex crate span several lines, quoting the pattern
+/// above the error; only the error itself is kept.
+fn pattern_error(error: &regex::Error) -> String {
+    let message = error.to_string();
+    let message = message
+        .lines()
+        .last()
+        .map(|line| line.trim_start_matches("error: "))
+        .unwrap_or_default();
+    format!("Invalid pattern: {}", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<RegexNode> {
+        RegexNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn execute(yaml: &str) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        node(yaml)?.execute(BaseNodeRunOptions::new(&heap, "regex"))
+    }
+
+    fn yaml(text: &str) -> Value {
+        serde_yml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_regex_node_match() {
+        assert_eq!(
+            execute("{ text: 'build ok', pattern: '\\bok$' }").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            execute("{ text: 'build failed', pattern: '\\bok$', operation: match }").unwrap(),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_regex_node_captures() {
+        let input = "{ text: 'rustc 1.82.0 (f6e511eec 2024-10-15)', operation: captures, \
+                     pattern: '(?P<major>\\d+)\\.(?P<minor>\\d+)\\.(?P<patch>\\d+)(?P<pre>-\\w+)?' }";
+        assert_eq!(
+            execute(input).unwrap(),
+            yaml("{ major: '1', minor: '82', patch: '0', pre: null }")
+        );
+
+        assert_eq!(
+            execute("{ text: none, pattern: '(?P<n>\\d+)', operation: captures }").unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_regex_node_find_all() {
+        assert_eq!(
+            execute("{ text: 'a=1, b=22', pattern: '\\d+', operation: find_all }").unwrap(),
+            yaml("['1', '22']")
+        );
+        assert_eq!(
+            execute("{ text: 'a=1, b=22', pattern: '(?P<key>\\w)=(?P<value>\\d+)', operation: find_all }")
+                .unwrap(),
+            yaml("[{ key: a, value: '1' }, { key: b, value: '22' }]")
+        );
+        assert_eq!(
+            execute("{ text: '', pattern: '\\d+', operation: find_all }").unwrap(),
+            yaml("[]")
+        );
+    }
+
+    #[test]
+    fn test_regex_node_replace() {
+        assert_eq!(
+            execute(
+                "{ text: 'v1.2 and v3.4', pattern: 'v(?P<version>[\\d.]+)', \
+                 operation: replace, replacement: 'version $version' }"
+            )
+            .unwrap(),
+            Value::from("version 1.2 and version 3.4")
+        );
+        assert_eq!(
+            execute("{ text: 404, pattern: '0', operation: replace, replacement: '' }").unwrap(),
+            Value::from("44")
+        );
+    }
+
+    #[test]
+    fn test_regex_node_invalid_input() {
+        assert!(node("{ pattern: a }").is_err());
+        assert!(node("{ text: a }").is_err());
+        assert!(node("{ text: a, pattern: a, operation: replace }").is_err());
+        assert!(node("{ text: a, pattern: a, operation: captures }").is_err());
+        assert_eq!(
+            node("{ text: a, pattern: a, operation: split }")
+                .unwrap_err()
+                .to_string(),
+            "Node builder error: Unknown regex operation 'split' (available: match, captures, find_all, replace)"
+        );
+        assert_eq!(
+            node("{ text: a, pattern: '(a' }").unwrap_err().to_string(),
+            "Node builder error: Invalid pattern: unclosed group"
+        );
+    }
+
+    #[test]
+    fn test_regex_node_check_input() {
+        assert!(RegexNode::check_input(&yaml("{ text: a, pattern: '\\d+' }")).is_ok());
+        assert!(RegexNode::check_input(&yaml("{ text: a, pattern: '${{ pattern }}[' }")).is_ok());
+        assert_eq!(
+            RegexNode::check_input(&yaml("{ text: a, pattern: 'a{2,1}' }")),
+            Err(
+                "Invalid pattern: invalid repetition count range, the start must be <= the end"
+                    .to_string()
+            )
+        );
+    }
+}
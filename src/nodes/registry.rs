@@ -0,0 +1,579 @@
+//! Node type registry
+//!
+//! This module maps node type names, as written in the `type` field of a
+//! workflow node, to factories creating the corresponding node instances.
+//! The registry is used both to execute workflows and to validate them
+//! without executing anything. Node types may also contribute a JSON Schema
+//! describing their input, which is embedded in the workflow file schema.
+//! Node types that evaluate the `${{ ... }}` placeholders of their input
+//! themselves, at execution time, are registered with a deferred input.
+//! Node types can also check their input when a workflow is validated,
+//! before anything runs, and declare what the validator needs to know about
+//! them: the branches they may take, the workflow they call, and the names
+//! their input may reference besides the heap.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json::Value as JsonValue;
+use serde_yml::Value;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::nodes::{
+    archive::ArchiveNode,
+    assert::AssertNode,
+    base::BaseNode,
+    base64::Base64Node,
+    date_format::DateFormatNode,
+    external::{ExternalNode, Plugin},
+    extract::ExtractNode,
+    hash::HashNode,
+    log::LogNode,
+    now::NowNode,
+    parse::ParseNode,
+    query::QueryNode,
+    random::RandomNode,
+    regex::RegexNode,
+    script::ScriptNode,
+    serialize::SerializeNode,
+    switch::SwitchNode,
+    template::TemplateNode,
+    uuid::UuidNode,
+    wait_until::WaitUntilNode,
+    wasm::{WasmNode, WasmPlugin},
+    workflow::SubWorkflowNode,
+};
+
+/// Function creating a node instance from its evaluated input
+pub type NodeFactory =
+    Arc<dyn Fn(Option<Value>) -> WorkflowResult<Box<dyn BaseNode>> + Send + Sync>;
+
+/// Function checking the input of a node, as written in the workflow file
+///
+/// The function returns the error message if the input is invalid.
+pub type InputCheck = Arc<dyn Fn(&Value) -> Result<(), String> + Send + Sync>;
+
+/// Function listing the branches a node may take, from its input as written
+///
+/// The function returns `None` if the branches are only known when running.
+pub type BranchList = Arc<dyn Fn(&Value) -> Option<Vec<String>> + Send + Sync>;
+
+/// Registry of the node types available to workflows
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::nodes::registry::NodeRegistry;
+/// use colossus::nodes::log::LogNode;
+///
+/// let mut registry = NodeRegistry::new();
+/// assert!(registry.contains("Log"));
+///
+/// registry.register("Print", |input| Ok(Box::new(LogNode::new(input))));
+/// assert_eq!(registry.node_types(), vec!["Archive", "Assert", "Base64", "DateFormat", "Extract", "Hash", "Log", "Now", "Parse", "Print", "Query", "Random", "Regex", "Script", "Serialize", "Switch", "Template", "UUID", "WaitUntil", "Workflow"]);
+/// assert!(registry.schema("Print").is_none());
+/// ```
+#[derive(Clone)]
+pub struct NodeRegistry {
+    factories: HashMap<String, NodeFactory>,
+    schemas: HashMap<String, JsonValue>,
+    deferred_inputs: HashSet<String>,
+    input_checks: HashMap<String, InputCheck>,
+    branches: HashMap<String, BranchList>,
+    workflow_calls: HashMap<String, String>,
+    bindings: HashMap<String, Vec<String>>,
+}
+
+impl NodeRegistry {
+    /// Creates a registry containing the built-in node types
+    pub fn new() -> Self {
+        Self::empty()
+            .with_node("Archive", |input| Ok(Box::new(ArchiveNode::new(input)?)))
+            .with_schema("Archive", ArchiveNode::input_schema())
+            .with_input_check("Archive", ArchiveNode::check_input)
+            .with_node("Assert", |input| Ok(Box::new(AssertNode::new(input)?)))
+            .with_schema("Assert", AssertNode::input_schema())
+            .with_deferred_input("Assert")
+            .with_node("Base64", |input| Ok(Box::new(Base64Node::new(input)?)))
+            .with_schema("Base64", Base64Node::input_schema())
+            .with_node("DateFormat", |input| {
+                Ok(Box::new(DateFormatNode::new(input)?))
+            })
+            .with_schema("DateFormat", DateFormatNode::input_schema())
+            .with_input_check("DateFormat", DateFormatNode::check_input)
+            .with_node("Extract", |input| Ok(Box::new(ExtractNode::new(input)?)))
+            .with_schema("Extract", ExtractNode::input_schema())
+            .with_input_check("Extract", ExtractNode::check_input)
+            .with_node("Hash", |input| Ok(Box::new(HashNode::new(input)?)))
+            .with_schema("Hash", HashNode::input_schema())
+            .with_node("Log", |input| Ok(Box::new(LogNode::new(input))))
+            .with_schema("Log", LogNode::input_schema())
+            .with_node("Now", |input| Ok(Box::new(NowNode::new(input)?)))
+            .with_schema("Now", NowNode::input_schema())
+            .with_input_check("Now", NowNode::check_input)
+            .with_node("Parse", |input| Ok(Box::new(ParseNode::new(input)?)))
+            .with_schema("Parse", ParseNode::input_schema())
+            .with_node("Query", |input| Ok(Box::new(QueryNode::new(input)?)))
+            .with_schema("Query", QueryNode::input_schema())
+            .with_input_check("Query", QueryNode::check_input)
+            .with_node("Random", |input| Ok(Box::new(RandomNode::new(input)?)))
+            .with_schema("Random", RandomNode::input_schema())
+            .with_node("Regex", |input| Ok(Box::new(RegexNode::new(input)?)))
+            .with_schema("Regex", RegexNode::input_schema())
+            .with_input_check("Regex", RegexNode::check_input)
+            .with_node("Script", |input| Ok(Box::new(ScriptNode::new(input)?)))
+            .with_schema("Script", ScriptNode::input_schema())
+            .with_node("Serialize", |input| {
+                Ok(Box::new(SerializeNode::new(input)?))
+            })
+            .with_schema("Serialize", SerializeNode::input_schema())
+            .with_node("Switch", |input| Ok(Box::new(SwitchNode::new(input)?)))
+            .with_schema("Switch", SwitchNode::input_schema())
+            .with_branches("Switch", SwitchNode::declared_branches)
+            .with_node("Template", |input| Ok(Box::new(TemplateNode::new(input)?)))
+            .with_schema("Template", TemplateNode::input_schema())
+            .with_node("UUID", |input| Ok(Box::new(UuidNode::new(input)?)))
+            .with_schema("UUID", UuidNode::input_schema())
+            .with_node("WaitUntil", |input| {
+                Ok(Box::new(WaitUntilNode::new(input)?))
+            })
+            .with_schema("WaitUntil", WaitUntilNode::input_schema())
+            .with_bindings("WaitUntil", WaitUntilNode::BINDINGS)
+            .with_node("Workflow", |input| {
+                Ok(Box::new(SubWorkflowNode::new(input)?))
+            })
+            .with_schema("Workflow", SubWorkflowNode::input_schema())
+            .with_workflow_call("Workflow", "path")
+    }
+
+    /// Creates a registry without any node type
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+            schemas: HashMap::new(),
+            deferred_inputs: HashSet::new(),
+            input_checks: HashMap::new(),
+            branches: HashMap::new(),
+            workflow_calls: HashMap::new(),
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Registers a node type, replacing any existing type with the same name
+    ///
+    /// # Arguments
+    ///
+    /// * `node_type` - The type name used in workflow files
+    /// * `factory` - Function creating a node from its evaluated input
+    pub fn register<F>(&mut self, node_type: impl Into<String>, factory: F)
+    where
+        F: Fn(Option<Value>) -> WorkflowResult<Box<dyn BaseNode>> + Send + Sync + 'static,
+    {
+        self.factories.insert(node_type.into(), Arc::new(factory));
+    }
+
+    /// Registers a node type
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_node<F>(mut self, node_type: impl Into<String>, factory: F) -> Self
+    where
+        F: Fn(Option<Value>) -> WorkflowResult<Box<dyn BaseNode>> + Send + Sync + 'static,
+    {
+        self.register(node_type, factory);
+        self
+    }
+
+    /// Registers the node type provided by a plugin executable
+    ///
+    /// The plugin schema, if any, becomes the schema of the node type.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_plugin(self, plugin: Plugin) -> Self {
+        let node_type = plugin.node_type().to_string();
+        let schema = plugin.schema().cloned();
+
+        let registry = self.with_node(node_type.clone(), move |input| {
+            Ok(Box::new(ExternalNode::new(plugin.clone(), input)))
+        });
+        match schema {
+            Some(schema) => registry.with_schema(node_type, schema),
+            None => registry,
+        }
+    }
+
+    /// Registers the node type provided by a WebAssembly module
+    ///
+    /// The module schema, if any, becomes the schema of the node type.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_wasm_plugin(self, plugin: WasmPlugin) -> Self {
+        let node_type = plugin.node_type().to_string();
+        let schema = plugin.schema().cloned();
+
+        let registry = self.with_node(node_type.clone(), move |input| {
+            Ok(Box::new(WasmNode::new(plugin.clone(), input)))
+        });
+        match schema {
+            Some(schema) => registry.with_schema(node_type, schema),
+            None => registry,
+        }
+    }
+
+    /// Sets the JSON Schema of the input of a node type
+    ///
+    /// # Arguments
+    ///
+    /// * `node_type` - The type name used in workflow files
+    /// * `schema` - JSON Schema the `input` field of such nodes must match
+    pub fn set_schema(&mut self, node_type: impl Into<String>, schema: JsonValue) {
+        self.schemas.insert(node_type.into(), schema);
+    }
+
+    /// Sets the JSON Schema of the input of a node type
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::nodes::{log::LogNode, registry::NodeRegistry};
+    /// use serde_json::json;
+    ///
+    /// let registry = NodeRegistry::empty()
+    ///     .with_node("Print", |input| Ok(Box::new(LogNode::new(input))))
+    ///     .with_schema("Print", json!({ "type": "string" }));
+    /// assert_eq!(registry.schema("Print"), Some(&json!({ "type": "string" })));
+    /// ```
+    pub fn with_schema(mut self, node_type: impl Into<String>, schema: JsonValue) -> Self {
+        self.set_schema(node_type, schema);
+        self
+    }
+
+    /// Sets the function checking the input of a node type at validation time
+    ///
+    /// Checks receive the input as written in the workflow file, with its
+    /// `${{ ... }}` placeholders, and should only report errors that do not
+    /// depend on them.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::nodes::{log::LogNode, registry::NodeRegistry};
+    /// use serde_yml::Value;
+    ///
+    /// let registry = NodeRegistry::empty()
+    ///     .with_node("Print", |input| Ok(Box::new(LogNode::new(input))))
+    ///     .with_input_check("Print", |input| match input {
+    ///         Value::String(_) => Ok(()),
+    ///         _ => Err("Print input must be a string".to_string()),
+    ///     });
+    /// assert!(registry.check_input("Print", &Value::from("hello")).is_ok());
+    /// assert!(registry.check_input("Print", &Value::from(1)).is_err());
+    /// assert!(registry.check_input("Log", &Value::from(1)).is_ok());
+    /// ```
+    pub fn with_input_check<F>(mut self, node_type: impl Into<String>, check: F) -> Self
+    where
+        F: Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.input_checks.insert(node_type.into(), Arc::new(check));
+        self
+    }
+
+    /// Checks the input of a node of the given type, as written in the workflow file
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message of the input check of the node type, if it
+    /// has one and the input is invalid.
+    pub fn check_input(&self, node_type: &str, input: &Value) -> Result<(), String> {
+        match self.input_checks.get(node_type) {
+            Some(check) => check(input),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the JSON Schema of the input of a node type, if it has one
+    pub fn schema(&self, node_type: &str) -> Option<&JsonValue> {
+        self.schemas.get(node_type)
+    }
+
+    /// Marks a node type as evaluating the placeholders of its input itself
+    ///
+    /// The input of such nodes is passed to their factory exactly as written
+    /// in the workflow file, instead of with its `${{ ... }}` placeholders
+    /// substituted.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::nodes::{log::LogNode, registry::NodeRegistry};
+    ///
+    /// let registry = NodeRegistry::empty()
+    ///     .with_node("Print", |input| Ok(Box::new(LogNode::new(input))))
+    ///     .with_deferred_input("Print");
+    /// assert!(registry.defers_input("Print"));
+    /// assert!(!registry.defers_input("Log"));
+    /// ```
+    pub fn with_deferred_input(mut self, node_type: impl Into<String>) -> Self {
+        self.deferred_inputs.insert(node_type.into());
+        self
+    }
+
+    /// Checks if a node type evaluates the placeholders of its input itself
+    pub fn defers_input(&self, node_type: &str) -> bool {
+        self.deferred_inputs.contains(node_type)
+    }
+
+    /// Declares that nodes of a type choose a branch, which later nodes can
+    /// follow with `on_branch`
+    ///
+    /// The validator uses `branches` to check the branch names that
+    /// `on_branch` refers to.
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::nodes::{log::LogNode, registry::NodeRegistry};
+    /// use serde_yml::Value;
+    ///
+    /// let registry = NodeRegistry::empty()
+    ///     .with_node("Toss", |input| Ok(Box::new(LogNode::new(input))))
+    ///     .with_branches("Toss", |_| Some(vec!["heads".to_string(), "tails".to_string()]));
+    /// assert!(registry.has_branches("Toss"));
+    /// assert_eq!(registry.branches("Toss", &Value::Null).unwrap().len(), 2);
+    /// assert!(!registry.has_branches("Log"));
+    /// ```
+    pub fn with_branches<F>(mut self, node_type: impl Into<String>, branches: F) -> Self
+    where
+        F: Fn(&Value) -> Option<Vec<String>> + Send + Sync + 'static,
+    {
+        self.branches.insert(node_type.into(), Arc::new(branches));
+        self
+    }
+
+    /// Checks if nodes of a type choose a branch
+    pub fn has_branches(&self, node_type: &str) -> bool {
+        self.branches.contains_key(node_type)
+    }
+
+    /// Lists the branches a node of the given type may take
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the node type has no branches, or if they are only
+    /// known when running.
+    pub fn branches(&self, node_type: &str, input: &Value) -> Option<Vec<String>> {
+        self.branches
+            .get(node_type)
+            .and_then(|branches| branches(input))
+    }
+
+    /// Declares that nodes of a type call the workflow file named by a field
+    /// of their input
+    ///
+    /// The validator follows these calls to report missing files, call
+    /// cycles and too deep a nesting before anything runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_type` - The type name used in workflow files
+    /// * `field` - Input field holding the path of the called workflow
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::nodes::{log::LogNode, registry::NodeRegistry};
+    ///
+    /// let registry = NodeRegistry::empty()
+    ///     .with_node("Call", |input| Ok(Box::new(LogNode::new(input))))
+    ///     .with_workflow_call("Call", "file");
+    /// assert_eq!(registry.workflow_call("Call"), Some("file"));
+    /// assert_eq!(registry.workflow_call("Log"), None);
+    /// ```
+    pub fn with_workflow_call(
+        mut self,
+        node_type: impl Into<String>,
+        field: impl Into<String>,
+    ) -> Self {
+        self.workflow_calls.insert(node_type.into(), field.into());
+        self
+    }
+
+    /// Returns the input field naming the workflow called by nodes of the
+    /// given type, if they call one
+    pub fn workflow_call(&self, node_type: &str) -> Option<&str> {
+        self.workflow_calls.get(node_type).map(String::as_str)
+    }
+
+    /// Declares names that the input of a node type may reference besides
+    /// the heap, because the node binds them itself when it runs
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::nodes::{log::LogNode, registry::NodeRegistry};
+    ///
+    /// let registry = NodeRegistry::empty()
+    ///     .with_node("Retry", |input| Ok(Box::new(LogNode::new(input))))
+    ///     .with_bindings("Retry", &["attempt"]);
+    /// assert_eq!(registry.bindings("Retry"), ["attempt"]);
+    /// assert!(registry.bindings("Log").is_empty());
+    /// ```
+    pub fn with_bindings(mut self, node_type: impl Into<String>, names: &[&str]) -> Self {
+        self.bindings.insert(
+            node_type.into(),
+            names.iter().map(|name| name.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Returns the names bound by nodes of the given type
+    pub fn bindings(&self, node_type: &str) -> &[String] {
+        self.bindings.get(node_type).map_or(&[], Vec::as_slice)
+    }
+
+    /// Checks if a node type is registered
+    pub fn contains(&self, node_type: &str) -> bool {
+        self.factories.contains_key(node_type)
+    }
+
+    /// Returns the registered node type names, sorted alphabetically
+    pub fn node_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        types.sort_unstable();
+        types
+    }
+
+    /// Creates a node of the given type
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::InvalidNode` if the type is not registered, or
+    /// the error returned by the node factory.
+    pub fn create(
+        &self,
+        node_type: &str,
+        input: Option<Value>,
+    ) -> WorkflowResult<Box<dyn BaseNode>> {
+        let factory = self
+            .factories
+            .get(node_type)
+            .ok_or_else(|| WorkflowError::InvalidNode(node_type.to_string()))?;
+
+        factory(input)
+    }
+}
+
+impl Default for NodeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for NodeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeRegistry")
+            .field("node_types", &self.node_types())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_registry_builtin_types() {
+        let registry = NodeRegistry::new();
+        assert!(registry.contains("Log"));
+        assert!(registry.contains("Workflow"));
+        assert!(!registry.contains("log"));
+    }
+
+    #[test]
+    fn test_node_registry_empty() {
+        let registry = NodeRegistry::empty();
+        assert!(registry.node_types().is_empty());
+        assert!(matches!(
+            registry.create("Log", None),
+            Err(WorkflowError::InvalidNode(_))
+        ));
+    }
+
+    #[test]
+    fn test_node_registry_register() {
+        let registry = NodeRegistry::empty()
+            .with_node("Echo", |input| Ok(Box::new(LogNode::new(input))))
+            .with_node("Alias", |input| Ok(Box::new(LogNode::new(input))));
+
+        assert_eq!(registry.node_types(), vec!["Alias", "Echo"]);
+        assert!(registry.create("Echo", None).is_ok());
+    }
+
+    #[test]
+    fn test_node_registry_schemas() {
+        let registry = NodeRegistry::new()
+            .with_schema("Echo", serde_json::json!({ "type": "string" }))
+            .with_node("Echo", |input| Ok(Box::new(LogNode::new(input))));
+
+        assert!(registry.schema("Log").is_some());
+        assert_eq!(
+            registry.schema("Echo"),
+            Some(&serde_json::json!({ "type": "string" }))
+        );
+        assert!(registry.schema("Missing").is_none());
+    }
+
+    #[test]
+    fn test_node_registry_plugin() {
+        let registry = NodeRegistry::new().with_plugin(
+            Plugin::new("Http", "plugins/http")
+                .with_schema(serde_json::json!({ "type": "object" })),
+        );
+
+        assert!(registry.contains("Http"));
+        assert_eq!(
+            registry.schema("Http"),
+            Some(&serde_json::json!({ "type": "object" }))
+        );
+        assert!(registry.create("Http", None).is_ok());
+    }
+
+    #[test]
+    fn test_node_registry_debug() {
+        let registry = NodeRegistry::new();
+        assert_eq!(
+            format!("{:?}", registry),
+            "NodeRegistry { node_types: [\"Archive\", \"Assert\", \"Base64\", \"DateFormat\", \"Extract\", \"Hash\", \"Log\", \"Now\", \"Parse\", \"Query\", \"Random\", \"Regex\", \"Script\", \"Serialize\", \"Switch\", \"Template\", \"UUID\", \"WaitUntil\", \"Workflow\"] }"
+        );
+    }
+}
@@ -0,0 +1,486 @@
+//! Script node implementation
+//!
+//! This module contains the `Script` node type, which runs a
+//! [Rhai](https://rhai.rs) script for logic that does not fit YAML. The
+//! value of the last expression of the script becomes the output of the
+//! node.
+//!
+//! ```yaml
+//! - id: total
+//!   type: Script
+//!   input:
+//!     script: |
+//!       let total = 0;
+//!       for item in fetch.items {
+//!           total += item.size;
+//!       }
+//!       total
+//! ```
+//!
+//! Scripts are sandboxed: the values of the heap are visible as read-only
+//! constants, names that are not valid identifiers, such as `fetch-data`,
+//! through the `heap` map, and the language has no access to files, the
+//! network or the environment. The number of operations a script may run,
+//! the size of the strings, arrays and maps it builds, the nesting depth of
+//! its expressions and of its function calls are limited, so a runaway
+//! script fails instead of hanging the engine or overflowing its stack.
+//!
+//! Memory is bounded per collection, not per script: each string, array or
+//! map stays within `max_size`, but a script may still build many of them,
+//! or nest them, within its operation limit.
+
+use std::path::PathBuf;
+
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use serde_yml::Value;
+use tracing::{debug, info};
+
+use crate::core::context::RunContext;
+use crate::core::control::ExecutionControl;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::heap::Heap;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// Operations a script may run when no `max_operations` is set
+pub const DEFAULT_MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Length of strings, arrays and maps when no `max_size` is set
+pub const DEFAULT_MAX_SIZE: usize = 100_000;
+
+/// Nesting depth of function calls in scripts
+const MAX_CALL_LEVELS: usize = 64;
+
+/// Nesting depth of expressions at the top level of scripts
+const MAX_EXPR_DEPTH: usize = 64;
+
+/// Nesting depth of expressions in the functions of scripts
+const MAX_FUNCTION_EXPR_DEPTH: usize = 32;
+
+/// Where the script of a node comes from
+#[derive(Debug, Clone)]
+enum Source {
+    Inline(AST),
+    File(PathBuf),
+}
+
+/// A node running a sandboxed Rhai script
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::script::ScriptNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("script: sizes.reduce(|sum, n| sum + n, 0)").unwrap();
+/// let node = ScriptNode::new(Some(input)).unwrap();
+///
+/// let mut heap = Heap::new();
+/// heap.insert("sizes", Some(serde_yml::from_str("[1, 2, 3]").unwrap()));
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "total")).unwrap();
+/// assert_eq!(output, Value::from(6));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScriptNode {
+    source: Source,
+    max_operations: u64,
+    max_size: usize,
+}
+
+impl ScriptNode {
+    /// Creates a script node from its evaluated input
+    ///
+    /// Inline scripts are compiled right away, so that syntax errors are
+    /// reported before the workflow runs; script files are read when the
+    /// node executes.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with either an inline `script` or the `file` it is
+    ///   read from, and optional `max_operations` and `max_size` limits
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if neither or both of `script`
+    /// and `file` are set, if a limit is not a positive integer, or if the
+    /// inline script does not compile.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let max_operations = limit(&input, "max_operations")?.unwrap_or(DEFAULT_MAX_OPERATIONS);
+        let max_size = limit(&input, "max_size")?
+            .map(|size| usize::try_from(size).unwrap_or(usize::MAX))
+            .unwrap_or(DEFAULT_MAX_SIZE);
+
+        let source = match (
+            string_field(&input, "script")?,
+            string_field(&input, "file")?,
+        ) {
+            (Some(script), None) => Source::Inline(
+                engine(max_operations, max_size, None)
+                    .compile(script)
+                    .map_err(|e| WorkflowError::NodeBuilder(format!("Invalid script: {}", e)))?,
+            ),
+            (None, Some(file)) if !file.trim().is_empty() => Source::File(PathBuf::from(file)),
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Script node requires either a `script` or a `file`".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            source,
+            max_operations,
+            max_size,
+        })
+    }
+
+    /// Returns the maximum number of operations the script may run
+    pub fn max_operations(&self) -> u64 {
+        self.max_operations
+    }
+
+    /// Returns the maximum length of the strings, arrays and maps of the script
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Returns the compiled script, reading it from its file if needed
+    fn script(&self, context: &RunContext) -> WorkflowResult<AST> {
+        match &self.source {
+            Source::Inline(ast) => Ok(ast.clone()),
+            Source::File(file) => {
+                let path = context.resolve_within(file)?;
+                let text = std::fs::read_to_string(&path).map_err(|e| {
+                    WorkflowError::NodeExecutionFailed(format!(
+                        "Failed to read script {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                engine(self.max_operations, self.max_size, None)
+                    .compile(&text)
+                    .map_err(|e| {
+                        WorkflowError::NodeExecutionFailed(format!(
+                            "Invalid script {}: {}",
+                            path.display(),
+                            e
+                        ))
+                    })
+            }
+        }
+    }
+
+    /// Describes why a script failed
+    fn failure(&self, error: &EvalAltResult) -> String {
+        match error {
+            EvalAltResult::ErrorTooManyOperations(_) => format!(
+                "Script exceeded its limit of {} operations",
+                self.max_operations
+            ),
+            EvalAltResult::ErrorDataTooLarge(kind, _) => format!(
+                "Script exceeded its size limit: {} is larger than {}",
+                kind, self.max_size
+            ),
+            EvalAltResult::ErrorStackOverflow(_) => format!(
+                "Script exceeded its limit of {} nested function calls",
+                MAX_CALL_LEVELS
+            ),
+            EvalAltResult::ErrorTerminated(reason, _) => {
+                format!("Script was terminated: {}", reason)
+            }
+            other => format!("Script failed: {}", other),
+        }
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "oneOf": [
+                { "required": ["script"] },
+                { "required": ["file"] }
+            ],
+            "properties": {
+                "script": {
+                    "type": "string",
+                    "description": "Inline Rhai script, whose last expression is the node output"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Rhai script file, relative to the workflow file"
+                },
+                "max_operations": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": format!("Operations the script may run, {} by default", DEFAULT_MAX_OPERATIONS)
+                },
+                "max_size": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": format!("Length of each string, array and map of the script, {} by default", DEFAULT_MAX_SIZE)
+                }
+            }
+        })
+    }
+}
+
+impl BaseNode for ScriptNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let ast = self.script(context)?;
+        let engine = engine(
+            self.max_operations,
+            self.max_size,
+            Some(context.control().clone()),
+        );
+        let mut scope = scope(options.heap())?;
+
+        let result = engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+            .map_err(|e| WorkflowError::NodeExecutionFailed(self.failure(&e)))?;
+
+        rhai::serde::from_dynamic::<Value>(&result).map_err(|e| {
+            WorkflowError::NodeExecutionFailed(format!(
+                "Script returned a value that cannot be stored: {}",
+                e
+            ))
+        })
+    }
+}
+
+/// Creates the sandboxed engine running scripts
+///
+/// When a control is given, the script is terminated once the run is
+/// stopped.
+fn engine(max_operations: u64, max_size: usize, control: Option<ExecutionControl>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(max_operations)
+        .set_max_string_size(max_size)
+        .set_max_array_size(max_size)
+        .set_max_map_size(max_size)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_FUNCTION_EXPR_DEPTH)
+        .disable_symbol("eval")
+        .on_print(|text| info!("{}", text))
+        .on_debug(|text, _, position| debug!("{} ({})", text, position));
+
+    if let Some(control) = control {
+        engine.on_progress(move |_| {
+            control
+                .is_stopped()
+                .then(|| Dynamic::from("the run was stopped"))
+        });
+    }
+
+    engine
+}
+
+/// Exposes the values of the heap to a script as constants
+fn scope(heap: &Heap) -> WorkflowResult<Scope<'static>> {
+    let mut scope = Scope::new();
+    let mut all = Map::new();
+
+    let mut entries: Vec<(&str, &Value)> = heap.iter().collect();
+    entries.sort_by_key(|(name, _)| *name);
+
+    for (name, value) in entries {
+        let value = rhai::serde::to_dynamic(value).map_err(|e| {
+            WorkflowError::NodeExecutionFailed(format!(
+                "Cannot pass `{}` to the script: {}",
+                name, e
+            ))
+        })?;
+        if is_identifier(name) {
+            scope.push_constant_dynamic(name.to_string(), value.clone());
+        }
+        all.insert(name.into(), value);
+    }
+
+    scope.push_constant("heap", all);
+    Ok(scope)
+}
+
+/// Checks whether a name can be used as a variable in scripts
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Returns an optional string field of the input
+fn string_field<'a>(input: &'a Value, field: &str) -> WorkflowResult<Option<&'a str>> {
+    match input.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(text)) => Ok(Some(text)),
+        Some(_) => Err(WorkflowError::NodeBuilder(format!(
+            "Script node `{}` must be a string",
+            field
+        ))),
+    }
+}
+
+/// Returns an optional positive integer field of the input
+fn limit(input: &Value, field: &str) -> WorkflowResult<Option<u64>> {
+    match input.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .filter(|limit| *limit > 0)
+            .map(Some)
+            .ok_or_else(|| {
+                WorkflowError::NodeBuilder(format!(
+                    "Script node `{}` must be a positive integer",
+                    field
+                ))
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(yaml: &str) -> WorkflowResult<ScriptNode> {
+        ScriptNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn heap() -> Heap {
+        let mut heap = Heap::new();
+        heap.insert(
+            "fetch",
+            Some(
+                serde_yml::from_str("{ status: 200, items: [{ size: 2 }, { size: 5 }] }").unwrap(),
+            ),
+        );
+        heap.insert("fetch-data", Some(Value::from("raw")));
+        heap.insert("skipped", None);
+        heap
+    }
+
+    fn run(node: &ScriptNode) -> WorkflowResult<Value> {
+        node.execute(BaseNodeRunOptions::new(&heap(), "script"))
+    }
+
+    #[test]
+    fn test_script_node_reads_heap() {
+        let node = node(
+            "script: |\n  let total = 0;\n  for item in fetch.items { total += item.size; }\n  #{ total: total, status: fetch.status, raw: heap[\"fetch-data\"] }",
+        )
+        .unwrap();
+
+        let output = run(&node).unwrap();
+        assert_eq!(output.get("total"), Some(&Value::from(7)));
+        assert_eq!(output.get("status"), Some(&Value::from(200)));
+        assert_eq!(output.get("raw"), Some(&Value::from("raw")));
+    }
+
+    #[test]
+    fn test_script_node_heap_is_read_only() {
+        let error = run(&node("script: 'fetch.status = 500; fetch'").unwrap()).unwrap_err();
+        assert!(error.to_string().contains("Script failed"), "{}", error);
+
+        let output =
+            run(&node("script: 'let copy = fetch; copy.status = 500; fetch.status'").unwrap());
+        assert_eq!(output.unwrap(), Value::from(200));
+    }
+
+    #[test]
+    fn test_script_node_operation_limit() {
+        let node = node("script: 'loop { }'\nmax_operations: 1000").unwrap();
+        assert_eq!(node.max_operations(), 1000);
+        assert_eq!(
+            run(&node).unwrap_err().to_string(),
+            "Node execution failed: Script exceeded its limit of 1000 operations"
+        );
+    }
+
+    #[test]
+    fn test_script_node_size_limit() {
+        let node = node("script: 'let s = \"ab\"; loop { s += s; }'\nmax_size: 1024").unwrap();
+        let error = run(&node).unwrap_err().to_string();
+        assert!(error.contains("size limit"), "{}", error);
+        assert!(error.contains("1024"), "{}", error);
+    }
+
+    #[test]
+    fn test_script_node_depth_limits() {
+        let nested = format!("script: '{}1{}'", "(".repeat(200), ")".repeat(200));
+        let error = node(&nested).unwrap_err().to_string();
+        assert!(error.contains("Invalid script"), "{}", error);
+
+        let node = node("script: 'fn down(n) { down(n + 1) } down(0)'").unwrap();
+        assert_eq!(
+            run(&node).unwrap_err().to_string(),
+            "Node execution failed: Script exceeded its limit of 64 nested function calls"
+        );
+    }
+
+    #[test]
+    fn test_script_node_stops_with_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let control = ExecutionControl::new();
+        control.stop();
+        let context = RunContext::new(dir.path().join("workflow.yml")).with_control(control);
+
+        let heap = heap();
+        let error = node("script: 'loop { }'")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "script").with_context(&context))
+            .unwrap_err();
+        assert!(error.to_string().contains("terminated"), "{}", error);
+    }
+
+    #[test]
+    fn test_script_node_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("double.rhai"), "fetch.status * 2").unwrap();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+
+        let heap = heap();
+        let output = node("file: double.rhai")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "script").with_context(&context))
+            .unwrap();
+        assert_eq!(output, Value::from(400));
+    }
+
+    #[test]
+    fn test_script_node_confined_to_workflow_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("project")).unwrap();
+        std::fs::write(dir.path().join("outside.rhai"), "1").unwrap();
+        let context = RunContext::new(dir.path().join("project/workflow.yml"));
+
+        let heap = heap();
+        for yaml in [
+            "file: ../outside.rhai".to_string(),
+            format!("file: {}", dir.path().join("outside.rhai").display()),
+        ] {
+            assert!(matches!(
+                node(&yaml)
+                    .unwrap()
+                    .execute(BaseNodeRunOptions::new(&heap, "script").with_context(&context)),
+                Err(WorkflowError::OutsideWorkflowDir { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_script_node_invalid_input() {
+        assert!(node("max_operations: 10").is_err());
+        assert!(node("script: a\nfile: b").is_err());
+        assert!(node("script: 'let = 1'").is_err());
+        assert!(node("script: 'eval(\"1\")'").is_err());
+        assert!(node("script: '1'\nmax_operations: 0").is_err());
+        assert!(node("script: '1'\nmax_size: -5").is_err());
+    }
+}
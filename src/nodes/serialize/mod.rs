@@ -0,0 +1,238 @@
+//! Serialize node implementation
+//!
+//! This module contains the `Serialize` node type, which converts a value to
+//! JSON, YAML, TOML or CSV text, optionally written to a file.
+//!
+//! ```yaml
+//! - id: report
+//!   type: Serialize
+//!   input:
+//!     value: "${{ results }}"
+//!     write: build/results.csv
+//! ```
+//!
+//! The format is inferred from the extension of the written file when not
+//! given, and paths are relative to the directory of the workflow file and
+//! must stay inside it.
+
+use std::path::PathBuf;
+
+use serde_yml::{Mapping, Value};
+use tracing::info;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::format::{self, CsvOptions, Format};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::parse::{format_options, format_schema};
+
+/// A node converting a value to text, optionally into a file
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::serialize::SerializeNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("{ format: toml, value: { port: 8080 } }").unwrap();
+/// let node = SerializeNode::new(Some(input)).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "config")).unwrap();
+/// assert_eq!(output.get("content"), Some(&Value::from("port = 8080\n")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SerializeNode {
+    value: Value,
+    format: Format,
+    csv: CsvOptions,
+    write: Option<PathBuf>,
+}
+
+impl SerializeNode {
+    /// Creates a serialize node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the `value` to serialize, its `format`, an
+    ///   optional `write` path for the result, and the CSV `headers` and
+    ///   `delimiter`
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if `value` is not set, or if the
+    /// format is missing or unknown.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let value = input
+            .as_mapping()
+            .and_then(|input| input.get("value"))
+            .cloned()
+            .ok_or_else(|| {
+                WorkflowError::NodeBuilder("Serialize node requires a `value`".to_string())
+            })?;
+
+        let write = match input.get("write") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(path)) if !path.trim().is_empty() => Some(path.as_str()),
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Serialize node `write` must be a path".to_string(),
+                ))
+            }
+        };
+        let (format, csv) = format_options("Serialize", &input, write)?;
+
+        Ok(Self {
+            value,
+            format,
+            csv,
+            write: write.map(PathBuf::from),
+        })
+    }
+
+    /// Returns the format of the serialized text
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the path the result is written to, if any
+    pub fn write_path(&self) -> Option<&PathBuf> {
+        self.write.as_ref()
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        let mut schema =
+            format_schema("Format of the text, inferred from the extension of the written file");
+        schema["required"] = serde_json::json!(["value"]);
+        schema["properties"]["value"] = serde_json::json!({
+            "description": "Value to serialize"
+        });
+        schema["properties"]["write"] = serde_json::json!({
+            "type": "string",
+            "description": "File the result is written to, relative to the workflow file"
+        });
+        schema
+    }
+}
+
+impl BaseNode for SerializeNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let content = format::serialize(&self.value, self.format, &self.csv)
+            .map_err(|e| WorkflowError::NodeExecutionFailed(e.to_string()))?;
+
+        let mut output = Mapping::new();
+        output.insert(Value::from("content"), Value::from(content.as_str()));
+
+        if let Some(write) = &self.write {
+            let path = context.resolve_within(write)?;
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, &content));
+            written.map_err(|e| {
+                WorkflowError::NodeExecutionFailed(format!(
+                    "Failed to write {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+            info!(
+                "Serialize '{}' wrote {} bytes of {} to {}",
+                options.prefix(),
+                content.len(),
+                self.format,
+                path.display()
+            );
+            output.insert(Value::from("path"), Value::from(path.display().to_string()));
+        }
+
+        Ok(Value::Mapping(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<SerializeNode> {
+        SerializeNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    #[test]
+    fn test_serialize_node_writes_inferred_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+
+        let serialize = node(
+            "value: [{ name: web, port: 80 }, { name: db }]\nwrite: out/hosts.csv\nheaders: [name]",
+        )
+        .unwrap();
+        assert_eq!(serialize.format(), Format::Csv);
+
+        let heap = Heap::new();
+        let output = serialize
+            .execute(BaseNodeRunOptions::new(&heap, "hosts").with_context(&context))
+            .unwrap();
+
+        let written = dir.path().join("out/hosts.csv");
+        assert_eq!(
+            std::fs::read_to_string(&written).unwrap(),
+            "name\nweb\ndb\n"
+        );
+        assert_eq!(output.get("content"), Some(&Value::from("name\nweb\ndb\n")));
+        assert_eq!(
+            output.get("path"),
+            Some(&Value::from(written.display().to_string()))
+        );
+    }
+
+    #[test]
+    fn test_serialize_node_write_confined_to_workflow_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("flows")).unwrap();
+        let context = RunContext::new(dir.path().join("flows/workflow.yml"));
+
+        let heap = Heap::new();
+        let result = node("value: [1]\nwrite: ../out.json")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "report").with_context(&context));
+
+        assert!(matches!(
+            result,
+            Err(WorkflowError::OutsideWorkflowDir { .. })
+        ));
+        assert!(!dir.path().join("out.json").exists());
+    }
+
+    #[test]
+    fn test_serialize_node_unrepresentable_value() {
+        let heap = Heap::new();
+        let error = node("format: toml\nvalue: [1, 2]")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "config"))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Node execution failed: Cannot serialize to TOML: the document must be a mapping"
+        );
+    }
+
+    #[test]
+    fn test_serialize_node_invalid_input() {
+        assert!(node("format: json").is_err());
+        assert!(node("value: 1").is_err());
+        assert!(node("value: 1\nwrite: out.txt").is_err());
+        assert!(node("value: 1\nformat: json\nwrite: 3").is_err());
+        assert!(node("value: null\nformat: json").is_ok());
+    }
+}
@@ -0,0 +1,249 @@
+//! Switch node implementation
+//!
+//! This module contains the `Switch` node type, which picks one of several
+//! named branches by comparing a value against its `cases`. The name of the
+//! chosen branch is recorded as the `branch` field of the node output, and
+//! nodes declaring `on_branch: <switch id>.<branch>` only run when their
+//! branch was chosen.
+//!
+//! ```yaml
+//! - id: route
+//!   type: Switch
+//!   input:
+//!     value: ${{ environment }}
+//!     cases:
+//!       production: prod
+//!       staging: stage
+//!     default: other
+//! - id: deploy
+//!   type: Log
+//!   input: Deploying to production
+//!   on_branch: route.production
+//! ```
+
+use serde_yml::{Mapping, Value};
+use tracing::info;
+
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// A node choosing a branch by matching a value against cases
+///
+/// Cases are tried in definition order and the first one equal to the value
+/// is chosen. When none matches, the `default` branch is chosen if set, and
+/// the node fails otherwise.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::nodes::switch::SwitchNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("value: b\ncases:\n  first: a\n  second: b").unwrap();
+/// let node = SwitchNode::new(Some(input)).unwrap();
+///
+/// assert_eq!(node.branch().unwrap(), "second");
+/// assert_eq!(node.branches(), vec!["first", "second"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SwitchNode {
+    value: Value,
+    cases: Vec<(String, Value)>,
+    default: Option<String>,
+}
+
+impl SwitchNode {
+    /// Creates a switch node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the `value` to match, the `cases` mapping
+    ///   branch names to the value they match, and an optional `default`
+    ///   branch name
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if `cases` is missing, empty, or
+    /// not a mapping with string keys, or if `default` is not a string.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+        let value = input.get("value").cloned().unwrap_or(Value::Null);
+
+        let cases = match input.get("cases") {
+            Some(Value::Mapping(map)) if !map.is_empty() => map
+                .iter()
+                .map(|(key, value)| match key.as_str() {
+                    Some(key) => Ok((key.to_string(), value.clone())),
+                    None => Err(WorkflowError::NodeBuilder(
+                        "Switch node `cases` keys must be strings".to_string(),
+                    )),
+                })
+                .collect::<WorkflowResult<_>>()?,
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Switch node requires a non-empty `cases` mapping".to_string(),
+                ))
+            }
+        };
+
+        let default = match input.get("default") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(branch)) => Some(branch.clone()),
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Switch node `default` must be a branch name".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            value,
+            cases,
+            default,
+        })
+    }
+
+    /// Returns the names of the branches declared by an input, as written in
+    /// the workflow file
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the cases are not written literally, e.g. computed
+    /// from a placeholder.
+    pub fn declared_branches(input: &Value) -> Option<Vec<String>> {
+        let Value::Mapping(cases) = input.get("cases")? else {
+            return None;
+        };
+
+        Some(
+            cases
+                .keys()
+                .filter_map(Value::as_str)
+                .chain(input.get("default").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// Returns the names of the branches, cases first and default last
+    pub fn branches(&self) -> Vec<&str> {
+        self.cases
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain(self.default.as_deref())
+            .collect()
+    }
+
+    /// Returns the name of the branch chosen for the value
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeExecutionFailed` if no case matches and
+    /// there is no default branch.
+    pub fn branch(&self) -> WorkflowResult<&str> {
+        self.cases
+            .iter()
+            .find(|(_, value)| *value == self.value)
+            .map(|(name, _)| name.as_str())
+            .or(self.default.as_deref())
+            .ok_or_else(|| {
+                WorkflowError::NodeExecutionFailed(format!(
+                    "No case matches {} and there is no default branch",
+                    serde_yml::to_string(&self.value).map_or_else(
+                        |_| "the value".to_string(),
+                        |text| text.trim_end().to_string()
+                    )
+                ))
+            })
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["cases"],
+            "properties": {
+                "value": {
+                    "description": "Value matched against the cases, usually a ${{ ... }} placeholder"
+                },
+                "cases": {
+                    "type": "object",
+                    "minProperties": 1,
+                    "description": "Branch names mapped to the value they match, tried in order"
+                },
+                "default": {
+                    "type": "string",
+                    "description": "Branch chosen when no case matches"
+                }
+            }
+        })
+    }
+}
+
+impl BaseNode for SwitchNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let branch = self.branch()?;
+        info!("Switch '{}' took branch '{}'", options.prefix(), branch);
+
+        let mut output = Mapping::new();
+        output.insert(Value::from("branch"), Value::from(branch));
+        output.insert(Value::from("value"), self.value.clone());
+        Ok(Value::Mapping(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<SwitchNode> {
+        SwitchNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    #[test]
+    fn test_switch_node_first_matching_case() {
+        let node = node("value: 2\ncases:\n  low: 1\n  two: 2\n  again: 2").unwrap();
+        assert_eq!(node.branch().unwrap(), "two");
+    }
+
+    #[test]
+    fn test_switch_node_default_branch() {
+        let node = node("value: c\ncases:\n  a: a\ndefault: other").unwrap();
+        assert_eq!(node.branch().unwrap(), "other");
+        assert_eq!(node.branches(), vec!["a", "other"]);
+    }
+
+    #[test]
+    fn test_switch_node_no_match() {
+        let error = node("value: c\ncases:\n  a: a")
+            .unwrap()
+            .branch()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Node execution failed: No case matches c and there is no default branch"
+        );
+    }
+
+    #[test]
+    fn test_switch_node_invalid_input() {
+        assert!(node("value: a").is_err());
+        assert!(node("value: a\ncases: {}").is_err());
+        assert!(node("value: a\ncases: [a]").is_err());
+        assert!(node("value: a\ncases:\n  a: a\ndefault: [b]").is_err());
+    }
+
+    #[test]
+    fn test_switch_node_execute() {
+        let heap = Heap::new();
+        let output = node("value: a\ncases:\n  first: a")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "route"))
+            .unwrap();
+
+        assert_eq!(output.get("branch"), Some(&Value::from("first")));
+        assert_eq!(output.get("value"), Some(&Value::from("a")));
+    }
+}
@@ -0,0 +1,320 @@
+//! Template node implementation
+//!
+//! This module contains the `Template` node type, which renders a
+//! [template](crate::core::template) with the heap as context. The template
+//! is written inline or read from a file, and the result can be written to
+//! a file as well as being the output of the node.
+//!
+//! ```yaml
+//! - id: compose
+//!   type: Template
+//!   input:
+//!     template: |
+//!       services:
+//!       {% for service in services %}
+//!         {{ service.name }}:
+//!           image: {{ service.image | default("scratch") }}
+//!       {% endfor %}
+//!     write: build/compose.yml
+//! ```
+//!
+//! Paths are relative to the directory of the workflow file, and must stay
+//! inside it.
+
+use std::path::PathBuf;
+
+use serde_yml::{Mapping, Value};
+use tracing::info;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::template::Template;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// Where the template of a node comes from
+#[derive(Debug, Clone)]
+enum Source {
+    Inline(Template),
+    File(PathBuf),
+}
+
+/// A node rendering a template, optionally into a file
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::template::TemplateNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str(
+///     "template: \"{% for n in names %}<{{ n }}>{% endfor %}\"",
+/// )
+/// .unwrap();
+/// let node = TemplateNode::new(Some(input)).unwrap();
+///
+/// let mut heap = Heap::new();
+/// heap.insert("names", Some(serde_yml::from_str("[a, b]").unwrap()));
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "render")).unwrap();
+/// assert_eq!(output.get("content"), Some(&Value::from("<a><b>")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TemplateNode {
+    source: Source,
+    write: Option<PathBuf>,
+}
+
+impl TemplateNode {
+    /// Creates a template node from its evaluated input
+    ///
+    /// Inline templates are parsed right away, so that syntax errors are
+    /// reported before the workflow runs; template files are read when the
+    /// node executes.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with either an inline `template` or the `file` it
+    ///   is read from, and an optional `write` path for the result
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if neither or both of `template`
+    /// and `file` are set, if a field is not a string, or if the inline
+    /// template is not valid.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let source = match (
+            string_field(&input, "template")?,
+            string_field(&input, "file")?,
+        ) {
+            (Some(template), None) => Source::Inline(
+                Template::parse(template)
+                    .map_err(|e| WorkflowError::NodeBuilder(format!("Invalid template: {}", e)))?,
+            ),
+            (None, Some(file)) if !file.trim().is_empty() => Source::File(PathBuf::from(file)),
+            _ => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Template node requires either a `template` or a `file`".to_string(),
+                ))
+            }
+        };
+
+        let write = string_field(&input, "write")?
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from);
+
+        Ok(Self { source, write })
+    }
+
+    /// Returns the path the result is written to, if any
+    pub fn write_path(&self) -> Option<&PathBuf> {
+        self.write.as_ref()
+    }
+
+    /// Returns the template, reading it from its file if needed
+    fn template(&self, context: &RunContext) -> WorkflowResult<Template> {
+        match &self.source {
+            Source::Inline(template) => Ok(template.clone()),
+            Source::File(file) => {
+                let path = context.resolve_within(file)?;
+                let text = std::fs::read_to_string(&path).map_err(|e| {
+                    WorkflowError::NodeExecutionFailed(format!(
+                        "Failed to read template {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                Template::parse(&text).map_err(|e| {
+                    WorkflowError::NodeExecutionFailed(format!(
+                        "Invalid template {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })
+            }
+        }
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "oneOf": [
+                { "required": ["template"] },
+                { "required": ["file"] }
+            ],
+            "properties": {
+                "template": {
+                    "type": "string",
+                    "description": "Inline template, with {{ ... }} values and {% if %} / {% for %} blocks"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Template file, relative to the workflow file"
+                },
+                "write": {
+                    "type": "string",
+                    "description": "File the result is written to, relative to the workflow file"
+                }
+            }
+        })
+    }
+}
+
+impl BaseNode for TemplateNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let content = self
+            .template(context)?
+            .render(options.heap())
+            .map_err(|e| {
+                WorkflowError::NodeExecutionFailed(format!("Failed to render template: {}", e))
+            })?;
+
+        let mut output = Mapping::new();
+        output.insert(Value::from("content"), Value::from(content.as_str()));
+
+        if let Some(write) = &self.write {
+            let path = context.resolve_within(write)?;
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, &content));
+            written.map_err(|e| {
+                WorkflowError::NodeExecutionFailed(format!(
+                    "Failed to write {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+            info!(
+                "Template '{}' wrote {} bytes to {}",
+                options.prefix(),
+                content.len(),
+                path.display()
+            );
+            output.insert(Value::from("path"), Value::from(path.display().to_string()));
+        }
+
+        Ok(Value::Mapping(output))
+    }
+}
+
+/// Returns an optional string field of the input
+fn string_field<'a>(input: &'a Value, field: &str) -> WorkflowResult<Option<&'a str>> {
+    match input.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(text)) => Ok(Some(text)),
+        Some(_) => Err(WorkflowError::NodeBuilder(format!(
+            "Template node `{}` must be a string",
+            field
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    fn node(yaml: &str) -> WorkflowResult<TemplateNode> {
+        TemplateNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn heap() -> Heap {
+        let mut heap = Heap::new();
+        heap.insert(
+            "hosts",
+            Some(serde_yml::from_str("[{ name: web, port: 80 }, { name: db }]").unwrap()),
+        );
+        heap
+    }
+
+    #[test]
+    fn test_template_node_renders_file_and_writes_result() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("templates")).unwrap();
+        std::fs::write(
+            dir.path().join("templates/hosts.tmpl"),
+            "{% for host in hosts %}\n{{ host.name }} {{ host.port | default(22) }}\n{% endfor %}\n",
+        )
+        .unwrap();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+
+        let heap = heap();
+        let output = node("file: templates/hosts.tmpl\nwrite: out/hosts.txt")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "hosts").with_context(&context))
+            .unwrap();
+
+        let written = dir.path().join("out/hosts.txt");
+        assert_eq!(
+            std::fs::read_to_string(&written).unwrap(),
+            "web 80\ndb 22\n"
+        );
+        assert_eq!(output.get("content"), Some(&Value::from("web 80\ndb 22\n")));
+        assert_eq!(
+            output.get("path"),
+            Some(&Value::from(written.display().to_string()))
+        );
+    }
+
+    #[test]
+    fn test_template_node_render_error() {
+        let heap = heap();
+        let error = node("template: '{{ hosts | upper }}'")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "render"))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Node execution failed: Failed to render template: Filter 'upper' cannot be applied to a sequence on line 1"
+        );
+    }
+
+    #[test]
+    fn test_template_node_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = RunContext::new(dir.path().join("workflow.yml"));
+        let heap = heap();
+        let error = node("file: missing.tmpl")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "render").with_context(&context))
+            .unwrap_err();
+        assert!(error.to_string().contains("Failed to read template"));
+    }
+
+    #[test]
+    fn test_template_node_confined_to_workflow_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("flows")).unwrap();
+        std::fs::write(dir.path().join("secret.tmpl"), "secret").unwrap();
+        let context = RunContext::new(dir.path().join("flows/workflow.yml"));
+        let heap = heap();
+
+        for yaml in ["file: ../secret.tmpl", "{ template: x, write: ../out.txt }"] {
+            assert!(matches!(
+                node(yaml)
+                    .unwrap()
+                    .execute(BaseNodeRunOptions::new(&heap, "render").with_context(&context)),
+                Err(WorkflowError::OutsideWorkflowDir { .. })
+            ));
+        }
+        assert!(!dir.path().join("out.txt").exists());
+    }
+
+    #[test]
+    fn test_template_node_invalid_input() {
+        assert!(node("write: out.txt").is_err());
+        assert!(node("template: a\nfile: b").is_err());
+        assert!(node("template: [a]").is_err());
+        assert!(node("template: '{% if x %}'").is_err());
+        assert!(node("file: a\nwrite: 3").is_err());
+    }
+}
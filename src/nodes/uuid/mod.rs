@@ -0,0 +1,109 @@
+//! UUID node implementation
+//!
+//! This module contains the `UUID` node type, which outputs a new random
+//! (version 4) UUID, e.g. to tag the resources created by a run.
+//!
+//! ```yaml
+//! - id: request_id
+//!   type: UUID
+//! ```
+//!
+//! The UUID is made from the [randomness source](crate::core::random) of the
+//! run, so tests injecting a seeded source get the same UUIDs on every run.
+
+use serde_yml::Value;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// A node outputting a random UUID
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::heap::Heap;
+/// use colossus::nodes::base::{BaseNode, BaseNodeRunOptions};
+/// use colossus::nodes::uuid::UuidNode;
+///
+/// let node = UuidNode::new(None).unwrap();
+///
+/// let heap = Heap::new();
+/// let output = node.execute(BaseNodeRunOptions::new(&heap, "id")).unwrap();
+/// let uuid = output.as_str().unwrap();
+/// assert_eq!(uuid.len(), 36);
+/// assert_eq!(&uuid[14..15], "4");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UuidNode;
+
+impl UuidNode {
+    /// Creates a UUID node
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if an input is given, as the node
+    /// takes none.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        match input {
+            None | Some(Value::Null) => Ok(Self),
+            Some(_) => Err(WorkflowError::NodeBuilder(
+                "UUID node takes no input".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "null",
+            "description": "The node takes no input"
+        })
+    }
+}
+
+impl BaseNode for UuidNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let mut bytes = [0u8; 16];
+        context.random().fill_bytes(&mut bytes);
+        let uuid = uuid::Builder::from_random_bytes(bytes).into_uuid();
+        Ok(Value::from(uuid.hyphenated().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+    use crate::core::random::SeededRandom;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_uuid_node_uses_context_random() {
+        let heap = Heap::new();
+        let generate = |seed: u64| {
+            let context =
+                RunContext::new("workflow.yml").with_random(Arc::new(SeededRandom::new(seed)));
+            UuidNode::new(None)
+                .unwrap()
+                .execute(BaseNodeRunOptions::new(&heap, "id").with_context(&context))
+                .unwrap()
+        };
+
+        let uuid = generate(1);
+        assert_eq!(uuid, generate(1));
+        assert_ne!(uuid, generate(2));
+
+        let uuid = uuid::Uuid::parse_str(uuid.as_str().unwrap()).unwrap();
+        assert_eq!(uuid.get_version(), Some(uuid::Version::Random));
+    }
+
+    #[test]
+    fn test_uuid_node_invalid_input() {
+        assert!(UuidNode::new(Some(Value::Null)).is_ok());
+        assert!(UuidNode::new(Some(Value::from("v7"))).is_err());
+    }
+}
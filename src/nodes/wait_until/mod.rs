@@ -0,0 +1,399 @@
+//! Wait-until node implementation
+//!
+//! This module contains the `WaitUntil` node type, which polls until
+//! something succeeds or a deadline passes. Every attempt either runs a child
+//! `node`, evaluates a `condition`, or both: the attempt succeeds when the
+//! child node succeeds and the condition, which sees the child output as
+//! `result`, is true. The attempt number, starting at 1, is available as
+//! `attempt`.
+//!
+//! ```yaml
+//! - id: healthy
+//!   type: WaitUntil
+//!   input:
+//!     node:
+//!       type: Workflow
+//!       input:
+//!         path: checks/health.yml
+//!     condition: result.status == "up"
+//!     interval: 2s
+//!     timeout: 1m
+//! ```
+//!
+//! Conditions are written without `${{ ... }}`, so that they are evaluated
+//! on every attempt rather than once when the node is built. Waiting goes
+//! through the clock of the run context, see [`crate::core::clock`].
+
+use std::time::Duration;
+
+use serde_yml::{Mapping, Value};
+use tracing::debug;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::core::expression::Expression;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::NodeBuilder;
+use crate::shared::types::workflow::node::WorkflowNode;
+use crate::shared::utils::duration::parse_duration;
+
+/// Delay between attempts when no `interval` is set
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time allowed for the wait when no `timeout` is set
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A node polling a child node or a condition until it succeeds
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use colossus::nodes::wait_until::WaitUntilNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("condition: attempt == 3\ninterval: 500ms").unwrap();
+/// let node = WaitUntilNode::new(Some(input)).unwrap();
+///
+/// assert_eq!(node.interval(), Duration::from_millis(500));
+/// assert_eq!(node.timeout(), Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WaitUntilNode {
+    node: Option<WorkflowNode>,
+    condition: Option<Expression>,
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl WaitUntilNode {
+    /// Names bound within every attempt: the attempt number and the output
+    /// of the child node
+    pub const BINDINGS: &'static [&'static str] = &["attempt", "result"];
+
+    /// Creates a wait-until node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with a child `node` and/or a `condition`, and
+    ///   optional `interval` and `timeout` durations, such as `2s` or a
+    ///   number of seconds
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if neither `node` nor `condition`
+    /// is set, if the child node has no type, if the condition does not
+    /// parse, or if a duration is invalid.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let node = match input.get("node") {
+            None | Some(Value::Null) => None,
+            Some(Value::Mapping(spec)) => {
+                let node_type = spec
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .filter(|node_type| !node_type.trim().is_empty())
+                    .ok_or_else(|| {
+                        WorkflowError::NodeBuilder(
+                            "WaitUntil node `node` requires a `type`".to_string(),
+                        )
+                    })?;
+                Some(WorkflowNode::new("", node_type, spec.get("input").cloned()))
+            }
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "WaitUntil node `node` must be a mapping with a `type` and an `input`"
+                        .to_string(),
+                ))
+            }
+        };
+
+        let condition = match input.get("condition") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(source)) => Some(Expression::parse(source).map_err(|e| {
+                WorkflowError::NodeBuilder(format!(
+                    "Invalid WaitUntil condition '{}': {}",
+                    source, e
+                ))
+            })?),
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "WaitUntil node `condition` must be an expression".to_string(),
+                ))
+            }
+        };
+
+        if node.is_none() && condition.is_none() {
+            return Err(WorkflowError::NodeBuilder(
+                "WaitUntil node requires a `node` or a `condition`".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            node,
+            condition,
+            interval: duration(&input, "interval")?.unwrap_or(DEFAULT_INTERVAL),
+            timeout: duration(&input, "timeout")?.unwrap_or(DEFAULT_TIMEOUT),
+        })
+    }
+
+    /// Returns the delay between attempts
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the time allowed before giving up
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        let duration = serde_json::json!([
+            { "type": "string" },
+            { "type": "number", "minimum": 0 }
+        ]);
+
+        serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "anyOf": [{ "required": ["node"] }, { "required": ["condition"] }],
+            "properties": {
+                "node": {
+                    "type": "object",
+                    "description": "Node run on every attempt, with its `type` and `input`",
+                    "required": ["type"],
+                    "properties": {
+                        "type": { "type": "string" },
+                        "input": {}
+                    }
+                },
+                "condition": {
+                    "type": "string",
+                    "description": "Expression that must be true, seeing the child output as `result`"
+                },
+                "interval": {
+                    "description": "Delay between attempts, such as 500ms or 2s (default 1s)",
+                    "oneOf": duration.clone()
+                },
+                "timeout": {
+                    "description": "Time allowed before giving up, such as 30s or 5m (default 1m)",
+                    "oneOf": duration
+                }
+            }
+        })
+    }
+
+    /// Makes a single attempt
+    ///
+    /// # Returns
+    ///
+    /// Returns the output of the child node, or `null` without one, if the
+    /// attempt succeeded, or why it did not.
+    fn attempt(
+        &self,
+        attempt: usize,
+        options: &BaseNodeRunOptions,
+        context: &RunContext,
+    ) -> Result<Value, String> {
+        let mut heap = options
+            .heap()
+            .scope([("attempt".to_string(), Value::from(attempt))]);
+
+        let result = match &self.node {
+            Some(node) => {
+                let child = NodeBuilder::new()
+                    .with_workflow_node(node.clone())
+                    .with_registry(context.registry().clone())
+                    .build(&mut heap)
+                    .map_err(|e| e.to_string())?;
+                child
+                    .execute(BaseNodeRunOptions::new(&heap, options.prefix()).with_context(context))
+                    .map_err(|e| e.to_string())?
+            }
+            None => Value::Null,
+        };
+
+        if let Some(condition) = &self.condition {
+            heap.insert("result", Some(result.clone()));
+            let met = condition
+                .evaluate_bool(&heap)
+                .map_err(|e| format!("condition '{}' failed: {}", condition, e))?;
+            if !met {
+                return Err(format!("condition '{}' is false", condition));
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl BaseNode for WaitUntilNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+        let clock = context.clock();
+
+        let started = clock.now();
+        let deadline =
+            started + chrono::Duration::from_std(self.timeout).unwrap_or(chrono::Duration::MAX);
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let reason = match self.attempt(attempt, &options, context) {
+                Ok(result) => {
+                    let mut output = Mapping::new();
+                    output.insert(Value::from("attempts"), Value::from(attempt));
+                    output.insert(Value::from("result"), result);
+                    return Ok(Value::Mapping(output));
+                }
+                Err(reason) => reason,
+            };
+            debug!(
+                "Attempt {} of '{}' failed: {}",
+                attempt,
+                options.prefix(),
+                reason
+            );
+
+            let now = clock.now();
+            if now >= deadline {
+                return Err(WorkflowError::NodeExecutionFailed(format!(
+                    "Gave up after {} attempt(s) in {}s: {}",
+                    attempt,
+                    (now - started).num_seconds(),
+                    reason
+                )));
+            }
+            if context.control().is_stopped() {
+                return Err(WorkflowError::NodeExecutionFailed(format!(
+                    "Stopped while waiting, after {} attempt(s): {}",
+                    attempt, reason
+                )));
+            }
+
+            let remaining = (deadline - now).to_std().unwrap_or_default();
+            clock.sleep(self.interval.min(remaining));
+        }
+    }
+}
+
+/// Reads an optional duration field, given as text or as seconds
+fn duration(input: &Value, field: &str) -> WorkflowResult<Option<Duration>> {
+    let invalid = |reason: String| {
+        WorkflowError::NodeBuilder(format!("Invalid WaitUntil `{}`: {}", field, reason))
+    };
+
+    match input.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(text)) => parse_duration(text).map(Some).map_err(invalid),
+        Some(Value::Number(seconds)) => seconds
+            .as_f64()
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+            .map(|seconds| Some(Duration::from_secs_f64(seconds)))
+            .ok_or_else(|| invalid(format!("{} is not a number of seconds", seconds))),
+        Some(_) => Err(invalid("expected a duration such as 30s".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::core::clock::ManualClock;
+    use crate::core::heap::Heap;
+    use crate::nodes::registry::NodeRegistry;
+
+    /// Node failing until its third run
+    struct FlakyNode(Arc<AtomicUsize>);
+
+    impl BaseNode for FlakyNode {
+        fn execute(&self, _options: BaseNodeRunOptions) -> WorkflowResult<Value> {
+            match self.0.fetch_add(1, Ordering::SeqCst) + 1 {
+                runs if runs < 3 => Err(WorkflowError::NodeExecutionFailed("not yet".to_string())),
+                runs => Ok(Value::from(runs)),
+            }
+        }
+    }
+
+    fn node(yaml: &str) -> WorkflowResult<WaitUntilNode> {
+        WaitUntilNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn run(yaml: &str) -> (WorkflowResult<Value>, Arc<ManualClock>) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let registry =
+            NodeRegistry::new().with_node("Flaky", move |_| Ok(Box::new(FlakyNode(runs.clone()))));
+        let clock = Arc::new(ManualClock::new(
+            chrono::Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        ));
+        let context = RunContext::default()
+            .with_registry(registry)
+            .with_clock(clock.clone());
+        let mut heap = Heap::new();
+        heap.insert("target", Some(Value::from(2)));
+
+        let result = node(yaml).and_then(|node| {
+            node.execute(BaseNodeRunOptions::new(&heap, "wait").with_context(&context))
+        });
+        (result, clock)
+    }
+
+    #[test]
+    fn test_wait_until_node_new() {
+        let node = node("node:\n  type: Log\ninterval: 2\ntimeout: 1m").unwrap();
+        assert_eq!(node.interval(), Duration::from_secs(2));
+        assert_eq!(node.timeout(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_wait_until_node_invalid_input() {
+        assert!(node("interval: 1s").is_err());
+        assert!(node("node: Log").is_err());
+        assert!(node("node:\n  input: x").is_err());
+        assert!(node("condition: 'attempt =='").is_err());
+        assert!(node("condition: 'true'\ntimeout: soon").is_err());
+        assert!(node("condition: 'true'\ninterval: -1").is_err());
+    }
+
+    #[test]
+    fn test_wait_until_node_retries_child_node() {
+        let (result, clock) = run("node:\n  type: Flaky\ninterval: 5s");
+
+        let output = result.unwrap();
+        assert_eq!(output.get("attempts"), Some(&Value::from(3)));
+        assert_eq!(output.get("result"), Some(&Value::from(3)));
+        assert_eq!(clock.slept(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_wait_until_node_condition() {
+        let (result, clock) = run("condition: attempt > target\ninterval: 1s");
+
+        assert_eq!(result.unwrap().get("attempts"), Some(&Value::from(3)));
+        assert_eq!(clock.slept(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_wait_until_node_condition_on_result() {
+        let (result, _) = run("node:\n  type: Flaky\ncondition: result >= 4");
+        assert_eq!(result.unwrap().get("result"), Some(&Value::from(4)));
+    }
+
+    #[test]
+    fn test_wait_until_node_times_out() {
+        let (result, clock) = run("condition: attempt < 0\ninterval: 4s\ntimeout: 10s");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Node execution failed: Gave up after 4 attempt(s) in 10s: condition 'attempt < 0' is false"
+        );
+        assert_eq!(clock.slept(), Duration::from_secs(10));
+    }
+}
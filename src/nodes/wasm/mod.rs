@@ -0,0 +1,722 @@
+//! WebAssembly node implementation
+//!
+//! This module runs nodes implemented as WebAssembly modules, a sandboxed
+//! alternative to [plugin executables](crate::nodes::external). A module is a
+//! WASI (preview 1) command speaking the same protocol as plugin executables:
+//! each `describe` or `execute` request is written as a line of JSON to the
+//! standard input of a fresh instance, whose `_start` function answers on its
+//! standard output.
+//!
+//! Modules have no ambient authority: they see no environment variables, no
+//! files and no network. Directories and environment variables are only
+//! visible to them when granted with [`WasmPermissions`] in
+//! [`ExecuteWorkflowOptions`](crate::core::engine::ExecuteWorkflowOptions),
+//! or by the `options.wasm` section of the workflow, and WASI preview 1
+//! provides no sockets at all.
+//!
+//! An instance still running after the timeout of its module is interrupted,
+//! like a plugin executable would be killed. Compiled modules are cached in
+//! the [cache directory](crate::infrastructure::paths::cache_dir), so that a
+//! module is only compiled again once it changes.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value as JsonValue;
+use serde_yml::Value;
+use tracing::{debug, warn};
+use wasmtime::{Config, Engine, Linker, Module, Store, Trap};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{DirPerms, FilePerms, I32Exit, WasiCtxBuilder};
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowResult};
+use crate::infrastructure::paths::cache_dir;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+use crate::nodes::external::{
+    describe_request, describe_response, describe_timeout, execute_output, execute_request,
+    plugin_error, DEFAULT_TIMEOUT, DESCRIBE_TIMEOUT,
+};
+
+/// Maximum size of a response written by a module, in bytes
+pub const MAX_RESPONSE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Maximum size of the standard error kept for error messages, in bytes
+const MAX_STDERR_SIZE: usize = 64 * 1024;
+
+/// Interval between two epochs of the engine, the precision of timeouts
+const EPOCH_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Directories and environment variables of the host that WebAssembly nodes
+/// may access
+///
+/// Host paths are relative to the current directory, and each directory is
+/// visible to the modules under its guest path. Environment variables are
+/// granted by name, and only passed to the modules when set on the host.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::core::engine::ExecuteWorkflowOptions;
+/// use colossus::nodes::wasm::WasmPermissions;
+///
+/// let permissions = WasmPermissions::new()
+///     .with_read_only_dir("assets", "/assets")
+///     .with_dir("build", "/out")
+///     .with_env("API_URL");
+/// let options = ExecuteWorkflowOptions::new("workflow.yml").with_wasm_permissions(permissions);
+/// assert_eq!(options.wasm_permissions().dirs().len(), 2);
+/// assert_eq!(options.wasm_permissions().env(), ["API_URL"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasmPermissions {
+    dirs: Vec<DirGrant>,
+    env: Vec<String>,
+}
+
+/// A host directory granted to WebAssembly nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirGrant {
+    /// Path of the directory on the host
+    pub host: PathBuf,
+
+    /// Path of the directory as seen by the modules
+    pub guest: String,
+
+    /// Whether modules may create, modify and remove files
+    pub writable: bool,
+}
+
+impl WasmPermissions {
+    /// Creates permissions granting nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method granting read and write access to a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Path of the directory on the host
+    /// * `guest` - Path of the directory as seen by the modules
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_dir(self, host: impl Into<PathBuf>, guest: impl Into<String>) -> Self {
+        self.with_grant(host.into(), guest.into(), true)
+    }
+
+    /// Builder method granting read access to a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Path of the directory on the host
+    /// * `guest` - Path of the directory as seen by the modules
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_read_only_dir(self, host: impl Into<PathBuf>, guest: impl Into<String>) -> Self {
+        self.with_grant(host.into(), guest.into(), false)
+    }
+
+    /// Builder method granting access to a host environment variable
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the environment variable
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_env(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.env.contains(&name) {
+            self.env.push(name);
+        }
+        self
+    }
+
+    /// Returns the granted directories
+    pub fn dirs(&self) -> &[DirGrant] {
+        &self.dirs
+    }
+
+    /// Returns the names of the granted environment variables
+    pub fn env(&self) -> &[String] {
+        &self.env
+    }
+
+    fn with_grant(mut self, host: PathBuf, guest: String, writable: bool) -> Self {
+        self.dirs.push(DirGrant {
+            host,
+            guest,
+            writable,
+        });
+        self
+    }
+}
+
+/// A node type provided by a WebAssembly module
+///
+/// The module is compiled once, when loaded or first executed, or taken
+/// from the compilation cache, and instantiated for every request.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use colossus::nodes::registry::NodeRegistry;
+/// use colossus::nodes::wasm::WasmPlugin;
+///
+/// let plugin = WasmPlugin::load("plugins/slugify.wasm").unwrap();
+/// let registry = NodeRegistry::new().with_wasm_plugin(plugin);
+/// ```
+#[derive(Clone)]
+pub struct WasmPlugin {
+    node_type: String,
+    path: PathBuf,
+    schema: Option<JsonValue>,
+    timeout: Duration,
+    module: Arc<OnceLock<Module>>,
+}
+
+impl WasmPlugin {
+    /// Creates a plugin without compiling the module or running its
+    /// `describe` handshake
+    ///
+    /// # Arguments
+    ///
+    /// * `node_type` - The type name used in workflow files
+    /// * `path` - Path of the module file
+    pub fn new(node_type: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            node_type: node_type.into(),
+            path: path.into(),
+            schema: None,
+            timeout: DEFAULT_TIMEOUT,
+            module: Arc::default(),
+        }
+    }
+
+    /// Builder method to set the JSON Schema of the node input
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_schema(mut self, schema: JsonValue) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Builder method to set the time an execution may take
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Compiles a module and runs its `describe` handshake
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::Plugin` if the module cannot be compiled, does
+    /// not answer in time, or does not answer with a node type.
+    pub fn load(path: impl Into<PathBuf>) -> WorkflowResult<Self> {
+        let path = path.into();
+        let module = compile(&path)?;
+
+        let response = run(
+            &path,
+            &module,
+            &describe_request(),
+            &WasmPermissions::new(),
+            DESCRIBE_TIMEOUT,
+        )?;
+        let (node_type, schema) = describe_response(&path, &response)?;
+        let timeout = describe_timeout(&path, &response)?.unwrap_or(DEFAULT_TIMEOUT);
+
+        Ok(Self {
+            node_type,
+            path,
+            schema,
+            timeout,
+            module: Arc::new(OnceLock::from(module)),
+        })
+    }
+
+    /// Returns the type name used in workflow files
+    pub fn node_type(&self) -> &str {
+        &self.node_type
+    }
+
+    /// Returns the path of the module file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the JSON Schema of the node input, if the module provides one
+    pub fn schema(&self) -> Option<&JsonValue> {
+        self.schema.as_ref()
+    }
+
+    /// Returns the time an execution may take
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Returns the compiled module, compiling it on first use
+    fn module(&self) -> WorkflowResult<&Module> {
+        if let Some(module) = self.module.get() {
+            return Ok(module);
+        }
+        let module = compile(&self.path)?;
+        Ok(self.module.get_or_init(|| module))
+    }
+}
+
+impl fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("node_type", &self.node_type)
+            .field("path", &self.path)
+            .field("schema", &self.schema)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A node executed by a WebAssembly module
+#[derive(Debug, Clone)]
+pub struct WasmNode {
+    plugin: WasmPlugin,
+    input: Option<Value>,
+}
+
+impl WasmNode {
+    /// Creates a node executed by the given module
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin` - The module providing the node type
+    /// * `input` - The evaluated input sent to the module
+    pub fn new(plugin: WasmPlugin, input: Option<Value>) -> Self {
+        Self { plugin, input }
+    }
+}
+
+impl BaseNode for WasmNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let request = execute_request(&options, context, &self.plugin.node_type, &self.input)?;
+        let response = self
+            .plugin
+            .module()
+            .and_then(|module| {
+                run(
+                    &self.plugin.path,
+                    module,
+                    &request,
+                    context.wasm_permissions(),
+                    self.plugin.timeout,
+                )
+            })
+            .map_err(|e| WorkflowError::NodeExecutionFailed(e.to_string()))?;
+
+        execute_output(&response)
+    }
+}
+
+/// Returns the engine shared by all modules
+///
+/// Its epoch is incremented by a background thread, so that instances can be
+/// given a deadline.
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let engine = new_engine(Some(&cache_dir()));
+        let ticker = engine.clone();
+        thread::spawn(move || loop {
+            thread::sleep(EPOCH_INTERVAL);
+            ticker.increment_epoch();
+        });
+        engine
+    })
+}
+
+/// Creates an engine with epoch interruption, caching compiled modules in
+/// a directory
+///
+/// When the cache cannot be configured, modules are compiled every time.
+fn new_engine(cache_dir: Option<&Path>) -> Engine {
+    let mut config = Config::new();
+    config.epoch_interruption(true);
+
+    if let Some(cache_dir) = cache_dir {
+        let cached = write_cache_config(cache_dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| config.cache_config_load(file).map(|_| ()));
+        if let Err(e) = cached {
+            warn!("Compiled WebAssembly modules are not cached: {:#}", e);
+        }
+    }
+
+    Engine::new(&config).expect("the WebAssembly engine configuration is valid")
+}
+
+/// Writes the configuration of the wasmtime compilation cache, storing
+/// compiled modules under a directory
+///
+/// # Returns
+///
+/// Returns the path of the configuration file.
+fn write_cache_config(cache_dir: &Path) -> std::io::Result<PathBuf> {
+    let directory = std::path::absolute(cache_dir.join("wasm"))?;
+    let content = format!(
+        "[cache]\nenabled = true\ndirectory = {}\n",
+        toml::Value::String(directory.display().to_string())
+    );
+
+    let file = cache_dir.join("wasmtime.toml");
+    if std::fs::read_to_string(&file).ok().as_deref() != Some(content.as_str()) {
+        std::fs::create_dir_all(cache_dir)?;
+        std::fs::write(&file, content)?;
+    }
+    Ok(file)
+}
+
+/// Compiles the module at a path
+fn compile(path: &Path) -> WorkflowResult<Module> {
+    Module::from_file(engine(), path)
+        .map_err(|e| plugin_error(path, &format!("cannot be compiled: {:#}", e)))
+}
+
+/// Sends a request to a fresh instance of a module and reads its response
+///
+/// The instance is interrupted once the timeout is over.
+fn run(
+    path: &Path,
+    module: &Module,
+    request: &JsonValue,
+    permissions: &WasmPermissions,
+    timeout: Duration,
+) -> WorkflowResult<JsonValue> {
+    debug!("Calling module {}: {}", path.display(), request["action"]);
+
+    let stdout = MemoryOutputPipe::new(MAX_RESPONSE_SIZE);
+    let stderr = MemoryOutputPipe::new(MAX_STDERR_SIZE);
+
+    let mut wasi = WasiCtxBuilder::new();
+    wasi.stdin(MemoryInputPipe::new(format!("{}\n", request)))
+        .stdout(stdout.clone())
+        .stderr(stderr.clone())
+        .args(&[path.display().to_string()]);
+    for name in permissions.env() {
+        if let Ok(value) = std::env::var(name) {
+            wasi.env(name, value);
+        }
+    }
+    for dir in permissions.dirs() {
+        let (dir_perms, file_perms) = if dir.writable {
+            (DirPerms::all(), FilePerms::all())
+        } else {
+            (DirPerms::READ, FilePerms::READ)
+        };
+        wasi.preopened_dir(&dir.host, &dir.guest, dir_perms, file_perms)
+            .map_err(|e| {
+                plugin_error(
+                    path,
+                    &format!("cannot be granted {}: {}", dir.host.display(), e),
+                )
+            })?;
+    }
+
+    let mut store = Store::new(engine(), wasi.build_p1());
+    let ticks = timeout
+        .as_nanos()
+        .div_ceil(EPOCH_INTERVAL.as_nanos())
+        .max(1);
+    store.set_epoch_deadline(u64::try_from(ticks).unwrap_or(u64::MAX));
+    let mut linker: Linker<WasiP1Ctx> = Linker::new(engine());
+    preview1::add_to_linker_sync(&mut linker, |wasi| wasi)
+        .map_err(|e| plugin_error(path, &format!("cannot be linked: {:#}", e)))?;
+
+    let start = linker
+        .instantiate(&mut store, module)
+        .and_then(|instance| instance.get_typed_func::<(), ()>(&mut store, "_start"))
+        .map_err(|e| plugin_error(path, &format!("cannot be instantiated: {:#}", e)))?;
+
+    if let Err(e) = start.call(&mut store, ()) {
+        if !matches!(e.downcast_ref::<I32Exit>(), Some(I32Exit(0))) {
+            let stderr = String::from_utf8_lossy(&stderr.contents())
+                .trim()
+                .to_string();
+            let message = match (e.downcast_ref::<I32Exit>(), e.downcast_ref::<Trap>()) {
+                (Some(I32Exit(code)), _) => format!("exited with code {}: {}", code, stderr),
+                (_, Some(Trap::Interrupt)) => {
+                    format!("did not answer within {} seconds", timeout.as_secs_f64())
+                }
+                _ => format!("failed: {:#}", e),
+            };
+            return Err(plugin_error(path, &message));
+        }
+    }
+    drop(store);
+
+    let stdout = stdout.contents();
+    let stdout = String::from_utf8_lossy(&stdout);
+    match serde_json::from_str::<JsonValue>(stdout.trim()) {
+        Ok(response) if response.is_object() => Ok(response),
+        _ => Err(plugin_error(
+            path,
+            &format!("did not answer with a JSON object: {}", stdout.trim()),
+        )),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::core::heap::Heap;
+
+    /// Module echoing its execute requests, and whether it can open
+    /// `secret.txt` in the first granted directory
+    const ECHO_MODULE: &str = r#"
+(module
+  (import "wasi_snapshot_preview1" "fd_read"
+    (func $fd_read (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write"
+    (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "path_open"
+    (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 100) "{\"type\":\"Echo\",\"input_schema\":{\"type\":\"object\"}}")
+  (data (i32.const 200) "{\"output\":{\"granted\":true,\"request\":")
+  (data (i32.const 300) "{\"output\":{\"granted\":false,\"request\":")
+  (data (i32.const 400) "}}")
+  (data (i32.const 420) "secret.txt")
+  (func $write (param $ptr i32) (param $len i32)
+    (i32.store (i32.const 32) (local.get $ptr))
+    (i32.store (i32.const 36) (local.get $len))
+    (drop (call $fd_write (i32.const 1) (i32.const 32) (i32.const 1) (i32.const 40))))
+  (func (export "_start")
+    (i32.store (i32.const 0) (i32.const 1024))
+    (i32.store (i32.const 4) (i32.const 60000))
+    (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 16)))
+    ;; {"action":"describe"} and {"action":"execute",...} differ at byte 11
+    (if (i32.eq (i32.load8_u (i32.const 1035)) (i32.const 100))
+      (then (call $write (i32.const 100) (i32.const 48)) (return)))
+    (if (i32.eqz (call $path_open (i32.const 3) (i32.const 0) (i32.const 420) (i32.const 10)
+                   (i32.const 0) (i64.const 2) (i64.const 0) (i32.const 0) (i32.const 48)))
+      (then (call $write (i32.const 200) (i32.const 36)))
+      (else (call $write (i32.const 300) (i32.const 37))))
+    (call $write (i32.const 1024) (i32.load (i32.const 16)))
+    (call $write (i32.const 400) (i32.const 2))))
+"#;
+
+    /// Module failing with an exit code
+    const FAILING_MODULE: &str = r#"
+(module
+  (import "wasi_snapshot_preview1" "fd_write"
+    (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 100) "crashed")
+  (func (export "_start")
+    (i32.store (i32.const 0) (i32.const 100))
+    (i32.store (i32.const 4) (i32.const 7))
+    (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 8)))
+    (call $proc_exit (i32.const 3))))
+"#;
+
+    /// Module never answering
+    const LOOPING_MODULE: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "_start") (loop (br 0))))
+"#;
+
+    /// Module answering with the number of its environment variables
+    const ENV_MODULE: &str = r#"
+(module
+  (import "wasi_snapshot_preview1" "environ_sizes_get"
+    (func $environ_sizes_get (param i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write"
+    (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 100) "{\"output\":0}")
+  (func (export "_start")
+    (drop (call $environ_sizes_get (i32.const 0) (i32.const 4)))
+    (i32.store8 (i32.const 110) (i32.add (i32.const 48) (i32.load (i32.const 0))))
+    (i32.store (i32.const 16) (i32.const 100))
+    (i32.store (i32.const 20) (i32.const 12))
+    (drop (call $fd_write (i32.const 1) (i32.const 16) (i32.const 1) (i32.const 24)))))
+"#;
+
+    /// Writes the module echoing its execute requests
+    pub(crate) fn echo_module(path: &Path) {
+        std::fs::write(path, wat::parse_str(ECHO_MODULE).unwrap()).unwrap();
+    }
+
+    fn execute(plugin: &WasmPlugin, permissions: WasmPermissions) -> WorkflowResult<Value> {
+        let dir = tempfile::tempdir().unwrap();
+        let context =
+            RunContext::new(dir.path().join("workflow.yml")).with_wasm_permissions(permissions);
+        let heap = Heap::new();
+        WasmNode::new(plugin.clone(), Some(Value::from("hello")))
+            .execute(BaseNodeRunOptions::new(&heap, "call").with_context(&context))
+    }
+
+    #[test]
+    fn test_wasm_plugin_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("echo.wasm");
+        echo_module(&path);
+
+        let plugin = WasmPlugin::load(&path).unwrap();
+        assert_eq!(plugin.node_type(), "Echo");
+        assert_eq!(plugin.path(), path);
+        assert_eq!(
+            plugin.schema(),
+            Some(&serde_json::json!({ "type": "object" }))
+        );
+    }
+
+    #[test]
+    fn test_wasm_plugin_load_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let invalid = dir.path().join("invalid.wasm");
+        std::fs::write(&invalid, b"\0asm garbage").unwrap();
+        let error = WasmPlugin::load(&invalid).unwrap_err().to_string();
+        assert!(error.contains("cannot be compiled"), "{}", error);
+
+        let failing = dir.path().join("failing.wasm");
+        std::fs::write(&failing, wat::parse_str(FAILING_MODULE).unwrap()).unwrap();
+        let error = WasmPlugin::load(&failing).unwrap_err().to_string();
+        assert!(error.ends_with("exited with code 3: crashed"), "{}", error);
+    }
+
+    #[test]
+    fn test_wasm_node_execute_without_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("echo.wasm");
+        echo_module(&path);
+        let plugin = WasmPlugin::load(&path).unwrap();
+
+        let output = execute(&plugin, WasmPermissions::new()).unwrap();
+        assert_eq!(output["granted"], Value::from(false));
+        assert_eq!(output["request"]["action"], Value::from("execute"));
+        assert_eq!(output["request"]["node"]["id"], Value::from("call"));
+        assert_eq!(output["request"]["node"]["type"], Value::from("Echo"));
+        assert_eq!(output["request"]["input"], Value::from("hello"));
+    }
+
+    #[test]
+    fn test_wasm_node_execute_with_granted_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("echo.wasm");
+        echo_module(&path);
+        let plugin = WasmPlugin::load(&path).unwrap();
+
+        let data = dir.path().join("data");
+        std::fs::create_dir(&data).unwrap();
+        std::fs::write(data.join("secret.txt"), "secret").unwrap();
+
+        let permissions = WasmPermissions::new().with_read_only_dir(&data, "/data");
+        let output = execute(&plugin, permissions).unwrap();
+        assert_eq!(output["granted"], Value::from(true));
+
+        let missing = WasmPermissions::new().with_dir(dir.path().join("missing"), "/data");
+        assert!(execute(&plugin, missing).is_err());
+    }
+
+    #[test]
+    fn test_wasm_plugin_new_compiles_on_first_execution() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("echo.wasm");
+        let plugin = WasmPlugin::new("Echo", &path);
+        assert!(plugin.schema().is_none());
+
+        let error = execute(&plugin, WasmPermissions::new()).unwrap_err();
+        assert!(
+            error.to_string().contains("cannot be compiled"),
+            "{}",
+            error
+        );
+
+        echo_module(&path);
+        let output = execute(&plugin, WasmPermissions::new()).unwrap();
+        assert_eq!(output["request"]["node"]["type"], Value::from("Echo"));
+    }
+
+    #[test]
+    fn test_wasm_node_execute_with_granted_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("env.wasm");
+        std::fs::write(&path, wat::parse_str(ENV_MODULE).unwrap()).unwrap();
+        let plugin = WasmPlugin::new("Env", &path);
+        std::env::set_var("COLOSSUS_TEST_WASM_ENV", "granted");
+
+        assert_eq!(
+            execute(&plugin, WasmPermissions::new()).unwrap(),
+            Value::from(0)
+        );
+        let permissions = WasmPermissions::new()
+            .with_env("COLOSSUS_TEST_WASM_ENV")
+            .with_env("COLOSSUS_TEST_WASM_UNSET");
+        assert_eq!(execute(&plugin, permissions).unwrap(), Value::from(1));
+    }
+
+    #[test]
+    fn test_wasm_node_interrupted_after_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loop.wasm");
+        std::fs::write(&path, wat::parse_str(LOOPING_MODULE).unwrap()).unwrap();
+        let plugin = WasmPlugin::new("Loop", &path).with_timeout(Duration::from_millis(200));
+
+        let started = std::time::Instant::now();
+        let error = execute(&plugin, WasmPermissions::new())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("did not answer within 0.2 seconds"),
+            "{}",
+            error
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_compiled_modules_are_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("echo.wasm");
+        echo_module(&path);
+        let cache = dir.path().join("cache");
+
+        let engine = new_engine(Some(&cache));
+        Module::from_file(&engine, &path).unwrap();
+
+        fn count_files(dir: &Path) -> usize {
+            std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .map(|entry| match entry.path() {
+                    path if path.is_dir() => count_files(&path),
+                    _ => 1,
+                })
+                .sum()
+        }
+        assert!(cache.join("wasmtime.toml").is_file());
+        assert!(count_files(&cache.join("wasm")) > 0);
+    }
+}
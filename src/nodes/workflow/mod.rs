@@ -0,0 +1,353 @@
+//! Sub-workflow node implementation
+//!
+//! This module contains the `Workflow` node type, which runs another
+//! workflow file as a single step. The called workflow runs against its own
+//! heap, so it only sees the inputs mapped to it and cannot modify the data
+//! of its caller; its evaluated `output` becomes the output of the node.
+//!
+//! ```yaml
+//! - id: build
+//!   type: Workflow
+//!   input:
+//!     path: lib/build.yml
+//!     inputs:
+//!       target: ${{ target }}
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_yml::{Mapping, Value};
+use tracing::info;
+
+use crate::core::context::RunContext;
+use crate::core::engine::{WorkflowError, WorkflowExecutor, WorkflowResult};
+use crate::core::heap::Heap;
+use crate::core::source::SourceFile;
+use crate::nodes::base::{BaseNode, BaseNodeRunOptions};
+
+/// A node that runs another workflow file
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::nodes::workflow::SubWorkflowNode;
+/// use serde_yml::Value;
+///
+/// let input: Value = serde_yml::from_str("path: build.yml\ninputs:\n  target: release").unwrap();
+/// let node = SubWorkflowNode::new(Some(input)).unwrap();
+///
+/// assert_eq!(node.path().to_str(), Some("build.yml"));
+/// assert_eq!(node.inputs().get("target"), Some(&Value::from("release")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubWorkflowNode {
+    path: PathBuf,
+    inputs: HashMap<String, Value>,
+}
+
+impl SubWorkflowNode {
+    /// Creates a sub-workflow node from its evaluated input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Mapping with the `path` of the workflow file, relative to
+    ///   the calling workflow, and optional `inputs` passed to it
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorkflowError::NodeBuilder` if `path` is missing or `inputs`
+    /// is not a mapping with string keys.
+    pub fn new(input: Option<Value>) -> WorkflowResult<Self> {
+        let input = input.unwrap_or(Value::Null);
+
+        let path = input
+            .get("path")
+            .and_then(Value::as_str)
+            .filter(|path| !path.trim().is_empty())
+            .ok_or_else(|| {
+                WorkflowError::NodeBuilder(
+                    "Workflow node requires a `path` to a workflow file".to_string(),
+                )
+            })?;
+
+        let inputs = match input.get("inputs") {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(Value::Mapping(map)) => map
+                .iter()
+                .map(|(key, value)| match key.as_str() {
+                    Some(key) => Ok((key.to_string(), value.clone())),
+                    None => Err(WorkflowError::NodeBuilder(
+                        "Workflow node `inputs` keys must be strings".to_string(),
+                    )),
+                })
+                .collect::<WorkflowResult<_>>()?,
+            Some(_) => {
+                return Err(WorkflowError::NodeBuilder(
+                    "Workflow node `inputs` must be a mapping".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            inputs,
+        })
+    }
+
+    /// Returns the path of the called workflow, relative to the caller
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the inputs passed to the called workflow
+    pub fn inputs(&self) -> &HashMap<String, Value> {
+        &self.inputs
+    }
+
+    /// Returns the JSON Schema of the node input
+    pub fn input_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["path"],
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Workflow file to run, relative to the calling workflow"
+                },
+                "inputs": {
+                    "type": "object",
+                    "description": "Values passed to the inputs of the called workflow"
+                }
+            }
+        })
+    }
+}
+
+impl BaseNode for SubWorkflowNode {
+    fn execute(&self, options: BaseNodeRunOptions) -> Result<Value, WorkflowError> {
+        let default_context = RunContext::default();
+        let context = options.context().unwrap_or(&default_context);
+
+        let child = context.enter(&self.path)?;
+        let workflow = SourceFile::read(child.workflow_path())?.parse()?;
+
+        info!(
+            "Running sub-workflow {} (depth {})",
+            child.workflow_path().display(),
+            child.depth()
+        );
+        let run =
+            WorkflowExecutor::run_in_context(workflow, &self.inputs, &mut Heap::new(), &child);
+
+        if let Some(e) = run.error {
+            return Err(WorkflowError::NodeExecutionFailed(format!(
+                "Sub-workflow {} failed: {}",
+                self.path.display(),
+                e
+            )));
+        }
+        if !run.is_successful() {
+            return Err(WorkflowError::NodeExecutionFailed(format!(
+                "Sub-workflow {} did not complete ({:?})",
+                self.path.display(),
+                run.status
+            )));
+        }
+
+        let mut keys: Vec<&String> = run.outputs.iter().map(|(key, _)| key).collect();
+        keys.sort();
+
+        let mut output = Mapping::new();
+        for key in keys {
+            if let Some(value) = run.outputs.get(key) {
+                output.insert(Value::from(key.as_str()), value.clone());
+            }
+        }
+
+        Ok(Value::Mapping(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(yaml: &str) -> WorkflowResult<SubWorkflowNode> {
+        SubWorkflowNode::new(Some(serde_yml::from_str(yaml).unwrap()))
+    }
+
+    fn run(dir: &Path, main: &str, yaml: &str) -> WorkflowResult<Value> {
+        let heap = Heap::new();
+        let context = RunContext::new(dir.join(main));
+        node(yaml)?.execute(BaseNodeRunOptions::new(&heap, "call").with_context(&context))
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_sub_workflow_node_new() {
+        let node = node("path: child.yml").unwrap();
+        assert_eq!(node.path(), Path::new("child.yml"));
+        assert!(node.inputs().is_empty());
+    }
+
+    #[test]
+    fn test_sub_workflow_node_invalid_input() {
+        assert!(matches!(
+            SubWorkflowNode::new(None),
+            Err(WorkflowError::NodeBuilder(_))
+        ));
+        assert!(matches!(
+            node("path: ''"),
+            Err(WorkflowError::NodeBuilder(_))
+        ));
+        assert!(matches!(
+            node("path: a.yml\ninputs: [1]"),
+            Err(WorkflowError::NodeBuilder(_))
+        ));
+    }
+
+    #[test]
+    fn test_sub_workflow_node_runs_child_with_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.yml", "nodes: []");
+        write(
+            dir.path(),
+            "lib/greet.yml",
+            r#"
+inputs:
+  - name: who
+    type: string
+  - name: punctuation
+    type: string
+    default: "!"
+nodes:
+  - id: message
+    type: Log
+    input: Hello ${{ who }}${{ punctuation }}
+output:
+  message: ${{ message }}
+  who: ${{ who }}
+"#,
+        );
+
+        let output = run(
+            dir.path(),
+            "main.yml",
+            "path: lib/greet.yml\ninputs:\n  who: World",
+        )
+        .unwrap();
+
+        let expected: Value = serde_yml::from_str("message: Hello World!\nwho: World").unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_sub_workflow_node_isolates_heap() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.yml", "nodes: []");
+        write(
+            dir.path(),
+            "child.yml",
+            "nodes:\n  - id: peek\n    type: Log\n    input: ${{ secret }}\noutput:\n  seen: ${{ peek }}\n",
+        );
+
+        let mut heap = Heap::new();
+        heap.insert("secret", Some(Value::from("caller data")));
+        let context = RunContext::new(dir.path().join("main.yml"));
+
+        let output = node("path: child.yml")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "call").with_context(&context))
+            .unwrap();
+
+        assert_eq!(output["seen"], Value::from("${{ secret }}"));
+    }
+
+    #[test]
+    fn test_sub_workflow_node_child_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.yml", "nodes: []");
+        write(
+            dir.path(),
+            "broken.yml",
+            "nodes:\n  - id: bad\n    type: Missing\n",
+        );
+
+        match run(dir.path(), "main.yml", "path: broken.yml") {
+            Err(WorkflowError::NodeExecutionFailed(message)) => {
+                assert!(message.starts_with("Sub-workflow broken.yml failed:"));
+                assert!(message.contains("Missing"));
+            }
+            other => panic!("expected a failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sub_workflow_node_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.yml", "nodes: []");
+
+        assert!(matches!(
+            run(dir.path(), "main.yml", "path: missing.yml"),
+            Err(WorkflowError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sub_workflow_node_detects_cycles_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "a.yml",
+            "nodes:\n  - id: call_b\n    type: Workflow\n    input:\n      path: b.yml\n",
+        );
+        write(
+            dir.path(),
+            "b.yml",
+            "nodes:\n  - id: call_a\n    type: Workflow\n    input:\n      path: a.yml\n",
+        );
+
+        match run(dir.path(), "a.yml", "path: b.yml") {
+            Err(WorkflowError::NodeExecutionFailed(message)) => {
+                assert!(message.contains("Workflow call cycle:"), "{}", message);
+            }
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sub_workflow_node_limits_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        for level in 0..4 {
+            write(
+                dir.path(),
+                &format!("level{}.yml", level),
+                &format!(
+                    "nodes:\n  - id: next\n    type: Workflow\n    input:\n      path: level{}.yml\n",
+                    level + 1
+                ),
+            );
+        }
+        write(dir.path(), "level4.yml", "nodes: []");
+
+        let heap = Heap::new();
+        let context = RunContext::new(dir.path().join("level0.yml")).with_max_depth(2);
+        let result = node("path: level1.yml")
+            .unwrap()
+            .execute(BaseNodeRunOptions::new(&heap, "call").with_context(&context));
+
+        match result {
+            Err(WorkflowError::NodeExecutionFailed(message)) => {
+                assert!(message.contains("nested more than 2 levels"), "{}", message);
+            }
+            other => panic!("expected a depth error, got {:?}", other),
+        }
+    }
+}
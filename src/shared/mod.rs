@@ -22,3 +22,4 @@
 //! ```
 
 pub mod types;
+pub mod utils;
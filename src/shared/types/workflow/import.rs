@@ -0,0 +1,56 @@
+//! Workflow import type definition
+//!
+//! This module contains the `WorkflowImport` struct that represents
+//! a file whose templates and variables are pulled into a workflow.
+
+use serde::{Deserialize, Serialize};
+
+/// Represents a file imported by a workflow
+///
+/// The node templates and variables of the imported file become available
+/// to the importing workflow. Imported files may import other files.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::shared::types::workflow::import::WorkflowImport;
+///
+/// let import = WorkflowImport {
+///     path: "lib/common.yml".to_string(),
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowImport {
+    /// Path of the imported file
+    ///
+    /// Relative paths are resolved against the directory of the importing
+    /// file.
+    pub path: String,
+}
+
+impl WorkflowImport {
+    /// Field names accepted in each entry of `imports`
+    pub const FIELDS: &'static [&'static str] = &["path"];
+
+    /// Creates a new workflow import
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the imported file
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `WorkflowImport` instance.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::import::WorkflowImport;
+    ///
+    /// let import = WorkflowImport::new("lib/common.yml");
+    /// assert_eq!(import.path, "lib/common.yml");
+    /// ```
+    pub fn new(path: impl Into<String>) -> Self {
+        Self { path: path.into() }
+    }
+}
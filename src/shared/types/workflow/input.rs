@@ -44,6 +44,9 @@ pub struct WorkflowInput {
 }
 
 impl WorkflowInput {
+    /// Field names accepted in each entry of `inputs`
+    pub const FIELDS: &'static [&'static str] = &["name", "type", "default"];
+
     /// Creates a new workflow input
     ///
     /// # Arguments
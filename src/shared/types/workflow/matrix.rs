@@ -0,0 +1,224 @@
+//! Node matrix type definition
+//!
+//! This module contains the `NodeMatrix` struct that represents the
+//! combinations a node is run for.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+
+/// A single combination of matrix values, by axis name
+pub type MatrixCombination = BTreeMap<String, Value>;
+
+/// Represents the combinations a node is run for
+///
+/// Every axis lists the values it can take, and the node runs once for
+/// every combination of one value per axis. `exclude` entries remove the
+/// combinations they match, and `include` entries add extra combinations.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::shared::types::workflow::matrix::NodeMatrix;
+///
+/// let matrix: NodeMatrix = serde_yml::from_str(
+///     "os: [linux, macos]\nversion: [1, 2]\nexclude:\n  - os: macos\n    version: 1\n",
+/// )
+/// .unwrap();
+/// assert_eq!(matrix.combinations().len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeMatrix {
+    /// Values of every axis, by axis name
+    ///
+    /// Combinations are generated with axes in alphabetical order.
+    #[serde(flatten)]
+    pub axes: BTreeMap<String, Vec<Value>>,
+
+    /// Extra combinations, added after the generated ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<MatrixCombination>,
+
+    /// Partial combinations removing every generated combination they match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<MatrixCombination>,
+}
+
+impl NodeMatrix {
+    /// Creates an empty matrix
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an axis to the matrix
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the axis
+    /// * `values` - The values the axis can take
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::matrix::NodeMatrix;
+    /// use serde_yml::Value;
+    ///
+    /// let matrix = NodeMatrix::new()
+    ///     .with_axis("os", vec![Value::from("linux"), Value::from("macos")])
+    ///     .with_axis("arch", vec![Value::from("x86_64")]);
+    /// assert_eq!(matrix.combinations().len(), 2);
+    /// ```
+    pub fn with_axis(mut self, name: impl Into<String>, values: Vec<Value>) -> Self {
+        self.axes.insert(name.into(), values);
+        self
+    }
+
+    /// Expands the matrix into the combinations the node runs for
+    ///
+    /// # Returns
+    ///
+    /// Returns the cartesian product of the axes without the excluded
+    /// combinations, followed by the included combinations that are not
+    /// already part of it.
+    pub fn combinations(&self) -> Vec<MatrixCombination> {
+        let mut combinations: Vec<MatrixCombination> = if self.axes.is_empty() {
+            Vec::new()
+        } else {
+            self.axes.iter().fold(
+                vec![MatrixCombination::new()],
+                |combinations, (axis, values)| {
+                    combinations
+                        .iter()
+                        .flat_map(|combination| {
+                            values.iter().map(move |value| {
+                                let mut combination = combination.clone();
+                                combination.insert(axis.clone(), value.clone());
+                                combination
+                            })
+                        })
+                        .collect()
+                },
+            )
+        };
+
+        combinations.retain(|combination| {
+            !self.exclude.iter().any(|exclude| {
+                exclude
+                    .iter()
+                    .all(|(axis, value)| combination.get(axis) == Some(value))
+            })
+        });
+
+        for include in &self.include {
+            if !combinations.contains(include) {
+                combinations.push(include.clone());
+            }
+        }
+
+        combinations
+    }
+
+    /// Returns the key identifying a combination
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::matrix::{MatrixCombination, NodeMatrix};
+    /// use serde_yml::Value;
+    ///
+    /// let combination = MatrixCombination::from([
+    ///     ("os".to_string(), Value::from("linux")),
+    ///     ("version".to_string(), Value::from(2)),
+    /// ]);
+    /// assert_eq!(NodeMatrix::key(&combination), "os=linux,version=2");
+    /// ```
+    pub fn key(combination: &MatrixCombination) -> String {
+        combination
+            .iter()
+            .map(|(axis, value)| {
+                let value = match value {
+                    Value::String(text) => text.clone(),
+                    other => serde_yml::to_string(other)
+                        .map(|text| text.trim_end().to_string())
+                        .unwrap_or_default(),
+                };
+                format!("{}={}", axis, value)
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(yaml: &str) -> NodeMatrix {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    fn keys(matrix: &NodeMatrix) -> Vec<String> {
+        matrix.combinations().iter().map(NodeMatrix::key).collect()
+    }
+
+    #[test]
+    fn test_matrix_cartesian_product() {
+        let matrix = matrix("version: [1, 2]\nos: [linux, macos]\n");
+
+        assert_eq!(
+            keys(&matrix),
+            vec![
+                "os=linux,version=1",
+                "os=linux,version=2",
+                "os=macos,version=1",
+                "os=macos,version=2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matrix_include_and_exclude() {
+        let matrix = matrix(
+            r#"
+os: [linux, macos]
+version: [1, 2]
+exclude:
+  - os: macos
+include:
+  - os: windows
+    version: 2
+  - os: linux
+    version: 1
+"#,
+        );
+
+        assert_eq!(
+            keys(&matrix),
+            vec![
+                "os=linux,version=1",
+                "os=linux,version=2",
+                "os=windows,version=2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matrix_empty() {
+        assert!(NodeMatrix::new().combinations().is_empty());
+        assert!(matrix("os: []").combinations().is_empty());
+        assert_eq!(keys(&matrix("include:\n  - os: linux\n")), vec!["os=linux"]);
+    }
+
+    #[test]
+    fn test_matrix_round_trip() {
+        let matrix = matrix("os: [linux]\nexclude:\n  - os: linux\n");
+        let text = serde_yml::to_string(&matrix).unwrap();
+
+        assert_eq!(text, "os:\n- linux\nexclude:\n- os: linux\n");
+    }
+}
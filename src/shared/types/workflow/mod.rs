@@ -9,6 +9,8 @@
 //!
 //! - **Workflow**: Main workflow structure and metadata
 //! - **Node**: Individual workflow step definitions
+//! - **Matrix**: Combinations a node is run for
+//! - **Import/Template**: Imported files and reusable node templates
 //! - **Input/Output**: Data flow definitions
 //! - **Options**: Configuration and execution options
 //! - **Status**: Workflow execution status tracking
@@ -31,10 +33,14 @@
 //! let output = WorkflowOutput::new();
 //! ```
 
+pub mod import;
 pub mod input;
+pub mod matrix;
 pub mod node;
 pub mod options;
 pub mod output;
 pub mod status;
+pub mod template;
 pub mod variable;
+#[allow(clippy::module_inception)]
 pub mod workflow;
@@ -23,6 +23,11 @@ use serde_yml::Value;
 ///     node_type: "log".to_string(),
 ///     input: Some(Value::String("Hello, World!".to_string())),
 ///     when: Some("debug == true".to_string()),
+///     on_branch: None,
+///     uses: None,
+///     with: None,
+///     for_each: None,
+///     matrix: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,8 +41,9 @@ pub struct WorkflowNode {
     /// The type of node
     ///
     /// This determines what action the node will perform (e.g., "log",
-    /// "http", "script", etc.).
-    #[serde(rename = "type")]
+    /// "http", "script", etc.). It may be omitted when the node uses a
+    /// template, which then provides it.
+    #[serde(rename = "type", default)]
     pub node_type: String,
 
     /// Input data for the node
@@ -52,9 +58,56 @@ pub struct WorkflowNode {
     /// evaluates to true. The condition can reference workflow
     /// variables and inputs.
     pub when: Option<String>,
+
+    /// Branch of a `Switch` node the node runs on
+    ///
+    /// This is written as `<switch id>.<branch>`, such as `route.production`.
+    /// The node is skipped unless the switch ran and chose that branch.
+    pub on_branch: Option<String>,
+
+    /// Name of the template the node is based on
+    ///
+    /// The template provides the type, input, and condition of the node,
+    /// unless the node sets them itself. Templates are expanded when the
+    /// workflow file is loaded, see [`crate::core::imports`].
+    pub uses: Option<String>,
+
+    /// Overrides of the template input
+    ///
+    /// Mappings are merged into the input of the template, key by key;
+    /// any other value replaces it.
+    pub with: Option<Value>,
+
+    /// Items to run the node for, once per item
+    ///
+    /// This is either a sequence or a placeholder evaluating to one, such as
+    /// `${{ files }}`. Each iteration sees the current item as `item` and its
+    /// position as `index`, and the node output is the sequence of the
+    /// outputs of every iteration.
+    pub for_each: Option<Value>,
+
+    /// Combinations to run the node for, once per combination
+    ///
+    /// Each run sees its combination as `matrix`, such as `${{ matrix.os }}`,
+    /// and the node output maps the key of every combination, such as
+    /// `os=linux,version=1`, to the output of its run.
+    pub matrix: Option<super::matrix::NodeMatrix>,
 }
 
 impl WorkflowNode {
+    /// Field names accepted in each entry of `nodes`
+    pub const FIELDS: &'static [&'static str] = &[
+        "id",
+        "type",
+        "input",
+        "when",
+        "on_branch",
+        "uses",
+        "with",
+        "for_each",
+        "matrix",
+    ];
+
     /// Creates a new workflow node
     ///
     /// # Arguments
@@ -81,6 +134,11 @@ impl WorkflowNode {
             node_type: node_type.into(),
             input,
             when: None,
+            on_branch: None,
+            uses: None,
+            with: None,
+            for_each: None,
+            matrix: None,
         }
     }
 
@@ -121,9 +179,156 @@ impl WorkflowNode {
             node_type: node_type.into(),
             input,
             when: Some(when.into()),
+            on_branch: None,
+            uses: None,
+            with: None,
+            for_each: None,
+            matrix: None,
+        }
+    }
+
+    /// Creates a new workflow node based on a template
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unique identifier for the node
+    /// * `template` - The name of the template
+    /// * `with` - Overrides of the template input
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `WorkflowNode` instance whose type is provided by the
+    /// template.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::node::WorkflowNode;
+    ///
+    /// let node = WorkflowNode::from_template("notify", "notify-team", None);
+    /// assert!(node.uses_template());
+    /// assert!(node.node_type.is_empty());
+    /// ```
+    pub fn from_template(
+        id: impl Into<String>,
+        template: impl Into<String>,
+        with: Option<Value>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            node_type: String::new(),
+            input: None,
+            when: None,
+            on_branch: None,
+            uses: Some(template.into()),
+            with,
+            for_each: None,
+            matrix: None,
         }
     }
 
+    /// Sets the items to run the node for
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - A sequence, or a placeholder evaluating to one
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::node::WorkflowNode;
+    /// use serde_yml::Value;
+    ///
+    /// let node = WorkflowNode::new("show", "Log", Some(Value::from("${{ item }}")))
+    ///     .with_for_each(Value::from("${{ files }}"));
+    /// assert!(node.for_each.is_some());
+    /// ```
+    pub fn with_for_each(mut self, items: Value) -> Self {
+        self.for_each = Some(items);
+        self
+    }
+
+    /// Sets the combinations to run the node for
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix` - The matrix of combinations
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::matrix::NodeMatrix;
+    /// use colossus::shared::types::workflow::node::WorkflowNode;
+    /// use serde_yml::Value;
+    ///
+    /// let node = WorkflowNode::new("build", "Log", Some(Value::from("${{ matrix.os }}")))
+    ///     .with_matrix(NodeMatrix::new().with_axis("os", vec![Value::from("linux")]));
+    /// assert_eq!(node.run_count(), 1);
+    /// ```
+    pub fn with_matrix(mut self, matrix: super::matrix::NodeMatrix) -> Self {
+        self.matrix = Some(matrix);
+        self
+    }
+
+    /// Sets the branch of a `Switch` node the node runs on
+    ///
+    /// # Arguments
+    ///
+    /// * `branch` - The branch, as `<switch id>.<branch>`
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::node::WorkflowNode;
+    ///
+    /// let node = WorkflowNode::new("deploy", "Log", None).with_on_branch("route.production");
+    /// assert_eq!(node.branch(), Some(("route", "production")));
+    /// ```
+    pub fn with_on_branch(mut self, branch: impl Into<String>) -> Self {
+        self.on_branch = Some(branch.into());
+        self
+    }
+
+    /// Returns the switch node id and the branch name of `on_branch`
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the node has no `on_branch`, or if it is not of the
+    /// form `<switch id>.<branch>`. Node ids cannot contain dots, since
+    /// expressions would read them as field accesses, so the switch id ends
+    /// at the first dot.
+    pub fn branch(&self) -> Option<(&str, &str)> {
+        self.on_branch
+            .as_deref()?
+            .split_once('.')
+            .filter(|(switch, branch)| !switch.is_empty() && !branch.is_empty())
+    }
+
+    /// Returns the number of times the node runs, if known before running
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of combinations of a matrix node, and 1 for other
+    /// nodes. The number of items of a `for_each` node is only known when
+    /// running, so it also counts as 1.
+    pub fn run_count(&self) -> usize {
+        self.matrix
+            .as_ref()
+            .map_or(1, |matrix| matrix.combinations().len())
+    }
+
     /// Checks if the node has a conditional expression
     ///
     /// # Returns
@@ -145,4 +350,13 @@ impl WorkflowNode {
     pub fn has_condition(&self) -> bool {
         self.when.is_some()
     }
+
+    /// Checks if the node is based on a template that was not expanded yet
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the node has a `uses` field, `false` otherwise.
+    pub fn uses_template(&self) -> bool {
+        self.uses.is_some()
+    }
 }
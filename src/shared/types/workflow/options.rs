@@ -18,6 +18,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// let options = WorkflowOptions {
 ///     concurrency: Some(4),
+///     wasm: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,9 +29,18 @@ pub struct WorkflowOptions {
     /// If not specified, the default behavior is determined by the
     /// execution engine.
     pub concurrency: Option<u32>,
+
+    /// What WebAssembly nodes are granted access to
+    ///
+    /// By default, WebAssembly nodes see no host directory and no
+    /// environment variable.
+    pub wasm: Option<WasmOptions>,
 }
 
 impl WorkflowOptions {
+    /// Field names accepted in the `options` section
+    pub const FIELDS: &'static [&'static str] = &["concurrency", "wasm"];
+
     /// Creates a new workflow options instance
     ///
     /// # Returns
@@ -46,7 +56,10 @@ impl WorkflowOptions {
     /// assert_eq!(options.concurrency, None);
     /// ```
     pub fn new() -> Self {
-        Self { concurrency: None }
+        Self {
+            concurrency: None,
+            wasm: None,
+        }
     }
 
     /// Creates a new workflow options instance with concurrency limit
@@ -70,6 +83,7 @@ impl WorkflowOptions {
     pub fn with_concurrency(concurrency: u32) -> Self {
         Self {
             concurrency: Some(concurrency),
+            wasm: None,
         }
     }
 
@@ -149,3 +163,50 @@ impl Default for WorkflowOptions {
         Self::new()
     }
 }
+
+/// Host resources granted to the WebAssembly nodes of a workflow
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::shared::types::workflow::options::WasmOptions;
+///
+/// let options: WasmOptions = serde_yml::from_str(
+///     "dirs:\n  - host: assets\n    guest: /assets\nenv: [API_URL]\n",
+/// )
+/// .unwrap();
+/// assert!(!options.dirs.unwrap()[0].writable);
+/// assert_eq!(options.env, Some(vec!["API_URL".to_string()]));
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WasmOptions {
+    /// Directories of the host visible to the modules
+    pub dirs: Option<Vec<WasmDirOption>>,
+
+    /// Names of the host environment variables visible to the modules
+    pub env: Option<Vec<String>>,
+}
+
+impl WasmOptions {
+    /// Field names accepted in the `options.wasm` section
+    pub const FIELDS: &'static [&'static str] = &["dirs", "env"];
+}
+
+/// A directory of the host granted to WebAssembly nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WasmDirOption {
+    /// Path of the directory, relative to the workflow file
+    pub host: String,
+
+    /// Path of the directory as seen by the modules
+    pub guest: String,
+
+    /// Whether modules may create, modify and remove files
+    #[serde(default)]
+    pub writable: bool,
+}
+
+impl WasmDirOption {
+    /// Field names accepted in the entries of `options.wasm.dirs`
+    pub const FIELDS: &'static [&'static str] = &["host", "guest", "writable"];
+}
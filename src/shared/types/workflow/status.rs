@@ -3,6 +3,8 @@
 //! This module contains the `Status` enum that represents the various
 //! states a workflow can be in during execution.
 
+use serde::{Deserialize, Serialize};
+
 /// Represents the execution status of a workflow or workflow node
 ///
 /// This enum defines all the possible states that a workflow or individual
@@ -21,9 +23,10 @@
 ///     _ => println!("Workflow is in another state"),
 /// }
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum Status {
     /// Workflow is waiting to be executed
+    #[default]
     Pending,
 
     /// Workflow is preparing to start execution
@@ -135,9 +138,3 @@ impl Status {
         matches!(self, Status::Starting | Status::Running | Status::Paused)
     }
 }
-
-impl Default for Status {
-    fn default() -> Self {
-        Status::Pending
-    }
-}
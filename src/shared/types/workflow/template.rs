@@ -0,0 +1,70 @@
+//! Node template type definition
+//!
+//! This module contains the `NodeTemplate` struct that represents
+//! a reusable node definition referenced with `uses:`.
+
+use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+
+/// Represents a reusable node definition
+///
+/// A template holds everything a node needs except its id. Nodes use a
+/// template by name with `uses:`, and can override its input with `with:`.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::shared::types::workflow::template::NodeTemplate;
+/// use serde_yml::Value;
+///
+/// let template = NodeTemplate {
+///     node_type: "Log".to_string(),
+///     input: Some(Value::String("Deploying ${{ target }}".to_string())),
+///     when: None,
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeTemplate {
+    /// The type of the nodes using the template
+    #[serde(rename = "type")]
+    pub node_type: String,
+
+    /// Input data of the nodes using the template
+    pub input: Option<Value>,
+
+    /// Conditional expression of the nodes using the template
+    pub when: Option<String>,
+}
+
+impl NodeTemplate {
+    /// Field names accepted in each entry of `templates`
+    pub const FIELDS: &'static [&'static str] = &["type", "input", "when"];
+
+    /// Creates a new node template
+    ///
+    /// # Arguments
+    ///
+    /// * `node_type` - The type of the nodes using the template
+    /// * `input` - The input data of the nodes using the template
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `NodeTemplate` instance without a conditional.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::template::NodeTemplate;
+    /// use serde_yml::Value;
+    ///
+    /// let template = NodeTemplate::new("Log", Some(Value::String("done".to_string())));
+    /// assert_eq!(template.node_type, "Log");
+    /// ```
+    pub fn new(node_type: impl Into<String>, input: Option<Value>) -> Self {
+        Self {
+            node_type: node_type.into(),
+            input,
+            when: None,
+        }
+    }
+}
@@ -37,6 +37,9 @@ pub struct WorkflowVariable {
 }
 
 impl WorkflowVariable {
+    /// Field names accepted in each entry of `variables`
+    pub const FIELDS: &'static [&'static str] = &["name", "value"];
+
     /// Creates a new workflow variable
     ///
     /// # Arguments
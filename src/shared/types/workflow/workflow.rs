@@ -3,8 +3,13 @@
 //! This module contains the core `Workflow` struct that represents
 //! a complete workflow definition with all its components.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::core::migration::FORMAT_VERSION;
+use crate::core::validation::{ValidationReport, WorkflowValidator};
+
 /// Represents a complete workflow definition
 ///
 /// This struct encapsulates all the information needed to define and execute
@@ -16,18 +21,30 @@ use serde::{Deserialize, Serialize};
 /// use colossus::shared::types::workflow::workflow::Workflow;
 ///
 /// let workflow = Workflow {
+///     colossus: Some(1),
 ///     id: Some("my-workflow".to_string()),
 ///     name: Some("My Workflow".to_string()),
 ///     version: Some("1.0.0".to_string()),
+///     imports: None,
 ///     variables: None,
 ///     inputs: None,
+///     templates: None,
 ///     nodes: None,
+///     on_failure: None,
+///     finally: None,
 ///     output: None,
 ///     options: None,
 /// };
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Workflow {
+    /// Version of the workflow file format
+    ///
+    /// This is distinct from [`Workflow::version`], which is the version of
+    /// the workflow itself. Files written for an older format are upgraded
+    /// by the [migration layer](crate::core::migration) when loaded.
+    pub colossus: Option<u32>,
+
     /// Unique identifier for the workflow
     ///
     /// This field is optional but recommended for workflows that will be
@@ -46,6 +63,12 @@ pub struct Workflow {
     /// track changes and ensure compatibility.
     pub version: Option<String>,
 
+    /// Files whose templates and variables are pulled into the workflow
+    ///
+    /// Imports are resolved when the workflow file is loaded, see
+    /// [`crate::core::imports`].
+    pub imports: Option<Vec<super::import::WorkflowImport>>,
+
     /// Global variables available throughout the workflow
     ///
     /// These variables can be referenced by nodes and provide a way to
@@ -58,12 +81,29 @@ pub struct Workflow {
     /// executing the workflow.
     pub inputs: Option<Vec<super::input::WorkflowInput>>,
 
+    /// Reusable node definitions, by name
+    ///
+    /// Nodes reference a template with `uses:`. Once imports are resolved,
+    /// this also holds the templates of the imported files.
+    pub templates: Option<BTreeMap<String, super::template::NodeTemplate>>,
+
     /// Nodes that make up the workflow execution graph
     ///
     /// Each node represents a step in the workflow and defines what
     /// action should be performed.
     pub nodes: Option<Vec<super::node::WorkflowNode>>,
 
+    /// Nodes run after the main nodes when one of them failed
+    ///
+    /// Handler nodes see the error as `run.error` and the id of the failed
+    /// node as `run.failed_node`, along with the outputs of the nodes that
+    /// completed.
+    pub on_failure: Option<Vec<super::node::WorkflowNode>>,
+
+    /// Nodes run after the main nodes and the failure handlers, whatever
+    /// the outcome of the run
+    pub finally: Option<Vec<super::node::WorkflowNode>>,
+
     /// Output definition for the workflow
     ///
     /// This defines what data the workflow will produce as its result.
@@ -77,6 +117,23 @@ pub struct Workflow {
 }
 
 impl Workflow {
+    /// Field names accepted at the top level of a workflow file
+    pub const FIELDS: &'static [&'static str] = &[
+        "colossus",
+        "id",
+        "name",
+        "version",
+        "imports",
+        "variables",
+        "inputs",
+        "templates",
+        "nodes",
+        "on_failure",
+        "finally",
+        "output",
+        "options",
+    ];
+
     /// Creates a new workflow with the given name
     ///
     /// # Arguments
@@ -94,15 +151,21 @@ impl Workflow {
     ///
     /// let workflow = Workflow::new("My Workflow");
     /// assert_eq!(workflow.name, Some("My Workflow".to_string()));
+    /// assert_eq!(workflow.colossus, Some(1));
     /// ```
     pub fn new(name: impl Into<String>) -> Self {
         Self {
+            colossus: Some(FORMAT_VERSION),
             id: None,
             name: Some(name.into()),
             version: None,
+            imports: None,
             variables: None,
             inputs: None,
+            templates: None,
             nodes: None,
+            on_failure: None,
+            finally: None,
             output: None,
             options: None,
         }
@@ -230,6 +293,38 @@ impl Workflow {
         self.nodes.as_ref().map_or(0, |nodes| nodes.len())
     }
 
+    /// Returns the number of node runs once matrices are expanded
+    ///
+    /// # Returns
+    ///
+    /// Returns the sum of the [run counts](super::node::WorkflowNode::run_count)
+    /// of the nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::{
+    ///     matrix::NodeMatrix, node::WorkflowNode, workflow::Workflow,
+    /// };
+    /// use serde_yml::Value;
+    ///
+    /// let mut workflow = Workflow::new("Build");
+    /// workflow.add_node(WorkflowNode::new("prepare", "Log", None));
+    /// workflow.add_node(
+    ///     WorkflowNode::new("build", "Log", None)
+    ///         .with_matrix(NodeMatrix::new().with_axis("os", vec![Value::from("linux"), Value::from("macos")])),
+    /// );
+    /// assert_eq!(workflow.node_count(), 2);
+    /// assert_eq!(workflow.expanded_node_count(), 3);
+    /// ```
+    pub fn expanded_node_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .flatten()
+            .map(|node| node.run_count())
+            .sum()
+    }
+
     /// Checks if the workflow has any nodes
     ///
     /// # Returns
@@ -266,6 +361,37 @@ impl Workflow {
         self.nodes.as_mut()
     }
 
+    /// Gets the node lists of the workflow, with the field they are defined in
+    ///
+    /// # Returns
+    ///
+    /// Returns the main nodes, the failure handlers, and the final nodes, in
+    /// the order they run; missing lists are empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::{workflow::Workflow, node::WorkflowNode};
+    ///
+    /// let mut workflow = Workflow::new("My Workflow");
+    /// workflow.add_node(WorkflowNode::new("build", "Log", None));
+    /// workflow.finally = Some(vec![WorkflowNode::new("cleanup", "Log", None)]);
+    ///
+    /// let sections: Vec<(&str, usize)> = workflow
+    ///     .sections()
+    ///     .into_iter()
+    ///     .map(|(field, nodes)| (field, nodes.len()))
+    ///     .collect();
+    /// assert_eq!(sections, vec![("nodes", 1), ("on_failure", 0), ("finally", 1)]);
+    /// ```
+    pub fn sections(&self) -> Vec<(&'static str, &[super::node::WorkflowNode])> {
+        vec![
+            ("nodes", self.nodes.as_deref().unwrap_or_default()),
+            ("on_failure", self.on_failure.as_deref().unwrap_or_default()),
+            ("finally", self.finally.as_deref().unwrap_or_default()),
+        ]
+    }
+
     /// Adds a node to the workflow
     ///
     /// # Arguments
@@ -290,20 +416,30 @@ impl Workflow {
             self.nodes = Some(vec![node]);
         }
     }
-}
 
-impl Default for Workflow {
-    fn default() -> Self {
-        Self {
-            id: None,
-            name: None,
-            version: None,
-            variables: None,
-            inputs: None,
-            nodes: None,
-            output: None,
-            options: None,
-        }
+    /// Validates the workflow without executing it
+    ///
+    /// Only the built-in node types are known; use
+    /// [`WorkflowValidator::with_registry`] to validate against custom types.
+    ///
+    /// # Returns
+    ///
+    /// Returns a report with every problem found in the workflow.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use colossus::shared::types::workflow::{workflow::Workflow, node::WorkflowNode};
+    ///
+    /// let mut workflow = Workflow::new("My Workflow");
+    /// workflow.add_node(WorkflowNode::new("greet", "Log", None));
+    /// assert!(workflow.validate().is_valid());
+    ///
+    /// workflow.add_node(WorkflowNode::new("greet", "Log", None));
+    /// assert!(!workflow.validate().is_valid());
+    /// ```
+    pub fn validate(&self) -> ValidationReport {
+        WorkflowValidator::new().validate(self)
     }
 }
 
@@ -461,4 +597,16 @@ mod tests {
         let workflow = Workflow::new(String::from("Dynamic Workflow"));
         assert_eq!(workflow.name, Some("Dynamic Workflow".to_string()));
     }
+
+    #[test]
+    fn test_workflow_validate() {
+        let mut workflow = Workflow::new("Test Workflow");
+        workflow.add_node(WorkflowNode::new("first", "Log", None));
+        assert!(workflow.validate().is_empty());
+
+        workflow.add_node(WorkflowNode::new("second", "Unknown", None));
+        let report = workflow.validate();
+        assert!(!report.is_valid());
+        assert_eq!(report.errors().count(), 1);
+    }
 }
@@ -0,0 +1,119 @@
+//! Human-friendly duration parsing
+//!
+//! This module parses durations written as a sequence of integers followed
+//! by a unit, such as `500ms`, `30s`, `1h30m`, or `7d`.
+
+use std::time::Duration;
+
+/// Parses a human-friendly duration
+///
+/// Supported units are `ms`, `s`, `m`, `h`, `d`, and `w`. Several components
+/// can be combined, e.g. `1h30m`. A bare number is interpreted as seconds.
+///
+/// # Arguments
+///
+/// * `input` - The duration to parse
+///
+/// # Returns
+///
+/// Returns the parsed `Duration`, or a message describing why it is invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use colossus::shared::utils::duration::parse_duration;
+///
+/// assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+/// assert_eq!(parse_duration("7d"), Ok(Duration::from_secs(7 * 24 * 60 * 60)));
+/// assert!(parse_duration("soon").is_err());
+/// ```
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Err("duration cannot be empty".to_string());
+    }
+
+    if let Ok(seconds) = input.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("invalid duration '{}': expected a number", input));
+        }
+
+        let amount: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| format!("invalid duration '{}': number is too large", input))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let component = match &rest[..unit_len] {
+            "ms" => Duration::from_millis(amount),
+            "s" => Duration::from_secs(amount),
+            "m" => Duration::from_secs(amount.saturating_mul(60)),
+            "h" => Duration::from_secs(amount.saturating_mul(60 * 60)),
+            "d" => Duration::from_secs(amount.saturating_mul(24 * 60 * 60)),
+            "w" => Duration::from_secs(amount.saturating_mul(7 * 24 * 60 * 60)),
+            unit => {
+                return Err(format!(
+                    "invalid duration '{}': unknown unit '{}' (expected ms, s, m, h, d, or w)",
+                    input, unit
+                ))
+            }
+        };
+
+        total = total.saturating_add(component);
+        rest = &rest[unit_len..];
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert_eq!(parse_duration("1w"), Ok(Duration::from_secs(604800)));
+    }
+
+    #[test]
+    fn test_parse_duration_bare_number_is_seconds() {
+        assert_eq!(parse_duration("42"), Ok(Duration::from_secs(42)));
+    }
+
+    #[test]
+    fn test_parse_duration_combined() {
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(
+            parse_duration(" 1m500ms "),
+            Ok(Duration::from_millis(60_500))
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("1.5h").is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+    }
+}
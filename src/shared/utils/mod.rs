@@ -0,0 +1,22 @@
+//! Shared utilities
+//!
+//! This module contains small helpers that are used by several layers of
+//! the application, such as parsing human-friendly values from the CLI or
+//! from workflow files.
+//!
+//! # Overview
+//!
+//! - **Duration**: Parsing of durations such as `30s`, `1h30m`, or `7d`
+//! - **Suggest**: "Did you mean" suggestions for misspelled names
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use colossus::shared::utils::duration::parse_duration;
+//!
+//! assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+//! ```
+
+pub mod duration;
+pub mod suggest;
@@ -0,0 +1,126 @@
+//! Spelling suggestions
+//!
+//! This module finds the known name closest to a misspelled one, to turn
+//! errors such as an unknown field into "did you mean" hints.
+
+/// Computes the edit distance between two strings
+///
+/// The distance is the minimum number of single character insertions,
+/// deletions, substitutions, or swaps of two adjacent characters turning one
+/// string into the other.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::shared::utils::suggest::edit_distance;
+///
+/// assert_eq!(edit_distance("wen", "when"), 1);
+/// assert_eq!(edit_distance("nmae", "name"), 1);
+/// assert_eq!(edit_distance("kitten", "sitting"), 3);
+/// ```
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Finds the candidate closest to a misspelled name
+///
+/// Names are compared case-insensitively, and a candidate is only suggested
+/// if it is close enough to be a plausible typo: at most one edit for every
+/// three characters, and always at least one.
+///
+/// # Arguments
+///
+/// * `name` - The misspelled name
+/// * `candidates` - The known names
+///
+/// # Returns
+///
+/// Returns the closest candidate, or `None` if none is close enough.
+///
+/// # Examples
+///
+/// ```rust
+/// use colossus::shared::utils::suggest::did_you_mean;
+///
+/// assert_eq!(did_you_mean("wen", ["id", "type", "when"]), Some("when"));
+/// assert_eq!(did_you_mean("depends-on", ["id", "type", "when"]), None);
+/// ```
+pub fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let limit = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("when", "when"), 0);
+        assert_eq!(edit_distance("tpye", "type"), 1);
+        assert_eq!(edit_distance("inptu", "input"), 1);
+        assert_eq!(edit_distance("ca", "abc"), 3);
+    }
+
+    #[test]
+    fn test_did_you_mean_typos() {
+        let fields = ["id", "type", "input", "when"];
+
+        assert_eq!(did_you_mean("wen", fields), Some("when"));
+        assert_eq!(did_you_mean("typ", fields), Some("type"));
+        assert_eq!(did_you_mean("inputs", fields), Some("input"));
+        assert_eq!(did_you_mean("Type", fields), Some("type"));
+    }
+
+    #[test]
+    fn test_did_you_mean_no_close_match() {
+        let fields = ["id", "type", "input", "when"];
+
+        assert_eq!(did_you_mean("depends-on", fields), None);
+        assert_eq!(did_you_mean("xyz", fields), None);
+        assert_eq!(did_you_mean("anything", []), None);
+    }
+
+    #[test]
+    fn test_did_you_mean_prefers_closest() {
+        assert_eq!(did_you_mean("nodez", ["node", "nodes"]), Some("node"));
+        assert_eq!(did_you_mean("names", ["name", "names"]), Some("names"));
+    }
+}